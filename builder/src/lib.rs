@@ -1,25 +1,43 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{any::Any, borrow::Cow, collections::HashMap, rc::Rc};
 
 pub use engel_core::builder::*;
 use engel_core::{
-    AlignHor, AlignVer, Circle, Clip, Comp, EventName, Fill, Group, Listener, Model, Node, Padding, Path, PathCommand,
-    Prim, Real, RealValue, Rect, Rounding, Shape, Stroke, Text, Transform,
+    find_icon, AlignHor, AlignVer, Anchor, Circle, Clip, Color, Comp, CompError, CursorIcon, Decoration, EventName,
+    Fill, Group, Icon, Listener, Margin, Model, Node, Overflow, Padding, Path, PathCommand, Prim, Real, RealValue,
+    Rect, Rounding, Shape, StateStyle, Stroke, Text, TextWrap, TileAtlas, TileId, TileLayer, TileMap, Transform,
+    VirtualList,
 };
 
 pub struct PrimBuilder<M: Model> {
     pub children: Vec<Node<M>>,
+    pub decorations: Vec<Decoration<M>>,
     pub listeners: HashMap<EventName, Vec<Listener<M>>>,
+    pub user_data: Option<Box<dyn Any>>,
+    pub capture_mouse: bool,
+    pub cursor: Option<CursorIcon>,
+    pub state_style: Option<StateStyle>,
 }
 
 impl<M: Model> Default for PrimBuilder<M> {
     fn default() -> Self {
         Self {
             children: Default::default(),
+            decorations: Default::default(),
             listeners: Default::default(),
+            user_data: None,
+            capture_mouse: false,
+            cursor: None,
+            state_style: None,
         }
     }
 }
 
+impl<M: Model> PrimBuilder<M> {
+    fn state_style(&mut self) -> &mut StateStyle {
+        self.state_style.get_or_insert_with(Default::default)
+    }
+}
+
 pub fn circle<M: Model>() -> CircleBuilder<M> {
     CircleBuilder {
         shape: Default::default(),
@@ -86,12 +104,24 @@ impl<M: Model> CircleBuilder<M> {
 
 impl<M: Model> Builder<M> for CircleBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let mut prim = Prim::new(
             Cow::Borrowed(Circle::NAME),
             Shape::Circle(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
+    }
+}
+
+impl<M: Model> Margined for CircleBuilder<M> {
+    fn margin_mut(&mut self) -> &mut Margin {
+        &mut self.shape.margin
     }
 }
 
@@ -118,6 +148,11 @@ impl<M: Model> Primitive<M> for CircleBuilder<M> {
         self
     }
 
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
     fn transparency(mut self, transparency: impl Into<Real>) -> Self {
         self.shape.transparency = transparency.into();
         self
@@ -128,11 +163,31 @@ impl<M: Model> Primitive<M> for CircleBuilder<M> {
         self
     }
 
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
     fn fill(mut self, fill: impl Into<Fill>) -> Self {
         self.shape.fill = Some(fill.into());
         self
     }
 
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
     fn remove_stroke(mut self) -> Self {
         self.shape.stroke = None;
         self
@@ -143,6 +198,21 @@ impl<M: Model> Primitive<M> for CircleBuilder<M> {
         self
     }
 
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
     fn clip(
         mut self,
         x: impl Into<RealValue>,
@@ -247,6 +317,11 @@ impl<M: Model> RectBuilder<M> {
         self
     }
 
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.shape.overflow = overflow;
+        self
+    }
+
     pub fn padding(mut self, padding: impl Into<Padding>) -> Self {
         self.shape.padding = padding.into();
         self
@@ -289,12 +364,24 @@ impl<M: Model> RectBuilder<M> {
 
 impl<M: Model> Builder<M> for RectBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let mut prim = Prim::new(
             Cow::Borrowed(Rect::NAME),
             Shape::Rect(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
+    }
+}
+
+impl<M: Model> Margined for RectBuilder<M> {
+    fn margin_mut(&mut self) -> &mut Margin {
+        &mut self.shape.margin
     }
 }
 
@@ -321,6 +408,11 @@ impl<M: Model> Primitive<M> for RectBuilder<M> {
         self
     }
 
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
     fn transparency(mut self, transparency: impl Into<Real>) -> Self {
         self.shape.transparency = transparency.into();
         self
@@ -331,11 +423,31 @@ impl<M: Model> Primitive<M> for RectBuilder<M> {
         self
     }
 
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
     fn fill(mut self, fill: impl Into<Fill>) -> Self {
         self.shape.fill = Some(fill.into());
         self
     }
 
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
     fn remove_stroke(mut self) -> Self {
         self.shape.stroke = None;
         self
@@ -346,6 +458,21 @@ impl<M: Model> Primitive<M> for RectBuilder<M> {
         self
     }
 
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
     fn clip(
         mut self,
         x: impl Into<RealValue>,
@@ -400,6 +527,11 @@ impl<M: Model> TextBuilder<M> {
         self
     }
 
+    pub fn wrap(mut self, wrap: TextWrap) -> Self {
+        self.shape.wrap = wrap;
+        self
+    }
+
     pub fn align(mut self, align: impl Into<(AlignHor, AlignVer)>) -> Self {
         self.shape.align = align.into();
         self
@@ -408,12 +540,24 @@ impl<M: Model> TextBuilder<M> {
 
 impl<M: Model> Builder<M> for TextBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let mut prim = Prim::new(
             Cow::Borrowed(Text::NAME),
             Shape::Text(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
+    }
+}
+
+impl<M: Model> Margined for TextBuilder<M> {
+    fn margin_mut(&mut self) -> &mut Margin {
+        &mut self.shape.margin
     }
 }
 
@@ -440,6 +584,11 @@ impl<M: Model> Primitive<M> for TextBuilder<M> {
         self
     }
 
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
     fn transparency(mut self, transparency: impl Into<Real>) -> Self {
         self.shape.transparency = transparency.into();
         self
@@ -450,11 +599,31 @@ impl<M: Model> Primitive<M> for TextBuilder<M> {
         self
     }
 
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
     fn fill(mut self, fill: impl Into<Fill>) -> Self {
         self.shape.fill = Some(fill.into());
         self
     }
 
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
     fn remove_stroke(mut self) -> Self {
         self.shape.stroke = None;
         self
@@ -465,6 +634,21 @@ impl<M: Model> Primitive<M> for TextBuilder<M> {
         self
     }
 
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
     fn clip(
         mut self,
         x: impl Into<RealValue>,
@@ -506,12 +690,18 @@ impl<M: Model> PathBuilder<M> {}
 
 impl<M: Model> Builder<M> for PathBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let mut prim = Prim::new(
             Cow::Borrowed(Path::NAME),
             Shape::Path(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
     }
 }
 
@@ -538,6 +728,11 @@ impl<M: Model> Primitive<M> for PathBuilder<M> {
         self
     }
 
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
     fn transparency(mut self, transparency: impl Into<Real>) -> Self {
         self.shape.transparency = transparency.into();
         self
@@ -548,11 +743,31 @@ impl<M: Model> Primitive<M> for PathBuilder<M> {
         self
     }
 
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
     fn fill(mut self, fill: impl Into<Fill>) -> Self {
         self.shape.fill = Some(fill.into());
         self
     }
 
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
     fn remove_stroke(mut self) -> Self {
         self.shape.stroke = None;
         self
@@ -563,6 +778,21 @@ impl<M: Model> Primitive<M> for PathBuilder<M> {
         self
     }
 
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
     fn clip(
         mut self,
         x: impl Into<RealValue>,
@@ -585,28 +815,378 @@ impl<M: Model> EventHandler<M> for PathBuilder<M> {
     }
 }
 
+pub fn tile_map<M: Model>(
+    cols: usize,
+    rows: usize,
+    tile_width: impl Into<Real>,
+    tile_height: impl Into<Real>,
+    atlas: Rc<TileAtlas>,
+) -> TileMapBuilder<M> {
+    TileMapBuilder {
+        shape: TileMap::new(cols, rows, tile_width.into(), tile_height.into(), atlas),
+        prim: Default::default(),
+    }
+}
+
+pub struct TileMapBuilder<M: Model> {
+    shape: TileMap,
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> TileMapBuilder<M> {
+    /// Adds a layer over the previous ones. The cells go row by row, the missing
+    /// cells are empty.
+    pub fn layer(mut self, cells: impl IntoIterator<Item = Option<TileId>>) -> Self {
+        let len = self.shape.cols * self.shape.rows;
+        let mut cells: Vec<_> = cells.into_iter().take(len).collect();
+        cells.resize(len, None);
+        self.shape.layers.push(TileLayer::new(cells));
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for TileMapBuilder<M> {
+    fn build(self) -> Node<M> {
+        let mut prim = Prim::new(
+            Cow::Borrowed(TileMap::NAME),
+            Shape::TileMap(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
+    }
+}
+
+impl<M: Model> Entity for TileMapBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for TileMapBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.children.push(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        self.prim.children.extend(children);
+        self
+    }
+
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn clip(
+        mut self,
+        x: impl Into<RealValue>,
+        y: impl Into<RealValue>,
+        width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for TileMapBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
+/// Builds a path from the registered icon with the given name.
+/// An unknown icon name produces an empty path.
+pub fn icon<M: Model>(name: &str) -> IconBuilder<M> {
+    let icon = find_icon(name);
+    let size = icon.as_ref().map(|icon| (icon.width, icon.height)).unwrap_or_default();
+    IconBuilder {
+        shape: Path {
+            fill: Some(Fill::default()),
+            ..Path::default()
+        },
+        icon,
+        size,
+        prim: Default::default(),
+    }
+}
+
+pub struct IconBuilder<M: Model> {
+    shape: Path,
+    icon: Option<Rc<Icon>>,
+    size: (Real, Real),
+    prim: PrimBuilder<M>,
+}
+
+impl<M: Model> IconBuilder<M> {
+    pub fn size(mut self, size: impl Into<Real>) -> Self {
+        let size = size.into();
+        self.size = (size, size);
+        self
+    }
+
+    pub fn width_and_height(mut self, width: impl Into<Real>, height: impl Into<Real>) -> Self {
+        self.size = (width.into(), height.into());
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.shape.fill = Some(Fill::color(color));
+        self
+    }
+}
+
+impl<M: Model> Builder<M> for IconBuilder<M> {
+    fn build(mut self) -> Node<M> {
+        if let Some(icon) = self.icon {
+            let (width, height) = self.size;
+            self.shape.cmd = icon.scaled_cmd(width, height);
+        }
+
+        let mut prim = Prim::new(
+            Cow::Borrowed(Path::NAME),
+            Shape::Path(self.shape),
+            self.prim.children,
+            self.prim.listeners,
+        )
+        .with_decorations(self.prim.decorations);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
+    }
+}
+
+impl<M: Model> Entity for IconBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.shape.id = Some(id.into());
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        self.shape.transform = transform.into();
+        self
+    }
+}
+
+impl<M: Model> Primitive<M> for IconBuilder<M> {
+    fn child(mut self, child: impl Builder<M>) -> Self {
+        self.prim.children.push(child.build());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node<M>>) -> Self {
+        self.prim.children.extend(children);
+        self
+    }
+
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
+    fn transparency(mut self, transparency: impl Into<Real>) -> Self {
+        self.shape.transparency = transparency.into();
+        self
+    }
+
+    fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.shape.stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
+    fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.shape.fill = Some(fill.into());
+        self
+    }
+
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
+    fn remove_stroke(mut self) -> Self {
+        self.shape.stroke = None;
+        self
+    }
+
+    fn remove_fill(mut self) -> Self {
+        self.shape.fill = None;
+        self
+    }
+
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
+    fn clip(
+        mut self,
+        x: impl Into<RealValue>,
+        y: impl Into<RealValue>,
+        width: impl Into<RealValue>,
+        height: impl Into<RealValue>,
+    ) -> Self {
+        self.shape.clip = Clip::new_scissor(x.into(), y.into(), width.into(), height.into());
+        self
+    }
+}
+
+impl<M: Model> EventHandler<M> for IconBuilder<M> {
+    fn add_listener(&mut self, listener: Listener<M>) {
+        self.prim
+            .listeners
+            .entry(listener.event_name())
+            .or_default()
+            .push(listener);
+    }
+}
+
 pub fn group<M: Model>() -> GroupBuilder<M> {
     GroupBuilder {
         shape: Default::default(),
         prim: Default::default(),
+        overlay: false,
     }
 }
 
 pub struct GroupBuilder<M: Model> {
     shape: Group,
     prim: PrimBuilder<M>,
+    overlay: bool,
 }
 
-impl<M: Model> GroupBuilder<M> {}
+impl<M: Model> GroupBuilder<M> {
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.shape.overflow = overflow;
+        self
+    }
+
+    /// Draws the group above the whole view, e.g. for a popup or a tooltip.
+    pub fn overlay(mut self) -> Self {
+        self.overlay = true;
+        self
+    }
+}
 
 impl<M: Model> Builder<M> for GroupBuilder<M> {
     fn build(self) -> Node<M> {
-        Node::Prim(Prim::new(
+        let mut prim = Prim::new(
             Cow::Borrowed(Group::NAME),
             Shape::Group(self.shape),
             self.prim.children,
             self.prim.listeners,
-        ))
+        )
+        .with_decorations(self.prim.decorations)
+        .with_overlay(self.overlay);
+        prim.user_data = self.prim.user_data;
+        prim.set_capture_on_press(self.prim.capture_mouse);
+        prim.set_cursor(self.prim.cursor);
+        prim.set_state_style(self.prim.state_style);
+        Node::Prim(prim)
     }
 }
 
@@ -633,6 +1213,11 @@ impl<M: Model> Primitive<M> for GroupBuilder<M> {
         self
     }
 
+    fn decoration(mut self, anchor: Anchor, decoration: impl Builder<M>) -> Self {
+        self.prim.decorations.push(Decoration::new(anchor, decoration.build()));
+        self
+    }
+
     fn transparency(mut self, transparency: impl Into<Real>) -> Self {
         self.shape.transparency = Some(transparency.into());
         self
@@ -643,11 +1228,31 @@ impl<M: Model> Primitive<M> for GroupBuilder<M> {
         self
     }
 
+    fn stroke_hover(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().hover_stroke = Some(stroke.into());
+        self
+    }
+
+    fn stroke_pressed(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.prim.state_style().pressed_stroke = Some(stroke.into());
+        self
+    }
+
     fn fill(mut self, fill: impl Into<Fill>) -> Self {
         self.shape.fill = Some(fill.into());
         self
     }
 
+    fn fill_hover(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().hover_fill = Some(fill.into());
+        self
+    }
+
+    fn fill_pressed(mut self, fill: impl Into<Fill>) -> Self {
+        self.prim.state_style().pressed_fill = Some(fill.into());
+        self
+    }
+
     fn remove_stroke(mut self) -> Self {
         self.shape.stroke = None;
         self
@@ -658,6 +1263,21 @@ impl<M: Model> Primitive<M> for GroupBuilder<M> {
         self
     }
 
+    fn user_data(mut self, data: impl Any) -> Self {
+        self.prim.user_data = Some(Box::new(data));
+        self
+    }
+
+    fn capture_mouse(mut self) -> Self {
+        self.prim.capture_mouse = true;
+        self
+    }
+
+    fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.prim.cursor = Some(cursor);
+        self
+    }
+
     fn clip(
         mut self,
         x: impl Into<RealValue>,
@@ -680,14 +1300,43 @@ impl<M: Model> EventHandler<M> for GroupBuilder<M> {
     }
 }
 
+/// The list which materializes only the rows in the viewport of the `list`, see `VirtualList`.
+pub fn virtual_list<M: Model>(list: &VirtualList, build_item: impl Fn(usize) -> Node<M>) -> Node<M> {
+    list.build_view(build_item)
+}
+
 pub fn comp(model: impl Model) -> CompBuilder {
     CompBuilder { comp: Comp::new(model) }
 }
 
+/// The nested component created from the properties, see `Comp::with_props`.
+pub fn comp_with_props<C: Model>(props: C::Properties) -> CompBuilder
+where
+    C::Properties: Clone,
+{
+    CompBuilder {
+        comp: Comp::with_props::<C>(props),
+    }
+}
+
+pub fn cloneable_comp(model: impl Model + Clone) -> CompBuilder {
+    CompBuilder {
+        comp: Comp::cloneable(model),
+    }
+}
+
 pub struct CompBuilder {
     comp: Comp,
 }
 
+impl CompBuilder {
+    /// Makes the component an error boundary with the fallback view, see `Comp::with_fallback`.
+    pub fn fallback<C: Model>(mut self, fallback: impl Fn(&CompError) -> Node<C> + 'static) -> Self {
+        self.comp = self.comp.with_fallback(fallback);
+        self
+    }
+}
+
 impl<M: Model> Builder<M> for CompBuilder {
     fn build(self) -> Node<M> {
         Node::Comp(self.comp)
@@ -1,25 +1,36 @@
 use std::{
+    any::Any,
     borrow::Cow,
     error::Error,
+    iter,
     path::Path,
+    sync::Mutex,
     thread,
     time::{Duration, Instant},
 };
 
-use engel_core::{controller, Color, Comp, KeyboardController, MouseController, Real, Render, SystemMessage};
+use engel_core::{
+    controller, provide_context, take_exit_request, Color, Comp, FixedClock, FixedTimestep, GlobalHotkeys, InputEvent,
+    KeyboardController, MessageBus, MouseController, MousePos, Real, Render, StatsCounter, SystemMessage, SystemTheme,
+    TouchController,
+};
 pub use gl;
 pub use glutin::{
     self,
     dpi::Pixel,
     event_loop::ControlFlow,
     monitor::{MonitorHandle, VideoMode},
-    window::{BadIcon, Fullscreen, Icon, WindowBuilder},
+    window::{BadIcon, CursorIcon, Fullscreen, Icon, Theme, Window, WindowBuilder},
     Api, Context, ContextBuilder, ContextError, CreationError, GlProfile, GlRequest, NotCurrent, Robustness, GL_CORE,
 };
 use glutin::{
     dpi::{LogicalSize, PhysicalSize},
-    event::{ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+    event::{
+        DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
     event_loop::EventLoop,
+    window::WindowId,
     PossiblyCurrent, WindowedContext,
 };
 use thiserror::Error;
@@ -107,6 +118,12 @@ pub enum AppState {
     Continue,
 }
 
+/// Decides whether the key event closes the app.
+type ExitWhen = dyn Fn(&controller::KeyboardEvent) -> bool;
+
+/// Adds a resource to the root component before the app runs.
+type AddResource = dyn FnOnce(&mut Comp);
+
 struct Font<'a> {
     name: Cow<'a, str>,
     path: Cow<'a, Path>,
@@ -117,8 +134,15 @@ pub struct App<'a, R> {
     context_builder: ContextBuilder<'a, NotCurrent>,
     renderer: R,
     background_color: Color,
-    exit_by_escape: bool,
+    exit_when: Option<Box<ExitWhen>>,
     font: Option<Font<'a>>,
+    global_hotkeys: GlobalHotkeys,
+    fixed_clock: Option<FixedClock>,
+    fixed_update: Option<FixedTimestep>,
+    stats: Option<StatsCounter>,
+    double_click_interval: Option<Duration>,
+    contexts: Vec<Box<dyn FnOnce()>>,
+    resources: Vec<Box<AddResource>>,
 }
 
 impl<'a, R: Render + 'static> App<'a, R> {
@@ -129,8 +153,15 @@ impl<'a, R: Render + 'static> App<'a, R> {
             context_builder: ContextBuilder::new(),
             renderer,
             background_color: Color::RGBA(0.8, 0.8, 0.8, 1.0),
-            exit_by_escape: true,
+            exit_when: Some(Box::new(is_escape)),
             font: None,
+            global_hotkeys: GlobalHotkeys::new(),
+            fixed_clock: None,
+            fixed_update: None,
+            stats: None,
+            double_click_interval: None,
+            contexts: Vec::new(),
+            resources: Vec::new(),
         }
     }
 
@@ -427,7 +458,15 @@ impl<'a, R: Render + 'static> App<'a, R> {
 
     #[inline]
     pub fn with_exit_by_escape(mut self, exit: bool) -> Self {
-        self.exit_by_escape = exit;
+        self.exit_when = if exit { Some(Box::new(is_escape)) } else { None };
+        self
+    }
+
+    /// Sets the key presses which close the window, Escape by default. Like the close
+    /// button they ask `Model::close_requested` of the root component first.
+    #[inline]
+    pub fn with_exit_when(mut self, exit: impl Fn(&controller::KeyboardEvent) -> bool + 'static) -> Self {
+        self.exit_when = Some(Box::new(exit));
         self
     }
 
@@ -440,6 +479,64 @@ impl<'a, R: Render + 'static> App<'a, R> {
         self
     }
 
+    /// Sets the hotkeys which send `SystemMessage::Hotkey`, on Windows and X11 even
+    /// when the window is unfocused, see `GlobalHotkeys`.
+    #[inline]
+    pub fn with_global_hotkeys(mut self, hotkeys: GlobalHotkeys) -> Self {
+        self.global_hotkeys = hotkeys;
+        self
+    }
+
+    /// Enables the deterministic mode: the component gets the `SystemMessage::Draw`
+    /// only with the fixed step, as many times as the steps are due by the virtual
+    /// clock, so the runs with the same inputs produce the same model states.
+    #[inline]
+    pub fn with_fixed_step(mut self, step: Duration) -> Self {
+        self.fixed_clock = Some(FixedClock::new(step));
+        self
+    }
+
+    /// Sends `SystemMessage::Update` with the fixed step at the fixed rate and
+    /// `SystemMessage::Interpolate` before each `SystemMessage::Draw`, see `FixedTimestep`.
+    #[inline]
+    pub fn with_update_rate(mut self, step: Duration) -> Self {
+        self.fixed_update = Some(FixedTimestep::new(step));
+        self
+    }
+
+    /// Sends `SystemMessage::Stats` to the root component with the interval, e.g. for the FPS counter.
+    #[inline]
+    pub fn with_stats(mut self, interval: Duration) -> Self {
+        self.stats = Some(StatsCounter::new(interval));
+        self
+    }
+
+    /// Sets the max time between the presses of a double click, 500 ms by default.
+    #[inline]
+    pub fn with_double_click_interval(mut self, interval: Duration) -> Self {
+        self.double_click_interval = Some(interval);
+        self
+    }
+
+    /// Provides the shared value to the models before the root component is created,
+    /// see `engel_core::provide_context`.
+    #[inline]
+    pub fn with_context<T: Any>(mut self, value: T) -> Self {
+        self.contexts.push(Box::new(move || {
+            provide_context(value);
+        }));
+        self
+    }
+
+    /// Inserts the resource into the root component, see `Comp::insert_resource`.
+    #[inline]
+    pub fn with_resource<T: Any>(mut self, value: T) -> Self {
+        self.resources.push(Box::new(move |comp| {
+            comp.insert_resource(value);
+        }));
+        self
+    }
+
     #[inline]
     pub fn renderer(&self) -> &R {
         &self.renderer
@@ -460,130 +557,568 @@ impl<'a, R: Render + 'static> App<'a, R> {
         comp: impl Into<Comp>,
         mut redraw_hook: impl FnMut(&mut Comp, &WindowedContext<PossiblyCurrent>, &mut R) -> AppState + 'static,
     ) -> Result<(), AppError<R::Error>> {
-        let mut comp = comp.into();
         let App {
             window_builder,
             context_builder,
-            mut renderer,
+            renderer,
             background_color,
-            exit_by_escape,
+            exit_when,
             font,
+            mut global_hotkeys,
+            fixed_clock,
+            fixed_update,
+            stats,
+            double_click_interval,
+            contexts,
+            resources,
         } = self;
-
-        let event_loop = EventLoop::new();
-        let context = context_builder.build_windowed(window_builder, &event_loop)?;
-        let context = unsafe { context.make_current().map_err(|(_, err)| err)? };
-
-        unsafe {
-            gl::load_with(|symbol| context.get_proc_address(symbol) as *const _);
-            let color = background_color.as_arr();
-            gl::ClearColor(color[0], color[1], color[2], color[3]);
+        for provide in contexts {
+            provide();
         }
-
-        let size = context.window().inner_size();
-        renderer.set_dimensions(size.width, size.height, context.window().scale_factor());
-        renderer.init(self.background_color).map_err(AppError::RendererError)?;
-        if let Some(Font { name, path }) = font {
-            renderer.load_font(name, path).map_err(AppError::RendererError)?;
+        let mut comp = comp.into();
+        for insert in resources {
+            insert(&mut comp);
         }
 
-        let mut mouse_controller = MouseController::new();
-        let keyboard_controller = KeyboardController::new();
-        let mut last_time = Instant::now();
+        let event_loop = EventLoop::with_user_event();
+        let settings = WindowSettings {
+            background_color,
+            font,
+            double_click_interval,
+            fixed_clock,
+            fixed_update,
+            stats,
+        };
+        let context = context_builder.build_windowed(window_builder, &event_loop)?;
+        let mut window = AppWindowState::new(context, renderer, comp, &settings, &event_loop)?;
 
         event_loop.run(move |event, _, control_flow| {
             *control_flow = ControlFlow::Poll;
 
             match event {
-                Event::LoopDestroyed => (),
-                Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::Resized(size) => {
-                        context.resize(size);
-                        comp.send_system_msg(SystemMessage::WindowResized {
-                            width: size.width,
-                            height: size.height,
-                        });
-                    },
-                    WindowEvent::CloseRequested => {
+                Event::LoopDestroyed => window.comp.shutdown(),
+                Event::Suspended => window.comp.send_system_msg(SystemMessage::Suspended),
+                Event::Resumed => window.comp.send_system_msg(SystemMessage::Resumed),
+                Event::WindowEvent { event, .. } => {
+                    let exit = window.handle_event(event, exit_when.as_deref());
+                    if exit {
                         *control_flow = ControlFlow::Exit;
-                    },
-                    WindowEvent::ReceivedCharacter(ch) => {
-                        keyboard_controller.input_char(&mut comp, ch);
-                    },
-                    WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } if exit_by_escape => {
-                        *control_flow = ControlFlow::Exit;
-                    },
-                    WindowEvent::KeyboardInput { input, .. } => {
-                        let KeyboardInput {
-                            scancode,
-                            state,
-                            virtual_keycode,
-                            ..
-                        } = input;
-                        if let ElementState::Pressed = state {
-                            keyboard_controller
-                                .pressed_comp(&mut comp, convert_keyboard_event(scancode, virtual_keycode));
-                        } else {
-                            keyboard_controller
-                                .released_comp(&mut comp, convert_keyboard_event(scancode, virtual_keycode));
-                        }
-                    },
-                    WindowEvent::CursorMoved { position, .. } => {
-                        mouse_controller.update_pos(position.x as Real, position.y as Real);
-                    },
-                    WindowEvent::MouseInput {
-                        state: ElementState::Pressed,
-                        button,
-                        ..
-                    } => {
-                        mouse_controller.pressed_comp(&mut comp, convert_mouse_button(button));
-                    },
-                    WindowEvent::MouseWheel {
-                        delta: MouseScrollDelta::LineDelta(x, y),
-                        ..
-                    } => {
-                        mouse_controller.mouse_scroll(&mut comp, (x, y));
-                    },
-                    _ => (),
+                    }
+                },
+                Event::DeviceEvent {
+                    event: DeviceEvent::Key(input),
+                    ..
+                } => handle_global_hotkey(&mut global_hotkeys, iter::once(&mut window.comp), input),
+                Event::UserEvent(()) => {
+                    window.comp.receive_events();
                 },
                 Event::MainEventsCleared => {
-                    context.window().request_redraw();
+                    window.context().window().request_redraw();
                 },
                 Event::RedrawRequested(_) => {
-                    let size = context.window().inner_size();
-                    unsafe {
-                        gl::Viewport(0, 0, size.width as i32, size.height as i32);
-                        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
-                    }
-
-                    if let AppState::Exit = redraw_hook(&mut comp, &context, &mut renderer) {
+                    window.clear();
+                    let context = window.context.as_ref().expect("Window context is lost");
+                    if let AppState::Exit = redraw_hook(&mut window.comp, context, &mut window.renderer) {
                         *control_flow = ControlFlow::Exit;
                         return;
                     }
 
-                    let elapsed = last_time.elapsed();
-                    last_time = Instant::now();
-                    comp.send_system_msg(SystemMessage::Draw(elapsed));
-                    if comp.update_view().is_some() {
-                        renderer.set_dimensions(size.width, size.height, context.window().scale_factor());
-                        if renderer.render(&mut comp).expect("Renderer error") {
-                            context.swap_buffers().expect("Swap buffers fail");
-                        }
-                    } else {
+                    if !window.draw_frame() {
                         thread::sleep(Duration::from_millis(10));
                     }
                 },
                 _ => (),
             }
+
+            if take_exit_request() {
+                *control_flow = ControlFlow::Exit;
+            }
         })
     }
+
+    /// Runs the windows with their root components in one event loop, e.g. with
+    /// the tool palettes and the inspector windows. The first window gets the
+    /// renderer and the resources of the app, the others get the renderers made
+    /// by `new_renderer`. The global hotkeys are sent to all the root components. The root components share the
+    /// `MessageBus` resource, whose events are delivered to all of them. The app
+    /// exits when all the windows are closed.
+    pub fn run_windows(
+        self,
+        windows: Vec<AppWindow>,
+        mut new_renderer: impl FnMut() -> R,
+    ) -> Result<(), AppError<R::Error>> {
+        let App {
+            context_builder,
+            renderer,
+            background_color,
+            exit_when,
+            font,
+            mut global_hotkeys,
+            fixed_clock,
+            fixed_update,
+            stats,
+            double_click_interval,
+            contexts,
+            resources,
+            ..
+        } = self;
+        for provide in contexts {
+            provide();
+        }
+
+        let event_loop = EventLoop::with_user_event();
+        let settings = WindowSettings {
+            background_color,
+            font,
+            double_click_interval,
+            fixed_clock,
+            fixed_update,
+            stats,
+        };
+        let bus = MessageBus::new();
+        let mut renderer = Some(renderer);
+        let mut resources = Some(resources);
+        let mut states = Vec::with_capacity(windows.len());
+        for AppWindow { builder, mut comp } in windows {
+            for insert in resources.take().into_iter().flatten() {
+                insert(&mut comp);
+            }
+            bus.subscribe(&mut comp);
+            comp.insert_resource(bus.clone());
+
+            let renderer = renderer.take().unwrap_or_else(&mut new_renderer);
+            let context = context_builder.clone().build_windowed(builder, &event_loop)?;
+            states.push(AppWindowState::new(context, renderer, comp, &settings, &event_loop)?);
+        }
+
+        event_loop.run(move |event, _, control_flow| {
+            *control_flow = ControlFlow::Poll;
+
+            match event {
+                Event::LoopDestroyed => {
+                    for window in &mut states {
+                        window.comp.shutdown();
+                    }
+                },
+                Event::Suspended | Event::Resumed => {
+                    let msg = match event {
+                        Event::Suspended => SystemMessage::Suspended,
+                        _ => SystemMessage::Resumed,
+                    };
+                    for window in &mut states {
+                        window.comp.send_system_msg(msg.clone());
+                    }
+                },
+                Event::WindowEvent { window_id, event } => {
+                    if let Some(idx) = states.iter().position(|window| window.id() == window_id) {
+                        states[idx].make_current().expect("Make context current fail");
+                        if states[idx].handle_event(event, exit_when.as_deref()) {
+                            states.remove(idx).comp.shutdown();
+                            if states.is_empty() {
+                                *control_flow = ControlFlow::Exit;
+                            }
+                        }
+                    }
+                },
+                Event::DeviceEvent {
+                    event: DeviceEvent::Key(input),
+                    ..
+                } => {
+                    let comps = states.iter_mut().map(|window| &mut window.comp);
+                    handle_global_hotkey(&mut global_hotkeys, comps, input);
+                },
+                Event::UserEvent(()) => {
+                    for window in &mut states {
+                        window.comp.receive_events();
+                    }
+                },
+                Event::MainEventsCleared => {
+                    for window in &states {
+                        window.context().window().request_redraw();
+                    }
+                },
+                Event::RedrawRequested(window_id) => {
+                    if let Some(window) = states.iter_mut().find(|window| window.id() == window_id) {
+                        window.make_current().expect("Make context current fail");
+                        window.clear();
+                        if !window.draw_frame() {
+                            thread::sleep(Duration::from_millis(10) / states.len() as u32);
+                        }
+                    }
+                },
+                _ => (),
+            }
+
+            if take_exit_request() {
+                *control_flow = ControlFlow::Exit;
+            }
+        })
+    }
+}
+
+/// The window of the multi-window app with its root component, see `App::run_windows`.
+pub struct AppWindow {
+    builder: WindowBuilder,
+    comp: Comp,
+}
+
+impl AppWindow {
+    pub fn new(builder: WindowBuilder, comp: impl Into<Comp>) -> Self {
+        Self {
+            builder,
+            comp: comp.into(),
+        }
+    }
+}
+
+/// The settings of the app shared by its windows.
+struct WindowSettings<'a> {
+    background_color: Color,
+    font: Option<Font<'a>>,
+    double_click_interval: Option<Duration>,
+    fixed_clock: Option<FixedClock>,
+    fixed_update: Option<FixedTimestep>,
+    stats: Option<StatsCounter>,
+}
+
+/// The window with its root component, renderer and input controllers.
+struct AppWindowState<R> {
+    /// It is taken only while the context is made current.
+    context: Option<WindowedContext<PossiblyCurrent>>,
+    renderer: R,
+    comp: Comp,
+    mouse_controller: MouseController,
+    keyboard_controller: KeyboardController,
+    touch_controller: TouchController,
+    cursor: controller::CursorIcon,
+    fixed_clock: Option<FixedClock>,
+    fixed_update: Option<FixedTimestep>,
+    stats: Option<StatsCounter>,
+    last_time: Instant,
+}
+
+impl<R: Render> AppWindowState<R> {
+    fn new(
+        context: WindowedContext<NotCurrent>,
+        mut renderer: R,
+        mut comp: Comp,
+        settings: &WindowSettings,
+        event_loop: &EventLoop<()>,
+    ) -> Result<Self, AppError<R::Error>> {
+        let proxy = Mutex::new(event_loop.create_proxy());
+        comp.set_waker(move || {
+            let _ = proxy.lock().unwrap().send_event(());
+        });
+        let context = unsafe { context.make_current().map_err(|(_, err)| err)? };
+
+        unsafe {
+            gl::load_with(|symbol| context.get_proc_address(symbol) as *const _);
+            let color = settings.background_color.as_arr();
+            gl::ClearColor(color[0], color[1], color[2], color[3]);
+        }
+
+        let size = context.window().inner_size();
+        renderer.set_dimensions(size.width, size.height, context.window().scale_factor());
+        renderer
+            .init(settings.background_color)
+            .map_err(AppError::RendererError)?;
+        if let Some(Font { name, path }) = settings.font.as_ref() {
+            renderer.load_font(name, path).map_err(AppError::RendererError)?;
+        }
+
+        // Only Windows reports the preference, it also matches the titlebar to it.
+        #[cfg(target_os = "windows")]
+        {
+            use glutin::platform::windows::WindowExtWindows;
+
+            let theme = convert_theme(context.window().theme());
+            comp.send_system_msg(SystemMessage::ThemeChanged(theme));
+        }
+
+        let mut mouse_controller = MouseController::new();
+        if let Some(interval) = settings.double_click_interval {
+            mouse_controller = mouse_controller.with_double_click_interval(interval);
+        }
+
+        Ok(Self {
+            context: Some(context),
+            renderer,
+            comp,
+            mouse_controller,
+            keyboard_controller: KeyboardController::new(),
+            touch_controller: TouchController::new(),
+            cursor: controller::CursorIcon::Default,
+            fixed_clock: settings.fixed_clock,
+            fixed_update: settings.fixed_update,
+            stats: settings.stats,
+            last_time: Instant::now(),
+        })
+    }
+
+    fn context(&self) -> &WindowedContext<PossiblyCurrent> {
+        self.context.as_ref().expect("Window context is lost")
+    }
+
+    fn id(&self) -> WindowId {
+        self.context().window().id()
+    }
+
+    /// Makes the GL context of the window current before it is drawn or resized.
+    fn make_current(&mut self) -> Result<(), ContextError> {
+        let context = self.context.take().expect("Window context is lost");
+        if context.is_current() {
+            self.context = Some(context);
+            return Ok(());
+        }
+
+        match unsafe { context.make_current() } {
+            Ok(context) => {
+                self.context = Some(context);
+                Ok(())
+            },
+            Err((context, err)) => {
+                self.context = Some(context);
+                Err(err)
+            },
+        }
+    }
+
+    /// Passes the event to the component, returns `true` if the window is to be closed.
+    fn handle_event(&mut self, event: WindowEvent, exit_when: Option<&ExitWhen>) -> bool {
+        let comp = &mut self.comp;
+        match event {
+            WindowEvent::Resized(size) => {
+                self.context().resize(size);
+                self.comp.send_system_msg(SystemMessage::WindowResized {
+                    width: size.width,
+                    height: size.height,
+                });
+            },
+            WindowEvent::Moved(position) => {
+                comp.send_system_msg(SystemMessage::WindowMoved {
+                    x: position.x,
+                    y: position.y,
+                });
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                comp.send_system_msg(SystemMessage::ScaleFactorChanged(scale_factor));
+            },
+            WindowEvent::Focused(focused) => {
+                if !focused {
+                    self.keyboard_controller.focus_lost();
+                }
+                comp.send_system_msg(SystemMessage::WindowFocused(focused));
+            },
+            WindowEvent::HoveredFile(path) => {
+                comp.send_system_msg(SystemMessage::Input(InputEvent::FileHovered(path)));
+            },
+            WindowEvent::HoveredFileCancelled => {
+                comp.send_system_msg(SystemMessage::Input(InputEvent::FileHoverCancelled));
+            },
+            WindowEvent::DroppedFile(path) => {
+                comp.send_system_msg(SystemMessage::Input(InputEvent::FileDropped(path)));
+            },
+            WindowEvent::ThemeChanged(theme) => {
+                comp.send_system_msg(SystemMessage::ThemeChanged(convert_theme(theme)));
+            },
+            WindowEvent::CloseRequested => return comp.close_requested(),
+            WindowEvent::ReceivedCharacter(ch) => {
+                self.keyboard_controller.input_char(comp, ch);
+            },
+            WindowEvent::KeyboardInput { input, .. } => {
+                let KeyboardInput {
+                    scancode,
+                    state,
+                    virtual_keycode,
+                    ..
+                } = input;
+                let event = convert_keyboard_event(scancode, virtual_keycode);
+                if exit_when.is_some_and(|exit| exit(&event)) {
+                    return state == ElementState::Pressed && comp.close_requested();
+                } else if let ElementState::Pressed = state {
+                    self.keyboard_controller.pressed_comp(comp, event);
+                } else {
+                    self.keyboard_controller.released_comp(comp, event);
+                }
+            },
+            WindowEvent::Touch(Touch {
+                phase,
+                location,
+                force,
+                id,
+                ..
+            }) => {
+                let phase = match phase {
+                    TouchPhase::Started => controller::TouchPhase::Start,
+                    TouchPhase::Moved => controller::TouchPhase::Move,
+                    TouchPhase::Ended | TouchPhase::Cancelled => controller::TouchPhase::End,
+                };
+                let pos = MousePos {
+                    x: location.x as Real,
+                    y: location.y as Real,
+                };
+                let mut touch = controller::Touch::new(id, pos);
+                if let Some(force) = force {
+                    touch = touch.with_force(force.normalized() as Real);
+                }
+                self.touch_controller.touch_comp(comp, phase, touch);
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_controller.update_pos(position.x as Real, position.y as Real);
+                self.mouse_controller.moved_comp(comp);
+                self.update_cursor();
+            },
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => {
+                self.mouse_controller.pressed_comp(comp, convert_mouse_button(button));
+                self.update_cursor();
+            },
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } => {
+                self.mouse_controller.released_comp(comp, convert_mouse_button(button));
+                self.update_cursor();
+            },
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(x, y),
+                ..
+            } => {
+                self.mouse_controller.mouse_scroll(comp, (x, y));
+            },
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::PixelDelta(position),
+                ..
+            } => {
+                self.mouse_controller
+                    .pixel_scroll(comp, (position.x as f32, position.y as f32));
+            },
+            _ => (),
+        }
+        false
+    }
+
+    /// Shows the cursor of the prim under the mouse, the window cursor is changed only when it differs.
+    fn update_cursor(&mut self) {
+        let pos = self.mouse_controller.last_pos();
+        let cursor = self.comp.cursor(pos.x, pos.y);
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            self.context().window().set_cursor_icon(convert_cursor(cursor));
+        }
+    }
+
+    fn clear(&self) {
+        let size = self.context().window().inner_size();
+        unsafe {
+            gl::Viewport(0, 0, size.width as i32, size.height as i32);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        }
+    }
+
+    /// Sends the frame messages to the component and renders its view if it is changed,
+    /// returns `false` if nothing is rendered.
+    fn draw_frame(&mut self) -> bool {
+        let elapsed = self.last_time.elapsed();
+        self.last_time = Instant::now();
+        let comp = &mut self.comp;
+        if let Some(timestep) = self.fixed_update.as_mut() {
+            timestep.advance_comp(comp, elapsed);
+        }
+        match self.fixed_clock.as_mut() {
+            Some(clock) => {
+                for _ in 0..clock.advance(elapsed) {
+                    comp.send_system_msg(SystemMessage::Draw(clock.step()));
+                }
+            },
+            None => comp.send_system_msg(SystemMessage::Draw(elapsed)),
+        }
+        comp.send_failures();
+
+        let mut draw_calls = None;
+        let rendered = comp.update_view().is_some();
+        if rendered {
+            let context = self.context.as_ref().expect("Window context is lost");
+            let size = context.window().inner_size();
+            #[cfg(feature = "tracing")]
+            let _span = engel_core::tracing::trace_span!("render", size.width, size.height).entered();
+            self.renderer
+                .set_dimensions(size.width, size.height, context.window().scale_factor());
+            if self.renderer.render(comp).expect("Renderer error") {
+                context.swap_buffers().expect("Swap buffers fail");
+                draw_calls = self.renderer.draw_calls();
+            }
+        }
+        if let Some(stats) = self.stats.as_mut() {
+            stats.frame_comp(comp, elapsed, draw_calls);
+        }
+        rendered
+    }
+}
+
+/// Passes the raw device keys to the global hotkeys and sends the triggered ones to the components.
+fn handle_global_hotkey<'a>(
+    global_hotkeys: &mut GlobalHotkeys,
+    comps: impl IntoIterator<Item = &'a mut Comp>,
+    input: KeyboardInput,
+) {
+    if let KeyboardInput {
+        virtual_keycode: Some(keycode),
+        state,
+        ..
+    } = input
+    {
+        if let Some(key) = convert_keyboard_event(0, Some(keycode)).keycode {
+            match state {
+                ElementState::Pressed => {
+                    let hotkeys = global_hotkeys.key_pressed(key);
+                    for comp in comps {
+                        for &hotkey in &hotkeys {
+                            comp.send_system_msg(SystemMessage::Hotkey(hotkey));
+                        }
+                    }
+                },
+                ElementState::Released => global_hotkeys.key_released(key),
+            }
+        }
+    }
+}
+
+fn convert_cursor(cursor: controller::CursorIcon) -> CursorIcon {
+    match cursor {
+        controller::CursorIcon::Default => CursorIcon::Default,
+        controller::CursorIcon::Pointer => CursorIcon::Hand,
+        controller::CursorIcon::Text => CursorIcon::Text,
+        controller::CursorIcon::Crosshair => CursorIcon::Crosshair,
+        controller::CursorIcon::Move => CursorIcon::Move,
+        controller::CursorIcon::Grab => CursorIcon::Grab,
+        controller::CursorIcon::Grabbing => CursorIcon::Grabbing,
+        controller::CursorIcon::NotAllowed => CursorIcon::NotAllowed,
+        controller::CursorIcon::Wait => CursorIcon::Wait,
+        controller::CursorIcon::Progress => CursorIcon::Progress,
+        controller::CursorIcon::Help => CursorIcon::Help,
+        controller::CursorIcon::EwResize => CursorIcon::EwResize,
+        controller::CursorIcon::NsResize => CursorIcon::NsResize,
+        controller::CursorIcon::NeswResize => CursorIcon::NeswResize,
+        controller::CursorIcon::NwseResize => CursorIcon::NwseResize,
+        controller::CursorIcon::ColResize => CursorIcon::ColResize,
+        controller::CursorIcon::RowResize => CursorIcon::RowResize,
+    }
+}
+
+fn convert_theme(theme: Theme) -> SystemTheme {
+    match theme {
+        Theme::Light => SystemTheme::Light,
+        Theme::Dark => SystemTheme::Dark,
+    }
+}
+
+fn is_escape(event: &controller::KeyboardEvent) -> bool {
+    event.keycode == Some(controller::VirtualKeyCode::Escape)
 }
 
 fn convert_keyboard_event(scancode: u32, keycode: Option<VirtualKeyCode>) -> controller::KeyboardEvent {
@@ -752,7 +1287,7 @@ fn convert_keyboard_event(scancode: u32, keycode: Option<VirtualKeyCode>) -> con
         VirtualKeyCode::Paste => controller::VirtualKeyCode::Paste,
         VirtualKeyCode::Cut => controller::VirtualKeyCode::Cut,
     });
-    controller::KeyboardEvent { scancode, keycode }
+    controller::KeyboardEvent::new(scancode, keycode)
 }
 
 fn convert_mouse_button(button: MouseButton) -> controller::MouseButton {
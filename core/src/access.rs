@@ -0,0 +1,27 @@
+/// The action the assistive technology, e.g. a screen reader, performs on a node
+/// instead of the pointer and the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessAction {
+    /// The default action, like the click on a button.
+    Activate,
+    Focus,
+    ScrollIntoView,
+}
+
+/// The assistive technology action on the prim with the id, sent by the
+/// controller as `SystemMessage::Access` and passed to the `on_access_action`
+/// listeners of the prim.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccessRequest {
+    pub target: String,
+    pub action: AccessAction,
+}
+
+impl AccessRequest {
+    pub fn new(target: impl Into<String>, action: AccessAction) -> Self {
+        Self {
+            target: target.into(),
+            action,
+        }
+    }
+}
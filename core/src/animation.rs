@@ -3,6 +3,8 @@ use std::{
     time::Duration,
 };
 
+use crate::{Color, ColorSpace, Real};
+
 #[derive(Debug, Default)]
 pub struct Animate<T> {
     current: T,
@@ -66,3 +68,379 @@ impl<T> Deref for Animate<T> {
         &self.current
     }
 }
+
+/// The values interpolated by the animations, `t` is from 0 to 1 but may
+/// overshoot with the easings like `Easing::BackOut`.
+pub trait Lerp: Copy {
+    fn lerp(self, to: Self, t: Real) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: Real) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(self, to: Self, t: Real) -> Self {
+        self + (to - self) * t as f64
+    }
+}
+
+impl<A: Lerp, B: Lerp> Lerp for (A, B) {
+    fn lerp(self, to: Self, t: Real) -> Self {
+        (self.0.lerp(to.0, t), self.1.lerp(to.1, t))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, to: Self, t: Real) -> Self {
+        self.mix(to, t, ColorSpace::Srgb)
+    }
+}
+
+/// The rate of change of a value over the time of a transition.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Overshoots the target a bit and settles back to it.
+    BackOut,
+    /// Holds the start value until the end of the transition.
+    Step,
+}
+
+impl Easing {
+    /// Maps the progress of the transition from 0 to 1 to the eased one.
+    pub fn apply(self, t: Real) -> Real {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            },
+            Easing::BackOut => {
+                const OVERSHOOT: Real = 1.70158;
+                let t = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * t * t * t + OVERSHOOT * t * t
+            },
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            },
+        }
+    }
+}
+
+/// The stop of the keyframe animation, reached at the time from the start by
+/// the transition with the easing from the previous stop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: Duration,
+    pub value: T,
+    pub easing: Easing,
+}
+
+/// The animation of a value through the sequence of the stops, e.g. a banner
+/// sliding in, overshooting and then settling, declared in one object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes<T> {
+    frames: Vec<Keyframe<T>>,
+    current: T,
+    elapsed: Duration,
+    looping: bool,
+}
+
+impl<T: Lerp> Keyframes<T> {
+    /// Creates the animation starting with the value.
+    pub fn new(start: T) -> Self {
+        Self {
+            frames: vec![Keyframe {
+                time: Duration::default(),
+                value: start,
+                easing: Easing::Linear,
+            }],
+            current: start,
+            elapsed: Duration::default(),
+            looping: false,
+        }
+    }
+
+    /// Adds the stop reached at the time from the start, the stops are kept ordered by the time.
+    pub fn key(mut self, time: Duration, value: T, easing: Easing) -> Self {
+        let idx = self.frames.iter().take_while(|frame| frame.time <= time).count();
+        self.frames.insert(idx, Keyframe { time, value, easing });
+        self.current = self.value_at(self.elapsed);
+        self
+    }
+
+    /// Restarts the animation from the first stop after the last one.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn frames(&self) -> &[Keyframe<T>] {
+        &self.frames
+    }
+
+    /// The time of the last stop.
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map(|frame| frame.time).unwrap_or_default()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn val(&self) -> T {
+        self.current
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.elapsed < self.duration()
+    }
+
+    /// The value at the time from the start.
+    pub fn value_at(&self, time: Duration) -> T {
+        let next = self.frames.iter().position(|frame| frame.time > time);
+        match next {
+            Some(0) => self.frames[0].value,
+            Some(idx) => {
+                let (from, to) = (&self.frames[idx - 1], &self.frames[idx]);
+                let span = (to.time - from.time).as_secs_f32();
+                let t = (time - from.time).as_secs_f32() / span;
+                from.value.lerp(to.value, to.easing.apply(t))
+            },
+            None => self.frames[self.frames.len() - 1].value,
+        }
+    }
+
+    /// Moves the animation to the time from the start.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time.min(self.duration());
+        self.current = self.value_at(self.elapsed);
+    }
+
+    pub fn restart(&mut self) {
+        self.seek(Duration::default());
+    }
+
+    pub fn animate(&mut self, elapsed: Duration) {
+        let duration = self.duration();
+        let mut time = self.elapsed + elapsed;
+        if self.looping && duration > Duration::default() {
+            while time >= duration {
+                time -= duration;
+            }
+        }
+        self.seek(time);
+    }
+}
+
+impl<T> Deref for Keyframes<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+/// The animation of a value pulled to the target by a damped spring. Unlike
+/// `Animate` it keeps the velocity when the target changes, so the value can be
+/// retargeted in the middle of the motion without a jump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring<T = Real> {
+    current: T,
+    target: T,
+    velocity: T,
+    stiffness: Real,
+    damping: Real,
+}
+
+impl Spring<Real> {
+    pub const DEFAULT_STIFFNESS: Real = 170.0;
+    pub const DEFAULT_DAMPING: Real = 26.0;
+
+    /// The spring is at rest once the distance to the target and the velocity are below it.
+    pub const REST_THRESHOLD: Real = 0.001;
+
+    /// The longest step of the simulation, the longer elapsed times are split into such steps.
+    const MAX_STEP: Real = 1.0 / 120.0;
+
+    pub fn new(start: Real, target: Real) -> Self {
+        Self {
+            current: start,
+            target,
+            velocity: 0.0,
+            stiffness: Self::DEFAULT_STIFFNESS,
+            damping: Self::DEFAULT_DAMPING,
+        }
+    }
+
+    pub fn with_stiffness(mut self, stiffness: Real) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the initial velocity in the units per second.
+    pub fn with_velocity(mut self, velocity: Real) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn val(&self) -> Real {
+        self.current
+    }
+
+    pub fn target(&self) -> Real {
+        self.target
+    }
+
+    pub fn velocity(&self) -> Real {
+        self.velocity
+    }
+
+    /// Jumps to the value and stops the motion.
+    pub fn from(&mut self, start: Real) {
+        self.current = start;
+        self.velocity = 0.0;
+    }
+
+    /// Retargets the spring, keeping the current value and velocity.
+    pub fn to(&mut self, target: Real) {
+        self.target = target;
+    }
+
+    pub fn is_transient(&self) -> bool {
+        (self.target - self.current).abs() >= Self::REST_THRESHOLD || self.velocity.abs() >= Self::REST_THRESHOLD
+    }
+
+    pub fn animate(&mut self, elapsed: Duration) {
+        let mut remaining = elapsed.as_secs_f32();
+        while remaining > 0.0 && self.is_transient() {
+            let dt = remaining.min(Self::MAX_STEP);
+            let force = self.stiffness * (self.target - self.current) - self.damping * self.velocity;
+            self.velocity += force * dt;
+            self.current += self.velocity * dt;
+            remaining -= dt;
+        }
+
+        if !self.is_transient() {
+            self.current = self.target;
+            self.velocity = 0.0;
+        }
+    }
+}
+
+impl<T> Deref for Spring<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+/// The fade of a color to the target over the duration, mixed in the color
+/// space, e.g. the hover highlights. Changing the target starts a new fade
+/// from the current color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorAnimate {
+    start: Color,
+    target: Color,
+    current: Color,
+    elapsed: Duration,
+    duration: Duration,
+    space: ColorSpace,
+    easing: Easing,
+}
+
+impl ColorAnimate {
+    pub fn new(start: Color, target: Color, duration: Duration) -> Self {
+        let mut animate = Self {
+            start,
+            target,
+            current: start,
+            elapsed: Duration::default(),
+            duration,
+            space: ColorSpace::default(),
+            easing: Easing::default(),
+        };
+        animate.animate(Duration::default());
+        animate
+    }
+
+    pub fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn val(&self) -> Color {
+        self.current
+    }
+
+    pub fn target(&self) -> Color {
+        self.target
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Jumps to the color and stops the fade.
+    pub fn from(&mut self, start: Color) {
+        self.start = start;
+        self.current = start;
+        self.elapsed = self.duration;
+        self.target = start;
+    }
+
+    /// Starts the fade from the current color to the target.
+    pub fn to(&mut self, target: Color) {
+        if target != self.target {
+            self.start = self.current;
+            self.target = target;
+            self.elapsed = Duration::default();
+        }
+    }
+
+    pub fn animate(&mut self, elapsed: Duration) {
+        self.elapsed = (self.elapsed + elapsed).min(self.duration);
+        self.current = if self.elapsed >= self.duration {
+            self.target
+        } else {
+            let t = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+            self.start.mix(self.target, self.easing.apply(t), self.space)
+        };
+    }
+}
+
+impl Deref for ColorAnimate {
+    type Target = Color;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
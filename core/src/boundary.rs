@@ -0,0 +1,65 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    error::Error,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{Model, Node};
+
+thread_local! {
+    static FAILURES: RefCell<Vec<CompError>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The fallback view of the failed error boundary, see `Comp::with_fallback`.
+pub type FallbackFn<M> = dyn Fn(&CompError) -> Node<M>;
+
+/// The panic caught by the error boundary in the update or the view code of
+/// the component or of its nested components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompError {
+    /// The type name of the model of the boundary component.
+    pub model: &'static str,
+    pub message: String,
+}
+
+impl CompError {
+    pub(crate) fn from_panic<M: Model>(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).to_string(),
+                Err(_) => "unknown panic".to_string(),
+            },
+        };
+
+        Self {
+            model: std::any::type_name::<M>(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for CompError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "component {} failed: {}", self.model, self.message)
+    }
+}
+
+impl Error for CompError {}
+
+/// Runs the code of the boundary component, catching its panic.
+pub(crate) fn catch_failure<M: Model, R>(f: impl FnOnce() -> R) -> Result<R, CompError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(CompError::from_panic::<M>)
+}
+
+pub(crate) fn report_failure(error: CompError) {
+    FAILURES.with(|failures| failures.borrow_mut().push(error));
+}
+
+/// Takes the errors caught by the boundaries since the last call, which
+/// `Comp::send_failures` delivers as `SystemMessage::CompFailed`.
+pub fn take_failures() -> Vec<CompError> {
+    FAILURES.with(|failures| failures.borrow_mut().drain(..).collect())
+}
@@ -0,0 +1,47 @@
+use std::{
+    any::Any,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Comp, CompSender, CustomEvent};
+
+/// The bus between the root components of the windows of one app, or any
+/// other components. The published events are delivered to all the subscribed
+/// components as `SystemMessage::Custom` by the controller, like the events
+/// of their senders. It can be published to from any thread.
+#[derive(Clone, Default)]
+pub struct MessageBus {
+    subscribers: Arc<Mutex<Vec<CompSender>>>,
+}
+
+impl MessageBus {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn subscribe(&self, comp: &mut Comp) {
+        self.subscribers.lock().unwrap().push(comp.sender());
+    }
+
+    /// Sends the event to all the subscribers, the dropped ones are unsubscribed.
+    pub fn publish(&self, event: impl Any + Send + Sync) {
+        let event = CustomEvent::new(event);
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(event.clone()));
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+
+impl fmt::Debug for MessageBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageBus")
+            .field("subscribers", &self.subscriber_count())
+            .finish()
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+};
+
+thread_local! {
+    static EMITTED: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The callback property of a nested component, which the component calls to
+/// notify its parent. The emitted value is mapped to the message of the parent,
+/// which gets it after the nested component handles the system message.
+pub struct Callback<T> {
+    map: Rc<dyn Fn(T) -> Box<dyn Any>>,
+}
+
+impl<T: 'static> Callback<T> {
+    /// Creates the callback mapping the value to the message of the parent component.
+    pub fn new<Msg: 'static>(map: impl Fn(T) -> Msg + 'static) -> Self {
+        Self {
+            map: Rc::new(move |value| Box::new(map(value))),
+        }
+    }
+
+    pub fn emit(&self, value: T) {
+        let msg = (self.map)(value);
+        EMITTED.with(|emitted| emitted.borrow_mut().push(msg));
+    }
+}
+
+impl<T> Clone for Callback<T> {
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone() }
+    }
+}
+
+impl<T> fmt::Debug for Callback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Marks the component handling a system message, the emitted messages nobody
+/// takes are dropped when the outermost one is done.
+pub(crate) struct Handling;
+
+impl Handling {
+    pub(crate) fn start() -> Self {
+        DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Handling
+    }
+}
+
+impl Drop for Handling {
+    fn drop(&mut self) {
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        if depth == 0 {
+            EMITTED.with(|emitted| emitted.borrow_mut().clear());
+        }
+    }
+}
+
+/// Takes the emitted messages of the type, the others are left to the outer components.
+pub(crate) fn take_emitted<Msg: 'static>() -> Vec<Msg> {
+    EMITTED.with(|emitted| {
+        let mut taken = vec![];
+        let mut rest = vec![];
+        for msg in emitted.borrow_mut().drain(..) {
+            match msg.downcast::<Msg>() {
+                Ok(msg) => taken.push(*msg),
+                Err(msg) => rest.push(msg),
+            }
+        }
+        *emitted.borrow_mut() = rest;
+        taken
+    })
+}
@@ -0,0 +1,231 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use crate::WakeFn;
+
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The async runtime running the future commands, e.g. `|future| { handle.spawn(future); }`
+/// with a tokio runtime handle or `|future| { async_std::task::spawn(future); }`.
+/// Without a spawner each future runs on its own thread.
+pub trait Spawner {
+    fn spawn(&self, future: BoxFuture);
+}
+
+impl<F: Fn(BoxFuture)> Spawner for F {
+    fn spawn(&self, future: BoxFuture) {
+        self(future)
+    }
+}
+
+type Output = Box<dyn Any + Send>;
+
+enum Run {
+    Blocking(Box<dyn FnOnce() -> Output + Send>),
+    Future(Pin<Box<dyn Future<Output = Output> + Send>>),
+}
+
+/// The work to run off the UI thread, e.g. the HTTP request, the file IO or a long
+/// computation, returned by `Model::commands`. Its result is sent back to the
+/// `update` of the model as the message.
+pub struct Command<Msg> {
+    run: Run,
+    _msg: PhantomData<fn() -> Msg>,
+}
+
+impl<Msg: Send + 'static> Command<Msg> {
+    /// The command run on its own thread.
+    pub fn new(run: impl FnOnce() -> Msg + Send + 'static) -> Self {
+        Self {
+            run: Run::Blocking(Box::new(move || Box::new(run()))),
+            _msg: PhantomData,
+        }
+    }
+
+    /// The command run by the spawner of the component. The future is dropped
+    /// unfinished when the component is shut down or dropped: the waiting task is
+    /// woken to finish without the result.
+    pub fn future(future: impl Future<Output = Msg> + Send + 'static) -> Self {
+        Self {
+            run: Run::Future(Box::pin(async move { Box::new(future.await) as Output })),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<Msg> fmt::Debug for Command<Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.run {
+            Run::Blocking(_) => f.write_str("Command::Blocking"),
+            Run::Future(_) => f.write_str("Command::Future"),
+        }
+    }
+}
+
+/// Runs the commands of a component and collects their results.
+pub(crate) struct CommandRunner {
+    sender: Sender<Output>,
+    receiver: Receiver<Output>,
+    spawner: Option<Rc<dyn Spawner>>,
+    waker: WakeFn,
+    shutdown: Arc<Shutdown>,
+    next_task_id: u64,
+}
+
+impl CommandRunner {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            spawner: None,
+            waker: Default::default(),
+            shutdown: Default::default(),
+            next_task_id: 0,
+        }
+    }
+
+    pub(crate) fn set_spawner(&mut self, spawner: Rc<dyn Spawner>) {
+        self.spawner = Some(spawner);
+    }
+
+    pub(crate) fn set_waker(&mut self, waker: WakeFn) {
+        self.waker = waker;
+    }
+
+    pub(crate) fn run<Msg>(&mut self, command: Command<Msg>) {
+        let sender = self.sender.clone();
+        let waker = self.waker.clone();
+        let send = move |result| {
+            if sender.send(result).is_ok() {
+                if let Some(wake) = waker.lock().unwrap().as_ref() {
+                    wake();
+                }
+            }
+        };
+
+        match command.run {
+            Run::Blocking(run) => {
+                thread::spawn(move || send(run()));
+            },
+            Run::Future(future) => {
+                let task = Closable {
+                    id: self.next_task_id,
+                    future,
+                    shutdown: self.shutdown.clone(),
+                };
+                self.next_task_id += 1;
+                let task: BoxFuture = Box::pin(async move {
+                    if let Some(result) = task.await {
+                        send(result);
+                    }
+                });
+                match self.spawner.as_ref() {
+                    Some(spawner) => spawner.spawn(task),
+                    None => {
+                        thread::spawn(move || block_on(task));
+                    },
+                }
+            },
+        }
+    }
+
+    /// The results of the finished commands.
+    pub(crate) fn finished<Msg: 'static>(&self) -> Vec<Msg> {
+        self.receiver
+            .try_iter()
+            .filter_map(|result| result.downcast().ok().map(|msg| *msg))
+            .collect()
+    }
+
+    /// Wakes the running futures to drop them at their next poll.
+    pub(crate) fn shutdown(&self) {
+        self.shutdown.closed.store(true, Ordering::SeqCst);
+        let wakers = mem::take(&mut *self.shutdown.wakers.lock().unwrap());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for CommandRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for CommandRunner {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The shutdown flag of the component and the wakers of its pending futures.
+#[derive(Default)]
+struct Shutdown {
+    closed: AtomicBool,
+    wakers: Mutex<HashMap<u64, Waker>>,
+}
+
+/// The future which stops with `None` when the component is shut down.
+struct Closable {
+    id: u64,
+    future: Pin<Box<dyn Future<Output = Output> + Send>>,
+    shutdown: Arc<Shutdown>,
+}
+
+impl Future for Closable {
+    type Output = Option<Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.shutdown.closed.load(Ordering::SeqCst) {
+            return Poll::Ready(None);
+        }
+        let poll = self.future.as_mut().poll(cx);
+        if poll.is_pending() {
+            self.shutdown.wakers.lock().unwrap().insert(self.id, cx.waker().clone());
+            // The shutdown between the check above and the insert has not seen the waker
+            if self.shutdown.closed.load(Ordering::SeqCst) {
+                return Poll::Ready(None);
+            }
+        }
+        poll.map(Some)
+    }
+}
+
+impl Drop for Closable {
+    fn drop(&mut self) {
+        self.shutdown.wakers.lock().unwrap().remove(&self.id);
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on(mut future: BoxFuture) {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    while future.as_mut().poll(&mut cx).is_pending() {
+        thread::park();
+    }
+}
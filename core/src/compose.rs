@@ -0,0 +1,59 @@
+use crate::ChangeView;
+
+/// A part of a large model with its own message enum and update, stored in a
+/// field of the model. The model message wraps the messages of the sub-models
+/// in its variants, which are mapped by `sub_models!`.
+pub trait SubModel {
+    type Message;
+
+    fn update(&mut self, msg: Self::Message) -> ChangeView;
+}
+
+/// Passes the message of the model to its sub-model, see `sub_models!`.
+pub trait Dispatch<M>: Sized {
+    /// Updates the sub-model by the wrapped message, or returns back the message
+    /// of the model itself.
+    fn dispatch(self, model: &mut M) -> Result<ChangeView, Self>;
+}
+
+/// Composes the model of the sub-models. For each `Variant(SubMessage) => field`
+/// it implements `From<SubMessage>` for the model message, so the listeners and
+/// the commands can make the messages of the sub-models with `into()`, and
+/// `Dispatch` passing the variant to the `SubModel::update` of the field.
+///
+/// ```ignore
+/// sub_models!(Game, Msg {
+///     Board(BoardMsg) => board,
+///     Score(ScoreMsg) => score,
+/// });
+///
+/// fn update(&mut self, msg: Msg) -> ChangeView {
+///     match msg.dispatch(self) {
+///         Ok(change) => change,
+///         Err(Msg::Restart) => self.restart(),
+///         Err(_) => ChangeView::None,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! sub_models {
+    ($model:ty, $msg:ident { $($variant:ident($sub_msg:ty) => $field:ident),* $(,)? }) => {
+        $(
+            impl ::std::convert::From<$sub_msg> for $msg {
+                fn from(msg: $sub_msg) -> Self {
+                    $msg::$variant(msg)
+                }
+            }
+        )*
+
+        impl $crate::Dispatch<$model> for $msg {
+            fn dispatch(self, model: &mut $model) -> ::std::result::Result<$crate::ChangeView, Self> {
+                match self {
+                    $($msg::$variant(msg) => Ok($crate::SubModel::update(&mut model.$field, msg)),)*
+                    #[allow(unreachable_patterns)]
+                    msg => Err(msg),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,30 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+
+thread_local! {
+    static CONTEXT: RefCell<HashMap<TypeId, Rc<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Provides the shared value of the type to all the models of the UI thread,
+/// e.g. the fonts, the theme, the translations or the services. Usually it is
+/// provided by the controller before the root component is created. Returns
+/// the value it replaces.
+pub fn provide_context<T: Any>(value: T) -> Option<Rc<T>> {
+    let previous = CONTEXT.with(|context| context.borrow_mut().insert(TypeId::of::<T>(), Rc::new(value)))?;
+    previous.downcast().ok()
+}
+
+/// The shared value of the type, readable from `build_view`, `update` and the listeners.
+pub fn context<T: Any>() -> Option<Rc<T>> {
+    let value = CONTEXT.with(|context| context.borrow().get(&TypeId::of::<T>()).cloned())?;
+    value.downcast().ok()
+}
+
+pub fn take_context<T: Any>() -> Option<Rc<T>> {
+    let value = CONTEXT.with(|context| context.borrow_mut().remove(&TypeId::of::<T>()))?;
+    value.downcast().ok()
+}
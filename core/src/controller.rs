@@ -1,20 +1,47 @@
-pub use self::{keyboard::*, mouse::*};
+pub use self::{gamepad::*, hotkey::*, keyboard::*, keymap::*, mouse::*, touch::*};
 
+use std::path::PathBuf;
+
+pub mod gamepad;
+pub mod hotkey;
 pub mod keyboard;
+pub mod keymap;
 pub mod mouse;
+pub mod touch;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum InputEvent {
     MouseDown(MouseDown),
+    MouseUp(MouseUp),
+    /// The cursor is moved, sent for every `CursorMoved` of the window.
+    MouseMove(MouseMove),
     MouseScroll(MouseScroll),
     KeyDown(KeyboardEvent),
     KeyUp(KeyboardEvent),
     Char(char),
+    TouchStart(Touch),
+    TouchMove(Touch),
+    TouchEnd(Touch),
+    GamepadButton(GamepadButtonEvent),
+    GamepadAxis(GamepadAxisEvent),
+    /// A file is dragged over the window from another application.
+    FileHovered(PathBuf),
+    /// The hovered file is dragged out of the window or the drag is cancelled.
+    FileHoverCancelled,
+    FileDropped(PathBuf),
 }
 
 impl InputEvent {
     pub fn mouse_down(pos: MousePos, button: MouseButton) -> Self {
-        Self::MouseDown(MouseDown { pos, button })
+        Self::MouseDown(MouseDown { pos, button, clicks: 1 })
+    }
+
+    pub fn mouse_up(pos: MousePos, button: MouseButton) -> Self {
+        Self::MouseUp(MouseUp { pos, button })
+    }
+
+    pub fn mouse_move(pos: MousePos) -> Self {
+        Self::MouseMove(MouseMove { pos })
     }
 
     pub fn mouse_scroll(scroll: MouseScroll) -> Self {
@@ -32,4 +59,16 @@ impl InputEvent {
     pub fn char(ch: char) -> Self {
         Self::Char(ch)
     }
+
+    /// The position of the mouse or the touch event.
+    pub fn pointer_pos(&self) -> Option<MousePos> {
+        match self {
+            Self::MouseDown(press) => Some(press.pos),
+            Self::MouseUp(release) => Some(release.pos),
+            Self::MouseMove(movement) => Some(movement.pos),
+            Self::MouseScroll(scroll) => Some(scroll.pos),
+            Self::TouchStart(touch) | Self::TouchMove(touch) | Self::TouchEnd(touch) => Some(touch.pos),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+
+use super::InputEvent;
+use crate::{Comp, SystemMessage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub usize);
+
+/// The buttons by their place on the standard layout, `South` is A on Xbox and Cross on PlayStation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Other(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    Other(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadButtonEvent {
+    pub gamepad: GamepadId,
+    pub button: GamepadButton,
+    pub pressed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadAxisEvent {
+    pub gamepad: GamepadId,
+    pub axis: GamepadAxis,
+    /// The position in -1..1 for the sticks and 0..1 for the triggers, zero in the dead zone.
+    pub value: f32,
+}
+
+/// The state of the connected gamepads. The windowing backend has no gamepad
+/// support, so the application feeds the controller from the gamepad library
+/// it uses (e.g. `gilrs`), and the changes are sent to the component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadController {
+    dead_zone: f32,
+    pressed: HashSet<(GamepadId, GamepadButton)>,
+    axes: HashMap<(GamepadId, GamepadAxis), f32>,
+}
+
+impl Default for GamepadController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GamepadController {
+    pub fn new() -> Self {
+        Self {
+            dead_zone: 0.1,
+            pressed: HashSet::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    /// Sets the axis values which are reported as zero, 0.1 by default.
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    pub fn is_pressed(&self, gamepad: GamepadId, button: GamepadButton) -> bool {
+        self.pressed.contains(&(gamepad, button))
+    }
+
+    pub fn axis(&self, gamepad: GamepadId, axis: GamepadAxis) -> f32 {
+        self.axes.get(&(gamepad, axis)).copied().unwrap_or_default()
+    }
+
+    /// Updates the button and returns the event, `None` if the button state is not changed.
+    pub fn button(&mut self, gamepad: GamepadId, button: GamepadButton, pressed: bool) -> Option<GamepadButtonEvent> {
+        let changed = if pressed {
+            self.pressed.insert((gamepad, button))
+        } else {
+            self.pressed.remove(&(gamepad, button))
+        };
+        if changed {
+            Some(GamepadButtonEvent {
+                gamepad,
+                button,
+                pressed,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Updates the axis and returns the event, `None` if the value is not changed out of the dead zone.
+    pub fn axis_moved(&mut self, gamepad: GamepadId, axis: GamepadAxis, value: f32) -> Option<GamepadAxisEvent> {
+        let value = if value.abs() < self.dead_zone { 0.0 } else { value };
+        let prev = self.axes.insert((gamepad, axis), value).unwrap_or_default();
+        if prev != value {
+            Some(GamepadAxisEvent { gamepad, axis, value })
+        } else {
+            None
+        }
+    }
+
+    /// Forgets the state of the disconnected gamepad.
+    pub fn disconnected(&mut self, gamepad: GamepadId) {
+        self.pressed.retain(|(id, _)| *id != gamepad);
+        self.axes.retain(|(id, _), _| *id != gamepad);
+    }
+
+    pub fn button_comp(&mut self, comp: &mut Comp, gamepad: GamepadId, button: GamepadButton, pressed: bool) {
+        if let Some(event) = self.button(gamepad, button, pressed) {
+            comp.send_system_msg(SystemMessage::Input(InputEvent::GamepadButton(event)))
+        }
+    }
+
+    pub fn axis_comp(&mut self, comp: &mut Comp, gamepad: GamepadId, axis: GamepadAxis, value: f32) {
+        if let Some(event) = self.axis_moved(gamepad, axis, value) {
+            comp.send_system_msg(SystemMessage::Input(InputEvent::GamepadAxis(event)))
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::VirtualKeyCode;
+use crate::{Comp, SystemMessage};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        ctrl: false,
+        shift: false,
+        alt: false,
+        logo: false,
+    };
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    pub fn logo(mut self) -> Self {
+        self.logo = true;
+        self
+    }
+
+    /// Updates the held modifiers by the key, returns `false` if it is not a modifier key.
+    pub(crate) fn update(&mut self, key: VirtualKeyCode, pressed: bool) -> bool {
+        let modifier = match key {
+            VirtualKeyCode::LControl | VirtualKeyCode::RControl => &mut self.ctrl,
+            VirtualKeyCode::LShift | VirtualKeyCode::RShift => &mut self.shift,
+            VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => &mut self.alt,
+            VirtualKeyCode::LWin | VirtualKeyCode::RWin => &mut self.logo,
+            _ => return false,
+        };
+        *modifier = pressed;
+        true
+    }
+}
+
+/// A key pressed while exactly the modifiers are held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub key: VirtualKeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl Hotkey {
+    pub fn new(key: VirtualKeyCode, modifiers: Modifiers) -> Self {
+        Self { key, modifiers }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HotkeyId(pub usize);
+
+/// The best-effort global hotkeys, which send `SystemMessage::Hotkey` to the root
+/// components. They are not registered with the OS, so they neither reserve the
+/// keys nor hide them from the other applications: the controller matches the raw
+/// device keys, which the platforms deliver regardless of the focus on Windows and
+/// X11 only. Elsewhere the hotkeys work only while a window of the app is focused.
+#[derive(Default, Debug, Clone)]
+pub struct GlobalHotkeys {
+    hotkeys: Vec<(HotkeyId, Hotkey)>,
+    next_id: usize,
+    held: Modifiers,
+}
+
+impl GlobalHotkeys {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn register(&mut self, hotkey: Hotkey) -> HotkeyId {
+        let id = HotkeyId(self.next_id);
+        self.next_id += 1;
+        self.hotkeys.push((id, hotkey));
+        id
+    }
+
+    pub fn unregister(&mut self, id: HotkeyId) -> Option<Hotkey> {
+        let idx = self.hotkeys.iter().position(|(hotkey_id, _)| *hotkey_id == id)?;
+        Some(self.hotkeys.remove(idx).1)
+    }
+
+    pub fn get(&self, id: HotkeyId) -> Option<&Hotkey> {
+        self.hotkeys
+            .iter()
+            .find(|(hotkey_id, _)| *hotkey_id == id)
+            .map(|(_, hotkey)| hotkey)
+    }
+
+    /// Updates the held modifiers and returns the hotkeys triggered by the pressed key.
+    pub fn key_pressed(&mut self, key: VirtualKeyCode) -> Vec<HotkeyId> {
+        if self.held.update(key, true) {
+            return vec![];
+        }
+        self.hotkeys
+            .iter()
+            .filter(|(_, hotkey)| hotkey.key == key && hotkey.modifiers == self.held)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn key_released(&mut self, key: VirtualKeyCode) {
+        self.held.update(key, false);
+    }
+
+    /// Sends `SystemMessage::Hotkey` for each hotkey triggered by the pressed key.
+    pub fn key_pressed_comp(&mut self, comp: &mut Comp, key: VirtualKeyCode) {
+        for id in self.key_pressed(key) {
+            comp.send_system_msg(SystemMessage::Hotkey(id));
+        }
+    }
+}
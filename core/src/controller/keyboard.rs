@@ -1,4 +1,6 @@
-use super::InputEvent;
+use std::collections::HashMap;
+
+use super::{InputEvent, KeyMap, Modifiers};
 use crate::{Comp, SystemMessage};
 
 #[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy)]
@@ -193,31 +195,116 @@ pub enum VirtualKeyCode {
 pub struct KeyboardEvent {
     pub scancode: u32,
     pub keycode: Option<VirtualKeyCode>,
+    /// The number of the auto-repeats of the held key before this press, 0 for
+    /// the first press and for the releases.
+    pub repeat: u32,
+}
+
+impl KeyboardEvent {
+    pub fn new(scancode: u32, keycode: Option<VirtualKeyCode>) -> Self {
+        Self {
+            scancode,
+            keycode,
+            repeat: 0,
+        }
+    }
+
+    /// Checks the press is the auto-repeat of the held key.
+    pub fn is_repeat(&self) -> bool {
+        self.repeat > 0
+    }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct KeyboardController {
     last_event: Option<KeyboardEvent>,
+    held: HashMap<u32, u32>,
+    modifiers: Modifiers,
+    /// The press which has not typed a char yet.
+    typing: Option<KeyboardEvent>,
+    keymap: KeyMap,
 }
 
 impl KeyboardController {
     pub fn new() -> Self {
-        Self { last_event: None }
+        Default::default()
+    }
+
+    /// Counts the press of the key, the presses of the held key are the auto-repeats.
+    pub fn press(&mut self, mut event: KeyboardEvent) -> KeyboardEvent {
+        let presses = self.held.entry(event.scancode).or_insert(0);
+        event.repeat = *presses;
+        *presses += 1;
+        if let Some(key) = event.keycode {
+            self.modifiers.update(key, true);
+        }
+        self.typing = Some(event);
+        self.last_event = Some(event);
+        event
+    }
+
+    pub fn release(&mut self, mut event: KeyboardEvent) -> KeyboardEvent {
+        self.held.remove(&event.scancode);
+        if let Some(key) = event.keycode {
+            self.modifiers.update(key, false);
+        }
+        if self.typing.map(|typing| typing.scancode) == Some(event.scancode) {
+            self.typing = None;
+        }
+        event.repeat = 0;
+        self.last_event = Some(event);
+        event
+    }
+
+    /// Forgets the held keys when the window loses the focus, since their
+    /// releases are not delivered to the unfocused window.
+    pub fn focus_lost(&mut self) {
+        self.held.clear();
+        self.modifiers = Modifiers::NONE;
+        self.typing = None;
+    }
+
+    /// The modifiers held now.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    pub fn keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    pub fn keymap_mut(&mut self) -> &mut KeyMap {
+        &mut self.keymap
+    }
+
+    /// The char the key press types under the current layout with the held modifiers.
+    pub fn char_for(&self, event: &KeyboardEvent) -> Option<char> {
+        self.keymap.char_for(event, self.modifiers)
     }
 
     pub fn last_event(&self) -> Option<&KeyboardEvent> {
         self.last_event.as_ref()
     }
 
-    pub fn pressed_comp(&self, comp: &mut Comp, event: KeyboardEvent) {
+    pub fn pressed_comp(&mut self, comp: &mut Comp, event: KeyboardEvent) {
+        let event = self.press(event);
         comp.send_system_msg(SystemMessage::Input(InputEvent::key_down(event)))
     }
 
-    pub fn released_comp(&self, comp: &mut Comp, event: KeyboardEvent) {
+    pub fn released_comp(&mut self, comp: &mut Comp, event: KeyboardEvent) {
+        let event = self.release(event);
         comp.send_system_msg(SystemMessage::Input(InputEvent::key_up(event)))
     }
 
-    pub fn input_char(&self, comp: &mut Comp, ch: char) {
+    /// Sends the typed char, the keymap learns it as typed by the last key press.
+    pub fn input_char(&mut self, comp: &mut Comp, ch: char) {
+        self.typed(ch);
         comp.send_system_msg(SystemMessage::Input(InputEvent::char(ch)))
     }
+
+    pub fn typed(&mut self, ch: char) {
+        if let Some(event) = self.typing.take() {
+            self.keymap.learn(&event, self.modifiers, ch);
+        }
+    }
 }
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use super::{KeyboardEvent, Modifiers, VirtualKeyCode};
+
+/// The characters produced by the keys under the current keyboard layout, to
+/// show the shortcuts and the key bindings the way they are printed on the keys.
+/// The layout is learned from the characters the system types after the key
+/// presses, see `KeyboardController::input_char`; the keys not typed yet are
+/// mapped by the US layout.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    learned: HashMap<(u32, bool, bool), char>,
+}
+
+impl KeyMap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Remembers the char typed by the key press with the modifiers. The control
+    /// chars and the presses with Ctrl or Logo, which are the shortcuts, are skipped.
+    pub fn learn(&mut self, event: &KeyboardEvent, modifiers: Modifiers, ch: char) {
+        if ch.is_control() || modifiers.ctrl || modifiers.logo {
+            return;
+        }
+        self.learned
+            .insert((event.scancode, modifiers.shift, modifiers.alt), ch);
+    }
+
+    /// The char the key press with the modifiers types, ignoring Ctrl and Logo.
+    pub fn char_for(&self, event: &KeyboardEvent, modifiers: Modifiers) -> Option<char> {
+        self.learned
+            .get(&(event.scancode, modifiers.shift, modifiers.alt))
+            .copied()
+            .or_else(|| {
+                if modifiers.alt {
+                    None
+                } else {
+                    us_char(event.keycode?, modifiers.shift)
+                }
+            })
+    }
+
+    /// Forgets the learned chars, e.g. when the user switches the layout.
+    pub fn clear(&mut self) {
+        self.learned.clear();
+    }
+}
+
+fn us_char(key: VirtualKeyCode, shift: bool) -> Option<char> {
+    use VirtualKeyCode::*;
+
+    let (ch, shifted) = match key {
+        Key1 => ('1', '!'),
+        Key2 => ('2', '@'),
+        Key3 => ('3', '#'),
+        Key4 => ('4', '$'),
+        Key5 => ('5', '%'),
+        Key6 => ('6', '^'),
+        Key7 => ('7', '&'),
+        Key8 => ('8', '*'),
+        Key9 => ('9', '('),
+        Key0 => ('0', ')'),
+        A | B | C | D | E | F | G | H | I | J | K | L | M | N | O | P | Q | R | S | T | U | V | W | X | Y | Z => {
+            let ch = (b'a' + key as u8 - A as u8) as char;
+            (ch, ch.to_ascii_uppercase())
+        },
+        Space => (' ', ' '),
+        Minus => ('-', '_'),
+        Equals => ('=', '+'),
+        LBracket => ('[', '{'),
+        RBracket => (']', '}'),
+        Backslash => ('\\', '|'),
+        Semicolon => (';', ':'),
+        Apostrophe => ('\'', '"'),
+        Grave => ('`', '~'),
+        Comma => (',', '<'),
+        Period => ('.', '>'),
+        Slash => ('/', '?'),
+        Numpad0 | Numpad1 | Numpad2 | Numpad3 | Numpad4 | Numpad5 | Numpad6 | Numpad7 | Numpad8 | Numpad9 => {
+            let ch = (b'0' + key as u8 - Numpad0 as u8) as char;
+            (ch, ch)
+        },
+        NumpadAdd => ('+', '+'),
+        NumpadSubtract => ('-', '-'),
+        NumpadMultiply => ('*', '*'),
+        NumpadDivide => ('/', '/'),
+        NumpadDecimal => ('.', '.'),
+        NumpadComma => (',', ','),
+        NumpadEquals => ('=', '='),
+        _ => return None,
+    };
+    Some(if shift { shifted } else { ch })
+}
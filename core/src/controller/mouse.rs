@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use super::InputEvent;
 use crate::{Comp, Real, SystemMessage};
 
@@ -9,22 +11,128 @@ pub enum MouseButton {
     Other(u16),
 }
 
+/// The mouse cursor shown over a prim, see `Prim::set_cursor`.
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum CursorIcon {
+    #[default]
+    Default,
+    /// The hand over the links and the buttons.
+    Pointer,
+    Text,
+    Crosshair,
+    Move,
+    Grab,
+    Grabbing,
+    NotAllowed,
+    Wait,
+    Progress,
+    Help,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MouseDown {
     pub pos: MousePos,
     pub button: MouseButton,
+    /// The number of the presses in a row, 2 for a double click.
+    pub clicks: u32,
+}
+
+/// The released mouse button, delivered to the prims under the cursor like `MouseDown`,
+/// even if the button was pressed elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseUp {
+    pub pos: MousePos,
+    pub button: MouseButton,
+}
+
+/// The new position of the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseMove {
+    pub pos: MousePos,
+}
+
+/// The device that produced the scroll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollSource {
+    /// A wheel with notches, which scrolls by whole lines.
+    Wheel,
+    /// A high-resolution or free-spinning wheel, which scrolls by fractions of lines.
+    PreciseWheel,
+    Touchpad,
+}
+
+impl ScrollSource {
+    /// Discrete scrolls suit paging, the others the smooth scrolling.
+    pub fn is_discrete(&self) -> bool {
+        matches!(self, ScrollSource::Wheel)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MouseScroll {
     pub pos: MousePos,
+    /// The scroll in lines.
     pub delta: (f32, f32),
+    /// The scroll as reported by the platform, in the units of the source.
+    pub raw_delta: (f32, f32),
+    pub source: ScrollSource,
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
+impl MouseScroll {
+    /// The pixels of the touchpad scroll counted as one line.
+    pub const LINE_PIXELS: f32 = 20.0;
+
+    /// The scroll by lines, from the wheel with notches if the lines are whole.
+    pub fn lines(pos: MousePos, delta: (f32, f32)) -> Self {
+        let source = if delta.0.fract() == 0.0 && delta.1.fract() == 0.0 {
+            ScrollSource::Wheel
+        } else {
+            ScrollSource::PreciseWheel
+        };
+        Self {
+            pos,
+            delta,
+            raw_delta: delta,
+            source,
+        }
+    }
+    /// The smooth scroll by pixels from the touchpad, the `delta` is normalized to lines.
+    pub fn pixels(pos: MousePos, pixels: (f32, f32)) -> Self {
+        Self {
+            pos,
+            delta: (pixels.0 / Self::LINE_PIXELS, pixels.1 / Self::LINE_PIXELS),
+            raw_delta: pixels,
+            source: ScrollSource::Touchpad,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LastPress {
+    time: Instant,
+    pos: MousePos,
+    button: MouseButton,
+    clicks: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MouseController {
     last_pos: Option<MousePos>,
     last_offset: Option<MousePos>,
+    last_press: Option<LastPress>,
+    double_click_interval: Duration,
+}
+
+impl Default for MouseController {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
@@ -34,13 +142,52 @@ pub struct MousePos {
 }
 
 impl MouseController {
+    /// The max distance between the presses of a double click.
+    pub const DOUBLE_CLICK_DISTANCE: Real = 4.0;
+
     pub fn new() -> Self {
         MouseController {
             last_pos: None,
             last_offset: None,
+            last_press: None,
+            double_click_interval: Duration::from_millis(500),
         }
     }
 
+    /// Sets the max time between the presses of a double click, 500 ms by default.
+    pub fn with_double_click_interval(mut self, interval: Duration) -> Self {
+        self.double_click_interval = interval;
+        self
+    }
+
+    pub fn double_click_interval(&self) -> Duration {
+        self.double_click_interval
+    }
+
+    /// Counts the press at the last position, the presses of the same button
+    /// close in time and place continue the count.
+    pub fn press(&mut self, button: MouseButton, time: Instant) -> MouseDown {
+        let pos = self.last_pos();
+        let clicks = match self.last_press {
+            Some(last)
+                if last.button == button
+                    && time.saturating_duration_since(last.time) <= self.double_click_interval
+                    && (pos.x - last.pos.x).abs() <= Self::DOUBLE_CLICK_DISTANCE
+                    && (pos.y - last.pos.y).abs() <= Self::DOUBLE_CLICK_DISTANCE =>
+            {
+                last.clicks + 1
+            },
+            _ => 1,
+        };
+        self.last_press = Some(LastPress {
+            time,
+            pos,
+            button,
+            clicks,
+        });
+        MouseDown { pos, button, clicks }
+    }
+
     pub fn update_pos(&mut self, x: Real, y: Real) {
         let offset = self
             .last_pos
@@ -58,16 +205,30 @@ impl MouseController {
         self.last_pos.unwrap_or_default()
     }
 
-    pub fn pressed_comp(&self, comp: &mut Comp, button: MouseButton) {
+    pub fn pressed_comp(&mut self, comp: &mut Comp, button: MouseButton) {
+        let press = self.press(button, Instant::now());
+        comp.send_system_msg(SystemMessage::Input(InputEvent::MouseDown(press)))
+    }
+
+    pub fn released_comp(&self, comp: &mut Comp, button: MouseButton) {
         let pos = self.last_pos();
-        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_down(pos, button)))
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_up(pos, button)))
     }
 
-    pub fn mouse_scroll(&self, comp: &mut Comp, delta: (f32, f32)) {
+    pub fn moved_comp(&self, comp: &mut Comp) {
         let pos = self.last_pos();
-        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_scroll(MouseScroll {
-            pos,
-            delta,
-        })))
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_move(pos)))
+    }
+
+    /// Sends the scroll by lines, see `MouseScroll::lines`.
+    pub fn mouse_scroll(&self, comp: &mut Comp, delta: (f32, f32)) {
+        let scroll = MouseScroll::lines(self.last_pos(), delta);
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_scroll(scroll)))
+    }
+
+    /// Sends the scroll by pixels, see `MouseScroll::pixels`.
+    pub fn pixel_scroll(&self, comp: &mut Comp, pixels: (f32, f32)) {
+        let scroll = MouseScroll::pixels(self.last_pos(), pixels);
+        comp.send_system_msg(SystemMessage::Input(InputEvent::mouse_scroll(scroll)))
     }
 }
@@ -0,0 +1,48 @@
+use super::{InputEvent, MousePos};
+use crate::{Comp, Real, SystemMessage};
+
+/// A finger or a pen on the touch screen, the id is kept from the start to the end of the touch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Touch {
+    pub id: u64,
+    pub pos: MousePos,
+    /// The pressure in the range `0.0..=1.0`, if the device and the platform report it.
+    pub force: Option<Real>,
+}
+
+impl Touch {
+    pub fn new(id: u64, pos: MousePos) -> Self {
+        Self { id, pos, force: None }
+    }
+
+    pub fn with_force(mut self, force: Real) -> Self {
+        self.force = Some(force.clamp(0.0, 1.0));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    Start,
+    Move,
+    End,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct TouchController;
+
+impl TouchController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Sends the touch, the cancelled touches are sent as ended.
+    pub fn touch_comp(&self, comp: &mut Comp, phase: TouchPhase, touch: Touch) {
+        let event = match phase {
+            TouchPhase::Start => InputEvent::TouchStart(touch),
+            TouchPhase::Move => InputEvent::TouchMove(touch),
+            TouchPhase::End => InputEvent::TouchEnd(touch),
+        };
+        comp.send_system_msg(SystemMessage::Input(event))
+    }
+}
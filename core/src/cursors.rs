@@ -0,0 +1,202 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    BoundingBox, Color, Fill, Group, Model, Node, Path, PathCommand, Prim, Real, Rect, Shape, Text, Theme, ThemeToken,
+    Transform,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor {
+    pub name: String,
+    pub label: String,
+    pub color: Color,
+    pub x: Real,
+    pub y: Real,
+    pub selection: Option<BoundingBox>,
+}
+
+/// Messages from the external source (network session, mirroring etc.) that
+/// drive the remote cursors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCursorEvent {
+    Joined {
+        name: String,
+        label: String,
+        color: Color,
+    },
+    Moved {
+        name: String,
+        x: Real,
+        y: Real,
+    },
+    Selected {
+        name: String,
+        selection: Option<BoundingBox>,
+    },
+    Left {
+        name: String,
+    },
+}
+
+/// A set of named remote cursors and selections, rendered as an overlay
+/// over the model view.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteCursors {
+    cursors: Vec<RemoteCursor>,
+    theme: Theme,
+}
+
+impl RemoteCursors {
+    pub const OVERLAY_ID: &'static str = "remote_cursors";
+    pub const WIDGET: &'static str = "remote_cursors";
+
+    const PALETTE: [Color; 6] = [
+        Color::RGB(0.9, 0.3, 0.2),
+        Color::RGB(0.2, 0.6, 0.9),
+        Color::RGB(0.3, 0.7, 0.3),
+        Color::RGB(0.8, 0.5, 0.1),
+        Color::RGB(0.6, 0.3, 0.8),
+        Color::RGB(0.1, 0.6, 0.6),
+    ];
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RemoteCursor> {
+        self.cursors.iter().find(|cursor| cursor.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RemoteCursor> {
+        self.cursors.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cursors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursors.is_empty()
+    }
+
+    /// Applies the event and returns `true` if the cursors were changed.
+    /// Cursors that move before joining get the name as label and a palette color.
+    pub fn handle(&mut self, event: RemoteCursorEvent) -> bool {
+        match event {
+            RemoteCursorEvent::Joined { name, label, color } => {
+                let cursor = self.cursor_mut(name);
+                cursor.label = label;
+                cursor.color = color;
+            },
+            RemoteCursorEvent::Moved { name, x, y } => {
+                let cursor = self.cursor_mut(name);
+                cursor.x = x;
+                cursor.y = y;
+            },
+            RemoteCursorEvent::Selected { name, selection } => {
+                self.cursor_mut(name).selection = selection;
+            },
+            RemoteCursorEvent::Left { name } => {
+                let len = self.cursors.len();
+                self.cursors.retain(|cursor| cursor.name != name);
+                return self.cursors.len() != len;
+            },
+        }
+        true
+    }
+
+    fn cursor_mut(&mut self, name: String) -> &mut RemoteCursor {
+        match self.cursors.iter().position(|cursor| cursor.name == name) {
+            Some(idx) => &mut self.cursors[idx],
+            None => {
+                let color = Self::PALETTE[self.cursors.len() % Self::PALETTE.len()];
+                self.cursors.push(RemoteCursor {
+                    label: name.clone(),
+                    name,
+                    color,
+                    x: 0.0,
+                    y: 0.0,
+                    selection: None,
+                });
+                self.cursors.last_mut().expect("Cursor must be pushed")
+            },
+        }
+    }
+
+    /// Builds the overlay node, which should be the last child of the view root
+    /// to be drawn over the other nodes.
+    pub fn build_view<M: Model>(&self) -> Node<M> {
+        let selections = self.cursors.iter().filter_map(|cursor| {
+            let bound = cursor.selection?;
+            let selection = Rect {
+                x: bound.min_x.into(),
+                y: bound.min_y.into(),
+                width: bound.width().into(),
+                height: bound.height().into(),
+                fill: Some(Fill::color(cursor.color.with_alpha(0.25))),
+                ..Default::default()
+            };
+            Some(prim(Shape::Rect(selection), vec![]))
+        });
+        let pointers = self.cursors.iter().map(|cursor| self.build_pointer(cursor));
+
+        let overlay = Group {
+            id: Some(Self::OVERLAY_ID.to_string()),
+            ..Default::default()
+        };
+        prim(Shape::Group(overlay), selections.chain(pointers).collect())
+    }
+
+    fn build_pointer<M: Model>(&self, cursor: &RemoteCursor) -> Node<M> {
+        use PathCommand::*;
+
+        let arrow = Path {
+            cmd: vec![
+                Move([0.0, 0.0]),
+                Line([0.0, 16.0]),
+                Line([4.5, 12.0]),
+                Line([11.0, 12.0]),
+                Close,
+            ],
+            fill: Some(Fill::color(cursor.color)),
+            ..Default::default()
+        };
+        let label = Rect {
+            x: 10.0.into(),
+            y: 16.0.into(),
+            padding: self.theme.spacing(3.0).into(),
+            rounding: Some(3.0.into()),
+            fill: Some(Fill::color(cursor.color)),
+            ..Default::default()
+        };
+        let label_text = Text {
+            content: cursor.label.clone(),
+            font_size: self.theme.font_size(12.0).into(),
+            fill: Some(Fill::color(self.theme.color(Self::WIDGET, ThemeToken::TextOnAccent))),
+            ..Default::default()
+        };
+        let pointer = Group {
+            id: Some(format!("{}_{}", Self::OVERLAY_ID, cursor.name)),
+            transform: Transform::new().with_translation(cursor.x, cursor.y),
+            ..Default::default()
+        };
+
+        prim(Shape::Group(pointer), vec![
+            prim(Shape::Path(arrow), vec![]),
+            prim(Shape::Rect(label), vec![prim(Shape::Text(label_text), vec![])]),
+        ])
+    }
+}
+
+fn prim<M: Model>(shape: Shape, children: Vec<Node<M>>) -> Node<M> {
+    Node::Prim(Prim::new(Cow::Borrowed(shape.name()), shape, children, HashMap::new()))
+}
@@ -0,0 +1,135 @@
+use std::{
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+/// The virtual clock of the deterministic mode. It turns the real frame time
+/// into a whole number of the fixed steps, so the models and the animations
+/// are stepped the same way on every run and on every machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock {
+    step: Duration,
+    accumulated: Duration,
+    ticks: u64,
+    max_steps: Option<u32>,
+}
+
+impl FixedClock {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            accumulated: Duration::default(),
+            ticks: 0,
+            max_steps: None,
+        }
+    }
+
+    /// Limits the number of the steps in one `advance` call. The time beyond
+    /// them is dropped, so a long frame does not stall the next ones.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// The time accumulated since the last step, less than the step.
+    pub fn accumulated(&self) -> Duration {
+        self.accumulated
+    }
+
+    /// The number of the steps since the start.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// The virtual time since the start, a multiple of the step.
+    pub fn time(&self) -> Duration {
+        self.step * self.ticks as u32
+    }
+
+    /// Accumulates the real elapsed time and returns the number of the steps
+    /// due. Without `with_max_steps` no steps are dropped after a long frame,
+    /// since the lockstep peers and the replays must run all of them.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        if self.step == Duration::default() {
+            return 0;
+        }
+
+        self.accumulated += elapsed;
+        let mut steps = 0;
+        while self.accumulated >= self.step {
+            self.accumulated -= self.step;
+            if self.max_steps.is_none_or(|max_steps| steps < max_steps) {
+                steps += 1;
+            }
+        }
+        self.ticks += steps as u64;
+        steps
+    }
+}
+
+/// The FNV-1a hasher. Unlike `DefaultHasher` its result does not depend on
+/// the Rust version, so the checksums can be compared between the builds.
+/// The integers are hashed in the little-endian order and the `usize` values
+/// as `u64`, to match on all the targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateHasher(u64);
+
+impl StateHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// The checksum of the state which is the same on every platform, to verify
+/// the lockstep peers and the replays stay in sync.
+pub fn checksum<T: Hash + ?Sized>(state: &T) -> u64 {
+    let mut hasher = StateHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
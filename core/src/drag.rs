@@ -0,0 +1,370 @@
+use std::{borrow::Cow, collections::HashMap, mem};
+
+use crate::{
+    BoundingBox, CompositeShape, DragInfo, Group, InputEvent, Model, MouseButton, MousePos, Node, Prim, Real, Rect,
+    Shape, ShapeTree, Stroke, Theme, ThemeToken, Transform, TransformMatrix,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DragEvent<P> {
+    Started { source: String },
+    Entered { source: String, target: String },
+    Left { source: String, target: String },
+    Dropped { source: String, target: String, payload: P },
+    Cancelled { source: String },
+}
+
+#[derive(Debug, Clone)]
+struct Drag<P> {
+    source: String,
+    payload: P,
+    start: MousePos,
+    pos: MousePos,
+    target: Option<String>,
+    target_bound: Option<BoundingBox>,
+    ghost: Option<(ShapeTree, TransformMatrix)>,
+}
+
+#[derive(Debug, Clone)]
+enum DragState<P> {
+    Idle,
+    Pressed {
+        source: String,
+        payload: P,
+        start: MousePos,
+    },
+    Dragging(Box<Drag<P>>),
+}
+
+impl<P: Clone> DragState<P> {
+    fn handle<M: Model>(
+        &mut self,
+        view: &Node<M>,
+        event: InputEvent,
+        source: &dyn Fn(&Prim<M>) -> Option<P>,
+        accept: &dyn Fn(&Prim<M>, &P) -> bool,
+    ) -> Vec<DragEvent<P>> {
+        let mut events = vec![];
+        match event {
+            InputEvent::MouseDown(press) if press.button == MouseButton::Left => {
+                let pressed = topmost(view, press.pos, &|prim| source(prim).is_some());
+                *self = match pressed.and_then(|prim| Some((prim.id()?, source(prim)?))) {
+                    Some((id, payload)) => DragState::Pressed {
+                        source: id.to_string(),
+                        payload,
+                        start: press.pos,
+                    },
+                    None => DragState::Idle,
+                };
+            },
+            InputEvent::MouseMove(movement) => {
+                if let DragState::Pressed { source, payload, start } = self {
+                    let (dx, dy) = (movement.pos.x - start.x, movement.pos.y - start.y);
+                    if (dx * dx + dy * dy).sqrt() < DragDrop::<P>::THRESHOLD {
+                        return events;
+                    }
+                    let ghost = view.get(source.as_str()).and_then(|node| {
+                        let parent_transform = node.layout()?.parent_transform;
+                        let mut tree = ShapeTree::snapshot(node)?;
+                        remove_ids(&mut tree);
+                        Some((tree, parent_transform))
+                    });
+                    events.push(DragEvent::Started { source: source.clone() });
+                    *self = DragState::Dragging(Box::new(Drag {
+                        source: mem::take(source),
+                        payload: payload.clone(),
+                        start: *start,
+                        pos: movement.pos,
+                        target: None,
+                        target_bound: None,
+                        ghost,
+                    }));
+                }
+                if let DragState::Dragging(drag) = self {
+                    drag.pos = movement.pos;
+                    let target = drag.target_at(view, accept);
+                    if drag.target != target {
+                        if let Some(target) = drag.target.take() {
+                            events.push(DragEvent::Left {
+                                source: drag.source.clone(),
+                                target,
+                            });
+                        }
+                        if let Some(target) = target.clone() {
+                            events.push(DragEvent::Entered {
+                                source: drag.source.clone(),
+                                target,
+                            });
+                        }
+                        drag.target_bound = target.as_ref().and_then(|target| view.global_bounds_of(target));
+                        drag.target = target;
+                    }
+                }
+            },
+            InputEvent::MouseUp(release) if release.button == MouseButton::Left => {
+                if let DragState::Dragging(mut drag) = mem::replace(self, DragState::Idle) {
+                    drag.pos = release.pos;
+                    match drag.target_at(view, accept) {
+                        Some(target) => events.push(DragEvent::Dropped {
+                            source: drag.source,
+                            target,
+                            payload: drag.payload,
+                        }),
+                        None => events.push(DragEvent::Cancelled { source: drag.source }),
+                    }
+                }
+            },
+            _ => (),
+        }
+        events
+    }
+}
+
+impl<P> Drag<P> {
+    /// The topmost prim under the pointer, other than the source, which accepts the payload.
+    fn target_at<M: Model>(&self, view: &Node<M>, accept: &dyn Fn(&Prim<M>, &P) -> bool) -> Option<String> {
+        topmost(view, self.pos, &|prim| {
+            prim.id().is_some_and(|id| id != self.source) && accept(prim, &self.payload)
+        })
+        .and_then(Prim::id)
+        .map(ToString::to_string)
+    }
+}
+
+/// Drag-and-drop between the prims of the view. The drag sources carry the
+/// payloads and the drop targets accept them by the predicate. The sources
+/// and the targets are the topmost of them under the pointer by
+/// `Prim::hit_test_by`, so the clips and the overlays are respected. The drag
+/// listeners of the prims are delivered by the same drag, see `DragTracker`.
+///
+/// `Model::system_update` gets the input without the view, so the model
+/// passes `SystemMessage::Input` events to `queue` and, if one is queued,
+/// returns a message for which `update` returns `ChangeView::Modify`. Then
+/// `modify_view` runs `handle_queued` with the view and `sync_overlay`. The
+/// overlay from `build_view` is the last child of the root to draw the drag
+/// ghost and the outline of the target in the `FocusRing` color of the theme.
+/// The code which has the view at hand with the event calls `handle` directly.
+#[derive(Debug, Clone)]
+pub struct DragDrop<P> {
+    sources: HashMap<String, P>,
+    targets: HashMap<String, fn(&P) -> bool>,
+    state: DragState<P>,
+    queued: Vec<InputEvent>,
+    theme: Theme,
+}
+
+impl<P: Clone> DragDrop<P> {
+    pub const OVERLAY_ID: &'static str = "drag_overlay";
+    pub const WIDGET: &'static str = "drag_drop";
+
+    /// The distance the mouse should move with the pressed button to start the drag.
+    pub const THRESHOLD: Real = 4.0;
+
+    pub fn new() -> Self {
+        Self {
+            sources: HashMap::new(),
+            targets: HashMap::new(),
+            state: DragState::Idle,
+            queued: vec![],
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn with_source(mut self, id: impl Into<String>, payload: P) -> Self {
+        self.add_source(id, payload);
+        self
+    }
+
+    pub fn with_target(mut self, id: impl Into<String>, accept: fn(&P) -> bool) -> Self {
+        self.add_target(id, accept);
+        self
+    }
+
+    pub fn add_source(&mut self, id: impl Into<String>, payload: P) {
+        self.sources.insert(id.into(), payload);
+    }
+
+    pub fn remove_source(&mut self, id: &str) -> Option<P> {
+        self.sources.remove(id)
+    }
+
+    pub fn add_target(&mut self, id: impl Into<String>, accept: fn(&P) -> bool) {
+        self.targets.insert(id.into(), accept);
+    }
+
+    pub fn remove_target(&mut self, id: &str) {
+        self.targets.remove(id);
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        matches!(self.state, DragState::Dragging(_))
+    }
+
+    /// The id of the dragged source.
+    pub fn dragged(&self) -> Option<&str> {
+        match &self.state {
+            DragState::Dragging(drag) => Some(&drag.source),
+            _ => None,
+        }
+    }
+
+    /// The id of the target under the dragged source which accepts its payload.
+    pub fn target(&self) -> Option<&str> {
+        match &self.state {
+            DragState::Dragging(drag) => drag.target.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Keeps the mouse event for `handle_queued`. Only the left button presses
+    /// are kept while no drag is pressed, so the other moves do not modify the view.
+    /// Returns `true` if the event is kept.
+    pub fn queue(&mut self, event: &InputEvent) -> bool {
+        let is_idle = matches!(self.state, DragState::Idle) && self.queued.is_empty();
+        let keep = match event {
+            InputEvent::MouseDown(press) => press.button == MouseButton::Left,
+            InputEvent::MouseMove(_) => !is_idle,
+            InputEvent::MouseUp(release) => release.button == MouseButton::Left && !is_idle,
+            _ => false,
+        };
+        if keep {
+            self.queued.push(event.clone());
+        }
+        keep
+    }
+
+    /// Handles the events kept by `queue` with the view and returns the drag events they caused.
+    pub fn handle_queued<M: Model>(&mut self, view: &Node<M>) -> Vec<DragEvent<P>> {
+        self.take_queued()
+            .into_iter()
+            .flat_map(|event| self.handle(view, event))
+            .collect()
+    }
+
+    pub(crate) fn take_queued(&mut self) -> Vec<InputEvent> {
+        mem::take(&mut self.queued)
+    }
+
+    /// Updates the drag by the input event and returns the drag events it caused.
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, event: InputEvent) -> Vec<DragEvent<P>> {
+        let (sources, targets) = (&self.sources, &self.targets);
+        self.state.handle(
+            view,
+            event,
+            &|prim| sources.get(prim.id()?).cloned(),
+            &|prim, payload| {
+                prim.id()
+                    .and_then(|id| targets.get(id))
+                    .is_some_and(|accept| accept(payload))
+            },
+        )
+    }
+
+    /// Like `handle`, but the sources and their payloads are found by `source` and the
+    /// targets by `accept`, e.g. by the listeners of the prims.
+    pub(crate) fn handle_by<M: Model>(
+        &mut self,
+        view: &Node<M>,
+        event: InputEvent,
+        source: &dyn Fn(&Prim<M>) -> Option<P>,
+        accept: &dyn Fn(&Prim<M>, &P) -> bool,
+    ) -> Vec<DragEvent<P>> {
+        self.state.handle(view, event, source, accept)
+    }
+
+    /// The current drag, if it is started.
+    pub(crate) fn info(&self) -> Option<DragInfo> {
+        match &self.state {
+            DragState::Dragging(drag) => Some(DragInfo {
+                source: drag.source.clone(),
+                start: drag.start,
+                pos: drag.pos,
+                target: drag.target.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Builds the overlay with the ghost of the dragged source and the outline of the target.
+    pub fn build_view<M: Model>(&self) -> Node<M> {
+        let overlay = Group {
+            id: Some(Self::OVERLAY_ID.to_string()),
+            ..Default::default()
+        };
+        let mut children = vec![];
+        if let DragState::Dragging(drag) = &self.state {
+            if let Some(bound) = drag.target_bound {
+                let outline = Rect {
+                    x: bound.min_x.into(),
+                    y: bound.min_y.into(),
+                    width: bound.width().into(),
+                    height: bound.height().into(),
+                    stroke: Some(Stroke::color(self.theme.color(Self::WIDGET, ThemeToken::FocusRing)).width(2.0)),
+                    transform: Transform::Global(TransformMatrix::identity()),
+                    ..Default::default()
+                };
+                children.push(prim(Shape::Rect(outline), vec![]));
+            }
+            if let Some((ghost, parent_transform)) = &drag.ghost {
+                let offset =
+                    TransformMatrix::identity().with_translation(drag.pos.x - drag.start.x, drag.pos.y - drag.start.y);
+                let ghost_group = Group {
+                    transform: Transform::Global(offset * *parent_transform),
+                    transparency: Some(0.5),
+                    ..Default::default()
+                };
+                children.push(prim(Shape::Group(ghost_group), vec![ghost.clone().into_node()]));
+            }
+        }
+        prim(Shape::Group(overlay), children)
+    }
+
+    /// Replaces the overlay in the view to move the ghost.
+    pub fn sync_overlay<M: Model>(&self, view: &mut Node<M>) {
+        if let Some(overlay) = view.get_prim_mut(Self::OVERLAY_ID) {
+            if let Node::Prim(prim) = self.build_view() {
+                overlay.shape = prim.shape;
+                overlay.children = prim.children;
+                overlay.invalidate_layout();
+            }
+        }
+    }
+}
+
+impl<P: Clone> Default for DragDrop<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn prim<M: Model>(shape: Shape, children: Vec<Node<M>>) -> Node<M> {
+    Node::Prim(Prim::new(Cow::Borrowed(shape.name()), shape, children, HashMap::new()))
+}
+
+/// The topmost prim with an id accepted by the filter under the point, see `Prim::hit_test_by`.
+fn topmost<'a, M: Model>(view: &'a Node<M>, pos: MousePos, accept: &dyn Fn(&Prim<M>) -> bool) -> Option<&'a Prim<M>> {
+    view.as_prim()?
+        .hit_test_by(pos.x, pos.y, &|prim| prim.id().is_some() && accept(prim))
+}
+
+/// The ghost copies have no ids, so they are not found instead of the originals.
+fn remove_ids(tree: &mut ShapeTree) {
+    match &mut tree.shape {
+        Shape::Rect(rect) => rect.id = None,
+        Shape::Circle(circle) => circle.id = None,
+        Shape::Path(path) => path.id = None,
+        Shape::Group(group) => group.id = None,
+        Shape::Text(text) => text.id = None,
+        Shape::TileMap(tile_map) => tile_map.id = None,
+    }
+    tree.children.iter_mut().for_each(remove_ids);
+}
@@ -0,0 +1,80 @@
+use std::{
+    any::{type_name, Any},
+    fmt,
+};
+
+use crate::{AsAny, ChangeView, Model};
+
+/// The message of any type, which the components and the models of any type
+/// take if it is of their message type, see `Comp::try_send`.
+pub struct DynMsg(Box<dyn Any>);
+
+impl DynMsg {
+    pub fn new(msg: impl Any) -> Self {
+        Self(Box::new(msg))
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        self.0.downcast().map(|msg| *msg).map_err(Self)
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    pub fn into_any(self) -> Box<dyn Any> {
+        self.0
+    }
+}
+
+impl From<Box<dyn Any>> for DynMsg {
+    fn from(msg: Box<dyn Any>) -> Self {
+        Self(msg)
+    }
+}
+
+impl fmt::Debug for DynMsg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DynMsg(..)")
+    }
+}
+
+/// The object-safe model with the type-erased message, to keep the models of the
+/// different types in one collection, e.g. the tabs of any content, without a
+/// common message enum. Every model is a `DynModel`, and every component gives
+/// access to its model as one.
+pub trait DynModel: AsAny {
+    fn model_name(&self) -> &'static str;
+
+    /// Updates the model by the message of its type, or returns the message back.
+    fn update_dyn(&mut self, msg: DynMsg) -> Result<ChangeView, DynMsg>;
+}
+
+impl<M: Model> DynModel for M {
+    fn model_name(&self) -> &'static str {
+        type_name::<M>()
+    }
+
+    fn update_dyn(&mut self, msg: DynMsg) -> Result<ChangeView, DynMsg> {
+        let msg = msg.downcast::<M::Message>()?;
+        Ok(self.update(msg))
+    }
+}
+
+impl dyn DynModel {
+    pub fn is<M: Model>(&self) -> bool {
+        self.as_any().is::<M>()
+    }
+
+    pub fn downcast_ref<M: Model>(&self) -> Option<&M> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<M: Model>(&mut self) -> Option<&mut M> {
+        self.as_any_mut().downcast_mut()
+    }
+}
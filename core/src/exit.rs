@@ -0,0 +1,17 @@
+use std::cell::Cell;
+
+thread_local! {
+    static EXIT_REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Asks the controller to close the window and to exit the app after the
+/// current event, e.g. from `update` of the "Quit" button of any component.
+/// Unlike the close button of the window, `Model::close_requested` is not asked.
+pub fn request_exit() {
+    EXIT_REQUESTED.with(|requested| requested.set(true));
+}
+
+/// Takes the exit request made by `request_exit` on the UI thread.
+pub fn take_exit_request() -> bool {
+    EXIT_REQUESTED.with(|requested| requested.replace(false))
+}
@@ -0,0 +1,164 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    marker::PhantomData,
+    mem,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use libloading::{Library, Symbol};
+
+use crate::{Model, Node};
+
+pub type BuildViewFn<M> = fn(&M) -> Node<M>;
+pub type ModifyViewFn<M> = fn(&mut M, &mut Node<M>);
+
+#[derive(Debug)]
+pub enum HotReloadError {
+    Io(io::Error),
+    Library(libloading::Error),
+}
+
+impl fmt::Display for HotReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotReloadError::Io(err) => write!(f, "Failed to copy the view library: {}", err),
+            HotReloadError::Library(err) => write!(f, "Failed to load the view library: {}", err),
+        }
+    }
+}
+
+impl Error for HotReloadError {}
+
+impl From<io::Error> for HotReloadError {
+    fn from(err: io::Error) -> Self {
+        HotReloadError::Io(err)
+    }
+}
+
+impl From<libloading::Error> for HotReloadError {
+    fn from(err: libloading::Error) -> Self {
+        HotReloadError::Library(err)
+    }
+}
+
+/// The view code of a model loaded from a cdylib, which is loaded again when it
+/// is rebuilt, while the app and the model state keep running. The library exports
+/// `#[no_mangle] pub fn engel_build_view(model: &M) -> Node<M>` and optionally
+/// `engel_modify_view(model: &mut M, view: &mut Node<M>)`, and is built by the same
+/// compiler with the same engel and model crates as the app.
+///
+/// The model calls `HotView::build_view` from its `build_view`, falling back to the
+/// built-in view, and `HotView::reload` on `SystemMessage::Draw`, rebuilding the view
+/// when it returns `true`. The library is loaded from a copy in the temp directory,
+/// so the build can overwrite it, and the copy is removed once it is loaded where
+/// the platform allows it.
+///
+/// The loaded libraries are never unloaded: the views built by them keep the
+/// listeners and the names pointing into their code.
+pub struct HotView<M> {
+    path: PathBuf,
+    libraries: Vec<Library>,
+    modified: Option<SystemTime>,
+    _model: PhantomData<fn(&M)>,
+}
+
+impl<M: Model> HotView<M> {
+    pub const BUILD_VIEW: &'static [u8] = b"engel_build_view";
+    pub const MODIFY_VIEW: &'static [u8] = b"engel_modify_view";
+
+    /// Creates the hot view of the library at the path, which is not loaded until `reload`.
+    ///
+    /// # Safety
+    ///
+    /// The library must export `engel_build_view` and `engel_modify_view`, if any, with
+    /// exactly the signatures of `BuildViewFn<M>` and `ModifyViewFn<M>` for this `M`,
+    /// built by the same compiler with the same versions of engel and of the model crate.
+    /// Its symbols are called without any checks.
+    pub unsafe fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            libraries: Vec::new(),
+            modified: None,
+            _model: PhantomData,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.libraries.is_empty()
+    }
+
+    /// Loads the library if it is changed since the last load. Returns `true` if
+    /// it is loaded. The old library is kept if the new one fails to load.
+    pub fn reload(&mut self) -> Result<bool, HotReloadError> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if self.is_loaded() && self.modified == Some(modified) {
+            return Ok(false);
+        }
+
+        // The same path is not loaded again by the system loader until it is unloaded
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("view");
+        let mut copy = env::temp_dir().join(format!("{}-hot-{}-{}", stem, std::process::id(), self.libraries.len()));
+        if let Some(extension) = self.path.extension() {
+            copy.set_extension(extension);
+        }
+        fs::copy(&self.path, &copy)?;
+
+        let loaded = Library::new(&copy).and_then(|library| {
+            unsafe { library.get::<BuildViewFn<M>>(Self::BUILD_VIEW) }?;
+            Ok(library)
+        });
+        // The loaded copy can't be removed on Windows, where it stays in the temp directory
+        let _ = fs::remove_file(&copy);
+        self.libraries.push(loaded?);
+        self.modified = Some(modified);
+        Ok(true)
+    }
+
+    /// The view built by the loaded library, `None` if it is not loaded.
+    pub fn build_view(&self, model: &M) -> Option<Node<M>> {
+        let build_view = self.symbol::<BuildViewFn<M>>(Self::BUILD_VIEW)?;
+        Some(build_view(model))
+    }
+
+    /// Modifies the view by the loaded library, returns `false` if it is not loaded
+    /// or does not export `engel_modify_view`.
+    pub fn modify_view(&self, model: &mut M, view: &mut Node<M>) -> bool {
+        match self.symbol::<ModifyViewFn<M>>(Self::MODIFY_VIEW) {
+            Some(modify_view) => {
+                modify_view(model, view);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn symbol<T: Copy>(&self, name: &[u8]) -> Option<T> {
+        let symbol: Symbol<T> = unsafe { self.libraries.last()?.get(name) }.ok()?;
+        Some(*symbol)
+    }
+}
+
+impl<M> fmt::Debug for HotView<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotView")
+            .field("path", &self.path)
+            .field("loads", &self.libraries.len())
+            .finish()
+    }
+}
+
+impl<M> Drop for HotView<M> {
+    fn drop(&mut self) {
+        // The views built by the libraries may outlive the hot view
+        for library in self.libraries.drain(..) {
+            mem::forget(library);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{PathCommand, Real, SvgPathError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Icon {
+    pub width: Real,
+    pub height: Real,
+    pub cmd: Vec<PathCommand>,
+}
+
+impl Icon {
+    /// Creates an icon from SVG path data drawn in the `width` x `height` view box.
+    pub fn from_svg_path(width: Real, height: Real, data: &str) -> Result<Self, SvgPathError> {
+        Ok(Self {
+            width,
+            height,
+            cmd: PathCommand::parse_svg(data)?,
+        })
+    }
+
+    /// Returns the path commands scaled to fit the given size.
+    pub fn scaled_cmd(&self, width: Real, height: Real) -> Vec<PathCommand> {
+        let sx = if self.width > 0.0 { width / self.width } else { 1.0 };
+        let sy = if self.height > 0.0 { height / self.height } else { 1.0 };
+        self.cmd.iter().map(|cmd| cmd.scaled(sx, sy)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IconPack {
+    pub name: String,
+    icons: HashMap<String, Rc<Icon>>,
+}
+
+impl IconPack {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            icons: HashMap::new(),
+        }
+    }
+
+    pub fn with(mut self, name: impl Into<String>, icon: Icon) -> Self {
+        self.insert(name, icon);
+        self
+    }
+
+    pub fn with_svg(
+        self,
+        name: impl Into<String>,
+        width: Real,
+        height: Real,
+        data: &str,
+    ) -> Result<Self, SvgPathError> {
+        Ok(self.with(name, Icon::from_svg_path(width, height, data)?))
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, icon: Icon) {
+        self.icons.insert(name.into(), Rc::new(icon));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<Icon>> {
+        self.icons.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.icons.keys().map(String::as_str)
+    }
+
+    /// The icon set bundled with the engine, 24x24 view box.
+    #[cfg(feature = "icons")]
+    pub fn bundled() -> Self {
+        const ICONS: &[(&str, &str)] = &[
+            ("add", "M11 5h2v6h6v2h-6v6h-2v-6H5v-2h6z"),
+            ("remove", "M5 11h14v2H5z"),
+            ("menu", "M3 6h18v2H3zM3 11h18v2H3zM3 16h18v2H3z"),
+            (
+                "close",
+                "M6.4 5 12 10.6 17.6 5 19 6.4 13.4 12 19 17.6 17.6 19 12 13.4 6.4 19 5 17.6 10.6 12 5 6.4z",
+            ),
+            ("check", "M9 16.2 4.8 12l-1.4 1.4L9 19 21 7l-1.4-1.4z"),
+            ("chevron-left", "M14 6l1.4 1.4L10.8 12l4.6 4.6L14 18l-6-6z"),
+            ("chevron-right", "M10 6 8.6 7.4 13.2 12l-4.6 4.6L10 18l6-6z"),
+            ("chevron-up", "M12 8l6 6-1.4 1.4-4.6-4.6-4.6 4.6L6 14z"),
+            ("chevron-down", "M7.4 8.6 12 13.2l4.6-4.6L18 10l-6 6-6-6z"),
+            ("arrow-right", "M4 11h12.2l-5.6-5.6L12 4l8 8-8 8-1.4-1.4 5.6-5.6H4z"),
+            ("play", "M8 5v14l11-7z"),
+            ("pause", "M6 5h4v14H6zM14 5h4v14h-4z"),
+            ("stop", "M6 6h12v12H6z"),
+        ];
+
+        ICONS.iter().fold(Self::new("engel"), |pack, (name, data)| {
+            pack.with_svg(*name, 24.0, 24.0, data)
+                .expect("Bundled icon must be valid")
+        })
+    }
+}
+
+thread_local! {
+    static ICON_PACKS: RefCell<Vec<IconPack>> = RefCell::new(default_icon_packs());
+}
+
+fn default_icon_packs() -> Vec<IconPack> {
+    #[cfg(feature = "icons")]
+    return vec![IconPack::bundled()];
+
+    #[cfg(not(feature = "icons"))]
+    vec![]
+}
+
+/// Registers the icon pack for the current thread. Icons of later registered packs
+/// take precedence over icons with the same name in earlier ones.
+pub fn register_icon_pack(pack: IconPack) {
+    ICON_PACKS.with(|packs| packs.borrow_mut().push(pack));
+}
+
+pub fn find_icon(name: &str) -> Option<Rc<Icon>> {
+    ICON_PACKS.with(|packs| packs.borrow().iter().rev().find_map(|pack| pack.get(name)))
+}
@@ -0,0 +1,248 @@
+use crate::{Color, PathCommand, Real, Theme, ThemeToken};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InkPoint {
+    pub x: Real,
+    pub y: Real,
+    /// Pen pressure in the range `0.0..=1.0`, e.g. `Touch::force` of the pen or the
+    /// touch input, or `pressure_by_speed` for the mouse.
+    pub pressure: Real,
+}
+
+impl InkPoint {
+    pub fn new(x: Real, y: Real, pressure: Real) -> Self {
+        Self {
+            x,
+            y,
+            pressure: pressure.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn distance(&self, other: &InkPoint) -> Real {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// Simulates the pen pressure for the input devices which don't report it, like
+/// the mouse: the faster the pointer moves (in units per second), the thinner the
+/// stroke. It is not the real pressure, which is `Touch::force` where available.
+pub fn pressure_by_speed(speed: Real) -> Real {
+    (1.0 - speed / 4000.0).clamp(0.3, 1.0)
+}
+
+/// A freehand stroke captured from the pointer input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InkStroke {
+    pub points: Vec<InkPoint>,
+    pub width: Real,
+    pub color: Color,
+}
+
+impl InkStroke {
+    /// The points closer than this distance to the previous one are skipped.
+    pub const MIN_DISTANCE: Real = 0.5;
+    pub const WIDGET: &'static str = "ink";
+
+    pub fn new(width: Real, color: Color) -> Self {
+        Self {
+            points: vec![],
+            width,
+            color,
+        }
+    }
+
+    /// Creates the stroke in the `TextPrimary` color of the theme, the default pen color.
+    pub fn themed(width: Real, theme: &Theme) -> Self {
+        Self::new(width, theme.color(Self::WIDGET, ThemeToken::TextPrimary))
+    }
+
+    pub fn push(&mut self, point: InkPoint) {
+        match self.points.last_mut() {
+            Some(last) if last.distance(&point) < Self::MIN_DISTANCE => {
+                last.pressure = last.pressure.max(point.pressure)
+            },
+            _ => self.points.push(point),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Removes the points that deviate from the stroke line less than the tolerance
+    /// (Ramer-Douglas-Peucker algorithm).
+    pub fn simplify(&mut self, tolerance: Real) {
+        if self.points.len() < 3 {
+            return;
+        }
+
+        let mut keep = vec![false; self.points.len()];
+        let last = self.points.len() - 1;
+        keep[0] = true;
+        keep[last] = true;
+
+        let mut ranges = vec![(0, last)];
+        while let Some((first, last)) = ranges.pop() {
+            let (start, end) = (self.points[first], self.points[last]);
+            let farthest = (first + 1..last)
+                .map(|idx| (idx, distance_to_segment(&self.points[idx], &start, &end)))
+                .fold(None, |max: Option<(usize, Real)>, (idx, distance)| match max {
+                    Some((_, max_distance)) if max_distance >= distance => max,
+                    _ => Some((idx, distance)),
+                });
+
+            if let Some((idx, distance)) = farthest {
+                if distance > tolerance {
+                    keep[idx] = true;
+                    ranges.push((first, idx));
+                    ranges.push((idx, last));
+                }
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        self.points.retain(|_| keep.next().unwrap_or(false));
+    }
+
+    /// The smoothed center line of the stroke, to draw it with a constant width stroke.
+    pub fn center_line(&self) -> Vec<PathCommand> {
+        let points: Vec<_> = self.points.iter().map(|point| [point.x, point.y]).collect();
+        catmull_rom(&points, false)
+    }
+
+    /// The smoothed outline of the stroke with the width depending on the pressure,
+    /// to draw it with a fill.
+    pub fn outline(&self) -> Vec<PathCommand> {
+        match self.points.len() {
+            0 => vec![],
+            1 => {
+                let point = self.points[0];
+                let half = self.half_width(&point);
+                let dot = [
+                    [point.x + half, point.y],
+                    [point.x, point.y + half],
+                    [point.x - half, point.y],
+                    [point.x, point.y - half],
+                ];
+                catmull_rom(&dot, true)
+            },
+            len => {
+                let mut left = Vec::with_capacity(len + 1);
+                let mut right = Vec::with_capacity(len + 1);
+                for idx in 0..len {
+                    let prev = self.points[idx.saturating_sub(1)];
+                    let next = self.points[(idx + 1).min(len - 1)];
+                    let (tx, ty) = normalize(next.x - prev.x, next.y - prev.y);
+
+                    let point = self.points[idx];
+                    let half = self.half_width(&point);
+                    left.push([point.x - ty * half, point.y + tx * half]);
+                    right.push([point.x + ty * half, point.y - tx * half]);
+
+                    // The tips make the caps rounded
+                    if idx == 0 {
+                        left.insert(0, [point.x - tx * half, point.y - ty * half]);
+                    } else if idx == len - 1 {
+                        left.push([point.x + tx * half, point.y + ty * half]);
+                    }
+                }
+                left.extend(right.into_iter().rev());
+                catmull_rom(&left, true)
+            },
+        }
+    }
+
+    fn half_width(&self, point: &InkPoint) -> Real {
+        self.width * point.pressure.max(0.1) / 2.0
+    }
+}
+
+fn normalize(x: Real, y: Real) -> (Real, Real) {
+    let len = x.hypot(y);
+    if len > 0.0 {
+        (x / len, y / len)
+    } else {
+        (1.0, 0.0)
+    }
+}
+
+fn distance_to_segment(point: &InkPoint, start: &InkPoint, end: &InkPoint) -> Real {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let len_sq = dx * dx + dy * dy;
+    if len_sq == 0.0 {
+        return point.distance(start);
+    }
+
+    let t = (((point.x - start.x) * dx + (point.y - start.y) * dy) / len_sq).clamp(0.0, 1.0);
+    (point.x - (start.x + t * dx)).hypot(point.y - (start.y + t * dy))
+}
+
+/// Builds the cubic Bezier curves through all the points (Catmull-Rom spline).
+fn catmull_rom(points: &[[Real; 2]], closed: bool) -> Vec<PathCommand> {
+    let len = points.len();
+    if len == 0 {
+        return vec![];
+    }
+
+    let at = |idx: isize| -> [Real; 2] {
+        if closed {
+            points[idx.rem_euclid(len as isize) as usize]
+        } else {
+            points[idx.max(0).min(len as isize - 1) as usize]
+        }
+    };
+
+    let mut cmd = vec![PathCommand::Move(points[0])];
+    let segments = if closed { len } else { len - 1 };
+    for idx in 0..segments as isize {
+        let (p0, p1, p2, p3) = (at(idx - 1), at(idx), at(idx + 1), at(idx + 2));
+        cmd.push(PathCommand::BezCtrl([
+            p1[0] + (p2[0] - p0[0]) / 6.0,
+            p1[1] + (p2[1] - p0[1]) / 6.0,
+        ]));
+        cmd.push(PathCommand::BezCtrl([
+            p2[0] - (p3[0] - p1[0]) / 6.0,
+            p2[1] - (p3[1] - p1[1]) / 6.0,
+        ]));
+        cmd.push(PathCommand::CubBezTo(p2));
+    }
+    if closed {
+        cmd.push(PathCommand::Close);
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stroke(points: &[(Real, Real)]) -> InkStroke {
+        let mut stroke = InkStroke::new(4.0, Color::Black);
+        for &(x, y) in points {
+            stroke.push(InkPoint::new(x, y, 1.0));
+        }
+        stroke
+    }
+
+    #[test]
+    fn simplify_straight_line() {
+        let mut line = stroke(&[(0.0, 0.0), (1.0, 0.05), (2.0, 0.0), (3.0, 0.1), (4.0, 0.0)]);
+        line.simplify(0.5);
+        assert_eq!(line.points.len(), 2);
+
+        let mut corner = stroke(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 2.0), (4.0, 4.0)]);
+        corner.simplify(0.5);
+        let points: Vec<_> = corner.points.iter().map(|point| (point.x, point.y)).collect();
+        assert_eq!(points, [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)]);
+    }
+
+    #[test]
+    fn outline_is_closed_curve() {
+        let line = stroke(&[(0.0, 0.0), (10.0, 0.0), (20.0, 5.0)]);
+        let outline = line.outline();
+        assert!(matches!(outline.first(), Some(PathCommand::Move(_))));
+        assert_eq!(outline.last(), Some(&PathCommand::Close));
+        // 3 points on each side and 2 tips
+        assert_eq!(outline.len(), 1 + 8 * 3 + 1);
+    }
+}
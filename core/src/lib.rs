@@ -1,8 +1,61 @@
-pub use self::{animation::*, controller::*, listener::*, model::*, node::*, render::*};
+#[cfg(feature = "hot-reload")]
+pub use self::hot_reload::*;
+#[cfg(feature = "rapier2d")]
+pub use self::rapier::*;
+#[cfg(feature = "serde")]
+pub use self::replay::*;
+#[cfg(feature = "serde")]
+pub use self::state::*;
+pub use self::{
+    access::*, animation::*, boundary::*, bus::*, callback::*, command::*, compose::*, context::*, controller::*,
+    cursors::*, determinism::*, drag::*, dynamic::*, exit::*, icon::*, ink::*, listener::*, model::*, node::*,
+    physics::*, render::*, reorder::*, resources::*, router::*, scene::*, scroll::*, sender::*, stats::*, svg::*,
+    theme::*, timer::*, timestep::*, undo::*, virtual_list::*,
+};
+#[cfg(feature = "rapier2d")]
+pub use rapier2d;
+#[cfg(feature = "tracing")]
+pub use tracing;
 
+pub mod access;
 pub mod animation;
+pub mod boundary;
+pub mod bus;
+pub mod callback;
+pub mod command;
+pub mod compose;
+pub mod context;
 pub mod controller;
+pub mod cursors;
+pub mod determinism;
+pub mod drag;
+pub mod dynamic;
+pub mod exit;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+pub mod icon;
+pub mod ink;
 pub mod listener;
 pub mod model;
 pub mod node;
+pub mod physics;
+#[cfg(feature = "rapier2d")]
+pub mod rapier;
 pub mod render;
+pub mod reorder;
+#[cfg(feature = "serde")]
+pub mod replay;
+pub mod resources;
+pub mod router;
+pub mod scene;
+pub mod scroll;
+pub mod sender;
+#[cfg(feature = "serde")]
+pub mod state;
+pub mod stats;
+pub mod svg;
+pub mod theme;
+pub mod timer;
+pub mod timestep;
+pub mod undo;
+pub mod virtual_list;
@@ -1,59 +1,696 @@
-use std::{ops::Deref, time::Duration};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    ops::{Deref, Range},
+    path::PathBuf,
+    time::Duration,
+};
 
-use crate::{KeyboardEvent, Model, MouseDown, MouseScroll, Prim};
+use crate::{
+    collect_overlays, AccessAction, BoundingBox, CompositeShape, DragDrop, DragEvent, Fill, GamepadAxisEvent,
+    GamepadButtonEvent, InputEvent, KeyboardEvent, Model, MouseButton, MouseDown, MouseMove, MousePos, MouseScroll,
+    MouseUp, Node, Prim, Real, Rect, ScrollSource, Shape, SystemMessage, Text, Theme, ThemeToken, Touch,
+};
 
 pub struct On<'a, M: Model, E> {
     pub prim: &'a Prim<M>,
     pub event: E,
+    stopped: Option<&'a Cell<bool>>,
+}
+
+impl<'a, M: Model, E> On<'a, M, E> {
+    pub fn new(prim: &'a Prim<M>, event: E) -> Self {
+        Self {
+            prim,
+            event,
+            stopped: None,
+        }
+    }
+
+    fn propagating(prim: &'a Prim<M>, event: E, stopped: &'a Cell<bool>) -> Self {
+        Self {
+            prim,
+            event,
+            stopped: Some(stopped),
+        }
+    }
+
+    /// Marks the pointer event handled, so it is not passed to the prims further along
+    /// its path: e.g. a press on a button does not reach the `on_mouse_down` of the
+    /// canvas under it. The other listeners of this prim are still called.
+    pub fn stop_propagation(&self) {
+        if let Some(stopped) = self.stopped {
+            stopped.set(true);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct EventName(&'static str);
+pub struct EventName {
+    name: &'static str,
+    capture: bool,
+}
 
 impl EventName {
-    pub const DRAW: EventName = EventName("Draw");
-    pub const ON_BLUR: EventName = EventName("OnBlur");
-    pub const ON_CLICK: EventName = EventName("OnClick");
-    pub const ON_INPUT_CHAR: EventName = EventName("OnInputChar");
-    pub const ON_KEY_DOWN: EventName = EventName("OnKeyDown");
-    pub const ON_KEY_UP: EventName = EventName("OnKeyUp");
-    pub const ON_MOUSE_DOWN: EventName = EventName("OnMouseDown");
-    pub const ON_MOUSE_SCROLL: EventName = EventName("OnMouseScroll");
-    pub const WINDOW_RESIZED: EventName = EventName("WindowResized");
+    pub const DRAW: EventName = EventName::new("Draw");
+    pub const ON_ACCESS_ACTION: EventName = EventName::new("OnAccessAction");
+    pub const ON_DRAG: EventName = EventName::new("OnDrag");
+    pub const ON_DRAG_END: EventName = EventName::new("OnDragEnd");
+    pub const ON_DRAG_START: EventName = EventName::new("OnDragStart");
+    pub const ON_DROP: EventName = EventName::new("OnDrop");
+    pub const ON_BLUR: EventName = EventName::new("OnBlur");
+    pub const ON_CLICK: EventName = EventName::new("OnClick");
+    pub const ON_CONTEXT_MENU: EventName = EventName::new("OnContextMenu");
+    pub const ON_DOUBLE_CLICK: EventName = EventName::new("OnDoubleClick");
+    pub const ON_FILE_DROP: EventName = EventName::new("OnFileDrop");
+    pub const ON_FILE_HOVER: EventName = EventName::new("OnFileHover");
+    pub const ON_GAMEPAD_AXIS: EventName = EventName::new("OnGamepadAxis");
+    pub const ON_GAMEPAD_BUTTON: EventName = EventName::new("OnGamepadButton");
+    pub const ON_INPUT_CHAR: EventName = EventName::new("OnInputChar");
+    pub const ON_KEY_DOWN: EventName = EventName::new("OnKeyDown");
+    pub const ON_KEY_UP: EventName = EventName::new("OnKeyUp");
+    pub const ON_LONG_PRESS: EventName = EventName::new("OnLongPress");
+    pub const ON_MOUSE_DOWN: EventName = EventName::new("OnMouseDown");
+    pub const ON_MOUSE_ENTER: EventName = EventName::new("OnMouseEnter");
+    pub const ON_MOUSE_LEAVE: EventName = EventName::new("OnMouseLeave");
+    pub const ON_MOUSE_MOVE: EventName = EventName::new("OnMouseMove");
+    pub const ON_MOUSE_SCROLL: EventName = EventName::new("OnMouseScroll");
+    pub const ON_MOUSE_UP: EventName = EventName::new("OnMouseUp");
+    pub const ON_PAN: EventName = EventName::new("OnPan");
+    pub const ON_PINCH: EventName = EventName::new("OnPinch");
+    pub const ON_ROTATE: EventName = EventName::new("OnRotate");
+    pub const ON_SELECT: EventName = EventName::new("OnSelect");
+    pub const ON_TOUCH_END: EventName = EventName::new("OnTouchEnd");
+    pub const ON_TOUCH_MOVE: EventName = EventName::new("OnTouchMove");
+    pub const ON_TOUCH_START: EventName = EventName::new("OnTouchStart");
+    pub const WINDOW_RESIZED: EventName = EventName::new("WindowResized");
+
+    pub const fn new(name: &'static str) -> Self {
+        Self { name, capture: false }
+    }
+
+    /// The name of the listeners called in the capture phase, on the way from the root to the target.
+    pub const fn capture(self) -> Self {
+        Self {
+            name: self.name,
+            capture: true,
+        }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.capture
+    }
 }
 
 impl Deref for EventName {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        self.0
+        self.name
     }
 }
 
 pub enum Listener<M: Model> {
     WindowResized(fn(u32, u32) -> M::Message),
     Draw(fn(Duration) -> M::Message),
+    ThrottledDraw(ListenerRate, fn(Duration) -> M::Message),
     OnMouseDown(fn(On<M, MouseDown>) -> M::Message),
+    OnMouseUp(fn(On<M, MouseUp>) -> M::Message),
+    OnMouseMove(fn(On<M, MouseMove>) -> M::Message),
+    OnMouseEnter(fn(On<M, MouseMove>) -> M::Message),
+    OnMouseLeave(fn(On<M, MouseMove>) -> M::Message),
     OnMouseScroll(fn(On<M, MouseScroll>) -> M::Message),
     OnKeyDown(fn(On<M, KeyboardEvent>) -> M::Message),
     OnKeyUp(fn(On<M, KeyboardEvent>) -> M::Message),
-    OnClick(fn(On<M, MouseDown>) -> M::Message),
+    OnClick(fn(On<M, MouseUp>) -> M::Message),
+    OnContextMenu(fn(On<M, MouseDown>) -> M::Message),
+    OnDoubleClick(fn(On<M, MouseDown>) -> M::Message),
     OnInputChar(fn(On<M, char>) -> M::Message),
     OnBlur(fn(On<M, MouseDown>) -> M::Message),
+    OnDragStart(fn(On<M, DragInfo>) -> M::Message),
+    OnDrag(fn(On<M, DragInfo>) -> M::Message),
+    OnDragEnd(fn(On<M, DragInfo>) -> M::Message),
+    OnDrop(fn(On<M, DragInfo>) -> M::Message),
+    OnTouchStart(fn(On<M, Touch>) -> M::Message),
+    OnTouchMove(fn(On<M, Touch>) -> M::Message),
+    OnTouchEnd(fn(On<M, Touch>) -> M::Message),
+    OnGamepadButton(fn(On<M, GamepadButtonEvent>) -> M::Message),
+    OnGamepadAxis(fn(On<M, GamepadAxisEvent>) -> M::Message),
+    OnPan(fn(On<M, Gesture>) -> M::Message),
+    OnPinch(fn(On<M, Gesture>) -> M::Message),
+    OnRotate(fn(On<M, Gesture>) -> M::Message),
+    OnFileHover(fn(On<M, PathBuf>) -> M::Message),
+    OnFileDrop(fn(On<M, PathBuf>) -> M::Message),
+    OnLongPress(Duration, fn(On<M, MousePos>) -> M::Message),
+    OnSelect(fn(On<M, TextSelection>) -> M::Message),
+    OnAccessAction(fn(On<M, AccessAction>) -> M::Message),
+    /// The pointer listener called in the capture phase.
+    Capture(Box<Listener<M>>),
 }
 
 impl<M: Model> Listener<M> {
     pub fn event_name(&self) -> EventName {
         match self {
             Listener::WindowResized(_) => EventName::WINDOW_RESIZED,
-            Listener::Draw(_) => EventName::DRAW,
+            Listener::Draw(_) | Listener::ThrottledDraw(..) => EventName::DRAW,
             Listener::OnMouseDown(_) => EventName::ON_MOUSE_DOWN,
+            Listener::OnMouseUp(_) => EventName::ON_MOUSE_UP,
+            Listener::OnMouseMove(_) => EventName::ON_MOUSE_MOVE,
+            Listener::OnMouseEnter(_) => EventName::ON_MOUSE_ENTER,
+            Listener::OnMouseLeave(_) => EventName::ON_MOUSE_LEAVE,
             Listener::OnMouseScroll(_) => EventName::ON_MOUSE_SCROLL,
             Listener::OnKeyDown(_) => EventName::ON_KEY_DOWN,
             Listener::OnKeyUp(_) => EventName::ON_KEY_UP,
             Listener::OnClick(_) => EventName::ON_CLICK,
+            Listener::OnContextMenu(_) => EventName::ON_CONTEXT_MENU,
+            Listener::OnDoubleClick(_) => EventName::ON_DOUBLE_CLICK,
             Listener::OnInputChar(_) => EventName::ON_INPUT_CHAR,
             Listener::OnBlur(_) => EventName::ON_BLUR,
+            Listener::OnDragStart(_) => EventName::ON_DRAG_START,
+            Listener::OnDrag(_) => EventName::ON_DRAG,
+            Listener::OnDragEnd(_) => EventName::ON_DRAG_END,
+            Listener::OnDrop(_) => EventName::ON_DROP,
+            Listener::OnTouchStart(_) => EventName::ON_TOUCH_START,
+            Listener::OnTouchMove(_) => EventName::ON_TOUCH_MOVE,
+            Listener::OnTouchEnd(_) => EventName::ON_TOUCH_END,
+            Listener::OnGamepadButton(_) => EventName::ON_GAMEPAD_BUTTON,
+            Listener::OnGamepadAxis(_) => EventName::ON_GAMEPAD_AXIS,
+            Listener::OnPan(_) => EventName::ON_PAN,
+            Listener::OnPinch(_) => EventName::ON_PINCH,
+            Listener::OnRotate(_) => EventName::ON_ROTATE,
+            Listener::OnFileHover(_) => EventName::ON_FILE_HOVER,
+            Listener::OnFileDrop(_) => EventName::ON_FILE_DROP,
+            Listener::OnLongPress(..) => EventName::ON_LONG_PRESS,
+            Listener::OnSelect(_) => EventName::ON_SELECT,
+            Listener::OnAccessAction(_) => EventName::ON_ACCESS_ACTION,
+            Listener::Capture(listener) => listener.event_name().capture(),
+        }
+    }
+
+    /// Calls the listener of the pointer event, returns `None` if it listens to another event.
+    pub fn call_pointer(&self, prim: &Prim<M>, input: &InputEvent, stopped: &Cell<bool>) -> Option<M::Message> {
+        Some(match (self, input) {
+            (Listener::Capture(listener), _) => return listener.call_pointer(prim, input, stopped),
+            (Listener::OnMouseDown(func), InputEvent::MouseDown(event))
+            | (Listener::OnDoubleClick(func), InputEvent::MouseDown(event)) => {
+                func(On::propagating(prim, *event, stopped))
+            },
+            (Listener::OnMouseUp(func), InputEvent::MouseUp(event))
+            | (Listener::OnClick(func), InputEvent::MouseUp(event)) => func(On::propagating(prim, *event, stopped)),
+            (Listener::OnMouseMove(func), InputEvent::MouseMove(event)) => func(On::propagating(prim, *event, stopped)),
+            (Listener::OnMouseScroll(func), InputEvent::MouseScroll(event)) => {
+                func(On::propagating(prim, *event, stopped))
+            },
+            (Listener::OnTouchStart(func), InputEvent::TouchStart(event))
+            | (Listener::OnTouchMove(func), InputEvent::TouchMove(event))
+            | (Listener::OnTouchEnd(func), InputEvent::TouchEnd(event)) => func(On::propagating(prim, *event, stopped)),
+            _ => return None,
+        })
+    }
+}
+
+impl<M: Model> Clone for Listener<M> {
+    fn clone(&self) -> Self {
+        match *self {
+            Listener::WindowResized(func) => Listener::WindowResized(func),
+            Listener::Draw(func) => Listener::Draw(func),
+            Listener::ThrottledDraw(rate, func) => Listener::ThrottledDraw(rate, func),
+            Listener::OnMouseDown(func) => Listener::OnMouseDown(func),
+            Listener::OnMouseUp(func) => Listener::OnMouseUp(func),
+            Listener::OnMouseMove(func) => Listener::OnMouseMove(func),
+            Listener::OnMouseEnter(func) => Listener::OnMouseEnter(func),
+            Listener::OnMouseLeave(func) => Listener::OnMouseLeave(func),
+            Listener::OnMouseScroll(func) => Listener::OnMouseScroll(func),
+            Listener::OnKeyDown(func) => Listener::OnKeyDown(func),
+            Listener::OnKeyUp(func) => Listener::OnKeyUp(func),
+            Listener::OnClick(func) => Listener::OnClick(func),
+            Listener::OnContextMenu(func) => Listener::OnContextMenu(func),
+            Listener::OnDoubleClick(func) => Listener::OnDoubleClick(func),
+            Listener::OnInputChar(func) => Listener::OnInputChar(func),
+            Listener::OnBlur(func) => Listener::OnBlur(func),
+            Listener::OnDragStart(func) => Listener::OnDragStart(func),
+            Listener::OnDrag(func) => Listener::OnDrag(func),
+            Listener::OnDragEnd(func) => Listener::OnDragEnd(func),
+            Listener::OnDrop(func) => Listener::OnDrop(func),
+            Listener::OnTouchStart(func) => Listener::OnTouchStart(func),
+            Listener::OnTouchMove(func) => Listener::OnTouchMove(func),
+            Listener::OnTouchEnd(func) => Listener::OnTouchEnd(func),
+            Listener::OnGamepadButton(func) => Listener::OnGamepadButton(func),
+            Listener::OnGamepadAxis(func) => Listener::OnGamepadAxis(func),
+            Listener::OnPan(func) => Listener::OnPan(func),
+            Listener::OnPinch(func) => Listener::OnPinch(func),
+            Listener::OnRotate(func) => Listener::OnRotate(func),
+            Listener::OnFileHover(func) => Listener::OnFileHover(func),
+            Listener::OnFileDrop(func) => Listener::OnFileDrop(func),
+            Listener::OnLongPress(duration, func) => Listener::OnLongPress(duration, func),
+            Listener::OnSelect(func) => Listener::OnSelect(func),
+            Listener::OnAccessAction(func) => Listener::OnAccessAction(func),
+            Listener::Capture(ref listener) => Listener::Capture(listener.clone()),
+        }
+    }
+}
+
+/// The max rate of a throttled listener.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ListenerRate {
+    EveryNthFrame(u32),
+    Every(Duration),
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    frames: u32,
+    elapsed: Duration,
+    due: Option<Duration>,
+    polled: bool,
+}
+
+/// Frame counters of the throttled draw listeners of a component. The state is
+/// keyed by the position of the listener in the polling order and its rate, so
+/// it survives the view rebuilds which keep the listeners in their places.
+#[derive(Debug, Default)]
+pub struct DrawThrottles {
+    states: HashMap<(usize, ListenerRate), ThrottleState>,
+    position: usize,
+}
+
+impl DrawThrottles {
+    /// Advances all the counters by one frame and drops the counters of the listeners
+    /// that were not polled since the previous frame.
+    pub fn tick(&mut self, duration: Duration) {
+        self.position = 0;
+        self.states.retain(|_, state| state.polled);
+        for ((_, rate), state) in self.states.iter_mut() {
+            state.polled = false;
+            state.frames += 1;
+            state.elapsed += duration;
+
+            let is_due = match *rate {
+                ListenerRate::EveryNthFrame(frames) => state.frames >= frames,
+                ListenerRate::Every(interval) => state.elapsed >= interval,
+            };
+            state.due = if is_due {
+                let elapsed = state.elapsed;
+                state.frames = 0;
+                state.elapsed = Duration::default();
+                Some(elapsed)
+            } else {
+                None
+            };
+        }
+    }
+
+    /// Returns the time elapsed since the previous call of the next listener, if it is due
+    /// in this frame. The listeners are polled in the same order on every frame.
+    pub fn poll(&mut self, rate: ListenerRate) -> Option<Duration> {
+        let state = self.states.entry((self.position, rate)).or_default();
+        self.position += 1;
+        state.polled = true;
+        state.due
+    }
+}
+
+/// The drag of a prim with the drag listeners, see `DragTracker`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragInfo {
+    /// The id of the dragged prim.
+    pub source: String,
+    pub start: MousePos,
+    pub pos: MousePos,
+    /// The id of the prim with the `on_drop` listener under the cursor.
+    pub target: Option<String>,
+}
+
+/// The drag state of a component, which delivers the drag listeners of its
+/// view by the `DragDrop` drag. The drag starts by the left button on the
+/// topmost prim with an id and any of the `on_drag_start`, `on_drag`,
+/// `on_drag_end` listeners, when the cursor moves by `DragDrop::THRESHOLD`.
+/// The drop targets are the prims with an id and the `on_drop` listener.
+#[derive(Debug, Default)]
+pub struct DragTracker {
+    drag: DragDrop<()>,
+}
+
+impl DragTracker {
+    /// The current drag, if it is started.
+    pub fn drag(&self) -> Option<DragInfo> {
+        self.drag.info()
+    }
+
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, event: InputEvent, outputs: &mut Vec<M::Message>) {
+        let names = [EventName::ON_DRAG_START, EventName::ON_DRAG, EventName::ON_DRAG_END];
+        let source = |prim: &Prim<M>| names.iter().any(|name| prim.listeners.contains_key(name)).then_some(());
+        let accept = |prim: &Prim<M>, _: &()| prim.listeners.contains_key(&EventName::ON_DROP);
+
+        let last = self.drag.info();
+        let release = match &event {
+            InputEvent::MouseUp(release) => Some(release.pos),
+            _ => None,
+        };
+        let moved = matches!(event, InputEvent::MouseMove(_));
+        for drag_event in self.drag.handle_by(view, event, &source, &accept) {
+            let target = match drag_event {
+                DragEvent::Started { source } => {
+                    if let Some(info) = self.drag.info() {
+                        fire(view, &source, EventName::ON_DRAG_START, &info, outputs);
+                    }
+                    continue;
+                },
+                DragEvent::Dropped { target, .. } => Some(target),
+                DragEvent::Cancelled { .. } => None,
+                DragEvent::Entered { .. } | DragEvent::Left { .. } => continue,
+            };
+            if let (Some(last), Some(pos)) = (&last, release) {
+                let info = DragInfo {
+                    pos,
+                    target,
+                    ..last.clone()
+                };
+                fire(view, &info.source, EventName::ON_DRAG_END, &info, outputs);
+                if let Some(target) = &info.target {
+                    fire(view, target, EventName::ON_DROP, &info, outputs);
+                }
+            }
+        }
+        if let Some(info) = self.drag.info().filter(|_| moved) {
+            fire(view, &info.source, EventName::ON_DRAG, &info, outputs);
         }
     }
 }
+
+/// The selection in a text prim with the `on_select` listener, made by the left
+/// button press on it and extended by the drag. The indices are in chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextSelection {
+    /// The caret position of the press.
+    pub anchor: usize,
+    /// The caret position under the cursor.
+    pub caret: usize,
+}
+
+impl TextSelection {
+    pub const WIDGET: &'static str = "text_selection";
+    pub const CARET_WIDTH: Real = 1.5;
+
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.caret
+    }
+
+    /// The rects of the selected chars in the `Selection` color and the caret in the `Caret`
+    /// color of the theme. They have the transform of the text, so they are drawn over it
+    /// when put next to the text prim in its parent.
+    pub fn highlight(&self, text: &Text, theme: &Theme) -> Vec<Shape> {
+        let rect = |bound: BoundingBox, token| {
+            Shape::Rect(Rect {
+                x: bound.min_x.into(),
+                y: bound.min_y.into(),
+                width: bound.width().into(),
+                height: bound.height().into(),
+                fill: Some(Fill::color(theme.color(Self::WIDGET, token))),
+                transform: text.transform,
+                ..Default::default()
+            })
+        };
+
+        let mut shapes: Vec<_> = text
+            .range_bounds(self.range())
+            .into_iter()
+            .map(|bound| rect(bound, ThemeToken::Selection))
+            .collect();
+        let (x, y) = text.caret_pos(self.caret);
+        let line_height = text.metrics.map_or(0.0, |metrics| metrics.line_height);
+        let caret = BoundingBox {
+            min_x: x - Self::CARET_WIDTH / 2.0,
+            min_y: y,
+            max_x: x + Self::CARET_WIDTH / 2.0,
+            max_y: y + line_height,
+        };
+        shapes.push(rect(caret, ThemeToken::Caret));
+        shapes
+    }
+}
+
+/// The change of the fingers on the touch screen or the touchpad since the previous gesture event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gesture {
+    /// The center of the fingers.
+    pub centroid: MousePos,
+    /// The move of the centroid.
+    pub pan: (Real, Real),
+    /// The ratio of the distance between the fingers to the previous one.
+    pub scale: Real,
+    /// The clockwise rotation of the fingers in radians.
+    pub rotation: Real,
+}
+
+impl Gesture {
+    /// Recognizes the gesture by the first two touches, or the pan by one touch.
+    fn between(before: &[(u64, MousePos)], after: &[(u64, MousePos)]) -> Option<Self> {
+        let centroid = |touches: &[(u64, MousePos)]| {
+            let touches = &touches[..touches.len().min(2)];
+            let (x, y) = touches
+                .iter()
+                .fold((0.0, 0.0), |(x, y), (_, pos)| (x + pos.x, y + pos.y));
+            let count = touches.len() as Real;
+            MousePos {
+                x: x / count,
+                y: y / count,
+            }
+        };
+        if before.is_empty() || before.len() != after.len() {
+            return None;
+        }
+        let (from, to) = (centroid(before), centroid(after));
+        let mut gesture = Gesture {
+            centroid: to,
+            pan: (to.x - from.x, to.y - from.y),
+            scale: 1.0,
+            rotation: 0.0,
+        };
+        if let ([(_, a0), (_, b0), ..], [(_, a1), (_, b1), ..]) = (before, after) {
+            let (dx0, dy0) = (b0.x - a0.x, b0.y - a0.y);
+            let (dx1, dy1) = (b1.x - a1.x, b1.y - a1.y);
+            let distance = (dx0 * dx0 + dy0 * dy0).sqrt();
+            if distance > 0.0 {
+                gesture.scale = (dx1 * dx1 + dy1 * dy1).sqrt() / distance;
+            }
+            let mut rotation = dy1.atan2(dx1) - dy0.atan2(dx0);
+            if rotation > std::f32::consts::PI {
+                rotation -= 2.0 * std::f32::consts::PI;
+            } else if rotation < -std::f32::consts::PI {
+                rotation += 2.0 * std::f32::consts::PI;
+            }
+            gesture.rotation = rotation;
+        }
+        Some(gesture)
+    }
+}
+
+/// The gesture state of a component, which delivers the `on_pan`, `on_pinch`
+/// and `on_rotate` listeners of the topmost prim under the gesture centroid.
+/// The touches pan by one finger and pinch and rotate by two, the touchpad
+/// scrolls pan.
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    touches: Vec<(u64, MousePos)>,
+}
+
+impl GestureRecognizer {
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, event: InputEvent, outputs: &mut Vec<M::Message>) {
+        let gesture = match event {
+            InputEvent::TouchStart(touch) => {
+                self.touches.retain(|(id, _)| *id != touch.id);
+                self.touches.push((touch.id, touch.pos));
+                None
+            },
+            InputEvent::TouchEnd(touch) => {
+                self.touches.retain(|(id, _)| *id != touch.id);
+                None
+            },
+            InputEvent::TouchMove(touch) => {
+                let before = self.touches.clone();
+                match self.touches.iter_mut().find(|(id, _)| *id == touch.id) {
+                    Some((_, pos)) => *pos = touch.pos,
+                    None => return,
+                }
+                Gesture::between(&before, &self.touches)
+            },
+            InputEvent::MouseScroll(scroll) if scroll.source == ScrollSource::Touchpad => Some(Gesture {
+                centroid: scroll.pos,
+                pan: scroll.raw_delta,
+                scale: 1.0,
+                rotation: 0.0,
+            }),
+            _ => None,
+        };
+        if let Some(gesture) = gesture {
+            send_gesture(view, gesture, outputs);
+        }
+    }
+}
+
+/// The long press state of a component, which delivers the `on_long_press`
+/// listeners of the topmost prim under the left button press or the single
+/// touch held still for the duration of the listener. The time is counted by
+/// the `SystemMessage::Draw` frames.
+#[derive(Debug, Default)]
+pub struct LongPressRecognizer {
+    press: Option<HeldPress>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HeldPress {
+    pos: MousePos,
+    touch: Option<u64>,
+    held: Duration,
+}
+
+impl LongPressRecognizer {
+    /// The max distance the held press can move.
+    pub const TOLERANCE: Real = 8.0;
+    pub const DEFAULT_DURATION: Duration = Duration::from_millis(500);
+
+    pub fn is_pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, msg: &SystemMessage, outputs: &mut Vec<M::Message>) {
+        let input = match msg {
+            SystemMessage::Draw(elapsed) => return self.tick(view, *elapsed, outputs),
+            SystemMessage::Input(input) => input,
+            _ => return,
+        };
+        match input {
+            InputEvent::MouseDown(press) if press.button == MouseButton::Left => self.start(press.pos, None),
+            InputEvent::TouchStart(touch) => match self.press {
+                Some(press) if press.touch.is_some() => self.press = None,
+                _ => self.start(touch.pos, Some(touch.id)),
+            },
+            InputEvent::MouseMove(movement) => self.moved(movement.pos, None),
+            InputEvent::TouchMove(touch) => self.moved(touch.pos, Some(touch.id)),
+            InputEvent::MouseUp(release) if release.button == MouseButton::Left => self.press = None,
+            InputEvent::TouchEnd(_) => self.press = None,
+            _ => (),
+        }
+    }
+
+    fn start(&mut self, pos: MousePos, touch: Option<u64>) {
+        self.press = Some(HeldPress {
+            pos,
+            touch,
+            held: Duration::default(),
+        });
+    }
+
+    fn moved(&mut self, pos: MousePos, touch: Option<u64>) {
+        if let Some(press) = self.press {
+            let far = (pos.x - press.pos.x).abs() > Self::TOLERANCE || (pos.y - press.pos.y).abs() > Self::TOLERANCE;
+            if press.touch == touch && far {
+                self.press = None;
+            }
+        }
+    }
+
+    fn tick<M: Model>(&mut self, view: &Node<M>, elapsed: Duration, outputs: &mut Vec<M::Message>) {
+        let press = match self.press.as_mut() {
+            Some(press) => press,
+            None => return,
+        };
+        let before = press.held;
+        press.held += elapsed;
+        let prim = match topmost(view, press.pos, &|prim| {
+            prim.listeners.contains_key(&EventName::ON_LONG_PRESS)
+        }) {
+            Some(prim) => prim,
+            None => return,
+        };
+        for listener in prim.listeners.get(&EventName::ON_LONG_PRESS).into_iter().flatten() {
+            if let Listener::OnLongPress(duration, func) = listener {
+                if before < *duration && *duration <= press.held {
+                    outputs.push(func(On::new(prim, press.pos)));
+                }
+            }
+        }
+    }
+}
+
+fn send_gesture<M: Model>(view: &Node<M>, gesture: Gesture, outputs: &mut Vec<M::Message>) {
+    let names = [
+        (EventName::ON_PAN, gesture.pan != (0.0, 0.0)),
+        (EventName::ON_PINCH, gesture.scale != 1.0),
+        (EventName::ON_ROTATE, gesture.rotation != 0.0),
+    ];
+    let accept = |prim: &Prim<M>| names.iter().any(|(name, _)| prim.listeners.contains_key(name));
+    let prim = match topmost(view, gesture.centroid, &accept) {
+        Some(prim) => prim,
+        None => return,
+    };
+    for (name, _) in names.iter().filter(|(_, changed)| *changed) {
+        for listener in prim.listeners.get(name).into_iter().flatten() {
+            if let Listener::OnPan(func) | Listener::OnPinch(func) | Listener::OnRotate(func) = listener {
+                outputs.push(func(On::new(prim, gesture)));
+            }
+        }
+    }
+}
+
+/// The last drawn prim under the point accepted by the predicate, out of the clipped areas.
+fn topmost<'a, M: Model>(node: &'a Node<M>, pos: MousePos, accept: &dyn Fn(&Prim<M>) -> bool) -> Option<&'a Prim<M>> {
+    match node {
+        Node::Prim(prim) => prim.hit_test_by(pos.x, pos.y, accept),
+        Node::Comp(_) => None,
+    }
+}
+
+/// Sends the right button press to the `on_context_menu` listeners of the
+/// topmost prim which has them under the cursor. The press is ignored over the
+/// overlays, so a shown context menu does not open another one.
+pub fn send_context_menu<M: Model>(view: &Node<M>, press: MouseDown, outputs: &mut Vec<M::Message>) {
+    if press.button != MouseButton::Right {
+        return;
+    }
+    let mut overlays = vec![];
+    collect_overlays(view, &mut overlays);
+    if overlays.iter().any(|overlay| contains(*overlay, press.pos)) {
+        return;
+    }
+
+    let accept = |prim: &Prim<M>| prim.listeners.contains_key(&EventName::ON_CONTEXT_MENU);
+    if let Some(prim) = topmost(view, press.pos, &accept) {
+        for listener in prim.listeners.get(&EventName::ON_CONTEXT_MENU).into_iter().flatten() {
+            if let Listener::OnContextMenu(func) = listener {
+                outputs.push(func(On::new(prim, press)));
+            }
+        }
+    }
+}
+
+fn contains(composite: &dyn CompositeShape, pos: MousePos) -> bool {
+    composite.intersect(pos.x, pos.y)
+        || composite
+            .children()
+            .into_iter()
+            .flatten()
+            .any(|child| contains(child, pos))
+}
+
+fn fire<M: Model>(view: &Node<M>, id: &str, name: EventName, info: &DragInfo, outputs: &mut Vec<M::Message>) {
+    let prim = match view.get_prim(id) {
+        Some(prim) => prim,
+        None => return,
+    };
+    for listener in prim.listeners.get(&name).into_iter().flatten() {
+        let func = match listener {
+            Listener::OnDragStart(func)
+            | Listener::OnDrag(func)
+            | Listener::OnDragEnd(func)
+            | Listener::OnDrop(func) => func,
+            _ => continue,
+        };
+        outputs.push(func(On::new(prim, info.clone())));
+    }
+}
@@ -1,6 +1,8 @@
-use std::time::Duration;
+use std::{borrow::Cow, time::Duration};
 
-use crate::{InputEvent, Node};
+use crate::{
+    AccessRequest, Command, CompError, CustomEvent, FrameStats, HotkeyId, InputEvent, Node, Real, SystemTheme, Timers,
+};
 
 pub trait Model: Sized + 'static {
     type Message;
@@ -15,27 +17,144 @@ pub trait Model: Sized + 'static {
 
     fn update(&mut self, msg: Self::Message) -> ChangeView;
 
+    /// Updates the model by all the messages of one event, e.g. the outputs of
+    /// the listeners and the finished commands of a frame, before the view is
+    /// updated once. Override it to coalesce the messages.
+    fn update_batch(&mut self, msgs: Vec<Self::Message>) -> ChangeView {
+        msgs.into_iter()
+            .fold(ChangeView::None, |change, msg| change.merge(self.update(msg)))
+    }
+
+    /// The commands to run on the other threads or on the async runtime set by
+    /// `Comp::set_spawner` after `update`, whose results come back to `update` as
+    /// the messages. Usually `update` queues them in the model and this method takes them.
+    fn commands(&mut self) -> Vec<Command<Self::Message>> {
+        Vec::new()
+    }
+
+    /// The timers of the model, advanced by the component every frame.
+    fn timers(&mut self) -> Option<&mut Timers<Self::Message>> {
+        None
+    }
+
+    /// Called with the new properties pushed by `Comp::change_props`, or when the parent
+    /// view is rebuilt with the new properties of the nested component created by
+    /// `Comp::with_props`, which keeps this model.
+    #[allow(unused_variables)]
+    fn change(&mut self, props: Self::Properties) -> ChangeView {
+        ChangeView::None
+    }
+
     fn build_view(&self) -> Node<Self>;
 
     #[allow(unused_variables)]
     fn modify_view(&mut self, view: &mut Node<Self>) {
         unimplemented!();
     }
+
+    /// Modifies the node with the id for `ChangeView::ModifyIds`. The modified prim
+    /// is drawn again, and laid out again only if its geometry is changed. Does
+    /// nothing by default.
+    #[allow(unused_variables)]
+    fn modify_node(&mut self, id: &str, node: &mut Node<Self>) {}
+
+    /// Called before the first view update of the component in the tree, and
+    /// again when it is put back after `Comp::unmount`, e.g. to start the timers
+    /// or to load the resources.
+    fn on_mount(&mut self) -> ChangeView {
+        ChangeView::None
+    }
+
+    /// Called after the component is drawn for the first time.
+    fn on_first_render(&mut self) -> ChangeView {
+        ChangeView::None
+    }
+
+    /// Called when the mounted component is removed from the tree by `Comp::unmount`
+    /// or dropped, e.g. to release the resources.
+    fn on_unmount(&mut self) {}
+
+    /// Returns `false` to skip the view update of the component and of its nested
+    /// components in the frames without a `ChangeView` from `update`, instead of
+    /// checking the whole view for the changes every frame. Then the prims changed
+    /// outside of `update` and `modify_view`, e.g. by the state styles, are drawn
+    /// with the next change.
+    fn should_render(&self) -> bool {
+        true
+    }
+
+    /// Called when the window is asked to close by the close button or the exit key.
+    /// Returns `false` to keep it open, e.g. to confirm the unsaved changes first.
+    fn close_requested(&mut self) -> bool {
+        true
+    }
+
+    /// The checksum of the model state for the lockstep and replay checks,
+    /// usually made by the `checksum` function. `None` if the model is not checked.
+    fn checksum(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// The handler of the messages to a component before its `update`, e.g. for the
+/// logging, the analytics, the feature flags or the recording of a replay. It
+/// returns the message to pass on, changed or not, or `None` to swallow it.
+pub trait Middleware<M: Model> {
+    fn handle(&mut self, model: &M, msg: M::Message) -> Option<M::Message>;
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+impl<M: Model, F: FnMut(&M, M::Message) -> Option<M::Message>> Middleware<M> for F {
+    fn handle(&mut self, model: &M, msg: M::Message) -> Option<M::Message> {
+        self(model, msg)
+    }
+}
+
+/// The id of the middleware added to a component, to remove it.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct MiddlewareId(pub(crate) u64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChangeView {
     Rebuild,
     Modify,
+    /// Modifies only the nodes with the ids by `Model::modify_node`, without
+    /// the whole view traversal of `Model::modify_view`.
+    ModifyIds(Vec<Cow<'static, str>>),
     RecalcOnly,
     None,
 }
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+impl ChangeView {
+    /// The change covering both of the changes.
+    pub fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (ChangeView::Rebuild, _) | (_, ChangeView::Rebuild) => ChangeView::Rebuild,
+            (ChangeView::Modify, _) | (_, ChangeView::Modify) => ChangeView::Modify,
+            (ChangeView::ModifyIds(mut ids), ChangeView::ModifyIds(other)) => {
+                for id in other {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+                ChangeView::ModifyIds(ids)
+            },
+            // The recalculation is done by `modify_view` only
+            (ChangeView::ModifyIds(_), ChangeView::RecalcOnly) | (ChangeView::RecalcOnly, ChangeView::ModifyIds(_)) => {
+                ChangeView::Modify
+            },
+            (change, ChangeView::None) | (ChangeView::None, change) => change,
+            (ChangeView::RecalcOnly, ChangeView::RecalcOnly) => ChangeView::RecalcOnly,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct ChangeViewState {
     pub need_rebuild: bool,
     pub need_modify: bool,
     pub need_recalc: bool,
+    /// The ids of the nodes to modify, unless the whole view is modified.
+    pub modify_ids: Vec<Cow<'static, str>>,
 }
 
 impl ChangeViewState {
@@ -43,15 +162,58 @@ impl ChangeViewState {
         match change_view {
             ChangeView::Rebuild => self.need_rebuild = true,
             ChangeView::Modify => self.need_modify = true,
+            ChangeView::ModifyIds(ids) => {
+                for id in ids {
+                    if !self.modify_ids.contains(&id) {
+                        self.modify_ids.push(id);
+                    }
+                }
+            },
             ChangeView::RecalcOnly => self.need_recalc = true,
             ChangeView::None => (),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SystemMessage {
-    WindowResized { width: u32, height: u32 },
+    WindowResized {
+        width: u32,
+        height: u32,
+    },
+    /// The window is moved, the position of its top-left corner on the desktop is in physical pixels.
+    WindowMoved {
+        x: i32,
+        y: i32,
+    },
+    /// The DPI scale factor of the window is changed, e.g. it is moved to another monitor.
+    ScaleFactorChanged(f64),
+    /// The app is sent to the background, e.g. on mobile platforms, where its
+    /// graphics resources may be lost until it is `Resumed`.
+    Suspended,
+    Resumed,
     Draw(Duration),
+    /// The fixed step of the game logic, sent at the fixed rate independent of
+    /// the frame rate before the `Draw`, see `FixedTimestep`.
+    Update(Duration),
+    /// The fraction of the fixed step passed since the last `Update`, to draw the
+    /// state interpolated between the last two updates.
+    Interpolate(Real),
     Input(InputEvent),
+    /// The OS light/dark preference is reported at the start or is changed.
+    ThemeChanged(SystemTheme),
+    /// The global hotkey is pressed, see `GlobalHotkeys` for the platforms where it
+    /// works while the window is unfocused.
+    Hotkey(HotkeyId),
+    /// The window gains (`true`) or loses the input focus, e.g. to pause a game
+    /// or to stop blinking the text caret.
+    WindowFocused(bool),
+    /// The event sent by a `CompSender`, usually from another thread.
+    Custom(CustomEvent),
+    /// The assistive technology performs the action on the prim.
+    Access(AccessRequest),
+    /// The frame stats sent periodically, see `StatsCounter`.
+    Stats(FrameStats),
+    /// The error boundary has caught the panic and shows its fallback view, see `Comp::with_fallback`.
+    CompFailed(CompError),
 }
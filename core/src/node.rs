@@ -1,12 +1,25 @@
-pub use self::{clip::*, comp::*, converter::*, prim::*, shape::*, transform::*, value::*};
-use crate::{Model, SystemMessage};
+pub use self::{
+    clip::*, comp::*, converter::*, decoration::*, fragment::*, index::*, layout::*, loader::*, prim::*, registry::*,
+    shape::*, shape_tree::*, state_style::*, transform::*, value::*,
+};
+use std::rc::Rc;
+
+use crate::{view_to_svg, Model, SystemMessage};
 
 pub mod builder;
 pub mod clip;
 pub mod comp;
 pub mod converter;
+pub mod decoration;
+pub mod fragment;
+pub mod index;
+pub mod layout;
+pub mod loader;
 pub mod prim;
+pub mod registry;
 pub mod shape;
+pub mod shape_tree;
+pub mod state_style;
 pub mod transform;
 pub mod value;
 
@@ -28,6 +41,7 @@ impl<M: Model> Node<M> {
             Node::Prim(prim) => prim.shape.set_id(id),
             Node::Comp(comp) => comp.set_id(id),
         }
+        self.refresh_id_index();
     }
 
     pub fn as_prim(&self) -> Option<&Prim<M>> {
@@ -72,12 +86,141 @@ impl<M: Model> Node<M> {
         }
     }
 
+    /// Returns a deep copy of the node, to stamp a prototype subtree or to keep
+    /// a snapshot of the view. Returns `None` if the subtree contains components
+    /// not created with `Comp::cloneable`.
+    pub fn try_clone(&self) -> Option<Self> {
+        match self {
+            Node::Prim(prim) => prim.try_clone().map(Node::Prim),
+            Node::Comp(comp) => comp.try_clone().map(Node::Comp),
+        }
+    }
+
+    /// Builds the id index of the subtree, which speeds up the lookups by id
+    /// from this node. The index must be rebuilt after the subtree changes,
+    /// otherwise the lookups fall back to the tree search.
+    pub fn build_id_index(&mut self) {
+        let index = IdIndex::build(self);
+        if let Node::Prim(prim) = self {
+            prim.set_id_index(Some(index));
+        }
+    }
+
+    pub fn node_at(&self, path: &[usize]) -> Option<&Node<M>> {
+        let mut node = self;
+        for &idx in path {
+            node = node.as_prim()?.node(idx)?;
+        }
+        Some(node)
+    }
+
+    pub fn node_at_mut(&mut self, path: &[usize]) -> Option<&mut Node<M>> {
+        let mut node = self;
+        for &idx in path {
+            node = node.as_prim_mut()?.node_mut(idx)?;
+        }
+        Some(node)
+    }
+
+    fn indexed(&self, id: &str) -> Option<&Node<M>> {
+        let path = self.as_prim()?.id_index()?.path(id)?;
+        self.node_at(path).filter(|node| node.get_id() == Some(id))
+    }
+
+    fn indexed_path(&self, id: &str) -> Option<Rc<[usize]>> {
+        let path = self.as_prim()?.id_index()?.shared_path(id)?;
+        if self.node_at(&path)?.get_id() == Some(id) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    fn child_path(&self, id: &str) -> Option<Vec<usize>> {
+        if let Some(path) = self.indexed_path(id) {
+            return Some(path.to_vec());
+        }
+
+        for (idx, child) in self.as_prim()?.nodes().enumerate() {
+            if child.get_id() == Some(id) {
+                return Some(vec![idx]);
+            }
+            if let Some(mut path) = child.child_path(id) {
+                path.insert(0, idx);
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    fn refresh_id_index(&mut self) {
+        if self.as_prim().and_then(Prim::id_index).is_some() {
+            self.build_id_index();
+        }
+    }
+
+    /// Removes the subtree with the id from its parent prim. The subtree keeps
+    /// its state, so it can be attached elsewhere or back later without rebuilding.
+    pub fn detach(&mut self, id: impl AsRef<str>) -> Option<Node<M>> {
+        let id = id.as_ref();
+        let mut path = self.child_path(id)?;
+        path.pop()?;
+
+        let node = self.node_at_mut(&path)?.as_prim_mut()?.remove_child(id)?;
+        self.refresh_id_index();
+        Some(node)
+    }
+
+    /// Inserts the subtree as a child of the prim with the parent id at the index.
+    /// Returns the subtree back if there is no such prim.
+    pub fn attach(&mut self, parent_id: impl AsRef<str>, idx: usize, node: Node<M>) -> Result<(), Box<Node<M>>> {
+        match self.get_prim_mut(parent_id) {
+            Some(parent) => parent.insert_child(idx, node),
+            None => return Err(Box::new(node)),
+        }
+        self.refresh_id_index();
+        Ok(())
+    }
+
+    /// Moves the subtree with the id under the prim with the parent id. Returns `false`
+    /// and leaves the subtree in place if any of the nodes is not found.
+    pub fn reparent(&mut self, id: impl AsRef<str>, parent_id: impl AsRef<str>, idx: usize) -> bool {
+        let id = id.as_ref();
+        let mut path = match self.child_path(id) {
+            Some(path) => path,
+            None => return false,
+        };
+        let old_idx = match path.pop() {
+            Some(old_idx) => old_idx,
+            None => return false,
+        };
+        let node = match self.detach(id) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        match self.attach(parent_id, idx, node) {
+            Ok(()) => true,
+            Err(node) => {
+                if let Some(parent) = self.node_at_mut(&path).and_then(Node::as_prim_mut) {
+                    parent.insert_child(old_idx, *node);
+                }
+                self.refresh_id_index();
+                false
+            },
+        }
+    }
+
     pub fn get(&self, id: impl AsRef<str>) -> Option<&Node<M>> {
         let id = id.as_ref();
+        if let Some(node) = self.indexed(id) {
+            return Some(node);
+        }
+
         match self {
             Node::Prim(prim) if prim.id() == Some(id) => Some(self),
             Node::Prim(prim) => {
-                for child in &prim.children {
+                for child in prim.nodes() {
                     if let Some(node) = child.get(id) {
                         return Some(node);
                     }
@@ -91,10 +234,14 @@ impl<M: Model> Node<M> {
 
     pub fn get_mut(&mut self, id: impl AsRef<str>) -> Option<&mut Node<M>> {
         let id = id.as_ref();
+        if let Some(path) = self.indexed_path(id) {
+            return self.node_at_mut(&path);
+        }
+
         match self {
             Node::Prim(prim) if prim.id() == Some(id) => Some(self),
             Node::Prim(prim) => {
-                for child in &mut prim.children {
+                for child in prim.nodes_mut() {
                     if let Some(node) = child.get_mut(id) {
                         return Some(node);
                     }
@@ -108,12 +255,16 @@ impl<M: Model> Node<M> {
 
     pub fn get_prim(&self, id: impl AsRef<str>) -> Option<&Prim<M>> {
         let id = id.as_ref();
+        if let Some(node) = self.indexed(id) {
+            return node.as_prim();
+        }
+
         match self {
             Node::Prim(prim) => {
                 if prim.id() == Some(id) {
                     Some(prim)
                 } else {
-                    for child in &prim.children {
+                    for child in prim.nodes() {
                         if let Some(prim) = child.get_prim(id) {
                             return Some(prim);
                         }
@@ -127,12 +278,16 @@ impl<M: Model> Node<M> {
 
     pub fn get_prim_mut(&mut self, id: impl AsRef<str>) -> Option<&mut Prim<M>> {
         let id = id.as_ref();
+        if let Some(path) = self.indexed_path(id) {
+            return self.node_at_mut(&path)?.as_prim_mut();
+        }
+
         match self {
             Node::Prim(prim) => {
                 if prim.id() == Some(id) {
                     Some(prim)
                 } else {
-                    for child in &mut prim.children {
+                    for child in prim.nodes_mut() {
                         if let Some(prim) = child.get_prim_mut(id) {
                             return Some(prim);
                         }
@@ -146,10 +301,14 @@ impl<M: Model> Node<M> {
 
     pub fn get_comp_mut(&mut self, id: impl AsRef<str>) -> Option<&mut Comp> {
         let id = id.as_ref();
+        if let Some(path) = self.indexed_path(id) {
+            return self.node_at_mut(&path)?.as_comp_mut();
+        }
+
         match self {
             Node::Comp(comp) if comp.id() == Some(id) => Some(comp),
             Node::Prim(prim) => {
-                for child in &mut prim.children {
+                for child in prim.nodes_mut() {
                     if let Some(comp) = child.get_comp_mut(id) {
                         return Some(comp);
                     }
@@ -167,6 +326,27 @@ impl<M: Model> Node<M> {
         }
     }
 
+    /// The transform to the window coordinates of the node with the id, see
+    /// `CompositeShape::global_transform`.
+    pub fn global_transform_of(&self, id: impl AsRef<str>) -> Option<TransformMatrix> {
+        self.get(id)?.global_transform()
+    }
+
+    /// The bound in the window coordinates of the node with the id.
+    pub fn global_bounds_of(&self, id: impl AsRef<str>) -> Option<BoundingBox> {
+        self.get(id)?.global_bounds()
+    }
+
+    /// The topmost prim under the point, see `Prim::hit_test`.
+    pub fn hit_test(&self, x: Real, y: Real) -> Option<&Prim<M>> {
+        self.as_prim()?.hit_test(x, y)
+    }
+
+    /// Exports the laid out view to an SVG document.
+    pub fn to_svg(&self) -> String {
+        view_to_svg(self)
+    }
+
     pub fn send_system_msg(&mut self, msg: SystemMessage, outputs: &mut Vec<M::Message>) {
         match self {
             Node::Prim(prim) => prim.send_system_msg(msg, outputs),
@@ -192,7 +372,7 @@ impl<M: Model> CompositeShape for Node<M> {
 
     fn shape_mut(&mut self) -> Option<&mut Shape> {
         match self {
-            Node::Prim(prim) => prim.shape_mut(),
+            Node::Prim(prim) => CompositeShape::shape_mut(prim),
             Node::Comp(comp) => comp.shape_mut(),
         }
     }
@@ -211,6 +391,27 @@ impl<M: Model> CompositeShape for Node<M> {
         }
     }
 
+    fn decorations(&self) -> Option<DecorationIter<'_>> {
+        match self {
+            Node::Prim(prim) => CompositeShape::decorations(prim),
+            Node::Comp(comp) => CompositeShape::decorations(comp),
+        }
+    }
+
+    fn decorations_mut(&mut self) -> Option<DecorationIterMut<'_>> {
+        match self {
+            Node::Prim(prim) => CompositeShape::decorations_mut(prim),
+            Node::Comp(comp) => CompositeShape::decorations_mut(comp),
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        match self {
+            Node::Prim(prim) => CompositeShape::is_overlay(prim),
+            Node::Comp(comp) => CompositeShape::is_overlay(comp),
+        }
+    }
+
     fn need_recalc(&self) -> Option<bool> {
         match self {
             Node::Prim(prim) => CompositeShape::need_recalc(prim),
@@ -224,4 +425,25 @@ impl<M: Model> CompositeShape for Node<M> {
             Node::Comp(comp) => CompositeShape::need_redraw(comp),
         }
     }
+
+    fn mark_drawn(&mut self) {
+        match self {
+            Node::Prim(prim) => CompositeShape::mark_drawn(prim),
+            Node::Comp(comp) => CompositeShape::mark_drawn(comp),
+        }
+    }
+
+    fn layout(&self) -> Option<Layout> {
+        match self {
+            Node::Prim(prim) => CompositeShape::layout(prim),
+            Node::Comp(comp) => CompositeShape::layout(comp),
+        }
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        match self {
+            Node::Prim(prim) => CompositeShape::set_layout(prim, layout),
+            Node::Comp(comp) => CompositeShape::set_layout(comp, layout),
+        }
+    }
 }
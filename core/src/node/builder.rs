@@ -1,5 +1,9 @@
+use std::{any::Any, path::PathBuf, time::Duration};
+
 use crate::{
-    Fill, KeyboardEvent, Listener, Model, MouseDown, MouseScroll, Node, On, Real, RealValue, Stroke, Transform,
+    AccessAction, Anchor, CursorIcon, DragInfo, Fill, GamepadAxisEvent, GamepadButtonEvent, Gesture, KeyboardEvent,
+    Listener, ListenerRate, LongPressRecognizer, Margin, Model, MouseDown, MouseMove, MousePos, MouseScroll, MouseUp,
+    Node, On, Real, RealValue, Stroke, TextSelection, Touch, Transform,
 };
 
 pub trait Builder<M: Model> {
@@ -17,14 +21,71 @@ pub trait Entity {
     fn transform(self, transform: impl Into<Transform>) -> Self;
 }
 
-pub trait Primitive<M: Model> {
+/// The outer spacing of the shapes which have the margin, see `Margin`.
+pub trait Margined: Sized {
+    fn margin_mut(&mut self) -> &mut Margin;
+
+    fn margin(mut self, margin: impl Into<Margin>) -> Self {
+        *self.margin_mut() = margin.into();
+        self
+    }
+
+    fn margin_top(mut self, top: impl Into<RealValue>) -> Self {
+        self.margin_mut().top = top.into();
+        self
+    }
+
+    fn margin_left(mut self, left: impl Into<RealValue>) -> Self {
+        self.margin_mut().left = left.into();
+        self
+    }
+
+    fn margin_right(mut self, right: impl Into<RealValue>) -> Self {
+        self.margin_mut().right = right.into();
+        self
+    }
+
+    fn margin_bottom(mut self, bottom: impl Into<RealValue>) -> Self {
+        self.margin_mut().bottom = bottom.into();
+        self
+    }
+
+    fn margin_top_and_bottom(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.margin_mut().top = margin;
+        self.margin_mut().bottom = margin;
+        self
+    }
+
+    fn margin_left_and_right(mut self, margin: impl Into<RealValue>) -> Self {
+        let margin = margin.into();
+        self.margin_mut().left = margin;
+        self.margin_mut().right = margin;
+        self
+    }
+}
+
+pub trait Primitive<M: Model>: Sized {
     fn child(self, child: impl Builder<M>) -> Self;
     fn children(self, children: impl IntoIterator<Item = Node<M>>) -> Self;
+    fn decoration(self, anchor: Anchor, decoration: impl Builder<M>) -> Self;
     fn transparency(self, transparency: impl Into<Real>) -> Self;
     fn stroke(self, stroke: impl Into<Stroke>) -> Self;
+    fn stroke_hover(self, stroke: impl Into<Stroke>) -> Self;
+    fn stroke_pressed(self, stroke: impl Into<Stroke>) -> Self;
     fn fill(self, fill: impl Into<Fill>) -> Self;
+    /// The fill while the node is hovered, see `StateStyle`.
+    fn fill_hover(self, fill: impl Into<Fill>) -> Self;
+    /// The fill while the node is pressed, see `StateStyle`.
+    fn fill_pressed(self, fill: impl Into<Fill>) -> Self;
     fn remove_stroke(self) -> Self;
     fn remove_fill(self) -> Self;
+    /// Attaches the application data to the node, see `Prim::user_data`.
+    fn user_data(self, data: impl Any) -> Self;
+    /// Captures the mouse by the press on the node until the release, see `Prim::set_mouse_capture`.
+    fn capture_mouse(self) -> Self;
+    /// Sets the cursor shown over the node, see `Prim::set_cursor`.
+    fn cursor(self, cursor: CursorIcon) -> Self;
     fn clip(
         self,
         x: impl Into<RealValue>,
@@ -32,12 +93,46 @@ pub trait Primitive<M: Model> {
         width: impl Into<RealValue>,
         height: impl Into<RealValue>,
     ) -> Self;
+
+    fn child_if(self, cond: bool, child: impl Builder<M>) -> Self {
+        if cond {
+            self.child(child)
+        } else {
+            self
+        }
+    }
+
+    fn maybe_child(self, child: Option<impl Builder<M>>) -> Self {
+        match child {
+            Some(child) => self.child(child),
+            None => self,
+        }
+    }
+
+    fn children_iter<B: Builder<M>>(self, children: impl IntoIterator<Item = B>) -> Self {
+        self.children(children.into_iter().map(Builder::build))
+    }
 }
 
 pub trait EventHandler<M: Model>: Sized {
     fn add_listener(&mut self, listener: Listener<M>);
 
-    fn on_click(self, _trigger: fn(()) -> M::Message) -> Self {
+    /// The left button pressed and released on the prim or its descendants, the
+    /// listener gets the release.
+    fn on_click(mut self, trigger: fn(On<M, MouseUp>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnClick(trigger));
+        self
+    }
+
+    fn on_draw(mut self, trigger: fn(Duration) -> M::Message) -> Self {
+        self.add_listener(Listener::Draw(trigger));
+        self
+    }
+
+    /// Like `on_draw`, but calls the trigger at most with the given rate. The trigger
+    /// gets the time elapsed since its previous call.
+    fn on_draw_throttled(mut self, rate: ListenerRate, trigger: fn(Duration) -> M::Message) -> Self {
+        self.add_listener(Listener::ThrottledDraw(rate, trigger));
         self
     }
 
@@ -46,11 +141,68 @@ pub trait EventHandler<M: Model>: Sized {
         self
     }
 
+    /// Like `on_mouse_down`, but called in the capture phase: before the listeners
+    /// of the prims under this one, on the way from the root to the pressed prim.
+    fn on_mouse_down_capture(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
+        self.add_listener(Listener::Capture(Box::new(Listener::OnMouseDown(trigger))));
+        self
+    }
+
+    /// The second press of a double click on the prim, see `MouseController::with_double_click_interval`.
+    fn on_double_click(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnDoubleClick(trigger));
+        self
+    }
+
+    /// The right button press on the prim, unless a prim above it has the listener
+    /// or the press is over an overlay (e.g. the shown context menu).
+    fn on_context_menu(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnContextMenu(trigger));
+        self
+    }
+
+    fn on_mouse_up(mut self, trigger: fn(On<M, MouseUp>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnMouseUp(trigger));
+        self
+    }
+
+    fn on_mouse_up_capture(mut self, trigger: fn(On<M, MouseUp>) -> M::Message) -> Self {
+        self.add_listener(Listener::Capture(Box::new(Listener::OnMouseUp(trigger))));
+        self
+    }
+
+    /// Called when the cursor moves over the prim, only while it intersects the cursor,
+    /// e.g. for the crosshair tooltips. See `on_mouse_enter` and `on_mouse_leave` for the hover.
+    fn on_mouse_move(mut self, trigger: fn(On<M, MouseMove>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnMouseMove(trigger));
+        self
+    }
+
+    fn on_mouse_move_capture(mut self, trigger: fn(On<M, MouseMove>) -> M::Message) -> Self {
+        self.add_listener(Listener::Capture(Box::new(Listener::OnMouseMove(trigger))));
+        self
+    }
+
+    fn on_mouse_enter(mut self, trigger: fn(On<M, MouseMove>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnMouseEnter(trigger));
+        self
+    }
+
+    fn on_mouse_leave(mut self, trigger: fn(On<M, MouseMove>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnMouseLeave(trigger));
+        self
+    }
+
     fn on_mouse_scroll(mut self, trigger: fn(On<M, MouseScroll>) -> M::Message) -> Self {
         self.add_listener(Listener::OnMouseScroll(trigger));
         self
     }
 
+    fn on_mouse_scroll_capture(mut self, trigger: fn(On<M, MouseScroll>) -> M::Message) -> Self {
+        self.add_listener(Listener::Capture(Box::new(Listener::OnMouseScroll(trigger))));
+        self
+    }
+
     fn on_key_down(mut self, trigger: fn(On<M, KeyboardEvent>) -> M::Message) -> Self {
         self.add_listener(Listener::OnKeyDown(trigger));
         self
@@ -61,13 +213,115 @@ pub trait EventHandler<M: Model>: Sized {
         self
     }
 
+    fn on_gamepad_button(mut self, trigger: fn(On<M, GamepadButtonEvent>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnGamepadButton(trigger));
+        self
+    }
+
+    fn on_gamepad_axis(mut self, trigger: fn(On<M, GamepadAxisEvent>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnGamepadAxis(trigger));
+        self
+    }
+
     fn on_input_char(mut self, trigger: fn(On<M, char>) -> M::Message) -> Self {
         self.add_listener(Listener::OnInputChar(trigger));
         self
     }
 
+    /// A file dragged over the window, the listeners of all the prims are called
+    /// since the platforms do not report the cursor position during the drag.
+    fn on_file_hover(mut self, trigger: fn(On<M, PathBuf>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnFileHover(trigger));
+        self
+    }
+
+    /// A file dropped on the window, see `on_file_hover`.
+    fn on_file_drop(mut self, trigger: fn(On<M, PathBuf>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnFileDrop(trigger));
+        self
+    }
+
     fn on_blur(mut self, trigger: fn(On<M, MouseDown>) -> M::Message) -> Self {
         self.add_listener(Listener::OnBlur(trigger));
         self
     }
+
+    fn on_touch_start(mut self, trigger: fn(On<M, Touch>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnTouchStart(trigger));
+        self
+    }
+
+    fn on_touch_move(mut self, trigger: fn(On<M, Touch>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnTouchMove(trigger));
+        self
+    }
+
+    fn on_touch_end(mut self, trigger: fn(On<M, Touch>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnTouchEnd(trigger));
+        self
+    }
+
+    /// The left button or the finger held still on the prim, see `LongPressRecognizer`.
+    fn on_long_press(self, trigger: fn(On<M, MousePos>) -> M::Message) -> Self {
+        self.on_long_press_after(LongPressRecognizer::DEFAULT_DURATION, trigger)
+    }
+
+    /// Like `on_long_press`, but with the given hold duration.
+    fn on_long_press_after(mut self, duration: Duration, trigger: fn(On<M, MousePos>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnLongPress(duration, trigger));
+        self
+    }
+
+    /// The text selection by the press on the text prim and the drag, called on the press
+    /// and on every move of the caret until the release.
+    fn on_select(mut self, trigger: fn(On<M, TextSelection>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnSelect(trigger));
+        self
+    }
+
+    /// The action of the assistive technology on the prim, which should have an id.
+    fn on_access_action(mut self, trigger: fn(On<M, AccessAction>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnAccessAction(trigger));
+        self
+    }
+
+    /// The move of the fingers over the prim, or the touchpad scroll.
+    fn on_pan(mut self, trigger: fn(On<M, Gesture>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnPan(trigger));
+        self
+    }
+
+    /// The change of the distance between two fingers over the prim.
+    fn on_pinch(mut self, trigger: fn(On<M, Gesture>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnPinch(trigger));
+        self
+    }
+
+    /// The rotation of two fingers over the prim.
+    fn on_rotate(mut self, trigger: fn(On<M, Gesture>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnRotate(trigger));
+        self
+    }
+
+    /// The drag of the prim is started, the prim needs an id to be dragged.
+    fn on_drag_start(mut self, trigger: fn(On<M, DragInfo>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnDragStart(trigger));
+        self
+    }
+
+    fn on_drag(mut self, trigger: fn(On<M, DragInfo>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnDrag(trigger));
+        self
+    }
+
+    fn on_drag_end(mut self, trigger: fn(On<M, DragInfo>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnDragEnd(trigger));
+        self
+    }
+
+    /// A prim is dropped on this one, the prim needs an id to be a drop target.
+    fn on_drop(mut self, trigger: fn(On<M, DragInfo>) -> M::Message) -> Self {
+        self.add_listener(Listener::OnDrop(trigger));
+        self
+    }
 }
@@ -1,8 +1,9 @@
-use crate::{RealValue, Transform};
+use crate::{Real, RealValue, Transform};
 
 /// A scissor defines a region on the screen in which drawing operations are
 /// allowed. Pixels drawn outside of this region are clipped.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scissor {
     pub x: RealValue,
     pub y: RealValue,
@@ -13,6 +14,7 @@ pub struct Scissor {
 
 /// Define how to clip specified region.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Clip {
     Scissor(Scissor),
     None,
@@ -41,6 +43,28 @@ impl Clip {
         }
     }
 
+    /// Checks the point in the window coordinates is not clipped out.
+    pub fn contains(&self, x: Real, y: Real) -> bool {
+        match self {
+            Clip::Scissor(scissor) => {
+                let matrix = scissor
+                    .transform
+                    .global_matrix()
+                    .unwrap_or_else(|| scissor.transform.matrix());
+                let (x, y) = if !matrix.is_identity() {
+                    matrix.inverse() * (x, y)
+                } else {
+                    (x, y)
+                };
+                x >= scissor.x.val()
+                    && x <= scissor.x.val() + scissor.width.val()
+                    && y >= scissor.y.val()
+                    && y <= scissor.y.val() + scissor.height.val()
+            },
+            Clip::None => true,
+        }
+    }
+
     pub fn scissor(&self) -> Option<&Scissor> {
         match self {
             Clip::Scissor(scissor) => Some(scissor),
@@ -69,3 +93,19 @@ impl Default for Clip {
         Clip::None
     }
 }
+
+/// Defines whether the children of a container are drawn outside of its bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Overflow {
+    #[default]
+    Visible,
+    /// The children are clipped to the container bounds, both for rendering and for hit testing.
+    Hidden,
+}
+
+impl Overflow {
+    pub fn is_hidden(&self) -> bool {
+        matches!(self, Overflow::Hidden)
+    }
+}
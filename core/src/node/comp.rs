@@ -1,8 +1,17 @@
-use std::any::{type_name, Any};
+use std::{
+    any::{type_name, Any},
+    hash::Hasher,
+    mem,
+    rc::Rc,
+};
 
 use crate::{
-    ChangeViewState, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, Model, Node, Shape, SystemMessage,
-    Transform,
+    boundary::{catch_failure, report_failure},
+    send_context_menu, take_emitted, take_failures, BoundingBox, ChangeView, ChangeViewState, CommandRunner, CompError,
+    CompSender, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, CursorIcon, CustomEvent, DecorationIter,
+    DecorationIterMut, DragTracker, DrawThrottles, DynModel, DynMsg, FallbackFn, GestureRecognizer, Handling,
+    InputEvent, Layout, LongPressRecognizer, Mailbox, Middleware, MiddlewareId, Model, Node, Prim, Real, ResourceScope,
+    Resources, Shape, Spawner, StateHasher, SystemMessage, Transform, TransformMatrix, WakeFn,
 };
 
 pub trait AsAny: Any {
@@ -34,14 +43,33 @@ pub trait CompApi: AsAny {
     fn as_composite_shape(&self) -> Option<&dyn CompositeShape>;
     fn as_composite_shape_mut(&mut self) -> Option<&mut dyn CompositeShape>;
     fn send_system_msg(&mut self, msg: SystemMessage);
+    fn update_dyn(&mut self, msg: DynMsg) -> Result<(), DynMsg>;
+    fn dyn_model(&self) -> &dyn DynModel;
+    fn dyn_model_mut(&mut self) -> &mut dyn DynModel;
+    fn cursor(&self, x: Real, y: Real) -> Option<CursorIcon>;
     fn update_view(&mut self) -> UpdateView;
     fn need_recalc(&self) -> bool;
     fn need_redraw(&self) -> bool;
+    fn mark_drawn(&mut self);
+    fn checksum(&self) -> Option<u64>;
+    fn close_requested(&mut self) -> bool;
+    fn set_spawner(&mut self, spawner: Rc<dyn Spawner>);
+    fn set_waker(&mut self, waker: WakeFn);
+    fn shutdown(&mut self);
+    fn unmount(&mut self);
+    fn change_props(&mut self, from: &mut dyn CompApi) -> bool;
+    fn error(&self) -> Option<&CompError>;
+    fn view_state(&self) -> &ChangeViewState;
+    fn last_change(&self) -> &ChangeView;
+    fn recover(&mut self);
+    fn try_clone(&self) -> Option<Box<dyn CompApi>>;
 }
 
 #[derive(Debug, Clone, Copy)]
 pub enum UpdateView {
     Recalc,
+    /// Only the paint is changed, the layout is kept.
+    Redraw,
     RecalcAndRedraw,
     None,
 }
@@ -60,35 +88,79 @@ impl UpdateView {
     }
 
     pub fn is_redraw(&self) -> bool {
-        matches!(self, Self::RecalcAndRedraw)
+        matches!(self, Self::Redraw | Self::RecalcAndRedraw)
     }
 
     pub fn merge(&self, other: Self) -> Self {
-        match self {
-            UpdateView::Recalc => {
-                if matches!(other, UpdateView::RecalcAndRedraw) {
-                    UpdateView::RecalcAndRedraw
-                } else {
-                    UpdateView::Recalc
-                }
-            },
-            UpdateView::RecalcAndRedraw => UpdateView::RecalcAndRedraw,
-            UpdateView::None => other,
+        match (self, other) {
+            (UpdateView::None, other) => other,
+            (update, UpdateView::None) => *update,
+            (UpdateView::Recalc, UpdateView::Recalc) => UpdateView::Recalc,
+            (UpdateView::Redraw, UpdateView::Redraw) => UpdateView::Redraw,
+            _ => UpdateView::RecalcAndRedraw,
         }
     }
 }
 
 pub struct Comp {
     inner: Box<dyn CompApi>,
+    mailbox: Option<Mailbox>,
+    resources: Resources,
 }
 
 impl Comp {
     pub fn new(model: impl Model) -> Self {
         Self {
             inner: Box::new(CompInner::new(model)),
+            mailbox: None,
+            resources: Default::default(),
+        }
+    }
+
+    /// Creates a component by `Model::create` with the properties. When the parent
+    /// view is rebuilt, it is replaced by the component of the previous view at the
+    /// same place, which keeps its state and gets the new properties by `Model::change`.
+    pub fn with_props<M: Model>(props: M::Properties) -> Self
+    where
+        M::Properties: Clone,
+    {
+        let mut inner = CompInner::new(M::create(props.clone()));
+        inner.props = Some(props);
+        Self {
+            inner: Box::new(inner),
+            mailbox: None,
+            resources: Default::default(),
+        }
+    }
+
+    /// Makes the component an error boundary. The panics of its update and view
+    /// code and of its nested components are caught instead of taking the whole
+    /// app down: the view is replaced by the static fallback view, the model gets
+    /// no messages until `recover`, and the error is delivered by `send_failures`.
+    pub fn with_fallback<M: Model>(mut self, fallback: impl Fn(&CompError) -> Node<M> + 'static) -> Self {
+        self.inner_mut::<M>().set_fallback(fallback);
+        self
+    }
+
+    /// Creates a component that can be copied with `try_clone` along with its model.
+    pub fn cloneable(model: impl Model + Clone) -> Self {
+        Self {
+            inner: Box::new(CompInner::new(model).with_model_clone(Clone::clone)),
+            mailbox: None,
+            resources: Default::default(),
         }
     }
 
+    /// Returns a copy of the component with its model and view, or `None` if
+    /// the component was not created as cloneable.
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            inner: self.inner.try_clone()?,
+            mailbox: None,
+            resources: Default::default(),
+        })
+    }
+
     pub fn id(&self) -> Option<&str> {
         self.inner.id()
     }
@@ -119,10 +191,32 @@ impl Comp {
 
     #[inline]
     pub fn inner_mut<M: Model>(&mut self) -> &mut CompInner<M> {
-        (*self.inner)
-            .as_any_mut()
-            .downcast_mut::<CompInner<M>>()
-            .unwrap_or_else(|| panic!("Can't downcast mut CompInner to {}", type_name::<CompInner<M>>()))
+        downcast_inner_mut(&mut *self.inner)
+    }
+
+    /// Checks the component is of the model type.
+    pub fn is<M: Model>(&self) -> bool {
+        self.inner.dyn_model().is::<M>()
+    }
+
+    pub fn model_name(&self) -> &'static str {
+        self.inner.dyn_model().model_name()
+    }
+
+    /// The model of any type, see `DynModel`.
+    pub fn dyn_model(&self) -> &dyn DynModel {
+        self.inner.dyn_model()
+    }
+
+    pub fn dyn_model_mut(&mut self) -> &mut dyn DynModel {
+        self.inner.dyn_model_mut()
+    }
+
+    /// Sends the message if it is of the message type of the model, or returns it back.
+    pub fn try_send(&mut self, msg: DynMsg) -> Result<(), DynMsg> {
+        let _handling = Handling::start();
+        let _resources = ResourceScope::enter(&mut self.resources);
+        self.inner.update_dyn(msg)
     }
 
     #[inline]
@@ -135,18 +229,290 @@ impl Comp {
         &mut self.inner_mut::<M>().model
     }
 
+    /// The transform to the window coordinates of the shape with the id in the
+    /// component view, to position the nodes (e.g. popups) next to it.
+    pub fn global_transform(&self, id: &str) -> Option<TransformMatrix> {
+        find_shape(self.inner.as_composite_shape()?, id)?.global_transform()
+    }
+
+    /// The bound in the window coordinates of the shape with the id in the component view.
+    pub fn global_bounds(&self, id: &str) -> Option<BoundingBox> {
+        find_shape(self.inner.as_composite_shape()?, id)?.global_bounds()
+    }
+
+    /// The topmost prim of the component view under the point in the window
+    /// coordinates, for the custom picking. `M` is the model of the component.
+    pub fn hit_test<M: Model>(&self, x: Real, y: Real) -> Option<&Prim<M>> {
+        self.inner::<M>().view.as_ref()?.hit_test(x, y)
+    }
+
     pub fn send<M: Model>(&mut self, msg: M::Message) {
-        let inner = self.inner_mut::<M>();
-        inner.view_state.update(inner.model.update(msg));
+        let _handling = Handling::start();
+        let _resources = ResourceScope::enter(&mut self.resources);
+        downcast_inner_mut::<M>(&mut *self.inner).update(msg);
+    }
+
+    /// Pushes the new properties into the component, which keeps its state, see `Model::change`.
+    pub fn change_props<M: Model>(&mut self, props: M::Properties) {
+        let _handling = Handling::start();
+        let _resources = ResourceScope::enter(&mut self.resources);
+        downcast_inner_mut::<M>(&mut *self.inner).change(props);
+    }
+
+    /// Adds the middleware of the messages to the model, see `Middleware`.
+    pub fn add_middleware<M: Model>(&mut self, middleware: impl Middleware<M> + 'static) -> MiddlewareId {
+        self.inner_mut::<M>().add_middleware(middleware)
+    }
+
+    /// Removes the middleware, returns `false` if it is not found.
+    pub fn remove_middleware<M: Model>(&mut self, id: MiddlewareId) -> bool {
+        self.inner_mut::<M>().remove_middleware(id)
+    }
+
+    /// Updates the model by the message past the middleware, e.g. to replay the
+    /// messages recorded after it.
+    pub fn send_unintercepted<M: Model>(&mut self, msg: M::Message) {
+        let _handling = Handling::start();
+        let _resources = ResourceScope::enter(&mut self.resources);
+        downcast_inner_mut::<M>(&mut *self.inner).update_unintercepted(msg);
+    }
+
+    /// Sends the messages at once, see `Model::update_batch`.
+    pub fn send_batch<M: Model>(&mut self, msgs: Vec<M::Message>) {
+        let _handling = Handling::start();
+        let _resources = ResourceScope::enter(&mut self.resources);
+        downcast_inner_mut::<M>(&mut *self.inner).update_batch(msgs);
     }
 
     pub fn send_system_msg(&mut self, msg: SystemMessage) {
+        let _resources = ResourceScope::enter(&mut self.resources);
         self.inner.send_system_msg(msg);
     }
 
+    /// Inserts the resource reached by the models of the component and of the
+    /// nested ones with `with_resource`. Returns the resource of the type it replaces.
+    pub fn insert_resource<T: Any>(&mut self, value: T) -> Option<T> {
+        self.resources.insert(value)
+    }
+
+    pub fn resource<T: Any>(&self) -> Option<&T> {
+        self.resources.get()
+    }
+
+    pub fn resource_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.resources.get_mut()
+    }
+
+    pub fn remove_resource<T: Any>(&mut self) -> Option<T> {
+        self.resources.remove()
+    }
+
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        &mut self.resources
+    }
+
+    /// The handle to send the custom events to the component from the other threads.
+    /// The controller passes them to the root component as `SystemMessage::Custom`.
+    pub fn sender(&mut self) -> CompSender {
+        self.mailbox.get_or_insert_with(Mailbox::new).sender()
+    }
+
+    /// The handle to send the messages to the model of the component from the other threads.
+    pub fn message_sender<M: Model>(&mut self) -> CompSender<M::Message>
+    where
+        M::Message: Send,
+    {
+        self.mailbox.get_or_insert_with(Mailbox::new).sender()
+    }
+
+    /// Sets the function called by the senders after each event and by the commands
+    /// after they are finished, which wakes the event loop.
+    pub fn set_waker(&mut self, wake: impl Fn() + Send + 'static) {
+        let mailbox = self.mailbox.get_or_insert_with(Mailbox::new);
+        mailbox.set_waker(Box::new(wake));
+        self.inner.set_waker(mailbox.waker());
+    }
+
+    /// Sets the async runtime the future commands of the component are spawned on.
+    pub fn set_spawner(&mut self, spawner: impl Spawner + 'static) {
+        self.inner.set_spawner(Rc::new(spawner));
+    }
+
+    /// Stops the running future commands of the component and the nested ones,
+    /// called by the controller when the app exits.
+    pub fn shutdown(&mut self) {
+        self.inner.shutdown();
+    }
+
+    /// Removes the component from the tree without dropping it, e.g. to keep it in
+    /// the back stack, see `Model::on_unmount`. It is mounted again by the next view
+    /// update in the tree.
+    pub fn unmount(&mut self) {
+        self.inner.unmount();
+    }
+
+    /// Sends the events and the messages received from the senders to the component, returns `true` if there are any.
+    pub fn receive_events(&mut self) -> bool {
+        let _resources = ResourceScope::enter(&mut self.resources);
+        let mut received = false;
+        while let Some(letter) = self.mailbox.as_ref().and_then(Mailbox::try_recv) {
+            match letter.downcast::<CustomEvent>() {
+                Ok(event) => self.inner.send_system_msg(SystemMessage::Custom(*event)),
+                Err(msg) => {
+                    let _handling = Handling::start();
+                    let _ = self.inner.update_dyn(DynMsg::from(msg as Box<dyn Any>));
+                },
+            }
+            received = true;
+        }
+        received
+    }
+
+    /// The cursor to show over the point of the component view, see `Prim::cursor_at`.
+    pub fn cursor(&self, x: Real, y: Real) -> CursorIcon {
+        self.inner.cursor(x, y).unwrap_or_default()
+    }
+
+    /// Asks the model whether the window can be closed, see `Model::close_requested`.
+    pub fn close_requested(&mut self) -> bool {
+        self.inner.close_requested()
+    }
+
+    /// The checksum of the component model combined with the checksums of the
+    /// nested components in the view order, `None` if any of them is not checked.
+    pub fn checksum(&self) -> Option<u64> {
+        self.inner.checksum()
+    }
+
     pub fn update_view(&mut self) -> UpdateView {
+        let _resources = ResourceScope::enter(&mut self.resources);
         self.inner.update_view()
     }
+
+    /// The current view tree of the component, `M` is its model.
+    pub fn view<M: Model>(&self) -> Option<&Node<M>> {
+        self.inner::<M>().view()
+    }
+
+    /// The view changes scheduled by the model and not applied by `update_view` yet.
+    pub fn view_state(&self) -> &ChangeViewState {
+        self.inner.view_state()
+    }
+
+    /// The view change returned by the model last, e.g. by `update`.
+    pub fn last_change(&self) -> &ChangeView {
+        self.inner.last_change()
+    }
+
+    /// The number of the events and the messages sent by the senders and not received yet.
+    pub fn pending_count(&self) -> usize {
+        self.mailbox.as_ref().map_or(0, |mailbox| mailbox.pending().len())
+    }
+
+    /// The copies of the messages to the model sent by the senders and not received
+    /// yet, see `message_sender`.
+    pub fn pending_messages<M: Model>(&self) -> Vec<M::Message>
+    where
+        M::Message: Clone,
+    {
+        match self.mailbox.as_ref() {
+            Some(mailbox) => mailbox
+                .pending()
+                .iter()
+                .filter_map(|letter| letter.downcast_ref::<M::Message>())
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The error caught by the boundary, see `with_fallback`.
+    pub fn error(&self) -> Option<&CompError> {
+        self.inner.error()
+    }
+
+    /// Clears the error of the boundary and rebuilds its view from the model, which is mounted again.
+    pub fn recover(&mut self) {
+        self.inner.recover();
+    }
+
+    /// Sends the errors caught by the boundaries of the UI thread to this root
+    /// component as `SystemMessage::CompFailed`. Returns `true` if there were any.
+    pub fn send_failures(&mut self) -> bool {
+        let failures = take_failures();
+        let failed = !failures.is_empty();
+        for error in failures {
+            self.send_system_msg(SystemMessage::CompFailed(error));
+        }
+        failed
+    }
+}
+
+fn downcast_inner_mut<M: Model>(inner: &mut dyn CompApi) -> &mut CompInner<M> {
+    inner
+        .as_any_mut()
+        .downcast_mut::<CompInner<M>>()
+        .unwrap_or_else(|| panic!("Can't downcast mut CompInner to {}", type_name::<CompInner<M>>()))
+}
+
+fn hash_nested_comps<M: Model>(node: &Node<M>, hasher: &mut StateHasher) -> Option<()> {
+    match node {
+        Node::Comp(comp) => hasher.write_u64(comp.checksum()?),
+        Node::Prim(prim) => {
+            for node in prim.nodes() {
+                hash_nested_comps(node, hasher)?;
+            }
+        },
+    }
+    Some(())
+}
+
+/// Moves the nested components of the old view to the same places of the rebuilt
+/// one, so they keep their state. The components are matched by the id, or by the
+/// order of the ones without an id, and should be of the same model and created
+/// by `Comp::with_props`.
+fn keep_nested_comps<M: Model>(view: &mut Node<M>, old: &mut Node<M>) {
+    let mut old_comps = vec![];
+    comps_mut(old, &mut old_comps);
+    let mut old_comps: Vec<_> = old_comps.into_iter().map(Some).collect();
+    let mut new_comps = vec![];
+    comps_mut(view, &mut new_comps);
+
+    for new in new_comps {
+        let slot = old_comps
+            .iter_mut()
+            .find(|old| matches!(old, Some(old) if old.id() == new.id()));
+        if let Some(old) = slot.and_then(Option::take) {
+            if old.inner.change_props(&mut *new.inner) {
+                old.set_transform(*new.transform());
+                mem::swap(old, new);
+            }
+        }
+    }
+}
+
+fn comps_mut<'a, M: Model>(node: &'a mut Node<M>, comps: &mut Vec<&'a mut Comp>) {
+    match node {
+        Node::Comp(comp) => comps.push(comp),
+        Node::Prim(prim) => {
+            for node in prim.nodes_mut() {
+                comps_mut(node, comps);
+            }
+        },
+    }
+}
+
+fn find_shape<'a>(view: &'a dyn CompositeShape, id: &str) -> Option<&'a dyn CompositeShape> {
+    if view.shape().and_then(Shape::id) == Some(id) {
+        return Some(view);
+    }
+    let found = view
+        .children()
+        .and_then(|mut children| children.find_map(|child| find_shape(child, id)));
+    found.or_else(|| {
+        view.decorations()?
+            .find_map(|(_, decoration)| find_shape(decoration, id))
+    })
 }
 
 impl CompositeShape for Comp {
@@ -166,6 +532,14 @@ impl CompositeShape for Comp {
         self.inner.as_composite_shape_mut()?.children_mut()
     }
 
+    fn decorations(&self) -> Option<DecorationIter<'_>> {
+        self.inner.as_composite_shape()?.decorations()
+    }
+
+    fn decorations_mut(&mut self) -> Option<DecorationIterMut<'_>> {
+        self.inner.as_composite_shape_mut()?.decorations_mut()
+    }
+
     fn need_recalc(&self) -> Option<bool> {
         Some(self.inner.need_recalc())
     }
@@ -173,6 +547,20 @@ impl CompositeShape for Comp {
     fn need_redraw(&self) -> Option<bool> {
         Some(self.inner.need_redraw())
     }
+
+    fn mark_drawn(&mut self) {
+        self.inner.mark_drawn();
+    }
+
+    fn layout(&self) -> Option<Layout> {
+        self.inner.as_composite_shape()?.layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        if let Some(view) = self.inner.as_composite_shape_mut() {
+            view.set_layout(layout);
+        }
+    }
 }
 
 impl<M: Model> From<M> for Comp {
@@ -183,31 +571,198 @@ impl<M: Model> From<M> for Comp {
 
 pub struct CompInner<M: Model> {
     id: Option<String>,
-    _props: Option<M::Properties>,
+    /// The properties the component is created with, passed to `Model::change`
+    /// of the component it replaces when the parent view is rebuilt.
+    props: Option<M::Properties>,
     model: M,
     view: Option<Node<M>>,
     view_state: ChangeViewState,
+    /// The last view change returned by the model, for the inspection in the tests.
+    last_change: ChangeView,
     view_update: UpdateView,
+    draw_throttles: DrawThrottles,
+    drag_tracker: DragTracker,
+    gestures: GestureRecognizer,
+    long_press: LongPressRecognizer,
+    commands: CommandRunner,
+    middleware: Vec<(MiddlewareId, Box<dyn Middleware<M>>)>,
+    next_middleware_id: u64,
+    mounted: bool,
+    rendered: bool,
+    fallback: Option<Rc<FallbackFn<M>>>,
+    error: Option<CompError>,
     transform: Transform,
+    clone_model: Option<fn(&M) -> M>,
 }
 
 impl<M: Model> CompInner<M> {
     pub fn new(model: M) -> Self {
-        let view = model.build_view();
+        let mut view = model.build_view();
+        view.build_id_index();
 
         Self {
             id: None,
-            _props: None,
+            props: None,
             model,
             view: Some(view),
             view_state: ChangeViewState {
                 need_rebuild: true,
                 ..Default::default()
             },
+            last_change: ChangeView::None,
             view_update: UpdateView::RecalcAndRedraw,
+            draw_throttles: Default::default(),
+            drag_tracker: Default::default(),
+            gestures: Default::default(),
+            long_press: Default::default(),
+            commands: Default::default(),
+            middleware: Vec::new(),
+            next_middleware_id: 0,
+            mounted: false,
+            rendered: false,
+            fallback: None,
+            error: None,
             transform: Default::default(),
+            clone_model: None,
         }
     }
+
+    pub fn with_model_clone(mut self, clone_model: fn(&M) -> M) -> Self {
+        self.clone_model = Some(clone_model);
+        self
+    }
+
+    /// Adds the middleware on top of the stack, it gets the messages after the ones added before.
+    pub fn add_middleware(&mut self, middleware: impl Middleware<M> + 'static) -> MiddlewareId {
+        let id = MiddlewareId(self.next_middleware_id);
+        self.next_middleware_id += 1;
+        self.middleware.push((id, Box::new(middleware)));
+        id
+    }
+
+    pub fn remove_middleware(&mut self, id: MiddlewareId) -> bool {
+        let len = self.middleware.len();
+        self.middleware.retain(|(middleware_id, _)| *middleware_id != id);
+        self.middleware.len() != len
+    }
+
+    pub fn set_fallback(&mut self, fallback: impl Fn(&CompError) -> Node<M> + 'static) {
+        self.fallback = Some(Rc::new(fallback));
+    }
+
+    /// Updates the model by the message passed through the middleware and runs its commands.
+    pub fn update(&mut self, msg: M::Message) {
+        self.guard(|this| {
+            if let Some(msg) = this.intercept(msg) {
+                this.apply_update(msg);
+            }
+        });
+    }
+
+    /// Updates the model by the message past the middleware and runs its commands.
+    pub fn update_unintercepted(&mut self, msg: M::Message) {
+        self.guard(|this| this.apply_update(msg));
+    }
+
+    fn apply_update(&mut self, msg: M::Message) {
+        let change = self.model.update(msg);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(model = type_name::<M>(), ?change, "update");
+        self.schedule(change);
+        self.run_commands();
+    }
+
+    /// Passes the new properties to `Model::change` and schedules the view change it returns.
+    pub fn change(&mut self, props: M::Properties) {
+        self.guard(|this| {
+            let change = this.model.change(props);
+            this.schedule(change);
+        });
+    }
+
+    /// Schedules the view change made outside of `update`.
+    pub fn change_view(&mut self, change: ChangeView) {
+        self.schedule(change);
+    }
+
+    fn schedule(&mut self, change: ChangeView) {
+        self.view_state.update(change.clone());
+        self.last_change = change;
+    }
+
+    /// Updates the model by the messages passed through the middleware with
+    /// `Model::update_batch` and runs its commands.
+    pub fn update_batch(&mut self, msgs: Vec<M::Message>) {
+        self.guard(|this| {
+            let msgs: Vec<_> = msgs.into_iter().filter_map(|msg| this.intercept(msg)).collect();
+            if msgs.is_empty() {
+                return;
+            }
+            #[cfg(feature = "tracing")]
+            let count = msgs.len();
+            let change = this.model.update_batch(msgs);
+            #[cfg(feature = "tracing")]
+            tracing::trace!(model = type_name::<M>(), count, ?change, "update_batch");
+            this.schedule(change);
+            this.run_commands();
+        });
+    }
+
+    fn intercept(&mut self, msg: M::Message) -> Option<M::Message> {
+        let model = &self.model;
+        self.middleware
+            .iter_mut()
+            .try_fold(msg, |msg, (_, middleware)| middleware.handle(model, msg))
+    }
+
+    fn run_commands(&mut self) {
+        for command in self.model.commands() {
+            self.commands.run(command);
+        }
+    }
+
+    /// Runs the code of the model and of the nested components, catching its panic
+    /// if the component is an error boundary. Returns `None` if the boundary has failed.
+    fn guard<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        if self.error.is_some() {
+            return None;
+        }
+        if self.fallback.is_none() {
+            return Some(f(self));
+        }
+
+        match catch_failure::<M, _>(|| f(self)) {
+            Ok(result) => Some(result),
+            Err(error) => {
+                self.fail(error);
+                None
+            },
+        }
+    }
+
+    fn fail(&mut self, error: CompError) {
+        #[cfg(feature = "tracing")]
+        tracing::error!(%error, "caught by the error boundary");
+        if let Some(fallback) = self.fallback.clone() {
+            let mut view = fallback(&error);
+            view.build_id_index();
+            self.view = Some(view);
+        }
+        self.view_state = Default::default();
+        self.view_update = UpdateView::RecalcAndRedraw;
+        self.mounted = false;
+        self.rendered = false;
+        report_failure(error.clone());
+        self.error = Some(error);
+    }
+
+    pub fn view(&self) -> Option<&Node<M>> {
+        self.view.as_ref()
+    }
+
+    pub fn view_mut(&mut self) -> Option<&mut Node<M>> {
+        self.view.as_mut()
+    }
 }
 
 impl<M: Model> CompApi for CompInner<M> {
@@ -240,53 +795,131 @@ impl<M: Model> CompApi for CompInner<M> {
     }
 
     fn send_system_msg(&mut self, msg: SystemMessage) {
-        let mut outputs = vec![];
-        if let Some(msg) = self.model.system_update(msg) {
-            outputs.push(msg);
-        }
+        self.guard(|this| {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("system_msg", model = type_name::<M>(), msg = ?msg).entered();
+            let _handling = Handling::start();
+            let mut outputs = this.commands.finished::<M::Message>();
+            if let Some(msg) = this.model.system_update(msg.clone()) {
+                outputs.push(msg);
+            }
 
-        if let Some(view) = self.view.as_mut() {
-            view.send_system_msg(msg, &mut outputs);
-        }
+            if let Some(view) = this.view.as_mut() {
+                view.send_system_msg(msg.clone(), &mut outputs);
+                this.long_press.handle(view, &msg, &mut outputs);
+                if let SystemMessage::Input(input) = &msg {
+                    this.drag_tracker.handle(view, input.clone(), &mut outputs);
+                    this.gestures.handle(view, input.clone(), &mut outputs);
+                    if let InputEvent::MouseDown(press) = *input {
+                        send_context_menu(view, press, &mut outputs);
+                    }
+                }
 
-        for msg in outputs {
-            self.view_state.update(self.model.update(msg));
-        }
+                if let (&SystemMessage::Draw(duration), Node::Prim(prim)) = (&msg, view) {
+                    this.draw_throttles.tick(duration);
+                    prim.send_throttled_draw(&mut this.draw_throttles, &mut outputs);
+                }
+            }
+            outputs.extend(take_emitted::<M::Message>());
+            if let (&SystemMessage::Draw(elapsed), Some(timers)) = (&msg, this.model.timers()) {
+                outputs.extend(timers.advance(elapsed));
+            }
+
+            this.update_batch(outputs);
+        });
+    }
+
+    fn update_dyn(&mut self, msg: DynMsg) -> Result<(), DynMsg> {
+        self.update(msg.downcast()?);
+        Ok(())
+    }
+
+    fn dyn_model(&self) -> &dyn DynModel {
+        &self.model
+    }
+
+    fn dyn_model_mut(&mut self) -> &mut dyn DynModel {
+        &mut self.model
+    }
+
+    fn cursor(&self, x: Real, y: Real) -> Option<CursorIcon> {
+        self.view.as_ref()?.as_prim()?.cursor_at(x, y)
     }
 
     fn update_view(&mut self) -> UpdateView {
-        let mut need_to_propagate_update = true;
-        let mut update = UpdateView::None;
+        let update = self.guard(|this| {
+            if !this.mounted {
+                this.mounted = true;
+                let change = this.model.on_mount();
+                this.schedule(change);
+                this.run_commands();
+            }
 
-        if self.view_state.need_rebuild {
-            let view = self.model.build_view();
-            self.view = Some(view);
-            self.view_state.need_rebuild = false;
-            need_to_propagate_update = false;
-            update = UpdateView::RecalcAndRedraw;
-        }
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::trace_span!("update_view", model = type_name::<M>(), state = ?this.view_state).entered();
 
-        if self.view_state.need_modify || self.view_state.need_recalc {
-            let mut view = self.view.take().unwrap();
-            self.model.modify_view(&mut view);
-            self.view = Some(view);
-            if self.view_state.need_recalc {
-                self.view_state.need_recalc = false;
-                update = UpdateView::Recalc;
+            if this.view_state == ChangeViewState::default() && !this.model.should_render() {
+                #[cfg(feature = "tracing")]
+                tracing::trace!("skipped by should_render");
+                this.view_update = UpdateView::None;
+                return UpdateView::None;
             }
-            if self.view_state.need_modify {
-                self.view_state.need_modify = false;
+
+            let mut need_to_propagate_update = true;
+            let mut update = UpdateView::None;
+
+            if this.view_state.need_rebuild {
+                let mut view = this.model.build_view();
+                if let Some(old) = this.view.as_mut() {
+                    keep_nested_comps(&mut view, old);
+                }
+                view.build_id_index();
+                this.view = Some(view);
+                this.view_state.need_rebuild = false;
+                need_to_propagate_update = false;
                 update = UpdateView::RecalcAndRedraw;
             }
-        }
 
-        if need_to_propagate_update {
-            if let Some(view) = self.view.as_mut() {
-                update = view.update_view().merge(update);
+            let modify_ids = mem::take(&mut this.view_state.modify_ids);
+            if need_to_propagate_update && !this.view_state.need_modify {
+                if let Some(view) = this.view.as_mut() {
+                    for id in modify_ids {
+                        if let Some(node) = view.get_mut(&id) {
+                            this.model.modify_node(&id, node);
+                            if let Node::Prim(prim) = node {
+                                prim.invalidate_paint();
+                            }
+                        }
+                    }
+                }
             }
-        }
-        self.view_update = update;
-        update
+
+            if this.view_state.need_modify || this.view_state.need_recalc {
+                let mut view = this.view.take().unwrap();
+                this.model.modify_view(&mut view);
+                this.view = Some(view);
+                if this.view_state.need_recalc {
+                    this.view_state.need_recalc = false;
+                    update = UpdateView::Recalc;
+                }
+                if this.view_state.need_modify {
+                    this.view_state.need_modify = false;
+                    update = UpdateView::RecalcAndRedraw;
+                }
+            }
+
+            if need_to_propagate_update {
+                if let Some(view) = this.view.as_mut() {
+                    update = view.update_view().merge(update);
+                }
+            }
+            #[cfg(feature = "tracing")]
+            tracing::trace!(?update, "view updated");
+            this.view_update = update;
+            update
+        });
+        update.unwrap_or(self.view_update)
     }
 
     fn need_recalc(&self) -> bool {
@@ -296,4 +929,142 @@ impl<M: Model> CompApi for CompInner<M> {
     fn need_redraw(&self) -> bool {
         self.view_update.is_redraw()
     }
+
+    fn mark_drawn(&mut self) {
+        if let Some(view) = self.view.as_mut() {
+            view.mark_drawn();
+        }
+        self.view_update = UpdateView::None;
+
+        if self.mounted && !self.rendered {
+            self.rendered = true;
+            self.guard(|this| {
+                let change = this.model.on_first_render();
+                this.schedule(change);
+                this.run_commands();
+            });
+        }
+    }
+
+    fn checksum(&self) -> Option<u64> {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.model.checksum()?);
+        if let Some(view) = self.view.as_ref() {
+            hash_nested_comps(view, &mut hasher)?;
+        }
+        Some(hasher.finish())
+    }
+
+    fn close_requested(&mut self) -> bool {
+        self.model.close_requested()
+    }
+
+    fn set_spawner(&mut self, spawner: Rc<dyn Spawner>) {
+        self.commands.set_spawner(spawner);
+    }
+
+    fn set_waker(&mut self, waker: WakeFn) {
+        self.commands.set_waker(waker);
+    }
+
+    fn shutdown(&mut self) {
+        self.commands.shutdown();
+        if let Some(view) = self.view.as_mut() {
+            let mut comps = vec![];
+            comps_mut(view, &mut comps);
+            for comp in comps {
+                comp.shutdown();
+            }
+        }
+    }
+
+    fn unmount(&mut self) {
+        if !self.mounted {
+            return;
+        }
+        self.mounted = false;
+        self.model.on_unmount();
+        if let Some(view) = self.view.as_mut() {
+            let mut comps = vec![];
+            comps_mut(view, &mut comps);
+            for comp in comps {
+                comp.unmount();
+            }
+        }
+    }
+
+    fn change_props(&mut self, from: &mut dyn CompApi) -> bool {
+        let props = match from.as_any_mut().downcast_mut::<Self>() {
+            Some(from) => from.props.take(),
+            None => return false,
+        };
+        match props {
+            Some(props) => {
+                self.change(props);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn error(&self) -> Option<&CompError> {
+        self.error.as_ref()
+    }
+
+    fn view_state(&self) -> &ChangeViewState {
+        &self.view_state
+    }
+
+    fn last_change(&self) -> &ChangeView {
+        &self.last_change
+    }
+
+    fn recover(&mut self) {
+        if self.error.take().is_some() {
+            self.view_state.update(ChangeView::Rebuild);
+        }
+    }
+
+    fn try_clone(&self) -> Option<Box<dyn CompApi>> {
+        let clone_model = self.clone_model?;
+        let view = match self.view.as_ref() {
+            Some(view) => {
+                let mut view = view.try_clone()?;
+                view.build_id_index();
+                Some(view)
+            },
+            None => None,
+        };
+
+        Some(Box::new(Self {
+            id: self.id.clone(),
+            props: None,
+            model: clone_model(&self.model),
+            view,
+            view_state: self.view_state.clone(),
+            last_change: ChangeView::None,
+            view_update: UpdateView::RecalcAndRedraw,
+            draw_throttles: Default::default(),
+            drag_tracker: Default::default(),
+            gestures: Default::default(),
+            long_press: Default::default(),
+            commands: Default::default(),
+            middleware: Vec::new(),
+            next_middleware_id: 0,
+            mounted: false,
+            rendered: false,
+            fallback: self.fallback.clone(),
+            error: self.error.clone(),
+            transform: self.transform,
+            clone_model: Some(clone_model),
+        }))
+    }
+}
+
+impl<M: Model> Drop for CompInner<M> {
+    fn drop(&mut self) {
+        if self.mounted {
+            self.model.on_unmount();
+        }
+    }
 }
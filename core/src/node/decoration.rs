@@ -0,0 +1,52 @@
+use crate::{BoundingBox, Model, Node, Real};
+
+/// The point of the host bounds a decoration is attached to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    #[default]
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn point(&self, bound: &BoundingBox) -> (Real, Real) {
+        let center_x = bound.min_x + bound.width() / 2.0;
+        let center_y = bound.min_y + bound.height() / 2.0;
+        match self {
+            Anchor::TopLeft => (bound.min_x, bound.min_y),
+            Anchor::Top => (center_x, bound.min_y),
+            Anchor::TopRight => (bound.max_x, bound.min_y),
+            Anchor::Left => (bound.min_x, center_y),
+            Anchor::Center => (center_x, center_y),
+            Anchor::Right => (bound.max_x, center_y),
+            Anchor::BottomLeft => (bound.min_x, bound.max_y),
+            Anchor::Bottom => (center_x, bound.max_y),
+            Anchor::BottomRight => (bound.max_x, bound.max_y),
+        }
+    }
+}
+
+/// A node drawn over its host prim (badge, status dot etc.). The coordinates
+/// of the node are relative to the anchor point of the host bounds, and
+/// the decoration does not affect the host size.
+pub struct Decoration<M: Model> {
+    pub anchor: Anchor,
+    pub node: Node<M>,
+}
+
+impl<M: Model> Decoration<M> {
+    pub fn new(anchor: Anchor, node: Node<M>) -> Self {
+        Self { anchor, node }
+    }
+
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self::new(self.anchor, self.node.try_clone()?))
+    }
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+
+use crate::{
+    builder::{Builder, Entity},
+    Model, Node, Transform,
+};
+
+/// A sub-view template with parameter slots. The template function runs once per
+/// distinct parameters, and every instance is a copy of the cached prototype node.
+/// The copies are laid out and rendered as separate nodes, the geometry is not
+/// shared between them.
+pub struct Fragment<M: Model, P> {
+    template: fn(&P) -> Node<M>,
+    prototype: RefCell<Option<(P, Node<M>)>>,
+}
+
+impl<M: Model, P: Clone + PartialEq> Fragment<M, P> {
+    pub fn new(template: fn(&P) -> Node<M>) -> Self {
+        Self {
+            template,
+            prototype: RefCell::new(None),
+        }
+    }
+
+    /// Creates an instance of the fragment. The id and transform of the instance
+    /// root can be set with the returned builder.
+    pub fn instance(&self, params: &P) -> FragmentBuilder<M> {
+        FragmentBuilder {
+            node: self.instantiate(params),
+        }
+    }
+
+    /// Drops the cached prototype, so the next instance builds the template again.
+    pub fn invalidate(&self) {
+        self.prototype.borrow_mut().take();
+    }
+
+    fn instantiate(&self, params: &P) -> Node<M> {
+        let mut prototype = self.prototype.borrow_mut();
+        if let Some((prototype_params, node)) = prototype.as_ref() {
+            if prototype_params == params {
+                if let Some(node) = node.try_clone() {
+                    return node;
+                }
+            }
+        }
+
+        let node = (self.template)(params);
+        // Fragments with non-cloneable components can't be copied, so they are built for each instance
+        match node.try_clone() {
+            Some(instance) => {
+                *prototype = Some((params.clone(), node));
+                instance
+            },
+            None => node,
+        }
+    }
+}
+
+pub struct FragmentBuilder<M: Model> {
+    node: Node<M>,
+}
+
+impl<M: Model> Builder<M> for FragmentBuilder<M> {
+    fn build(self) -> Node<M> {
+        self.node
+    }
+}
+
+impl<M: Model> Entity for FragmentBuilder<M> {
+    fn id(mut self, id: impl Into<String>) -> Self {
+        self.node.set_id(id);
+        self
+    }
+
+    fn transform(mut self, transform: impl Into<Transform>) -> Self {
+        *self.node.transform_mut() = transform.into();
+        self
+    }
+}
@@ -0,0 +1,53 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{Model, Node};
+
+/// Paths to the nodes with ids, as the child positions from the root node.
+/// The positions of the decoration nodes follow the children ones.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IdIndex {
+    paths: HashMap<String, Rc<[usize]>>,
+}
+
+impl IdIndex {
+    pub fn build<M: Model>(root: &Node<M>) -> Self {
+        let mut index = Self::default();
+        let mut path = Vec::new();
+        index.add(root, &mut path);
+        index
+    }
+
+    pub fn path(&self, id: &str) -> Option<&[usize]> {
+        self.paths.get(id).map(|path| &path[..])
+    }
+
+    /// The shared path, which the mutable lookups hold while borrowing the tree.
+    pub(crate) fn shared_path(&self, id: &str) -> Option<Rc<[usize]>> {
+        self.paths.get(id).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    fn add<M: Model>(&mut self, node: &Node<M>, path: &mut Vec<usize>) {
+        if let Some(id) = node.get_id() {
+            // The first node in depth-first order wins, as with the linear search
+            self.paths
+                .entry(id.to_string())
+                .or_insert_with(|| path.as_slice().into());
+        }
+
+        if let Node::Prim(prim) = node {
+            for (idx, child) in prim.nodes().enumerate() {
+                path.push(idx);
+                self.add(child, path);
+                path.pop();
+            }
+        }
+    }
+}
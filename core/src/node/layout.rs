@@ -0,0 +1,81 @@
+use std::ops::Mul;
+
+use crate::{Margin, Real, TransformMatrix};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: Real,
+    pub min_y: Real,
+    pub max_x: Real,
+    pub max_y: Real,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> Real {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> Real {
+        self.max_y - self.min_y
+    }
+
+    /// The box containing the transformed corners of this box.
+    pub fn transformed(&self, matrix: TransformMatrix) -> Self {
+        let points = matrix * *self;
+        let mut bound = BoundingBox {
+            min_x: points[0].0,
+            min_y: points[0].1,
+            max_x: points[0].0,
+            max_y: points[0].1,
+        };
+        for &(x, y) in &points[1..] {
+            bound.min_x = bound.min_x.min(x);
+            bound.min_y = bound.min_y.min(y);
+            bound.max_x = bound.max_x.max(x);
+            bound.max_y = bound.max_y.max(y);
+        }
+        bound
+    }
+
+    /// Extends the box to also reserve the margin space of its shape.
+    pub fn with_margin(mut self, margin: &Margin) -> Self {
+        self.max_x += margin.left_and_right();
+        self.max_y += margin.top_and_bottom();
+        self
+    }
+}
+
+impl Mul<BoundingBox> for TransformMatrix {
+    type Output = [(Real, Real); 4];
+
+    fn mul(self, rhs: BoundingBox) -> Self::Output {
+        [
+            self * (rhs.min_x, rhs.min_y),
+            self * (rhs.min_x, rhs.max_y),
+            self * (rhs.max_x, rhs.min_y),
+            self * (rhs.max_x, rhs.max_y),
+        ]
+    }
+}
+
+/// Inputs and result of the last layout pass over a node. The renderer reuses
+/// the cached bound while the node is clean and the inputs are the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub parent_bound: BoundingBox,
+    pub parent_transform: TransformMatrix,
+    /// The width the texts of the node wrap at, set by the nearest ancestor rect.
+    pub wrap_width: Option<Real>,
+    pub bound: BoundingBox,
+}
+
+impl Layout {
+    pub fn is_valid_for(
+        &self,
+        parent_bound: BoundingBox,
+        parent_transform: TransformMatrix,
+        wrap_width: Option<Real>,
+    ) -> bool {
+        self.parent_bound == parent_bound && self.parent_transform == parent_transform && self.wrap_width == wrap_width
+    }
+}
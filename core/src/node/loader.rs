@@ -0,0 +1,137 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{CompRegistry, EventName, Listener, Model, Node, Prim, Shape};
+
+/// A data-driven view: the shapes with the names of their listeners. With the
+/// `serde` feature it is read from any format, e.g. by `ron::from_str` or
+/// `serde_json::from_str`, and turned into the view by the [`ViewLoader`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewDescription {
+    pub shape: Shape,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub listeners: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<ViewDescription>,
+    /// The registered component placed instead of the shape, which gives it the id.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub comp: Option<CompDescription>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompDescription {
+    /// The name of the component in the [`CompRegistry`].
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub props: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadViewError {
+    UnknownListener { id: Option<String>, name: String },
+    UnknownComp { name: String },
+    InvalidProps { name: String, props: String },
+}
+
+impl fmt::Display for LoadViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadViewError::UnknownListener { id: Some(id), name } => {
+                write!(f, "Unknown listener '{}' of the node '{}'", name, id)
+            },
+            LoadViewError::UnknownListener { id: None, name } => write!(f, "Unknown listener '{}'", name),
+            LoadViewError::UnknownComp { name } => write!(f, "Unknown component '{}'", name),
+            LoadViewError::InvalidProps { name, props } => {
+                write!(f, "Invalid properties '{}' of the component '{}'", props, name)
+            },
+        }
+    }
+}
+
+impl Error for LoadViewError {}
+
+/// Builds the views from the descriptions, binding the listener names to
+/// the listeners registered in the loader and creating the components of the registry.
+pub struct ViewLoader<M: Model> {
+    listeners: HashMap<String, Listener<M>>,
+    registry: CompRegistry,
+}
+
+impl<M: Model> ViewLoader<M> {
+    pub fn new() -> Self {
+        Self {
+            listeners: HashMap::new(),
+            registry: CompRegistry::new(),
+        }
+    }
+
+    pub fn with_listener(mut self, name: impl Into<String>, listener: Listener<M>) -> Self {
+        self.add_listener(name, listener);
+        self
+    }
+
+    pub fn add_listener(&mut self, name: impl Into<String>, listener: Listener<M>) {
+        self.listeners.insert(name.into(), listener);
+    }
+
+    pub fn with_registry(mut self, registry: CompRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn registry(&self) -> &CompRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut CompRegistry {
+        &mut self.registry
+    }
+
+    pub fn load(&self, description: ViewDescription) -> Result<Node<M>, LoadViewError> {
+        let ViewDescription {
+            shape,
+            listeners: names,
+            children,
+            comp,
+        } = description;
+
+        if let Some(CompDescription { name, props }) = comp {
+            if !self.registry.contains(&name) {
+                return Err(LoadViewError::UnknownComp { name });
+            }
+            let mut comp = match self.registry.create(&name, &props) {
+                Some(comp) => comp,
+                None => return Err(LoadViewError::InvalidProps { name, props }),
+            };
+            if let Some(id) = shape.id() {
+                comp.set_id(id);
+            }
+            return Ok(Node::Comp(comp));
+        }
+
+        let mut listeners: HashMap<EventName, Vec<Listener<M>>> = HashMap::new();
+        for name in names {
+            let listener = match self.listeners.get(&name) {
+                Some(listener) => listener.clone(),
+                None => {
+                    let id = shape.id().map(ToString::to_string);
+                    return Err(LoadViewError::UnknownListener { id, name });
+                },
+            };
+            listeners.entry(listener.event_name()).or_default().push(listener);
+        }
+
+        let children = children
+            .into_iter()
+            .map(|child| self.load(child))
+            .collect::<Result<_, _>>()?;
+        Ok(Node::Prim(Prim::new(shape.name().into(), shape, children, listeners)))
+    }
+}
+
+impl<M: Model> Default for ViewLoader<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
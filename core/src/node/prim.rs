@@ -1,15 +1,33 @@
-use std::{borrow::Cow, collections::HashMap, marker::PhantomData};
+use std::{any::Any, borrow::Cow, cell::Cell, collections::HashMap, marker::PhantomData, ptr};
 
 use crate::{
-    CompositeShape, CompositeShapeIter, CompositeShapeIterMut, EventName, InputEvent, Listener, Model, Node, On, Shape,
-    SystemMessage, Transform, UpdateView,
+    Clip, CompositeShape, CompositeShapeIter, CompositeShapeIterMut, CursorIcon, Decoration, DecorationIter,
+    DecorationIterMut, DrawThrottles, EventName, Fill, IdIndex, InputEvent, Layout, Listener, Model, MouseButton,
+    MousePos, Node, On, Real, Shape, StateStyle, Stroke, SystemMessage, TextSelection, Transform, UpdateView,
 };
 
 pub struct Prim<M: Model> {
     pub name: Cow<'static, str>,
+    /// The direct changes of the shape are laid out or drawn by the next render
+    /// pass only after `invalidate_layout` or `invalidate_paint`, see `shape_mut`.
     pub shape: Shape,
     pub children: Vec<Node<M>>,
+    pub decorations: Vec<Decoration<M>>,
     pub listeners: HashMap<EventName, Vec<Listener<M>>>,
+    /// The application data associated with the node, e.g. the domain object it shows.
+    pub user_data: Option<Box<dyn Any>>,
+    layout: Option<Layout>,
+    need_recalc: bool,
+    need_redraw: bool,
+    overlay: bool,
+    hovered: bool,
+    capture_on_press: bool,
+    mouse_captured: bool,
+    pressed: bool,
+    selection: Option<TextSelection>,
+    cursor: Option<CursorIcon>,
+    state_style: Option<StateStyle>,
+    id_index: Option<IdIndex>,
     _model: PhantomData<M>,
 }
 
@@ -24,76 +42,571 @@ impl<M: Model> Prim<M> {
             name,
             shape,
             children,
+            decorations: Vec::new(),
             listeners,
+            user_data: None,
+            layout: None,
+            need_recalc: true,
+            need_redraw: true,
+            overlay: false,
+            hovered: false,
+            capture_on_press: false,
+            mouse_captured: false,
+            pressed: false,
+            selection: None,
+            cursor: None,
+            state_style: None,
+            id_index: None,
             _model: PhantomData,
         }
     }
 
+    pub fn with_decorations(mut self, decorations: Vec<Decoration<M>>) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn with_user_data(mut self, data: impl Any) -> Self {
+        self.set_user_data(data);
+        self
+    }
+
+    pub fn set_user_data(&mut self, data: impl Any) {
+        self.user_data = Some(Box::new(data));
+    }
+
+    /// The user data if it is of the type.
+    pub fn user_data<T: Any>(&self) -> Option<&T> {
+        self.user_data.as_ref()?.downcast_ref()
+    }
+
+    pub fn user_data_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.user_data.as_mut()?.downcast_mut()
+    }
+
+    /// Removes the user data if it is of the type.
+    pub fn take_user_data<T: Any>(&mut self) -> Option<T> {
+        match self.user_data.take()?.downcast() {
+            Ok(data) => Some(*data),
+            Err(data) => {
+                self.user_data = Some(data);
+                None
+            },
+        }
+    }
+
+    /// Moves the prim with its subtree to the overlay layer: it is laid out in
+    /// place, but drawn and hit tested above the whole view and is not clipped by
+    /// its ancestors. Used for the popups and tooltips.
+    pub fn with_overlay(mut self, overlay: bool) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Iterates over the children and then the decoration nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node<M>> {
+        self.children
+            .iter()
+            .chain(self.decorations.iter().map(|decoration| &decoration.node))
+    }
+
+    /// The child node or the decoration node following the children.
+    pub fn node(&self, idx: usize) -> Option<&Node<M>> {
+        match self.children.get(idx) {
+            Some(child) => Some(child),
+            None => self
+                .decorations
+                .get(idx - self.children.len())
+                .map(|decoration| &decoration.node),
+        }
+    }
+
+    pub fn node_mut(&mut self, idx: usize) -> Option<&mut Node<M>> {
+        match self.children.len() {
+            len if idx < len => self.children.get_mut(idx),
+            len => self
+                .decorations
+                .get_mut(idx - len)
+                .map(|decoration| &mut decoration.node),
+        }
+    }
+
+    /// The topmost prim of the subtree under the point in the window coordinates,
+    /// in the drawing order: decorations over children over the prim itself.
+    /// The views of the nested components are not tested.
+    pub fn hit_test(&self, x: Real, y: Real) -> Option<&Prim<M>> {
+        self.hit_test_by(x, y, &|_| true)
+    }
+
+    /// Like `hit_test`, but passes through the prims not accepted by the filter. The groups
+    /// have no area of their own, so an accepted group is hit over any of its hit descendants.
+    pub fn hit_test_by(&self, x: Real, y: Real, accept: &dyn Fn(&Prim<M>) -> bool) -> Option<&Prim<M>> {
+        let mut overlays = vec![];
+        self.collect_overlays(&mut overlays);
+        overlays
+            .into_iter()
+            .rev()
+            .find_map(|overlay| overlay.hit_test_clipped(x, y, Clip::None, accept, &mut false))
+            .or_else(|| self.hit_test_clipped(x, y, Clip::None, accept, &mut false))
+    }
+
+    /// The indices of the nodes from the prim to the topmost prim under the point.
+    fn hit_path(&self, x: Real, y: Real) -> Option<Vec<usize>> {
+        let target: *const Self = self.hit_test(x, y)?;
+        self.path_to(&|prim| ptr::eq(prim, target))
+    }
+
+    fn collect_overlays<'a>(&'a self, overlays: &mut Vec<&'a Prim<M>>) {
+        for node in self.nodes() {
+            if let Node::Prim(prim) = node {
+                if prim.overlay {
+                    overlays.push(prim);
+                }
+                prim.collect_overlays(overlays);
+            }
+        }
+    }
+
+    /// Sets `covered` if any prim of the subtree is under the point, accepted or not.
+    fn hit_test_clipped(
+        &self,
+        x: Real,
+        y: Real,
+        clip: Clip,
+        accept: &dyn Fn(&Prim<M>) -> bool,
+        covered: &mut bool,
+    ) -> Option<&Prim<M>> {
+        let children_clip = match &self.shape {
+            Shape::Group(group) => group.clip.or(clip),
+            _ => clip,
+        };
+        let children_visible = self.overflow_clip().is_none_or(|clip| clip.contains(x, y));
+        let children = self.children.iter().rev().filter(|_| children_visible);
+        let mut children_covered = false;
+        for node in self
+            .decorations
+            .iter()
+            .map(|decoration| &decoration.node)
+            .rev()
+            .chain(children)
+        {
+            if let Node::Prim(prim) = node {
+                if prim.overlay {
+                    continue;
+                }
+                if let Some(hit) = prim.hit_test_clipped(x, y, children_clip, accept, &mut children_covered) {
+                    return Some(hit);
+                }
+            }
+        }
+
+        let area_hit = match self.shape {
+            Shape::Group(_) => children_covered,
+            _ => self.intersect(x, y),
+        };
+        let hit = self.shape.clip().or(clip).contains(x, y) && area_hit;
+        *covered |= hit || children_covered;
+        if hit && accept(self) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    pub fn id_index(&self) -> Option<&IdIndex> {
+        self.id_index.as_ref()
+    }
+
+    pub fn set_id_index(&mut self, index: Option<IdIndex>) {
+        self.id_index = index;
+    }
+
+    pub fn nodes_mut(&mut self) -> impl Iterator<Item = &mut Node<M>> {
+        self.children
+            .iter_mut()
+            .chain(self.decorations.iter_mut().map(|decoration| &mut decoration.node))
+    }
+
+    /// Returns a deep copy of the prim, or `None` if the subtree contains
+    /// components that can't be cloned or the user data.
+    pub fn try_clone(&self) -> Option<Self> {
+        if self.user_data.is_some() {
+            return None;
+        }
+        Some(Self {
+            name: self.name.clone(),
+            shape: self.shape.clone(),
+            children: self.children.iter().map(Node::try_clone).collect::<Option<_>>()?,
+            decorations: self
+                .decorations
+                .iter()
+                .map(Decoration::try_clone)
+                .collect::<Option<_>>()?,
+            listeners: self.listeners.clone(),
+            user_data: None,
+            layout: None,
+            need_recalc: true,
+            need_redraw: true,
+            overlay: self.overlay,
+            hovered: false,
+            capture_on_press: self.capture_on_press,
+            mouse_captured: false,
+            pressed: false,
+            selection: None,
+            cursor: self.cursor,
+            state_style: self.state_style.clone(),
+            id_index: None,
+            _model: PhantomData,
+        })
+    }
+
     pub fn id(&self) -> Option<&str> {
         self.shape.id()
     }
 
+    /// Checks the cursor was over the prim at the last mouse move.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// Routes the mouse moves and releases to the prim even when the cursor is
+    /// out of its bounds, e.g. while a slider thumb is dragged.
+    pub fn set_mouse_capture(&mut self, capture: bool) {
+        self.mouse_captured = capture;
+    }
+
+    pub fn has_mouse_capture(&self) -> bool {
+        self.mouse_captured
+    }
+
+    /// Captures the mouse by the press on the prim and releases it by the button release.
+    pub fn set_capture_on_press(&mut self, capture: bool) {
+        self.capture_on_press = capture;
+    }
+
+    /// Checks the left button is pressed on the prim or its descendants and not released yet.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// The selection in progress in the text prim, from the press until the release.
+    pub fn selection(&self) -> Option<TextSelection> {
+        self.selection
+    }
+
+    pub fn state_style(&self) -> Option<&StateStyle> {
+        self.state_style.as_ref()
+    }
+
+    /// Sets the fill and the stroke applied while the prim is hovered or pressed.
+    pub fn set_state_style(&mut self, style: Option<StateStyle>) {
+        self.state_style = style;
+        self.apply_state_style();
+    }
+
+    fn apply_state_style(&mut self) {
+        if let Some(style) = self.state_style.as_mut() {
+            if style.apply(&mut self.shape, self.hovered, self.pressed) {
+                self.need_redraw = true;
+            }
+        }
+    }
+
+    pub fn cursor(&self) -> Option<CursorIcon> {
+        self.cursor
+    }
+
+    /// Sets the cursor shown over the prim and its descendants which have none.
+    pub fn set_cursor(&mut self, cursor: Option<CursorIcon>) {
+        self.cursor = cursor;
+    }
+
+    /// The cursor of the prim holding the mouse capture, or else of the topmost
+    /// prim under the point, inherited from the nearest ancestor which has one.
+    pub fn cursor_at(&self, x: Real, y: Real) -> Option<CursorIcon> {
+        let path = self
+            .path_to(&|prim| prim.mouse_captured)
+            .or_else(|| self.hit_path(x, y))?;
+        self.chain(&path).into_iter().rev().find_map(|prim| prim.cursor)
+    }
+
     pub fn set_id(&mut self, id: impl Into<String>) {
         self.shape.set_id(id);
+        self.need_redraw = true;
     }
 
     pub fn set_text(&mut self, content: impl Into<String>) -> bool {
         match self.shape {
             Shape::Text(ref mut text) => {
                 text.content = content.into();
+                self.invalidate_layout();
                 true
             },
             _ => false,
         }
     }
 
+    /// The shape to change, which is laid out again by the next render pass.
+    pub fn shape_mut(&mut self) -> &mut Shape {
+        self.invalidate_layout();
+        &mut self.shape
+    }
+
+    /// Sets the fill, which is drawn by the next render pass without the layout.
+    pub fn set_fill(&mut self, fill: Option<Fill>) {
+        *self.shape.fill_mut() = fill;
+        self.need_redraw = true;
+    }
+
+    /// Sets the stroke, which is drawn by the next render pass without the layout.
+    pub fn set_stroke(&mut self, stroke: Option<Stroke>) {
+        *self.shape.stroke_mut() = stroke;
+        self.need_redraw = true;
+    }
+
     pub fn transform(&self) -> &Transform {
         self.shape.transform()
     }
 
     pub fn transform_mut(&mut self) -> &mut Transform {
+        self.invalidate_layout();
         self.shape.transform_mut()
     }
 
+    /// Removes the direct child with the id. The child subtree keeps its state
+    /// (including the component models), so it can be inserted back later.
+    pub fn remove_child(&mut self, id: impl AsRef<str>) -> Option<Node<M>> {
+        let id = id.as_ref();
+        let idx = self.children.iter().position(|child| child.get_id() == Some(id))?;
+        self.invalidate_layout();
+        Some(self.children.remove(idx))
+    }
+
+    /// Inserts the child at the index, or at the end if the index exceeds the children count.
+    pub fn insert_child(&mut self, idx: usize, child: Node<M>) {
+        self.invalidate_layout();
+        self.children.insert(idx.min(self.children.len()), child);
+    }
+
+    /// Drops the cached layout, so the next render pass lays out this prim again.
+    pub fn invalidate_layout(&mut self) {
+        self.layout = None;
+        self.need_redraw = true;
+    }
+
+    /// Marks the prim to be drawn again by the next render pass without the layout.
+    pub fn invalidate_paint(&mut self) {
+        self.need_redraw = true;
+    }
+
+    /// Sends the message to the listeners of the subtree. The pointer events go
+    /// along the hit path to the topmost prim under the pointer, or to the prim
+    /// holding the mouse capture: first to the capture phase listeners from the
+    /// root down to the target, then to the other listeners back up to the root,
+    /// until a listener stops the propagation by `On::stop_propagation`.
     pub fn send_system_msg(&mut self, msg: SystemMessage, outputs: &mut Vec<M::Message>) {
-        match msg {
-            SystemMessage::Input(input) => match input {
+        let mut hits = vec![];
+        if let SystemMessage::Input(input) = &msg {
+            let hit_path = input.pointer_pos().and_then(|pos| self.hit_path(pos.x, pos.y));
+            if let Some(path) = &hit_path {
+                hits = self.chain(path).into_iter().map(|prim| prim as *const Self).collect();
+            }
+            self.dispatch_pointer(input, hit_path, outputs);
+        }
+        self.broadcast(&msg, &hits, outputs);
+    }
+
+    fn dispatch_pointer(&mut self, input: &InputEvent, hit_path: Option<Vec<usize>>, outputs: &mut Vec<M::Message>) {
+        let target = match input {
+            InputEvent::MouseMove(_) => self.path_to(&|prim| prim.mouse_captured),
+            InputEvent::MouseUp(release) => match self.path_to(&|prim| prim.mouse_captured) {
+                None if release.button == MouseButton::Left => self.path_to(&Self::is_press_target),
+                captured => captured,
+            },
+            _ => None,
+        };
+        let path = match target.or_else(|| hit_path.clone()) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let chain = self.chain(&path);
+        let stopped = Cell::new(false);
+        let phases = chain
+            .iter()
+            .map(|prim| (prim, true))
+            .chain(chain.iter().rev().map(|prim| (prim, false)));
+        for (prim, capture) in phases {
+            for name in pointer_events(input) {
+                prim.fire_pointer(if capture { name.capture() } else { *name }, input, &stopped, outputs);
+            }
+            if stopped.get() {
+                break;
+            }
+        }
+
+        match input {
+            InputEvent::MouseDown(press) => self.press_along(&path, press.button),
+            InputEvent::MouseUp(release) if release.button == MouseButton::Left => {
+                // The click goes to the deepest prim both pressed and released on.
+                let chain = hit_path.map_or(vec![], |path| self.chain(&path));
+                if let Some(clicked) = chain.iter().rposition(|prim| prim.pressed) {
+                    let stopped = Cell::new(false);
+                    for prim in chain[..=clicked].iter().rev() {
+                        prim.fire_pointer(EventName::ON_CLICK, input, &stopped, outputs);
+                        if stopped.get() {
+                            break;
+                        }
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// The indices of the nodes from the prim to the first prim of the subtree matching the predicate.
+    fn path_to(&self, matches: &dyn Fn(&Self) -> bool) -> Option<Vec<usize>> {
+        if matches(self) {
+            return Some(vec![]);
+        }
+        self.nodes().enumerate().find_map(|(idx, node)| match node {
+            Node::Prim(prim) => {
+                let mut path = prim.path_to(matches)?;
+                path.insert(0, idx);
+                Some(path)
+            },
+            Node::Comp(_) => None,
+        })
+    }
+
+    fn chain(&self, path: &[usize]) -> Vec<&Self> {
+        let mut chain = vec![self];
+        for idx in path {
+            match chain.last().and_then(|prim| prim.node(*idx)) {
+                Some(Node::Prim(prim)) => chain.push(prim),
+                _ => break,
+            }
+        }
+        chain
+    }
+
+    fn press_along(&mut self, path: &[usize], button: MouseButton) {
+        if self.capture_on_press {
+            self.mouse_captured = true;
+        }
+        if button == MouseButton::Left {
+            self.pressed = true;
+            self.apply_state_style();
+        }
+        if let Some((idx, path)) = path.split_first() {
+            if let Some(Node::Prim(prim)) = self.node_mut(*idx) {
+                prim.press_along(path, button);
+            }
+        }
+    }
+
+    /// The deepest prim of the pressed ones, which gets the release.
+    fn is_press_target(&self) -> bool {
+        self.pressed
+            && !self
+                .nodes()
+                .any(|node| matches!(node, Node::Prim(prim) if prim.pressed))
+    }
+
+    fn fire_pointer(&self, name: EventName, input: &InputEvent, stopped: &Cell<bool>, outputs: &mut Vec<M::Message>) {
+        for listener in self.listeners.get(&name).into_iter().flatten() {
+            if let Some(msg) = listener.call_pointer(self, input, stopped) {
+                outputs.push(msg);
+            }
+        }
+    }
+
+    fn start_selection(&mut self, pos: MousePos, outputs: &mut Vec<M::Message>) {
+        if !self.listeners.contains_key(&EventName::ON_SELECT) {
+            return;
+        }
+        if let Shape::Text(text) = &self.shape {
+            let caret = text.char_index_at(pos.x, pos.y);
+            self.selection = Some(TextSelection { anchor: caret, caret });
+            self.fire_selection(outputs);
+        }
+    }
+
+    fn extend_selection(&mut self, pos: MousePos, outputs: &mut Vec<M::Message>) {
+        if let (Some(selection), Shape::Text(text)) = (self.selection.as_mut(), &self.shape) {
+            let caret = text.char_index_at(pos.x, pos.y);
+            if caret != selection.caret {
+                selection.caret = caret;
+                self.fire_selection(outputs);
+            }
+        }
+    }
+
+    fn fire_selection(&self, outputs: &mut Vec<M::Message>) {
+        let selection = match self.selection {
+            Some(selection) => selection,
+            None => return,
+        };
+        for listener in self.listeners.get(&EventName::ON_SELECT).into_iter().flatten() {
+            if let Listener::OnSelect(func) = listener {
+                outputs.push(func(On::new(self, selection)));
+            }
+        }
+    }
+
+    /// Sends the message to the listeners of the other events, which get it regardless of the hit path.
+    /// The `hits` are the prims under the pointer: the topmost one and its ancestors.
+    fn broadcast(&mut self, msg: &SystemMessage, hits: &[*const Self], outputs: &mut Vec<M::Message>) {
+        let hit = hits.contains(&(self as *const Self));
+        match *msg {
+            SystemMessage::Input(ref input) => match *input {
                 InputEvent::MouseDown(press) => {
-                    if self.intersect(press.pos.x, press.pos.y) {
-                        if let Some(listeners) = self.listeners.get(&EventName::ON_MOUSE_DOWN) {
+                    if hit && press.button == MouseButton::Left {
+                        self.start_selection(press.pos, outputs);
+                    }
+                    if !hit {
+                        if let Some(listeners) = self.listeners.get(&EventName::ON_BLUR) {
                             for listener in listeners {
                                 let msg = match listener {
-                                    Listener::OnMouseDown(func) => func(On {
-                                        prim: self,
-                                        event: press,
-                                    }),
+                                    Listener::OnBlur(func) => func(On::new(self, press)),
                                     _ => continue,
                                 };
                                 outputs.push(msg);
                             }
                         }
-                    } else if let Some(listeners) = self.listeners.get(&EventName::ON_BLUR) {
-                        for listener in listeners {
-                            let msg = match listener {
-                                Listener::OnBlur(func) => func(On {
-                                    prim: self,
-                                    event: press,
-                                }),
-                                _ => continue,
-                            };
-                            outputs.push(msg);
-                        }
                     }
                 },
-                InputEvent::MouseScroll(scroll) => {
-                    if self.intersect(scroll.pos.x, scroll.pos.y) {
-                        if let Some(listeners) = self.listeners.get(&EventName::ON_MOUSE_SCROLL) {
+                InputEvent::MouseUp(release) => {
+                    if self.capture_on_press {
+                        self.mouse_captured = false;
+                    }
+                    if release.button == MouseButton::Left {
+                        self.pressed = false;
+                        self.selection = None;
+                        self.apply_state_style();
+                    }
+                },
+                InputEvent::MouseMove(movement) => {
+                    self.extend_selection(movement.pos, outputs);
+                    let hovered = hit;
+                    if hovered != self.hovered {
+                        self.hovered = hovered;
+                        self.apply_state_style();
+                        let name = if hovered {
+                            EventName::ON_MOUSE_ENTER
+                        } else {
+                            EventName::ON_MOUSE_LEAVE
+                        };
+                        if let Some(listeners) = self.listeners.get(&name) {
                             for listener in listeners {
                                 let msg = match listener {
-                                    Listener::OnMouseScroll(func) => func(On {
-                                        prim: self,
-                                        event: scroll,
-                                    }),
+                                    Listener::OnMouseEnter(func) | Listener::OnMouseLeave(func) => {
+                                        func(On::new(self, movement))
+                                    },
                                     _ => continue,
                                 };
                                 outputs.push(msg);
@@ -101,11 +614,12 @@ impl<M: Model> Prim<M> {
                         }
                     }
                 },
+                InputEvent::MouseScroll(_) => (),
                 InputEvent::KeyDown(event) => {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_KEY_DOWN) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnKeyDown(func) => func(On { prim: self, event }),
+                                Listener::OnKeyDown(func) => func(On::new(self, event)),
                                 _ => continue,
                             };
                             outputs.push(msg);
@@ -116,7 +630,30 @@ impl<M: Model> Prim<M> {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_KEY_UP) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnKeyUp(func) => func(On { prim: self, event }),
+                                Listener::OnKeyUp(func) => func(On::new(self, event)),
+                                _ => continue,
+                            };
+                            outputs.push(msg);
+                        }
+                    }
+                },
+                InputEvent::TouchStart(_) | InputEvent::TouchMove(_) | InputEvent::TouchEnd(_) => (),
+                InputEvent::GamepadButton(event) => {
+                    if let Some(listeners) = self.listeners.get(&EventName::ON_GAMEPAD_BUTTON) {
+                        for listener in listeners {
+                            let msg = match listener {
+                                Listener::OnGamepadButton(func) => func(On::new(self, event)),
+                                _ => continue,
+                            };
+                            outputs.push(msg);
+                        }
+                    }
+                },
+                InputEvent::GamepadAxis(event) => {
+                    if let Some(listeners) = self.listeners.get(&EventName::ON_GAMEPAD_AXIS) {
+                        for listener in listeners {
+                            let msg = match listener {
+                                Listener::OnGamepadAxis(func) => func(On::new(self, event)),
                                 _ => continue,
                             };
                             outputs.push(msg);
@@ -127,13 +664,31 @@ impl<M: Model> Prim<M> {
                     if let Some(listeners) = self.listeners.get(&EventName::ON_INPUT_CHAR) {
                         for listener in listeners {
                             let msg = match listener {
-                                Listener::OnInputChar(func) => func(On { prim: self, event: ch }),
+                                Listener::OnInputChar(func) => func(On::new(self, ch)),
+                                _ => continue,
+                            };
+                            outputs.push(msg);
+                        }
+                    }
+                },
+                InputEvent::FileHovered(ref path) | InputEvent::FileDropped(ref path) => {
+                    let name = match input {
+                        InputEvent::FileHovered(_) => EventName::ON_FILE_HOVER,
+                        _ => EventName::ON_FILE_DROP,
+                    };
+                    if let Some(listeners) = self.listeners.get(&name) {
+                        for listener in listeners {
+                            let msg = match listener {
+                                Listener::OnFileHover(func) | Listener::OnFileDrop(func) => {
+                                    func(On::new(self, path.clone()))
+                                },
                                 _ => continue,
                             };
                             outputs.push(msg);
                         }
                     }
                 },
+                InputEvent::FileHoverCancelled => (),
             },
             SystemMessage::Draw(duration) => {
                 if let Some(listeners) = self.listeners.get(&EventName::DRAW) {
@@ -157,18 +712,70 @@ impl<M: Model> Prim<M> {
                     }
                 }
             },
+            SystemMessage::Access(ref request) => {
+                if self.id() == Some(request.target.as_str()) {
+                    for listener in self.listeners.get(&EventName::ON_ACCESS_ACTION).into_iter().flatten() {
+                        if let Listener::OnAccessAction(func) = listener {
+                            outputs.push(func(On::new(self, request.action)));
+                        }
+                    }
+                }
+            },
+            SystemMessage::WindowMoved { .. }
+            | SystemMessage::ScaleFactorChanged(_)
+            | SystemMessage::Update(_)
+            | SystemMessage::Interpolate(_)
+            | SystemMessage::Stats(_)
+            | SystemMessage::Suspended
+            | SystemMessage::Resumed
+            | SystemMessage::ThemeChanged(_)
+            | SystemMessage::Hotkey(_)
+            | SystemMessage::WindowFocused(_)
+            | SystemMessage::Custom(_)
+            | SystemMessage::CompFailed(_) => (),
+        }
+
+        for node in self.nodes_mut() {
+            match node {
+                Node::Prim(prim) => prim.broadcast(msg, hits, outputs),
+                Node::Comp(comp) => comp.send_system_msg(msg.clone()),
+            }
+        }
+    }
+
+    /// Calls the throttled draw listeners that are due in the current frame. The nested
+    /// components are skipped: they get the draw message by the broadcast and call the
+    /// listeners of their views by their own throttles.
+    pub fn send_throttled_draw(&self, throttles: &mut DrawThrottles, outputs: &mut Vec<M::Message>) {
+        if let Some(listeners) = self.listeners.get(&EventName::DRAW) {
+            for listener in listeners {
+                if let Listener::ThrottledDraw(rate, func) = listener {
+                    if let Some(elapsed) = throttles.poll(*rate) {
+                        outputs.push(func(elapsed));
+                    }
+                }
+            }
         }
 
-        for child in self.children.iter_mut() {
-            child.send_system_msg(msg, outputs);
+        for child in self.nodes() {
+            if let Node::Prim(prim) = child {
+                prim.send_throttled_draw(throttles, outputs);
+            }
         }
     }
 
     pub fn update_view(&mut self) -> UpdateView {
-        let mut update = UpdateView::None;
-        for child in self.children.iter_mut() {
+        let mut update = if self.layout.is_none() {
+            UpdateView::RecalcAndRedraw
+        } else if self.need_redraw {
+            UpdateView::Redraw
+        } else {
+            UpdateView::None
+        };
+        for child in self.nodes_mut() {
             update = child.update_view().merge(update);
         }
+        self.need_recalc = update.is_recalc();
         update
     }
 }
@@ -178,6 +785,10 @@ impl<M: Model> CompositeShape for Prim<M> {
         Some(&self.shape)
     }
 
+    fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+
     fn shape_mut(&mut self) -> Option<&mut Shape> {
         Some(&mut self.shape)
     }
@@ -192,11 +803,56 @@ impl<M: Model> CompositeShape for Prim<M> {
         ))
     }
 
+    fn decorations(&self) -> Option<DecorationIter<'_>> {
+        Some(Box::new(self.decorations.iter().map(|decoration| {
+            (decoration.anchor, &decoration.node as &dyn CompositeShape)
+        })))
+    }
+
+    fn decorations_mut(&mut self) -> Option<DecorationIterMut<'_>> {
+        Some(Box::new(self.decorations.iter_mut().map(|decoration| {
+            (decoration.anchor, &mut decoration.node as &mut dyn CompositeShape)
+        })))
+    }
+
     fn need_recalc(&self) -> Option<bool> {
-        None
+        Some(self.need_recalc || self.layout.is_none())
     }
 
     fn need_redraw(&self) -> Option<bool> {
-        None
+        let dirty =
+            self.need_redraw || self.layout.is_none() || self.nodes().any(|node| node.need_redraw().unwrap_or(true));
+        Some(dirty)
+    }
+
+    fn mark_drawn(&mut self) {
+        self.need_redraw = false;
+        for node in self.nodes_mut() {
+            node.mark_drawn();
+        }
+    }
+
+    fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+        self.need_recalc = false;
+    }
+}
+
+/// The listeners of the pointer event along its path.
+fn pointer_events(input: &InputEvent) -> &'static [EventName] {
+    match input {
+        InputEvent::MouseDown(press) if press.clicks == 2 => &[EventName::ON_MOUSE_DOWN, EventName::ON_DOUBLE_CLICK],
+        InputEvent::MouseDown(_) => &[EventName::ON_MOUSE_DOWN],
+        InputEvent::MouseUp(_) => &[EventName::ON_MOUSE_UP],
+        InputEvent::MouseMove(_) => &[EventName::ON_MOUSE_MOVE],
+        InputEvent::MouseScroll(_) => &[EventName::ON_MOUSE_SCROLL],
+        InputEvent::TouchStart(_) => &[EventName::ON_TOUCH_START],
+        InputEvent::TouchMove(_) => &[EventName::ON_TOUCH_MOVE],
+        InputEvent::TouchEnd(_) => &[EventName::ON_TOUCH_END],
+        _ => &[],
     }
 }
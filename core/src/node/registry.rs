@@ -0,0 +1,66 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{Comp, Model};
+
+pub type CompFactory = Box<dyn Fn(&str) -> Option<Comp>>;
+
+/// The components registered by the names, to create them at runtime, e.g. by the
+/// [`ViewLoader`](crate::ViewLoader) from the view descriptions or by the plugins.
+/// The factory gets the properties as a string, usually in the format of the
+/// description, and returns `None` if they are invalid.
+#[derive(Default)]
+pub struct CompRegistry {
+    factories: HashMap<String, CompFactory>,
+}
+
+impl CompRegistry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, factory: impl Fn(&str) -> Option<Comp> + 'static) -> Self {
+        self.register(name, factory);
+        self
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, factory: impl Fn(&str) -> Option<Comp> + 'static) {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Registers the model created with the default properties, which takes no properties.
+    pub fn register_model<M: Model>(&mut self, name: impl Into<String>)
+    where
+        M::Properties: Default,
+    {
+        self.register(name, |props| {
+            if props.trim().is_empty() {
+                Some(Comp::new(M::create(Default::default())))
+            } else {
+                None
+            }
+        });
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.factories.remove(name).is_some()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Creates the component, returns `None` if the name is unknown or the properties are invalid.
+    pub fn create(&self, name: &str, props: &str) -> Option<Comp> {
+        self.factories.get(name)?(props)
+    }
+}
+
+impl fmt::Debug for CompRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.factories.keys()).finish()
+    }
+}
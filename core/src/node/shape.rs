@@ -1,11 +1,13 @@
 pub use self::{
-    circle::*, fill::*, group::*, padding::*, paint::*, path::*, rect::*, rounding::*, stroke::*, text::*, translate::*,
+    circle::*, fill::*, group::*, margin::*, padding::*, paint::*, path::*, rect::*, rounding::*, stroke::*, text::*,
+    tile_map::*, translate::*,
 };
-use crate::{Real, Transform};
+use crate::{Anchor, BoundingBox, Clip, Layout, Real, Transform, TransformMatrix};
 
 pub mod circle;
 pub mod fill;
 pub mod group;
+pub mod margin;
 pub mod padding;
 pub mod paint;
 pub mod path;
@@ -13,15 +15,18 @@ pub mod rect;
 pub mod rounding;
 pub mod stroke;
 pub mod text;
+pub mod tile_map;
 pub mod translate;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shape {
     Rect(Rect),
     Circle(Circle),
     Path(Path),
     Group(Group),
     Text(Text),
+    TileMap(TileMap),
 }
 
 pub trait Shaped {
@@ -39,12 +44,26 @@ pub trait Shaped {
 
     fn text(&self) -> Option<&Text>;
     fn text_mut(&mut self) -> Option<&mut Text>;
+
+    fn tile_map(&self) -> Option<&TileMap>;
+    fn tile_map_mut(&mut self) -> Option<&mut TileMap>;
 }
 
 pub struct ShapeRef<'a>(pub &'a Shape);
 pub struct ShapeRefMut<'a>(pub &'a mut Shape);
 
 impl Shape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Rect(_) => Rect::NAME,
+            Shape::Circle(_) => Circle::NAME,
+            Shape::Path(_) => Path::NAME,
+            Shape::Group(_) => Group::NAME,
+            Shape::Text(_) => Text::NAME,
+            Shape::TileMap(_) => TileMap::NAME,
+        }
+    }
+
     pub fn id(&self) -> Option<&str> {
         match self {
             Shape::Rect(rect) => rect.id(),
@@ -52,6 +71,7 @@ impl Shape {
             Shape::Path(path) => path.id(),
             Shape::Group(group) => group.id(),
             Shape::Text(text) => text.id(),
+            Shape::TileMap(tile_map) => tile_map.id(),
         }
     }
 
@@ -63,6 +83,7 @@ impl Shape {
             Shape::Path(path) => path.id = id,
             Shape::Group(group) => group.id = id,
             Shape::Text(text) => text.id = id,
+            Shape::TileMap(tile_map) => tile_map.id = id,
         }
     }
 
@@ -73,6 +94,40 @@ impl Shape {
             Shape::Path(path) => &path.transform,
             Shape::Group(group) => &group.transform,
             Shape::Text(text) => &text.transform,
+            Shape::TileMap(tile_map) => &tile_map.transform,
+        }
+    }
+
+    pub fn clip(&self) -> &Clip {
+        match self {
+            Shape::Rect(rect) => &rect.clip,
+            Shape::Circle(circle) => &circle.clip,
+            Shape::Path(path) => &path.clip,
+            Shape::Group(group) => &group.clip,
+            Shape::Text(text) => &text.clip,
+            Shape::TileMap(tile_map) => &tile_map.clip,
+        }
+    }
+
+    pub fn fill_mut(&mut self) -> &mut Option<Fill> {
+        match self {
+            Shape::Rect(rect) => &mut rect.fill,
+            Shape::Circle(circle) => &mut circle.fill,
+            Shape::Path(path) => &mut path.fill,
+            Shape::Group(group) => &mut group.fill,
+            Shape::Text(text) => &mut text.fill,
+            Shape::TileMap(tile_map) => &mut tile_map.fill,
+        }
+    }
+
+    pub fn stroke_mut(&mut self) -> &mut Option<Stroke> {
+        match self {
+            Shape::Rect(rect) => &mut rect.stroke,
+            Shape::Circle(circle) => &mut circle.stroke,
+            Shape::Path(path) => &mut path.stroke,
+            Shape::Group(group) => &mut group.stroke,
+            Shape::Text(text) => &mut text.stroke,
+            Shape::TileMap(tile_map) => &mut tile_map.stroke,
         }
     }
 
@@ -83,6 +138,7 @@ impl Shape {
             Shape::Path(path) => &mut path.transform,
             Shape::Group(group) => &mut group.transform,
             Shape::Text(text) => &mut text.transform,
+            Shape::TileMap(tile_map) => &mut tile_map.transform,
         }
     }
 
@@ -177,6 +233,22 @@ impl Shaped for Shape {
             _ => None,
         }
     }
+
+    #[inline]
+    fn tile_map(&self) -> Option<&TileMap> {
+        match self {
+            Shape::TileMap(tile_map) => Some(tile_map),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn tile_map_mut(&mut self) -> Option<&mut TileMap> {
+        match self {
+            Shape::TileMap(tile_map) => Some(tile_map),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> ShapeRef<'a> {
@@ -204,6 +276,11 @@ impl<'a> ShapeRef<'a> {
     pub fn text(&self) -> Option<&Text> {
         self.0.text()
     }
+
+    #[inline]
+    pub fn tile_map(&self) -> Option<&TileMap> {
+        self.0.tile_map()
+    }
 }
 
 impl<'a> ShapeRefMut<'a> {
@@ -231,6 +308,11 @@ impl<'a> ShapeRefMut<'a> {
     pub fn text(&mut self) -> Option<&mut Text> {
         self.0.text_mut()
     }
+
+    #[inline]
+    pub fn tile_map(&mut self) -> Option<&mut TileMap> {
+        self.0.tile_map_mut()
+    }
 }
 
 impl From<Rect> for Shape {
@@ -263,6 +345,12 @@ impl From<Text> for Shape {
     }
 }
 
+impl From<TileMap> for Shape {
+    fn from(tile_map: TileMap) -> Self {
+        Shape::TileMap(tile_map)
+    }
+}
+
 impl From<String> for Shape {
     fn from(text: String) -> Self {
         Shape::Text(Text {
@@ -274,6 +362,25 @@ impl From<String> for Shape {
 
 pub type CompositeShapeIter<'a> = Box<dyn Iterator<Item = &'a dyn CompositeShape> + 'a>;
 pub type CompositeShapeIterMut<'a> = Box<dyn Iterator<Item = &'a mut dyn CompositeShape> + 'a>;
+pub type DecorationIter<'a> = Box<dyn Iterator<Item = (Anchor, &'a dyn CompositeShape)> + 'a>;
+pub type DecorationIterMut<'a> = Box<dyn Iterator<Item = (Anchor, &'a mut dyn CompositeShape)> + 'a>;
+
+/// Collects the overlay subtrees of the composite in the drawing order, the
+/// nested overlays follow their ancestors.
+pub fn collect_overlays<'a>(composite: &'a dyn CompositeShape, overlays: &mut Vec<&'a dyn CompositeShape>) {
+    let children = composite.children().into_iter().flatten();
+    let decorations = composite
+        .decorations()
+        .into_iter()
+        .flatten()
+        .map(|(_, decoration)| decoration);
+    for node in children.chain(decorations) {
+        if node.is_overlay() {
+            overlays.push(node);
+        }
+        collect_overlays(node, overlays);
+    }
+}
 
 pub trait CompositeShape {
     fn shape(&self) -> Option<&Shape>;
@@ -284,17 +391,69 @@ pub trait CompositeShape {
 
     fn children_mut(&mut self) -> Option<CompositeShapeIterMut>;
 
+    fn decorations(&self) -> Option<DecorationIter<'_>>;
+
+    fn decorations_mut(&mut self) -> Option<DecorationIterMut<'_>>;
+
     fn need_recalc(&self) -> Option<bool>;
 
     fn need_redraw(&self) -> Option<bool>;
 
+    /// Clears the redraw flags of the subtree, called by the renderer after drawing it.
+    fn mark_drawn(&mut self) {
+        for child in self.children_mut().into_iter().flatten() {
+            child.mark_drawn();
+        }
+        for (_, decoration) in self.decorations_mut().into_iter().flatten() {
+            decoration.mark_drawn();
+        }
+    }
+
+    fn layout(&self) -> Option<Layout>;
+
+    fn set_layout(&mut self, layout: Layout);
+
+    /// The transform of the shape to the window coordinates, calculated by the last layout pass.
+    fn global_transform(&self) -> Option<TransformMatrix> {
+        self.shape()?.transform().global_matrix()
+    }
+
+    /// The bound of the shape in the window coordinates, calculated by the last layout pass.
+    fn global_bounds(&self) -> Option<BoundingBox> {
+        let bound = self.layout()?.bound;
+        Some(match self.global_transform() {
+            Some(matrix) => bound.transformed(matrix),
+            None => bound,
+        })
+    }
+
+    /// Checks the subtree is drawn on the overlay layer above the whole view,
+    /// out of the clips of its ancestors.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// The clip of the children when the overflow of the rect or the group is hidden.
+    fn overflow_clip(&self) -> Option<Clip> {
+        match self.shape()? {
+            Shape::Rect(rect) => rect.overflow_clip(),
+            Shape::Group(group) => {
+                let layout = self.layout()?;
+                group.overflow_clip(layout.parent_bound, layout.parent_transform)
+            },
+            _ => None,
+        }
+    }
+
     fn intersect(&self, x: Real, y: Real) -> bool {
         if let Some(shape) = self.shape() {
             match shape {
                 Shape::Rect(rect) => rect.intersect(x, y),
                 Shape::Circle(circle) => circle.intersect(x, y),
                 Shape::Path(path) => path.intersect(x, y),
-                _ => false,
+                Shape::TileMap(tile_map) => tile_map.intersect(x, y),
+                Shape::Text(text) => text.intersect(x, y),
+                Shape::Group(_) => false,
             }
         } else {
             false
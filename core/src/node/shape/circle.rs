@@ -1,12 +1,15 @@
-use crate::node::{Clip, Fill, Padding, Real, RealValue, Stroke, Transform, TransformMatrix};
+use crate::node::{Clip, Fill, Margin, Padding, Real, RealValue, Stroke, Transform, TransformMatrix};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Circle {
     pub id: Option<String>,
     pub cx: RealValue,
     pub cy: RealValue,
     pub r: RealValue,
     pub padding: Padding,
+    pub margin: Margin,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
@@ -22,6 +25,7 @@ impl Circle {
     }
 
     pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+        let parent_global = parent_global * self.margin.offset();
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
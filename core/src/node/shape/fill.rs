@@ -2,6 +2,8 @@ use super::{Color, Gradient, Paint};
 use crate::node::ConvertTo;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Fill {
     pub paint: Paint,
 }
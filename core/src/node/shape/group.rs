@@ -1,12 +1,15 @@
-use crate::node::{Clip, Fill, Real, Stroke, Transform, TransformMatrix};
+use crate::node::{BoundingBox, Clip, Fill, Overflow, Real, RealValue, Scissor, Stroke, Transform, TransformMatrix};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Group {
     pub id: Option<String>,
     pub transparency: Option<Real>,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
+    pub overflow: Overflow,
     pub transform: Transform,
 }
 
@@ -24,6 +27,20 @@ impl Group {
         self.transform.calculate_global(parent_global)
     }
 
+    /// The clip of the children by the bound the group is placed in when the overflow is hidden.
+    pub fn overflow_clip(&self, parent_bound: BoundingBox, parent_transform: TransformMatrix) -> Option<Clip> {
+        if !self.overflow.is_hidden() {
+            return None;
+        }
+        Some(Clip::Scissor(Scissor {
+            x: RealValue::px(parent_bound.min_x),
+            y: RealValue::px(parent_bound.min_y),
+            width: RealValue::px(parent_bound.width()),
+            height: RealValue::px(parent_bound.height()),
+            transform: Transform::Global(parent_transform),
+        }))
+    }
+
     pub fn empty_overrides(&self) -> bool {
         self.stroke.is_none() && self.fill.is_none() && self.transform.is_not_exist()
     }
@@ -0,0 +1,40 @@
+use crate::{Real, RealValue, TransformMatrix};
+
+/// Outer spacing of a shape: offsets the shape inside its parent and enlarges
+/// the area the parent reserves for it.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Margin {
+    pub top: RealValue,
+    pub left: RealValue,
+    pub right: RealValue,
+    pub bottom: RealValue,
+}
+
+impl Margin {
+    pub fn top_and_bottom(&self) -> Real {
+        self.top.val() + self.bottom.val()
+    }
+
+    pub fn left_and_right(&self) -> Real {
+        self.left.val() + self.right.val()
+    }
+
+    /// Translation applied in the parent space before the shape's own transform.
+    pub fn offset(&self) -> TransformMatrix {
+        TransformMatrix::identity().with_translation(self.left.val(), self.top.val())
+    }
+}
+
+impl<T: Into<RealValue>> From<T> for Margin {
+    fn from(margin: T) -> Self {
+        let margin = margin.into();
+        Self {
+            top: margin,
+            left: margin,
+            right: margin,
+            bottom: margin,
+        }
+    }
+}
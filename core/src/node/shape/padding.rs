@@ -1,6 +1,8 @@
 use crate::RealValue;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Padding {
     pub top: RealValue,
     pub left: RealValue,
@@ -1,6 +1,7 @@
 use crate::Real;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Paint {
     Color(Color),
     Gradient(Gradient),
@@ -13,6 +14,7 @@ impl Default for Paint {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Red,
     Green,
@@ -44,6 +46,101 @@ impl Color {
     }
 }
 
+/// The color space in which the colors are mixed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorSpace {
+    /// Mixes the sRGB channels as they are.
+    #[default]
+    Srgb,
+    /// Mixes the linear light, which keeps the midpoints from going dark.
+    LinearRgb,
+    /// Mixes the hue by the shortest way around the wheel, the saturation and the lightness.
+    Hsl,
+}
+
+impl Color {
+    /// Mixes the colors in the color space, `t` is 0 for this color and 1 for the other one.
+    pub fn mix(self, to: Color, t: Real, space: ColorSpace) -> Color {
+        let ([r1, g1, b1, a1], [r2, g2, b2, a2]) = (self.as_arr(), to.as_arr());
+        let lerp = |from: Real, to: Real| from + (to - from) * t;
+        let alpha = lerp(a1, a2);
+        match space {
+            ColorSpace::Srgb => Color::RGBA(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2), alpha),
+            ColorSpace::LinearRgb => Color::RGBA(
+                linear_to_srgb(lerp(srgb_to_linear(r1), srgb_to_linear(r2))),
+                linear_to_srgb(lerp(srgb_to_linear(g1), srgb_to_linear(g2))),
+                linear_to_srgb(lerp(srgb_to_linear(b1), srgb_to_linear(b2))),
+                alpha,
+            ),
+            ColorSpace::Hsl => {
+                let (h1, s1, l1) = rgb_to_hsl(r1, g1, b1);
+                let (h2, s2, l2) = rgb_to_hsl(r2, g2, b2);
+                let mut delta = h2 - h1;
+                if delta > 180.0 {
+                    delta -= 360.0;
+                } else if delta < -180.0 {
+                    delta += 360.0;
+                }
+                let hue = (h1 + delta * t).rem_euclid(360.0);
+                let (r, g, b) = hsl_to_rgb(hue, lerp(s1, s2), lerp(l1, l2));
+                Color::RGBA(r, g, b, alpha)
+            },
+        }
+    }
+}
+
+fn srgb_to_linear(c: Real) -> Real {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: Real) -> Real {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn rgb_to_hsl(r: Real, g: Real, b: Real) -> (Real, Real, Real) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: Real, saturation: Real, lightness: Real) -> (Real, Real, Real) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
 impl Default for Color {
     fn default() -> Self {
         Color::Black
@@ -70,6 +167,7 @@ impl From<(Color, f32)> for Paint {
 
 /// Gradient paint used to fill or stroke paths with gradient.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Gradient {
     Linear {
         start: (Real, Real),
@@ -1,6 +1,10 @@
+use std::{error::Error, fmt};
+
 use crate::node::{Clip, Fill, Real, Stroke, Transform, TransformMatrix};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Path {
     pub id: Option<String>,
     pub cmd: Vec<PathCommand>,
@@ -31,6 +35,7 @@ impl Path {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PathCommand {
     Move([Real; 2]),
     MoveRel([Real; 2]),
@@ -49,3 +54,376 @@ pub enum PathCommand {
     CubBezTo([Real; 2]),
     CubBezToRel([Real; 2]),
 }
+
+impl PathCommand {
+    /// Parses SVG path data (the `d` attribute) into absolute commands.
+    /// Elliptical arcs are not supported.
+    pub fn parse_svg(data: &str) -> Result<Vec<PathCommand>, SvgPathError> {
+        SvgPathParser::new(data).parse()
+    }
+
+    /// Formats the commands as absolute SVG path data (the `d` attribute).
+    pub fn to_svg(cmd: &[PathCommand]) -> String {
+        use PathCommand::*;
+
+        let mut data = String::new();
+        let mut current = [0.0, 0.0];
+        let mut start = [0.0, 0.0];
+        let mut bez_ctrls = [[0.0, 0.0]; 2];
+        for cmd in cmd {
+            let (name, points) = match *cmd {
+                Move(xy) | MoveRel(xy) => {
+                    current = if let MoveRel(_) = cmd { add(current, xy) } else { xy };
+                    start = current;
+                    ("M", vec![current])
+                },
+                Line(xy) | LineRel(xy) => {
+                    current = if let LineRel(_) = cmd { add(current, xy) } else { xy };
+                    ("L", vec![current])
+                },
+                LineAlonX(x) | LineAlonXRel(x) => {
+                    current[0] = if let LineAlonXRel(_) = cmd { current[0] + x } else { x };
+                    ("L", vec![current])
+                },
+                LineAlonY(y) | LineAlonYRel(y) => {
+                    current[1] = if let LineAlonYRel(_) = cmd { current[1] + y } else { y };
+                    ("L", vec![current])
+                },
+                Close => {
+                    current = start;
+                    ("Z", vec![])
+                },
+                BezCtrl(xy) => {
+                    bez_ctrls = [bez_ctrls[1], xy];
+                    continue;
+                },
+                BezCtrlRel(xy) => {
+                    bez_ctrls = [bez_ctrls[1], add(current, xy)];
+                    continue;
+                },
+                BezReflectCtrl => {
+                    let reflected = [2.0 * current[0] - bez_ctrls[1][0], 2.0 * current[1] - bez_ctrls[1][1]];
+                    bez_ctrls = [bez_ctrls[1], reflected];
+                    continue;
+                },
+                QuadBezTo(xy) | QuadBezToRel(xy) => {
+                    current = if let QuadBezToRel(_) = cmd {
+                        add(current, xy)
+                    } else {
+                        xy
+                    };
+                    ("Q", vec![bez_ctrls[1], current])
+                },
+                CubBezTo(xy) | CubBezToRel(xy) => {
+                    current = if let CubBezToRel(_) = cmd { add(current, xy) } else { xy };
+                    ("C", vec![bez_ctrls[0], bez_ctrls[1], current])
+                },
+            };
+
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            data.push_str(name);
+            for [x, y] in points {
+                data.push_str(&format!(" {} {}", x, y));
+            }
+        }
+        data
+    }
+
+    /// Scales all coordinates of the command.
+    pub fn scaled(self, x: Real, y: Real) -> Self {
+        use PathCommand::*;
+
+        match self {
+            Move([px, py]) => Move([px * x, py * y]),
+            MoveRel([px, py]) => MoveRel([px * x, py * y]),
+            Line([px, py]) => Line([px * x, py * y]),
+            LineRel([px, py]) => LineRel([px * x, py * y]),
+            LineAlonX(px) => LineAlonX(px * x),
+            LineAlonXRel(px) => LineAlonXRel(px * x),
+            LineAlonY(py) => LineAlonY(py * y),
+            LineAlonYRel(py) => LineAlonYRel(py * y),
+            Close => Close,
+            BezCtrl([px, py]) => BezCtrl([px * x, py * y]),
+            BezCtrlRel([px, py]) => BezCtrlRel([px * x, py * y]),
+            BezReflectCtrl => BezReflectCtrl,
+            QuadBezTo([px, py]) => QuadBezTo([px * x, py * y]),
+            QuadBezToRel([px, py]) => QuadBezToRel([px * x, py * y]),
+            CubBezTo([px, py]) => CubBezTo([px * x, py * y]),
+            CubBezToRel([px, py]) => CubBezToRel([px * x, py * y]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SvgPathError {
+    UnexpectedChar { ch: char, pos: usize },
+    ExpectedNumber { pos: usize },
+    UnsupportedCommand(char),
+}
+
+impl fmt::Display for SvgPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgPathError::UnexpectedChar { ch, pos } => write!(f, "Unexpected char '{}' at {}", ch, pos),
+            SvgPathError::ExpectedNumber { pos } => write!(f, "Expected number at {}", pos),
+            SvgPathError::UnsupportedCommand(cmd) => write!(f, "Unsupported path command '{}'", cmd),
+        }
+    }
+}
+
+impl Error for SvgPathError {}
+
+fn add(point: [Real; 2], offset: [Real; 2]) -> [Real; 2] {
+    [point[0] + offset[0], point[1] + offset[1]]
+}
+
+struct SvgPathParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    current: [Real; 2],
+    start: [Real; 2],
+    cubic_ctrl: Option<[Real; 2]>,
+    quad_ctrl: Option<[Real; 2]>,
+    closed: bool,
+    cmd: Vec<PathCommand>,
+}
+
+impl<'a> SvgPathParser<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            pos: 0,
+            current: [0.0, 0.0],
+            start: [0.0, 0.0],
+            cubic_ctrl: None,
+            quad_ctrl: None,
+            closed: false,
+            cmd: vec![],
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<PathCommand>, SvgPathError> {
+        let mut command = None;
+        loop {
+            self.skip_separators();
+            let ch = match self.data.get(self.pos) {
+                Some(&ch) => ch,
+                None => break,
+            };
+
+            if ch.is_ascii_alphabetic() {
+                self.pos += 1;
+                command = Some(ch);
+            }
+
+            let command_ch = match command {
+                Some(command_ch) => command_ch,
+                None => {
+                    return Err(SvgPathError::UnexpectedChar {
+                        ch: ch as char,
+                        pos: self.pos,
+                    })
+                },
+            };
+            self.parse_segment(command_ch)?;
+
+            // Subsequent coordinate pairs after a move are implicit lines
+            command = match command_ch {
+                b'M' => Some(b'L'),
+                b'm' => Some(b'l'),
+                b'Z' | b'z' => None,
+                _ => command,
+            };
+        }
+        Ok(self.cmd)
+    }
+
+    fn parse_segment(&mut self, ch: u8) -> Result<(), SvgPathError> {
+        let origin = if ch.is_ascii_lowercase() {
+            self.current
+        } else {
+            [0.0, 0.0]
+        };
+        let kind = ch.to_ascii_uppercase();
+
+        if self.closed && kind != b'M' && kind != b'Z' {
+            self.cmd.push(PathCommand::Move(self.current));
+        }
+        self.closed = false;
+
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+        match kind {
+            b'M' => {
+                let point = self.point(origin)?;
+                self.cmd.push(PathCommand::Move(point));
+                self.current = point;
+                self.start = point;
+            },
+            b'L' => {
+                let point = self.point(origin)?;
+                self.line_to(point);
+            },
+            b'H' => {
+                let x = self.number()? + origin[0];
+                self.line_to([x, self.current[1]]);
+            },
+            b'V' => {
+                let y = self.number()? + origin[1];
+                self.line_to([self.current[0], y]);
+            },
+            b'C' | b'S' => {
+                let ctrl1 = if kind == b'C' {
+                    self.point(origin)?
+                } else {
+                    self.reflect(self.cubic_ctrl)
+                };
+                let ctrl2 = self.point(origin)?;
+                let point = self.point(origin)?;
+                self.cmd.push(PathCommand::BezCtrl(ctrl1));
+                self.cmd.push(PathCommand::BezCtrl(ctrl2));
+                self.cmd.push(PathCommand::CubBezTo(point));
+                self.current = point;
+                cubic_ctrl = Some(ctrl2);
+            },
+            b'Q' | b'T' => {
+                let ctrl = if kind == b'Q' {
+                    self.point(origin)?
+                } else {
+                    self.reflect(self.quad_ctrl)
+                };
+                let point = self.point(origin)?;
+                self.cmd.push(PathCommand::BezCtrl(ctrl));
+                self.cmd.push(PathCommand::QuadBezTo(point));
+                self.current = point;
+                quad_ctrl = Some(ctrl);
+            },
+            b'Z' => {
+                self.cmd.push(PathCommand::Close);
+                self.current = self.start;
+                self.closed = true;
+            },
+            _ => return Err(SvgPathError::UnsupportedCommand(ch as char)),
+        }
+        self.cubic_ctrl = cubic_ctrl;
+        self.quad_ctrl = quad_ctrl;
+        Ok(())
+    }
+
+    fn line_to(&mut self, point: [Real; 2]) {
+        self.cmd.push(PathCommand::Line(point));
+        self.current = point;
+    }
+
+    fn reflect(&self, ctrl: Option<[Real; 2]>) -> [Real; 2] {
+        let [x, y] = self.current;
+        ctrl.map(|[cx, cy]| [2.0 * x - cx, 2.0 * y - cy])
+            .unwrap_or(self.current)
+    }
+
+    fn point(&mut self, origin: [Real; 2]) -> Result<[Real; 2], SvgPathError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok([origin[0] + x, origin[1] + y])
+    }
+
+    fn number(&mut self) -> Result<Real, SvgPathError> {
+        self.skip_separators();
+        let start = self.pos;
+        let mut end = start;
+        if let Some(b'+') | Some(b'-') = self.data.get(end) {
+            end += 1;
+        }
+
+        let mut has_dot = false;
+        let mut has_exp = false;
+        while let Some(&ch) = self.data.get(end) {
+            match ch {
+                b'0'..=b'9' => {},
+                b'.' if !has_dot && !has_exp => has_dot = true,
+                b'e' | b'E' if !has_exp && end > start => {
+                    has_exp = true;
+                    if let Some(b'+') | Some(b'-') = self.data.get(end + 1) {
+                        end += 1;
+                    }
+                },
+                _ => break,
+            }
+            end += 1;
+        }
+
+        let number = std::str::from_utf8(&self.data[start..end])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(SvgPathError::ExpectedNumber { pos: start })?;
+        self.pos = end;
+        Ok(number)
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(ch) = self.data.get(self.pos) {
+            if ch.is_ascii_whitespace() || *ch == b',' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathCommand::*, *};
+
+    #[test]
+    fn parse_svg_lines() {
+        let cmd = PathCommand::parse_svg("M11 5h2v6h6v2h-6v6h-2v-6H5v-2h6z").unwrap();
+        assert_eq!(cmd[0], Move([11.0, 5.0]));
+        assert_eq!(cmd[1], Line([13.0, 5.0]));
+        assert_eq!(cmd[2], Line([13.0, 11.0]));
+        assert_eq!(cmd.last(), Some(&Close));
+        assert_eq!(cmd.len(), 13);
+    }
+
+    #[test]
+    fn parse_svg_implicit_commands() {
+        let cmd = PathCommand::parse_svg("m1-2 3.5.5 1e1,0").unwrap();
+        assert_eq!(cmd, vec![Move([1.0, -2.0]), Line([4.5, -1.5]), Line([14.5, -1.5])]);
+    }
+
+    #[test]
+    fn parse_svg_smooth_curves() {
+        let cmd = PathCommand::parse_svg("M0 0C0 1 1 1 1 0S2 -1 2 0").unwrap();
+        assert_eq!(cmd[4], BezCtrl([1.0, -1.0]));
+        assert_eq!(cmd[6], CubBezTo([2.0, 0.0]));
+    }
+
+    #[test]
+    fn parse_svg_errors() {
+        assert_eq!(
+            PathCommand::parse_svg("M0 0A1 1 0 0 1 2 2"),
+            Err(SvgPathError::UnsupportedCommand('A'))
+        );
+        assert_eq!(
+            PathCommand::parse_svg("M0"),
+            Err(SvgPathError::ExpectedNumber { pos: 2 })
+        );
+        assert_eq!(
+            PathCommand::parse_svg("10 10"),
+            Err(SvgPathError::UnexpectedChar { ch: '1', pos: 0 })
+        );
+    }
+
+    #[test]
+    fn to_svg_round_trip() {
+        let data = "M 0 0 L 10 0 C 10 5 5 10 0 10 Z";
+        let cmd = PathCommand::parse_svg(data).unwrap();
+        assert_eq!(PathCommand::to_svg(&cmd), data);
+        assert_eq!(
+            PathCommand::to_svg(&[MoveRel([1.0, 1.0]), LineAlonXRel(2.0)]),
+            "M 1 1 L 3 1"
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{Clip, Fill, Padding, Real, RealValue, Rounding, Stroke, Transform, TransformMatrix};
+use crate::{
+    Clip, Fill, Margin, Overflow, Padding, Real, RealValue, Rounding, Scissor, Stroke, Transform, TransformMatrix,
+};
 
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Rect {
     pub id: Option<String>,
     pub x: RealValue,
@@ -9,10 +13,12 @@ pub struct Rect {
     pub height: RealValue,
     pub rounding: Option<Rounding>,
     pub padding: Padding,
+    pub margin: Margin,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
     pub clip: Clip,
+    pub overflow: Overflow,
     pub transform: Transform,
 }
 
@@ -24,12 +30,31 @@ impl Rect {
     }
 
     pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+        let parent_global = parent_global * self.margin.offset();
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
         self.transform.calculate_global(parent_global)
     }
 
+    /// The clip of the children by the rect area when the overflow is hidden.
+    pub fn overflow_clip(&self) -> Option<Clip> {
+        if !self.overflow.is_hidden() {
+            return None;
+        }
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        Some(Clip::Scissor(Scissor {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            transform: Transform::Global(matrix),
+        }))
+    }
+
     #[inline]
     pub fn intersect(&self, x: Real, y: Real) -> bool {
         let matrix = self
@@ -41,6 +66,9 @@ impl Rect {
         } else {
             (x, y)
         };
-        x >= self.x.val() && x <= self.width.val() && y >= self.y.val() && y <= self.height.val()
+        x >= self.x.val()
+            && x <= self.x.val() + self.width.val()
+            && y >= self.y.val()
+            && y <= self.y.val() + self.height.val()
     }
 }
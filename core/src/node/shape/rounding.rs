@@ -1,6 +1,8 @@
 use crate::RealValue;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Rounding {
     pub top_left: RealValue,
     pub top_right: RealValue,
@@ -2,6 +2,7 @@ use crate::{Color, ConvertTo, Gradient, Paint, Real};
 
 /// Controls how the end of line is drawn.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineCap {
     Butt,
     Round,
@@ -10,6 +11,7 @@ pub enum LineCap {
 
 /// Controls how lines are joined together.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineJoin {
     Miter,
     Round,
@@ -17,6 +19,7 @@ pub enum LineJoin {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stroke {
     pub paint: Paint,
     pub width: Real,
@@ -1,6 +1,10 @@
-use crate::node::{Clip, ConvertTo, Fill, Real, RealValue, Stroke, Transform, TransformMatrix};
+use std::{ops::Range, slice};
+
+use crate::node::{BoundingBox, Clip, ConvertTo, Fill, Margin, Real, RealValue, Stroke, Transform, TransformMatrix};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GlyphPos {
     pub x: Real,
     pub y: Real,
@@ -15,23 +19,45 @@ impl GlyphPos {
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct TextMetrics {
     pub ascender: f32,
     pub descender: f32,
     pub line_height: f32,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextWrap {
+    /// The text is drawn on a single line.
+    #[default]
+    None,
+    /// The lines are broken at the spaces (or inside the words longer than the line)
+    /// to fit the content width of the parent.
+    Word,
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Text {
     pub id: Option<String>,
     pub content: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub glyph_positions: Vec<GlyphPos>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub metrics: Option<TextMetrics>,
+    /// The char ranges of the wrapped lines, empty if the text is on a single line.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub wrapped_lines: Vec<Range<usize>>,
     pub x: RealValue,
     pub y: RealValue,
     pub font_name: String,
     pub font_size: RealValue,
     pub align: (AlignHor, AlignVer),
+    pub wrap: TextWrap,
+    pub margin: Margin,
     pub transparency: Real,
     pub stroke: Option<Stroke>,
     pub fill: Option<Fill>,
@@ -47,16 +73,178 @@ impl Text {
     }
 
     pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+        let parent_global = parent_global * self.margin.offset();
         if let Some(transform) = self.clip.transform_mut() {
             transform.calculate_global(parent_global);
         }
         self.transform.calculate_global(parent_global)
     }
 
-    #[inline]
-    pub fn intersect(&self, _x: Real, _y: Real) -> bool {
-        // TODO: calculate intersect
-        false
+    /// The lines of the content as they are drawn.
+    pub fn lines(&self) -> Vec<&str> {
+        if self.wrapped_lines.is_empty() {
+            return vec![&self.content];
+        }
+        let mut bytes: Vec<_> = self.content.char_indices().map(|(idx, _)| idx).collect();
+        bytes.push(self.content.len());
+        self.wrapped_lines
+            .iter()
+            .map(|line| &self.content[bytes[line.start]..bytes[line.end]])
+            .collect()
+    }
+
+    /// Breaks the content into the char ranges of the lines not wider than the max width,
+    /// by the glyph positions of the single-line layout. The line feeds always break the line.
+    pub fn break_lines(&self, max_width: Real) -> Vec<Range<usize>> {
+        let chars: Vec<char> = self.content.chars().collect();
+        let end_x = self.glyph_positions.last().map(GlyphPos::max_x).unwrap_or(0.0);
+        let x = |idx: usize| self.glyph_positions.get(idx).map_or(end_x, |pos| pos.x);
+        let max_x = |idx: usize| self.glyph_positions.get(idx).map_or(end_x, GlyphPos::max_x);
+
+        let mut lines = vec![];
+        let mut start = 0;
+        let mut word_start = None;
+        for (idx, ch) in chars.iter().enumerate() {
+            if *ch == '\n' {
+                lines.push(start..idx);
+                start = idx + 1;
+                word_start = None;
+                continue;
+            }
+            if !ch.is_whitespace() && idx > start && max_x(idx) - x(start) > max_width {
+                let end = match word_start {
+                    Some(word_start) if word_start > start => word_start,
+                    _ => idx,
+                };
+                lines.push(start..end);
+                start = end;
+            }
+            if ch.is_whitespace() {
+                word_start = Some(idx + 1);
+            }
+        }
+        lines.push(start..chars.len());
+        lines
+    }
+
+    /// Wraps the text to the max width if its wrap mode is on: breaks the lines and
+    /// moves the glyphs of each line under the previous one. The glyph positions
+    /// of the single-line layout and the metrics should be calculated before.
+    pub fn wrap_lines(&mut self, max_width: Real) {
+        self.wrapped_lines.clear();
+        if self.wrap == TextWrap::None {
+            return;
+        }
+
+        let lines = self.break_lines(max_width);
+        if lines.len() < 2 {
+            return;
+        }
+        let line_height = self.metrics.map_or(0.0, |metrics| metrics.line_height);
+        for (line_idx, line) in lines.iter().enumerate() {
+            let shift = self.glyph_positions.get(line.start).map_or(0.0, |pos| pos.x)
+                - self.glyph_positions.first().map_or(0.0, |pos| pos.x);
+            for pos in self.glyph_positions.iter_mut().take(line.end).skip(line.start) {
+                pos.x -= shift;
+                pos.y += line_idx as Real * line_height;
+            }
+        }
+        self.wrapped_lines = lines;
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.wrapped_lines.len().max(1)
+    }
+
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        if self.glyph_positions.is_empty() {
+            return false;
+        }
+        let (x, y) = self.to_local(x, y);
+        let width = self.glyph_positions.iter().map(GlyphPos::max_x).fold(0.0, Real::max);
+        let height = self.metrics.map_or(0.0, |metrics| metrics.line_height) * self.line_count() as Real;
+        x >= self.x.val() && x <= self.x.val() + width && y >= self.y.val() && y <= self.y.val() + height
+    }
+
+    /// The char index of the caret placed by a click at the point in the window
+    /// coordinates: the nearest boundary between the chars on the line under the
+    /// point. The points above or below the text go to the first or the last line.
+    pub fn char_index_at(&self, x: Real, y: Real) -> usize {
+        let (x, y) = self.to_local(x, y);
+        let line_height = self.metrics.map_or(0.0, |metrics| metrics.line_height);
+        let line = if self.wrapped_lines.is_empty() {
+            0..self.glyph_positions.len()
+        } else {
+            let idx = if line_height > 0.0 {
+                ((y - self.y.val()) / line_height).max(0.0) as usize
+            } else {
+                0
+            };
+            self.wrapped_lines[idx.min(self.wrapped_lines.len() - 1)].clone()
+        };
+        let end = line.end.min(self.glyph_positions.len());
+        (line.start..end)
+            .find(|idx| {
+                let pos = &self.glyph_positions[*idx];
+                x < self.x.val() + pos.x + pos.width / 2.0
+            })
+            .unwrap_or(end)
+    }
+
+    /// The top of the caret before the char at the index in the local coordinates of the text.
+    pub fn caret_pos(&self, idx: usize) -> (Real, Real) {
+        let pos = match self.glyph_positions.get(idx) {
+            Some(pos) => (pos.x, pos.y),
+            None => self
+                .glyph_positions
+                .last()
+                .map_or((0.0, 0.0), |pos| (pos.max_x(), pos.y)),
+        };
+        (self.x.val() + pos.0, self.y.val() + pos.1)
+    }
+
+    /// The bounds of the chars of the range on each line in the local coordinates of the text,
+    /// as the caret positions.
+    pub fn range_bounds(&self, range: Range<usize>) -> Vec<BoundingBox> {
+        let line_height = self.metrics.map_or(0.0, |metrics| metrics.line_height);
+        let whole = 0..self.glyph_positions.len();
+        let lines = if self.wrapped_lines.is_empty() {
+            slice::from_ref(&whole)
+        } else {
+            &self.wrapped_lines[..]
+        };
+        lines
+            .iter()
+            .filter_map(|line| {
+                let (start, end) = (range.start.max(line.start), range.end.min(line.end));
+                if start >= end {
+                    return None;
+                }
+                let (min_x, min_y) = self.caret_pos(start);
+                let max_x = self
+                    .glyph_positions
+                    .get(end - 1)
+                    .map_or(min_x, |pos| self.x.val() + pos.max_x());
+                Some(BoundingBox {
+                    min_x,
+                    min_y,
+                    max_x,
+                    max_y: min_y + line_height,
+                })
+            })
+            .collect()
+    }
+
+    fn to_local(&self, x: Real, y: Real) -> (Real, Real) {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        }
     }
 
     pub fn insert(&mut self, idx: usize, ch: char) {
@@ -82,6 +270,7 @@ impl Text {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignHor {
     Left,
     Right,
@@ -95,6 +284,7 @@ impl Default for AlignHor {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignVer {
     Bottom,
     Middle,
@@ -131,3 +321,75 @@ impl ConvertTo<(AlignHor, AlignVer)> for AlignVer {
         self.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(content: &str) -> Text {
+        Text {
+            content: content.to_string(),
+            glyph_positions: (0..content.chars().count())
+                .map(|idx| GlyphPos {
+                    x: idx as Real * 10.0,
+                    y: 0.0,
+                    width: 10.0,
+                })
+                .collect(),
+            metrics: Some(TextMetrics {
+                ascender: 12.0,
+                descender: 4.0,
+                line_height: 16.0,
+            }),
+            wrap: TextWrap::Word,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn break_lines_at_spaces() {
+        let text = text("one two three\nfour");
+        assert_eq!(text.break_lines(80.0), vec![0..8, 8..13, 14..18]);
+        assert_eq!(text.break_lines(1000.0), vec![0..13, 14..18]);
+    }
+
+    #[test]
+    fn break_long_word() {
+        assert_eq!(text("abcdefgh").break_lines(30.0), vec![0..3, 3..6, 6..8]);
+    }
+
+    #[test]
+    fn wrap_lines() {
+        let mut text = text("one two three");
+        text.wrap_lines(80.0);
+        assert_eq!(text.lines(), vec!["one two ", "three"]);
+        assert_eq!(text.line_count(), 2);
+        assert_eq!(text.glyph_positions[8], GlyphPos {
+            x: 0.0,
+            y: 16.0,
+            width: 10.0
+        });
+
+        text.wrap = TextWrap::None;
+        text.wrap_lines(80.0);
+        assert_eq!(text.lines(), vec!["one two three"]);
+    }
+
+    #[test]
+    fn char_index_at() {
+        let mut text = text("one two three");
+        assert_eq!(text.char_index_at(-5.0, 5.0), 0);
+        assert_eq!(text.char_index_at(14.0, 5.0), 1);
+        assert_eq!(text.char_index_at(16.0, 5.0), 2);
+        assert_eq!(text.char_index_at(500.0, 5.0), 13);
+        assert_eq!(text.caret_pos(2), (20.0, 0.0));
+        assert_eq!(text.caret_pos(13), (130.0, 0.0));
+
+        text.wrap_lines(80.0);
+        assert_eq!(text.char_index_at(16.0, 20.0), 10);
+        assert_eq!(text.char_index_at(500.0, 100.0), 13);
+        assert_eq!(text.caret_pos(10), (20.0, 16.0));
+        assert!(text.intersect(75.0, 10.0));
+        assert!(!text.intersect(75.0, 40.0));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{ops::Range, rc::Rc};
+
+use crate::node::{BoundingBox, Clip, Fill, PathCommand, Real, Stroke, Transform, TransformMatrix};
+
+pub type TileId = usize;
+
+/// A tile picture, with the coordinates from zero to the tile size.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Tile {
+    pub cmd: Vec<PathCommand>,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TileAtlas {
+    tiles: Vec<Tile>,
+}
+
+impl TileAtlas {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with(mut self, tile: Tile) -> Self {
+        self.add(tile);
+        self
+    }
+
+    pub fn add(&mut self, tile: Tile) -> TileId {
+        self.tiles.push(tile);
+        self.tiles.len() - 1
+    }
+
+    pub fn get(&self, id: TileId) -> Option<&Tile> {
+        self.tiles.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+/// The tiles of the map cells, row by row.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileLayer {
+    pub cells: Vec<Option<TileId>>,
+    pub visible: bool,
+}
+
+impl TileLayer {
+    pub fn new(cells: Vec<Option<TileId>>) -> Self {
+        Self { cells, visible: true }
+    }
+}
+
+/// A grid of tiles from the atlas, drawn in layers. The tiles are rendered in batches
+/// (one path per tile kind and layer) and only the cells in view are drawn.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TileMap {
+    pub id: Option<String>,
+    pub cols: usize,
+    pub rows: usize,
+    pub tile_width: Real,
+    pub tile_height: Real,
+    pub atlas: Rc<TileAtlas>,
+    pub layers: Vec<TileLayer>,
+    pub transparency: Real,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Fill>,
+    pub clip: Clip,
+    pub transform: Transform,
+}
+
+impl TileMap {
+    pub const NAME: &'static str = "tile_map";
+
+    pub fn new(cols: usize, rows: usize, tile_width: Real, tile_height: Real, atlas: Rc<TileAtlas>) -> Self {
+        Self {
+            cols,
+            rows,
+            tile_width,
+            tile_height,
+            atlas,
+            ..Default::default()
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    pub fn width(&self) -> Real {
+        self.cols as Real * self.tile_width
+    }
+
+    pub fn height(&self) -> Real {
+        self.rows as Real * self.tile_height
+    }
+
+    pub fn bound(&self) -> BoundingBox {
+        BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: self.width(),
+            max_y: self.height(),
+        }
+    }
+
+    /// Adds an empty layer over the others and returns its index.
+    pub fn add_layer(&mut self) -> usize {
+        self.layers.push(TileLayer::new(vec![None; self.cols * self.rows]));
+        self.layers.len() - 1
+    }
+
+    pub fn tile(&self, layer: usize, row: usize, col: usize) -> Option<TileId> {
+        let idx = self.cell_idx(row, col)?;
+        *self.layers.get(layer)?.cells.get(idx)?
+    }
+
+    /// Sets the tile of the cell and returns `false` if the layer or the cell does not exist.
+    pub fn set_tile(&mut self, layer: usize, row: usize, col: usize, tile: Option<TileId>) -> bool {
+        match (self.cell_idx(row, col), self.layers.get_mut(layer)) {
+            (Some(idx), Some(layer)) if idx < layer.cells.len() => {
+                layer.cells[idx] = tile;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn cell_idx(&self, row: usize, col: usize) -> Option<usize> {
+        if row < self.rows && col < self.cols {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// The cell `(row, col)` at the point in the map coordinates.
+    pub fn cell_at(&self, x: Real, y: Real) -> Option<(usize, usize)> {
+        if x < 0.0 || y < 0.0 || self.tile_width <= 0.0 || self.tile_height <= 0.0 {
+            return None;
+        }
+
+        let (row, col) = ((y / self.tile_height) as usize, (x / self.tile_width) as usize);
+        self.cell_idx(row, col).map(|_| (row, col))
+    }
+
+    /// The cell `(row, col)` at the point in the window coordinates, for hit testing.
+    pub fn hit_cell(&self, x: Real, y: Real) -> Option<(usize, usize)> {
+        let (x, y) = self.to_local(x, y);
+        self.cell_at(x, y)
+    }
+
+    /// The rows and columns of the cells intersecting the area in the map coordinates.
+    pub fn visible_cells(&self, area: &BoundingBox) -> (Range<usize>, Range<usize>) {
+        if self.tile_width <= 0.0 || self.tile_height <= 0.0 {
+            return (0..0, 0..0);
+        }
+
+        let range = |min: Real, max: Real, size: Real, len: usize| {
+            let start = (min / size).floor().max(0.0) as usize;
+            let end = ((max / size).ceil().max(0.0) as usize).min(len);
+            start.min(end)..end
+        };
+        (
+            range(area.min_y, area.max_y, self.tile_height, self.rows),
+            range(area.min_x, area.max_x, self.tile_width, self.cols),
+        )
+    }
+
+    /// Groups the visible cells of the layer by tile. Returns the origins of the cells
+    /// for each tile of the atlas.
+    pub fn batches(&self, layer: &TileLayer, rows: Range<usize>, cols: Range<usize>) -> Vec<(TileId, Vec<[Real; 2]>)> {
+        let mut batches: Vec<Vec<[Real; 2]>> = vec![vec![]; self.atlas.len()];
+        for row in rows {
+            for col in cols.clone() {
+                let tile = self.cell_idx(row, col).and_then(|idx| *layer.cells.get(idx)?);
+                if let Some(origins) = tile.and_then(|tile| batches.get_mut(tile)) {
+                    origins.push([col as Real * self.tile_width, row as Real * self.tile_height]);
+                }
+            }
+        }
+
+        batches
+            .into_iter()
+            .enumerate()
+            .filter(|(_, origins)| !origins.is_empty())
+            .collect()
+    }
+
+    pub fn recalculate_transform(&mut self, parent_global: TransformMatrix) -> TransformMatrix {
+        if let Some(transform) = self.clip.transform_mut() {
+            transform.calculate_global(parent_global);
+        }
+        self.transform.calculate_global(parent_global)
+    }
+
+    #[inline]
+    pub fn intersect(&self, x: Real, y: Real) -> bool {
+        let (x, y) = self.to_local(x, y);
+        x >= 0.0 && x <= self.width() && y >= 0.0 && y <= self.height()
+    }
+
+    fn to_local(&self, x: Real, y: Real) -> (Real, Real) {
+        let matrix = self
+            .transform
+            .global_matrix()
+            .unwrap_or_else(|| self.transform.matrix());
+        if !matrix.is_identity() {
+            matrix.inverse() * (x, y)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_cells_and_batches() {
+        let atlas = Rc::new(TileAtlas::new().with(Tile::default()).with(Tile::default()));
+        let mut map = TileMap::new(4, 3, 10.0, 10.0, atlas);
+        let layer = map.add_layer();
+        assert!(map.set_tile(layer, 0, 1, Some(0)));
+        assert!(map.set_tile(layer, 2, 3, Some(1)));
+        assert!(!map.set_tile(layer, 3, 0, Some(1)));
+
+        let area = BoundingBox {
+            min_x: 5.0,
+            min_y: -20.0,
+            max_x: 25.0,
+            max_y: 15.0,
+        };
+        assert_eq!(map.visible_cells(&area), (0..2, 0..3));
+
+        let batches = map.batches(&map.layers[layer], 0..3, 0..4);
+        assert_eq!(batches, vec![(0, vec![[10.0, 0.0]]), (1, vec![[30.0, 20.0]])]);
+        assert_eq!(map.cell_at(35.0, 25.0), Some((2, 3)));
+        assert_eq!(map.cell_at(45.0, 25.0), None);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::node::{ConvertTo, Pct, Real, RealValue};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Translate {
     pub x: RealValue,
     pub y: RealValue,
@@ -0,0 +1,43 @@
+use crate::{CompositeShape, Model, Node, Prim, Shape};
+
+/// An untyped copy of the view shapes, without components, listeners and
+/// decorations. With the `serde` feature the tree can be saved, compared in
+/// tests and loaded as an asset.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeTree {
+    pub shape: Shape,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub children: Vec<ShapeTree>,
+}
+
+impl ShapeTree {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            children: vec![],
+        }
+    }
+
+    /// Copies the shapes of the view. The views of the nested components are
+    /// copied in place of the components.
+    pub fn snapshot(view: &dyn CompositeShape) -> Option<Self> {
+        let shape = view.shape()?.clone();
+        let children = view
+            .children()
+            .map(|children| children.filter_map(Self::snapshot).collect())
+            .unwrap_or_default();
+        Some(Self { shape, children })
+    }
+
+    /// Builds the view of the tree for the model, named after the shapes.
+    pub fn into_node<M: Model>(self) -> Node<M> {
+        let children = self.children.into_iter().map(Self::into_node).collect();
+        Node::Prim(Prim::new(
+            self.shape.name().into(),
+            self.shape,
+            children,
+            Default::default(),
+        ))
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{Fill, Shape, Stroke};
+
+/// The fill and the stroke of a prim while it is hovered or pressed, applied by
+/// the event system itself, so the visual feedback needs no model messages.
+/// The pressed style falls back to the hover one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateStyle {
+    pub hover_fill: Option<Fill>,
+    pub hover_stroke: Option<Stroke>,
+    pub pressed_fill: Option<Fill>,
+    pub pressed_stroke: Option<Stroke>,
+    /// The own style of the shape to restore and the style applied over it, saved when
+    /// a state style is applied.
+    applied: Option<Applied>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Applied {
+    base: (Option<Fill>, Option<Stroke>),
+    style: (Option<Fill>, Option<Stroke>),
+}
+
+impl StateStyle {
+    /// Applies the style of the state to the shape, returns `true` if the shape is changed.
+    pub fn apply(&mut self, shape: &mut Shape, hovered: bool, pressed: bool) -> bool {
+        let (fill, stroke) = if pressed {
+            (
+                self.pressed_fill.or(self.hover_fill),
+                self.pressed_stroke.or(self.hover_stroke),
+            )
+        } else if hovered {
+            (self.hover_fill, self.hover_stroke)
+        } else {
+            (None, None)
+        };
+
+        let current = (*shape.fill_mut(), *shape.stroke_mut());
+        if let Some(applied) = self.applied.as_mut() {
+            // The fill or the stroke changed over the applied style is the new own style of the shape
+            if current.0 != applied.style.0 {
+                applied.base.0 = current.0;
+            }
+            if current.1 != applied.style.1 {
+                applied.base.1 = current.1;
+            }
+        }
+
+        let (fill, stroke) = if fill.is_some() || stroke.is_some() {
+            let applied = self.applied.get_or_insert(Applied {
+                base: current,
+                style: current,
+            });
+            applied.style = (fill.or(applied.base.0), stroke.or(applied.base.1));
+            applied.style
+        } else {
+            match self.applied.take() {
+                Some(applied) => applied.base,
+                None => return false,
+            }
+        };
+
+        let changed = current != (fill, stroke);
+        *shape.fill_mut() = fill;
+        *shape.stroke_mut() = stroke;
+        changed
+    }
+}
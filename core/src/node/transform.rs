@@ -3,6 +3,7 @@ use std::ops::Mul;
 use crate::Real;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Transform {
     Local(TransformMatrix),
     Global(TransformMatrix),
@@ -151,6 +152,7 @@ impl Transform {
 /// always [0 0 1].
 // TODO: need add transformation methods
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransformMatrix {
     pub matrix: [Real; 6],
 }
@@ -77,6 +77,7 @@ impl From<i32> for Pct<Real> {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueType {
     Auto,
     Px,
@@ -90,6 +91,7 @@ impl Default for ValueType {
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Value<T>(pub T, pub ValueType);
 
 impl<T: Debug + Default + Clone + Copy + PartialEq> Value<T> {
@@ -109,6 +111,10 @@ impl<T: Debug + Default + Clone + Copy + PartialEq> Value<T> {
         self.0
     }
 
+    pub fn is_auto(&self) -> bool {
+        self.1 == ValueType::Auto
+    }
+
     pub fn set_val(&mut self, v: T) {
         self.0 = v
     }
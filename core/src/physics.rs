@@ -0,0 +1,137 @@
+use std::{collections::HashMap, hash::Hash, mem, time::Duration};
+
+use crate::{FixedClock, Model, Node, Real, Transform};
+
+/// The position and rotation of a rigid body in the view coordinates.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BodyPose {
+    pub x: Real,
+    pub y: Real,
+    pub rotation: Real,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contact<B> {
+    Started(B, B),
+    Stopped(B, B),
+}
+
+/// A 2D physics engine driven by the [`PhysicsBridge`], with the body handles as `Body`.
+/// The `rapier2d` feature provides the `RapierWorld` implementation, other physics
+/// crates can be plugged in by implementing it over their world.
+pub trait PhysicsWorld {
+    type Body: Copy + Eq + Hash;
+
+    fn step(&mut self, dt: Duration);
+
+    fn pose(&self, body: Self::Body) -> Option<BodyPose>;
+
+    /// Returns the contacts started or stopped since the previous call.
+    fn drain_contacts(&mut self) -> Vec<Contact<Self::Body>>;
+}
+
+/// A collision between the nodes mirroring the bodies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollisionEvent {
+    Started { first: String, second: String },
+    Stopped { first: String, second: String },
+}
+
+/// Steps the physics world with a fixed tick and mirrors the body poses
+/// into the transforms of the bound nodes.
+pub struct PhysicsBridge<W: PhysicsWorld> {
+    world: W,
+    bodies: HashMap<String, W::Body>,
+    nodes: HashMap<W::Body, String>,
+    clock: FixedClock,
+    collisions: Vec<CollisionEvent>,
+}
+
+impl<W: PhysicsWorld> PhysicsBridge<W> {
+    /// The max number of steps in one `advance` call, so a long frame does not
+    /// stall the next ones.
+    pub const MAX_STEPS: u32 = 8;
+
+    pub fn new(world: W, fixed_step: Duration) -> Self {
+        Self {
+            world,
+            bodies: HashMap::new(),
+            nodes: HashMap::new(),
+            clock: FixedClock::new(fixed_step).with_max_steps(Self::MAX_STEPS),
+            collisions: vec![],
+        }
+    }
+
+    pub fn world(&self) -> &W {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut W {
+        &mut self.world
+    }
+
+    /// Binds the node with the id to the body, replacing the previous binding of both.
+    pub fn bind(&mut self, id: impl Into<String>, body: W::Body) {
+        let id = id.into();
+        self.unbind(&id);
+        if let Some(id) = self.nodes.remove(&body) {
+            self.bodies.remove(&id);
+        }
+        self.bodies.insert(id.clone(), body);
+        self.nodes.insert(body, id);
+    }
+
+    pub fn unbind(&mut self, id: &str) -> Option<W::Body> {
+        let body = self.bodies.remove(id)?;
+        self.nodes.remove(&body);
+        Some(body)
+    }
+
+    pub fn body(&self, id: &str) -> Option<W::Body> {
+        self.bodies.get(id).copied()
+    }
+
+    pub fn node_id(&self, body: W::Body) -> Option<&str> {
+        self.nodes.get(&body).map(String::as_str)
+    }
+
+    /// Runs the fixed steps due for the elapsed time and returns their number.
+    /// Collisions of the bound bodies are collected for `drain_collisions`.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        let steps = self.clock.advance(elapsed);
+        for _ in 0..steps {
+            self.world.step(self.clock.step());
+            for contact in self.world.drain_contacts() {
+                let (first, second, started) = match contact {
+                    Contact::Started(first, second) => (first, second, true),
+                    Contact::Stopped(first, second) => (first, second, false),
+                };
+                if let (Some(first), Some(second)) = (self.nodes.get(&first), self.nodes.get(&second)) {
+                    let (first, second) = (first.clone(), second.clone());
+                    self.collisions.push(if started {
+                        CollisionEvent::Started { first, second }
+                    } else {
+                        CollisionEvent::Stopped { first, second }
+                    });
+                }
+            }
+        }
+        steps
+    }
+
+    pub fn drain_collisions(&mut self) -> Vec<CollisionEvent> {
+        mem::take(&mut self.collisions)
+    }
+
+    /// Sets the transforms of the bound nodes of the view to the body poses.
+    /// Usually called from `Model::modify_view` after `advance`.
+    pub fn sync<M: Model>(&self, view: &mut Node<M>) {
+        for (id, &body) in &self.bodies {
+            if let (Some(pose), Some(node)) = (self.world.pose(body), view.get_mut(id)) {
+                *node.transform_mut() = Transform::new()
+                    .with_translation(pose.x, pose.y)
+                    .with_rotation(pose.rotation);
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::{fmt, time::Duration};
+
+use rapier2d::{
+    crossbeam::channel::{self, Receiver},
+    prelude::*,
+};
+
+use crate::{BodyPose, Contact, PhysicsWorld};
+
+/// The `rapier2d` world for the [`PhysicsBridge`](crate::PhysicsBridge), with the
+/// rigid body handles as the bodies. The colliders inserted by `insert` report
+/// the collisions of their bodies.
+pub struct RapierWorld {
+    pub gravity: Vector<Real>,
+    pub integration_parameters: IntegrationParameters,
+    pub bodies: RigidBodySet,
+    pub colliders: ColliderSet,
+    pub impulse_joints: ImpulseJointSet,
+    pub multibody_joints: MultibodyJointSet,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    ccd_solver: CCDSolver,
+    events: ChannelEventCollector,
+    collisions: Receiver<CollisionEvent>,
+    _contact_forces: Receiver<ContactForceEvent>,
+}
+
+impl RapierWorld {
+    pub fn new(gravity_x: Real, gravity_y: Real) -> Self {
+        let (collision_sender, collisions) = channel::unbounded();
+        let (contact_force_sender, contact_forces) = channel::unbounded();
+        Self {
+            gravity: vector![gravity_x, gravity_y],
+            integration_parameters: IntegrationParameters::default(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            ccd_solver: CCDSolver::new(),
+            events: ChannelEventCollector::new(collision_sender, contact_force_sender),
+            collisions,
+            _contact_forces: contact_forces,
+        }
+    }
+
+    /// Inserts the body with its collider, which reports the collision events.
+    pub fn insert(&mut self, body: impl Into<RigidBody>, collider: impl Into<Collider>) -> RigidBodyHandle {
+        let handle = self.bodies.insert(body);
+        let mut collider = collider.into();
+        collider.set_active_events(collider.active_events() | ActiveEvents::COLLISION_EVENTS);
+        self.colliders.insert_with_parent(collider, handle, &mut self.bodies);
+        handle
+    }
+
+    /// Removes the body with its colliders and joints.
+    pub fn remove(&mut self, handle: RigidBodyHandle) -> Option<RigidBody> {
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        )
+    }
+
+    fn collider_body(&self, collider: ColliderHandle) -> Option<RigidBodyHandle> {
+        self.colliders.get(collider)?.parent()
+    }
+}
+
+impl Default for RapierWorld {
+    fn default() -> Self {
+        Self::new(0.0, 0.0)
+    }
+}
+
+impl fmt::Debug for RapierWorld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(RapierWorld))
+            .field("gravity", &self.gravity)
+            .field("bodies", &self.bodies.len())
+            .field("colliders", &self.colliders.len())
+            .finish()
+    }
+}
+
+impl PhysicsWorld for RapierWorld {
+    type Body = RigidBodyHandle;
+
+    fn step(&mut self, dt: Duration) {
+        self.integration_parameters.dt = dt.as_secs_f32();
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            None,
+            &(),
+            &self.events,
+        );
+    }
+
+    fn pose(&self, body: Self::Body) -> Option<BodyPose> {
+        let body = self.bodies.get(body)?;
+        Some(BodyPose {
+            x: body.translation().x,
+            y: body.translation().y,
+            rotation: body.rotation().angle(),
+        })
+    }
+
+    fn drain_contacts(&mut self) -> Vec<Contact<Self::Body>> {
+        self.collisions
+            .try_iter()
+            .filter_map(|event| {
+                let first = self.collider_body(event.collider1())?;
+                let second = self.collider_body(event.collider2())?;
+                Some(if event.started() {
+                    Contact::Started(first, second)
+                } else {
+                    Contact::Stopped(first, second)
+                })
+            })
+            .collect()
+    }
+}
@@ -15,4 +15,9 @@ pub trait Render {
     fn set_dimensions(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f64) {}
 
     fn render(&mut self, node: &mut dyn CompositeShape) -> Result<bool, Self::Error>;
+
+    /// The number of the draw calls of the last rendered frame, for `FrameStats`.
+    fn draw_calls(&self) -> Option<usize> {
+        None
+    }
 }
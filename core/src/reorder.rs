@@ -0,0 +1,343 @@
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
+use crate::{
+    Animate, DragDrop, DragEvent, Fill, Group, InputEvent, Model, MouseButton, Node, Overflow, Pct, Prim, Real, Rect,
+    Shape, Theme, ThemeToken, Transform,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorderEvent {
+    Started {
+        index: usize,
+    },
+    /// The item is moved from the index to the other one, the items are already reordered.
+    Completed {
+        from: usize,
+        to: usize,
+    },
+    /// The item is dropped outside of the list and returns to its place.
+    Cancelled {
+        index: usize,
+    },
+}
+
+/// A list of the fixed-height rows which are reordered by dragging them by
+/// their handles. The rows under the dragged one move apart to open the gap
+/// at the drop place, and the list scrolls when the pointer is near its edges.
+///
+/// The model passes `SystemMessage::Input` events from `system_update` to
+/// `queue` and, if one is queued, returns a message for which `update` returns
+/// `ChangeView::Modify`. Then `modify_view` runs `handle_queued` with the view
+/// and applies the changes by `sync_view`. On `SystemMessage::Draw` the model
+/// calls `animate` with the elapsed time while `is_transient` and modifies the
+/// view the same way. The view is rebuilt after the reorder is completed.
+#[derive(Debug)]
+pub struct ReorderableList<T> {
+    items: Vec<T>,
+    item_height: Real,
+    height: Real,
+    scroll: Real,
+    offsets: Vec<Animate<Real>>,
+    drag: DragDrop<usize>,
+    pointer_y: Option<Real>,
+    insertion: Option<usize>,
+    theme: Theme,
+}
+
+impl<T> ReorderableList<T> {
+    pub const LIST_ID: &'static str = "reorderable_list";
+    pub const WIDGET: &'static str = "reorderable_list";
+
+    /// The duration of the gap opening.
+    pub const GAP_DURATION: Duration = Duration::from_millis(120);
+
+    /// The distance from the list edge where the auto-scroll starts.
+    pub const SCROLL_EDGE: Real = 24.0;
+
+    /// The auto-scroll speed in pixels per millisecond.
+    pub const SCROLL_SPEED: Real = 0.5;
+
+    const CONTENT_ID: &'static str = "reorderable_list_content";
+    const HANDLE_PREFIX: &'static str = "reorderable_handle_";
+    const ITEM_PREFIX: &'static str = "reorderable_item_";
+
+    pub fn new(items: Vec<T>, item_height: Real, height: Real) -> Self {
+        let mut list = Self {
+            items: vec![],
+            item_height,
+            height,
+            scroll: 0.0,
+            offsets: vec![],
+            drag: DragDrop::new(),
+            pointer_y: None,
+            insertion: None,
+            theme: Theme::default(),
+        };
+        list.set_items(items);
+        list
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Replaces the items and stops the current drag.
+    pub fn set_items(&mut self, items: Vec<T>) {
+        let speed = self.item_height / Self::GAP_DURATION.as_millis() as Real;
+        self.offsets = (0..items.len()).map(|_| Animate::new(0.0, 0.0, speed)).collect();
+        self.drag = DragDrop::new().with_target(Self::LIST_ID, |_| true);
+        for idx in 0..items.len() {
+            self.drag.add_source(Self::item_id(idx), idx);
+        }
+        self.items = items;
+        self.pointer_y = None;
+        self.insertion = None;
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    pub fn scroll(&self) -> Real {
+        self.scroll
+    }
+
+    pub fn set_scroll(&mut self, scroll: Real) {
+        self.scroll = scroll.max(0.0).min(self.max_scroll());
+    }
+
+    fn max_scroll(&self) -> Real {
+        (self.items.len() as Real * self.item_height - self.height).max(0.0)
+    }
+
+    /// The index of the dragged item.
+    pub fn dragged(&self) -> Option<usize> {
+        Self::item_index(self.drag.dragged()?)
+    }
+
+    /// The index the dragged item takes if it is dropped now.
+    pub fn insertion(&self) -> Option<usize> {
+        self.insertion
+    }
+
+    /// Checks the gap is opening or closing or the list is auto-scrolling.
+    pub fn is_transient(&self) -> bool {
+        self.offsets.iter().any(Animate::is_transient) || self.scroll_step() != 0.0
+    }
+
+    pub fn item_id(idx: usize) -> String {
+        format!("{}{}", Self::ITEM_PREFIX, idx)
+    }
+
+    pub fn handle_id(idx: usize) -> String {
+        format!("{}{}", Self::HANDLE_PREFIX, idx)
+    }
+
+    fn item_index(id: &str) -> Option<usize> {
+        id.strip_prefix(Self::ITEM_PREFIX)?.parse().ok()
+    }
+
+    /// Keeps the mouse event for `handle_queued`, returns `true` if the event is kept.
+    pub fn queue(&mut self, event: &InputEvent) -> bool {
+        self.drag.queue(event)
+    }
+
+    /// Handles the events kept by `queue` with the view and returns the reorder events they caused.
+    pub fn handle_queued<M: Model>(&mut self, view: &Node<M>) -> Vec<ReorderEvent> {
+        self.drag
+            .take_queued()
+            .into_iter()
+            .flat_map(|event| self.handle(view, event))
+            .collect()
+    }
+
+    /// Updates the drag by the input event and returns the reorder events it caused.
+    /// The drag starts only by the item handle.
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, event: InputEvent) -> Vec<ReorderEvent> {
+        if let InputEvent::MouseDown(press) = event {
+            if press.button == MouseButton::Left {
+                let is_handle = |prim: &Prim<M>| prim.id().is_some_and(|id| id.starts_with(Self::HANDLE_PREFIX));
+                let on_handle = view
+                    .as_prim()
+                    .and_then(|view| view.hit_test_by(press.pos.x, press.pos.y, &is_handle))
+                    .is_some();
+                if !on_handle {
+                    return vec![];
+                }
+            }
+        }
+        if let InputEvent::MouseMove(movement) = event {
+            self.pointer_y = view
+                .global_bounds_of(Self::LIST_ID)
+                .map(|bound| movement.pos.y - bound.min_y);
+        }
+
+        let mut events = vec![];
+        for drag_event in self.drag.handle(view, event) {
+            match drag_event {
+                DragEvent::Started { source } => {
+                    if let Some(index) = Self::item_index(&source) {
+                        self.insertion = Some(index);
+                        events.push(ReorderEvent::Started { index });
+                    }
+                },
+                DragEvent::Dropped { payload: from, .. } => {
+                    let to = self.insertion.unwrap_or(from);
+                    if from != to {
+                        let item = self.items.remove(from);
+                        self.items.insert(to, item);
+                    }
+                    self.stop_drag();
+                    self.offsets.iter_mut().for_each(|offset| offset.from(0.0));
+                    events.push(ReorderEvent::Completed { from, to });
+                },
+                DragEvent::Cancelled { source } => {
+                    self.stop_drag();
+                    if let Some(index) = Self::item_index(&source) {
+                        events.push(ReorderEvent::Cancelled { index });
+                    }
+                },
+                DragEvent::Entered { .. } | DragEvent::Left { .. } => (),
+            }
+        }
+        self.update_insertion();
+        events
+    }
+
+    /// Moves the gap and auto-scrolls the list by the elapsed time.
+    pub fn animate(&mut self, elapsed: Duration) {
+        let step = self.scroll_step();
+        if step != 0.0 {
+            self.set_scroll(self.scroll + step * elapsed.as_millis() as Real);
+            self.update_insertion();
+        }
+        self.offsets.iter_mut().for_each(|offset| offset.animate(elapsed));
+    }
+
+    fn stop_drag(&mut self) {
+        self.pointer_y = None;
+        self.insertion = None;
+        self.offsets.iter_mut().for_each(|offset| offset.to(0.0));
+    }
+
+    /// The scroll speed with the sign of the direction, zero if the pointer is not near the edges.
+    fn scroll_step(&self) -> Real {
+        match (self.drag.is_dragging(), self.pointer_y) {
+            (true, Some(y)) if y < Self::SCROLL_EDGE && self.scroll > 0.0 => -Self::SCROLL_SPEED,
+            (true, Some(y)) if y > self.height - Self::SCROLL_EDGE && self.scroll < self.max_scroll() => {
+                Self::SCROLL_SPEED
+            },
+            _ => 0.0,
+        }
+    }
+
+    fn update_insertion(&mut self) {
+        let (from, y) = match (self.dragged(), self.pointer_y) {
+            (Some(from), Some(y)) => (from, y),
+            _ => return,
+        };
+        let last = self.items.len().saturating_sub(1) as Real;
+        let to = ((y + self.scroll) / self.item_height).floor().max(0.0).min(last) as usize;
+        self.insertion = Some(to);
+
+        for (idx, offset) in self.offsets.iter_mut().enumerate() {
+            let target = if from < idx && idx <= to {
+                -self.item_height
+            } else if to <= idx && idx < from {
+                self.item_height
+            } else {
+                0.0
+            };
+            offset.to(target);
+        }
+    }
+
+    /// Builds the list with the rows made by the `build_item` and the handles on their left.
+    pub fn build_view<M: Model>(&self, build_item: impl Fn(&T) -> Node<M>) -> Node<M> {
+        let handle_width = self.theme.hit_target(24.0);
+        let grip_color = self.theme.color(Self::WIDGET, ThemeToken::TextSecondary);
+
+        let rows = self.items.iter().enumerate().map(|(idx, item)| {
+            let grips = (0..3)
+                .map(|line| {
+                    let grip = Rect {
+                        x: (handle_width / 2.0 - 6.0).into(),
+                        y: (self.item_height / 2.0 - 4.0 + line as Real * 3.5).into(),
+                        width: 12.0.into(),
+                        height: 1.5.into(),
+                        fill: Some(Fill::color(grip_color)),
+                        ..Default::default()
+                    };
+                    prim(Shape::Rect(grip), vec![])
+                })
+                .collect();
+            let handle = Rect {
+                id: Some(Self::handle_id(idx)),
+                width: handle_width.into(),
+                height: self.item_height.into(),
+                ..Default::default()
+            };
+            let content = Group {
+                transform: Transform::new().with_translation(handle_width, 0.0),
+                ..Default::default()
+            };
+            let row = Group {
+                id: Some(Self::item_id(idx)),
+                transform: Transform::new().with_translation(0.0, self.row_y(idx)),
+                ..Default::default()
+            };
+            prim(Shape::Group(row), vec![
+                prim(Shape::Rect(handle), grips),
+                prim(Shape::Group(content), vec![build_item(item)]),
+            ])
+        });
+
+        let content = Group {
+            id: Some(Self::CONTENT_ID.to_string()),
+            transform: Transform::new().with_translation(0.0, -self.scroll),
+            ..Default::default()
+        };
+        let list = Rect {
+            id: Some(Self::LIST_ID.to_string()),
+            width: Pct(100.0).into(),
+            height: self.height.into(),
+            overflow: Overflow::Hidden,
+            ..Default::default()
+        };
+        prim(Shape::Rect(list), vec![
+            prim(Shape::Group(content), rows.collect()),
+            self.drag.build_view(),
+        ])
+    }
+
+    /// Moves the rows, the scroll and the drag ghost in the view built by `build_view`.
+    pub fn sync_view<M: Model>(&self, view: &mut Node<M>) {
+        if let Some(content) = view.get_prim_mut(Self::CONTENT_ID) {
+            *content.transform_mut() = Transform::new().with_translation(0.0, -self.scroll);
+        }
+        let dragged = self.dragged();
+        for idx in 0..self.items.len() {
+            if let Some(row) = view.get_prim_mut(Self::item_id(idx)) {
+                *row.transform_mut() = Transform::new().with_translation(0.0, self.row_y(idx));
+                if let Shape::Group(group) = row.shape_mut() {
+                    group.transparency = if dragged == Some(idx) { Some(1.0) } else { None };
+                }
+            }
+        }
+        self.drag.sync_overlay(view);
+    }
+
+    fn row_y(&self, idx: usize) -> Real {
+        idx as Real * self.item_height + self.offsets.get(idx).map_or(0.0, Animate::val)
+    }
+}
+
+fn prim<M: Model>(shape: Shape, children: Vec<Node<M>>) -> Node<M> {
+    Node::Prim(Prim::new(Cow::Borrowed(shape.name()), shape, children, HashMap::new()))
+}
@@ -0,0 +1,164 @@
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Comp, LoadState, MiddlewareId, SaveState};
+
+/// The message with the time since the start of the recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMsg<Msg> {
+    pub time: Duration,
+    pub msg: Msg,
+}
+
+/// The initial state of a model and the messages it is updated by afterwards,
+/// which reproduce the session, e.g. the one a bug is reported for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording<S, Msg> {
+    pub state: S,
+    pub msgs: Vec<RecordedMsg<Msg>>,
+}
+
+/// Records the messages passed to the `update` of a component by the middleware,
+/// after the middleware added before it. The `Replayer` sends them past the
+/// middleware, so they are not changed by it twice.
+pub struct Recorder<M: SaveState> {
+    state: Option<M::State>,
+    msgs: Rc<RefCell<Vec<RecordedMsg<M::Message>>>>,
+    active: Rc<Cell<bool>>,
+    middleware: MiddlewareId,
+}
+
+impl<M: SaveState> Recorder<M>
+where
+    M::Message: Clone,
+{
+    /// Saves the state of the component model and starts recording its messages.
+    pub fn start(comp: &mut Comp) -> Self {
+        let msgs = Rc::new(RefCell::new(Vec::new()));
+        let active = Rc::new(Cell::new(true));
+        let start = Instant::now();
+        let middleware = comp.add_middleware::<M>({
+            let msgs = msgs.clone();
+            let active = active.clone();
+            move |_: &M, msg: M::Message| {
+                if active.get() {
+                    msgs.borrow_mut().push(RecordedMsg {
+                        time: start.elapsed(),
+                        msg: msg.clone(),
+                    });
+                }
+                Some(msg)
+            }
+        });
+
+        Self {
+            state: Some(comp.save_state::<M>()),
+            msgs,
+            active,
+            middleware,
+        }
+    }
+
+    /// The number of the recorded messages.
+    pub fn len(&self) -> usize {
+        self.msgs.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the middleware of the recorder from the component and returns the recording.
+    pub fn stop(mut self, comp: &mut Comp) -> Recording<M::State, M::Message> {
+        self.active.set(false);
+        comp.remove_middleware::<M>(self.middleware);
+        Recording {
+            state: self.state.take().expect("The state is taken once"),
+            msgs: mem::take(&mut *self.msgs.borrow_mut()),
+        }
+    }
+}
+
+impl<M: SaveState> Drop for Recorder<M> {
+    fn drop(&mut self) {
+        // The middleware is left in the component without `stop` and passes the messages on
+        self.active.set(false);
+    }
+}
+
+/// Drives a component by the recording, from its initial state. It can step the
+/// messages one by one, play them by the recorded time, or seek back and forth.
+pub struct Replayer<M: LoadState> {
+    recording: Recording<M::State, M::Message>,
+    position: usize,
+}
+
+impl<M: LoadState> Replayer<M>
+where
+    M::State: Clone,
+    M::Message: Clone,
+{
+    pub fn new(recording: Recording<M::State, M::Message>) -> Self {
+        Self { recording, position: 0 }
+    }
+
+    pub fn recording(&self) -> &Recording<M::State, M::Message> {
+        &self.recording
+    }
+
+    /// The number of the messages sent.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.msgs.len()
+    }
+
+    /// Restores the initial state of the recording.
+    pub fn restart(&mut self, comp: &mut Comp) {
+        comp.load_state::<M>(self.recording.state.clone());
+        self.position = 0;
+    }
+
+    /// Sends the next message, returns its time or `None` if the recording is finished.
+    pub fn step(&mut self, comp: &mut Comp) -> Option<Duration> {
+        let recorded = self.recording.msgs.get(self.position)?;
+        self.position += 1;
+        comp.send_unintercepted::<M>(recorded.msg.clone());
+        Some(recorded.time)
+    }
+
+    /// Sends the messages recorded up to the time, to play the recording at its pace.
+    pub fn play_until(&mut self, comp: &mut Comp, time: Duration) {
+        while self
+            .recording
+            .msgs
+            .get(self.position)
+            .is_some_and(|recorded| recorded.time <= time)
+        {
+            self.step(comp);
+        }
+    }
+
+    /// Brings the component to the state after the number of the messages, going
+    /// back by the replay from the initial state.
+    pub fn seek(&mut self, comp: &mut Comp, position: usize) {
+        if position < self.position {
+            self.restart(comp);
+        }
+        while self.position < position && self.step(comp).is_some() {}
+    }
+
+    /// Replays the whole recording from the initial state.
+    pub fn replay(&mut self, comp: &mut Comp) {
+        self.restart(comp);
+        self.seek(comp, self.recording.msgs.len());
+    }
+}
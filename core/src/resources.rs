@@ -0,0 +1,104 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    mem,
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Resources>> = const { RefCell::new(None) };
+}
+
+/// The values of the component stored by their types, e.g. the config, the
+/// audio handles or the services. Unlike the context they are owned by the
+/// component, and its models reach them by `with_resource` while the component
+/// handles a message or updates the view.
+#[derive(Default)]
+pub struct Resources {
+    values: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts the value, returns the value of the type it replaces.
+    pub fn insert<T: Any>(&mut self, value: T) -> Option<T> {
+        let previous = self.values.insert(TypeId::of::<T>(), Box::new(value))?;
+        previous.downcast().ok().map(|value| *value)
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.values.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    pub fn remove<T: Any>(&mut self) -> Option<T> {
+        let value = self.values.remove(&TypeId::of::<T>())?;
+        value.downcast().ok().map(|value| *value)
+    }
+
+    pub fn contains<T: Any>(&self) -> bool {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Lends the resources of the outermost component which handles the current
+/// event to its models and the nested ones, and takes them back on drop.
+pub(crate) struct ResourceScope<'a> {
+    resources: &'a mut Resources,
+    lent: bool,
+}
+
+impl<'a> ResourceScope<'a> {
+    pub(crate) fn enter(resources: &'a mut Resources) -> Self {
+        let lent = !resources.is_empty()
+            && CURRENT.with(|current| {
+                let mut current = current.borrow_mut();
+                if current.is_some() {
+                    return false;
+                }
+                *current = Some(mem::take(resources));
+                true
+            });
+
+        Self { resources, lent }
+    }
+}
+
+impl Drop for ResourceScope<'_> {
+    fn drop(&mut self) {
+        if self.lent {
+            if let Some(resources) = CURRENT.with(|current| current.borrow_mut().take()) {
+                *self.resources = resources;
+            }
+        }
+    }
+}
+
+/// Calls the function with the resource of the type of the component which
+/// handles the current event, see `Comp::insert_resource`. Returns `None` if
+/// there is no such resource. The resources can't be reached from the function.
+pub fn with_resource<T: Any, R>(f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    CURRENT.with(|current| {
+        let mut current = current.try_borrow_mut().ok()?;
+        current.as_mut()?.get_mut::<T>().map(f)
+    })
+}
+
+/// The copy of the resource of the type, see `with_resource`.
+pub fn resource<T: Any + Clone>() -> Option<T> {
+    with_resource(|value: &mut T| value.clone())
+}
@@ -0,0 +1,221 @@
+use std::{cell::RefCell, collections::VecDeque, mem, rc::Rc};
+
+use crate::{
+    ChangeView, Comp, Group, Model, Node, Prim, SceneStage, SceneSwitcher, SceneTransition, Shape, SystemMessage,
+};
+
+/// Creates the screen component of the route.
+pub type RouteFn<R> = Rc<dyn Fn(&R) -> Comp>;
+
+enum Navigation<R> {
+    Push(R, SceneTransition),
+    Replace(R, SceneTransition),
+    Back(SceneTransition),
+}
+
+/// A handle to navigate the router from any component. The router applies the
+/// requests on the next frame.
+pub struct Navigator<R> {
+    requests: Rc<RefCell<Vec<Navigation<R>>>>,
+}
+
+impl<R> Navigator<R> {
+    pub fn new() -> Self {
+        Self {
+            requests: Default::default(),
+        }
+    }
+
+    /// Shows the screen of the route, the current screen is kept in the back stack.
+    pub fn push(&self, route: R, transition: SceneTransition) {
+        self.requests.borrow_mut().push(Navigation::Push(route, transition));
+    }
+
+    /// Shows the screen of the route instead of the current one, which is dropped.
+    pub fn replace(&self, route: R, transition: SceneTransition) {
+        self.requests.borrow_mut().push(Navigation::Replace(route, transition));
+    }
+
+    /// Returns to the previous screen with its state, unless the current one is the first.
+    pub fn back(&self, transition: SceneTransition) {
+        self.requests.borrow_mut().push(Navigation::Back(transition));
+    }
+
+    fn take(&self) -> Vec<Navigation<R>> {
+        mem::take(&mut *self.requests.borrow_mut())
+    }
+}
+
+impl<R> Clone for Navigator<R> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests.clone(),
+        }
+    }
+}
+
+impl<R> Default for Navigator<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Page<R> {
+    key: u64,
+    route: R,
+    /// The screen while it is in the back stack, `None` while it is shown.
+    comp: Option<Comp>,
+}
+
+/// The component that shows the screens of the routes requested by the `Navigator`,
+/// with the transitions of the `SceneStage`. The screens in the back stack keep their state.
+pub struct Router<R> {
+    routes: RouteFn<R>,
+    navigator: Navigator<R>,
+    switcher: SceneSwitcher,
+    stack: Vec<Page<R>>,
+    shown: Option<u64>,
+    /// The pages of the screens the stage is going to remove, in the order of removal.
+    leaving: VecDeque<u64>,
+    next_key: u64,
+}
+
+impl<R: 'static> Router<R> {
+    pub fn new(initial: R, navigator: Navigator<R>, routes: impl Fn(&R) -> Comp + 'static) -> Self {
+        let mut router = Self {
+            routes: Rc::new(routes),
+            navigator,
+            switcher: SceneSwitcher::keeping_outgoing(),
+            stack: Vec::new(),
+            shown: None,
+            leaving: VecDeque::new(),
+            next_key: 0,
+        };
+        router.push(initial);
+        router.show(SceneTransition::Cut);
+        router
+    }
+
+    pub fn navigator(&self) -> &Navigator<R> {
+        &self.navigator
+    }
+
+    /// The route of the current screen.
+    pub fn route(&self) -> &R {
+        &self.stack.last().expect("The router stack is never empty").route
+    }
+
+    /// The routes of the back stack, from the first one to the current one.
+    pub fn history(&self) -> impl Iterator<Item = &R> {
+        self.stack.iter().map(|page| &page.route)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.stack.len() > 1
+    }
+
+    fn push(&mut self, route: R) {
+        self.stack.push(Page {
+            key: self.next_key,
+            route,
+            comp: None,
+        });
+        self.next_key += 1;
+    }
+
+    fn navigate(&mut self, navigation: Navigation<R>) -> SceneTransition {
+        match navigation {
+            Navigation::Push(route, transition) => {
+                self.push(route);
+                transition
+            },
+            Navigation::Replace(route, transition) => {
+                self.stack.pop();
+                self.push(route);
+                transition
+            },
+            Navigation::Back(transition) => {
+                if self.can_go_back() {
+                    self.stack.pop();
+                }
+                transition
+            },
+        }
+    }
+
+    fn show(&mut self, transition: SceneTransition) {
+        let page = self.stack.last_mut().expect("The router stack is never empty");
+        if self.shown == Some(page.key) {
+            return;
+        }
+
+        let comp = match page.comp.take() {
+            Some(comp) => comp,
+            None => (self.routes)(&page.route),
+        };
+        if let Some(key) = self.shown {
+            self.leaving.push_back(key);
+        }
+        self.shown = Some(page.key);
+        self.switcher.switch_to(comp, transition);
+    }
+
+    /// Keeps the screen removed by the stage in its page of the back stack. The screen
+    /// is dropped if its page is popped, or shown again by a new screen in the meantime.
+    fn keep(&mut self, mut comp: Comp) {
+        comp.unmount();
+        match self.leaving.pop_front() {
+            Some(key) if self.shown != Some(key) => {
+                if let Some(page) = self.stack.iter_mut().find(|page| page.key == key) {
+                    page.comp = Some(comp);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+impl<R: 'static> Model for Router<R> {
+    type Message = ();
+    type Properties = (R, Navigator<R>, RouteFn<R>);
+
+    fn create((initial, navigator, routes): Self::Properties) -> Self {
+        Self::new(initial, navigator, move |route: &R| routes(route))
+    }
+
+    fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+        match msg {
+            SystemMessage::Draw(_) => Some(()),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ChangeView {
+        for comp in self.switcher.take_outgoing() {
+            self.keep(comp);
+        }
+
+        let mut transition = None;
+        for navigation in self.navigator.take() {
+            transition = Some(self.navigate(navigation));
+        }
+        if let Some(transition) = transition {
+            self.show(transition);
+        }
+        ChangeView::None
+    }
+
+    fn build_view(&self) -> Node<Self> {
+        let stage = Comp::with_props::<SceneStage>(self.switcher.clone());
+        let group = Group {
+            id: Some("router".to_string()),
+            ..Default::default()
+        };
+        Node::Prim(Prim::new(
+            Group::NAME.into(),
+            Shape::Group(group),
+            vec![Node::Comp(stage)],
+            Default::default(),
+        ))
+    }
+}
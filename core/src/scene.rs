@@ -0,0 +1,310 @@
+use std::{cell::RefCell, mem, rc::Rc, time::Duration};
+
+use crate::{
+    ChangeView, Comp, Fill, Group, Model, Node, Pct, Prim, Real, Rect, Shape, Shaped, SystemMessage, Theme, ThemeToken,
+    Transform,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlideDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SlideDirection {
+    fn unit(&self) -> (Real, Real) {
+        match self {
+            SlideDirection::Left => (-1.0, 0.0),
+            SlideDirection::Right => (1.0, 0.0),
+            SlideDirection::Up => (0.0, -1.0),
+            SlideDirection::Down => (0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneTransition {
+    Cut,
+    Fade(Duration),
+    /// Both scenes move in the direction, the new one comes in from the opposite side.
+    Slide(SlideDirection, Duration),
+}
+
+impl SceneTransition {
+    fn duration(&self) -> Duration {
+        match *self {
+            SceneTransition::Cut => Duration::default(),
+            SceneTransition::Fade(duration) | SceneTransition::Slide(_, duration) => duration,
+        }
+    }
+}
+
+/// A handle to request the scene change from any component. The stage applies
+/// the last request on the next frame.
+#[derive(Clone, Default)]
+pub struct SceneSwitcher {
+    request: Rc<RefCell<Option<(Comp, SceneTransition)>>>,
+    outgoing: Option<Rc<RefCell<Vec<Comp>>>>,
+}
+
+impl SceneSwitcher {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates the switcher which keeps the scenes the stage removes, instead of
+    /// dropping them, to be taken by `take_outgoing`.
+    pub fn keeping_outgoing() -> Self {
+        Self {
+            outgoing: Some(Default::default()),
+            ..Default::default()
+        }
+    }
+
+    /// The scenes removed by the stage since the last call, in the order of removal.
+    pub fn take_outgoing(&self) -> Vec<Comp> {
+        match self.outgoing.as_ref() {
+            Some(outgoing) => mem::take(&mut *outgoing.borrow_mut()),
+            None => Vec::new(),
+        }
+    }
+
+    fn retire<M: Model>(&self, nodes: Vec<Node<M>>) {
+        if let Some(outgoing) = self.outgoing.as_ref() {
+            outgoing
+                .borrow_mut()
+                .extend(nodes.into_iter().filter_map(|node| match node {
+                    Node::Comp(comp) => Some(comp),
+                    Node::Prim(_) => None,
+                }));
+        }
+    }
+
+    pub fn switch_to(&self, scene: impl Into<Comp>, transition: SceneTransition) {
+        *self.request.borrow_mut() = Some((scene.into(), transition));
+    }
+
+    fn take(&self) -> Option<(Comp, SceneTransition)> {
+        self.request.borrow_mut().take()
+    }
+}
+
+pub enum SceneMsg {
+    Resize { width: Real, height: Real },
+    Draw(Duration),
+}
+
+/// The root component that shows one scene component at a time and animates
+/// the changes of the scenes requested by the `SceneSwitcher`. The scenes keep
+/// their state during the transition. The stage is filled with the `Surface`
+/// color of the theme behind the scenes.
+pub struct SceneStage {
+    switcher: SceneSwitcher,
+    incoming: Option<(Comp, SceneTransition)>,
+    transition: Option<(SceneTransition, Duration)>,
+    width: Real,
+    height: Real,
+    theme: Theme,
+}
+
+impl SceneStage {
+    pub const WIDGET: &'static str = "scene_stage";
+
+    const STAGE_ID: &'static str = "scene_stage";
+    const CURRENT_ID: &'static str = "scene_current";
+    const OUTGOING_ID: &'static str = "scene_outgoing";
+
+    /// Creates the stage, which shows the scene requested by the switcher first.
+    pub fn new(switcher: SceneSwitcher) -> Self {
+        Self {
+            switcher,
+            incoming: None,
+            transition: None,
+            width: 0.0,
+            height: 0.0,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the theme, applied by the next change of the view.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn switcher(&self) -> &SceneSwitcher {
+        &self.switcher
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn progress(&self) -> Real {
+        match self.transition {
+            Some((transition, elapsed)) if transition.duration() > elapsed => {
+                elapsed.as_secs_f32() / transition.duration().as_secs_f32()
+            },
+            _ => 1.0,
+        }
+    }
+
+    fn scene_group(id: &str) -> Node<Self> {
+        let group = Group {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+        Node::Prim(Prim::new(
+            Group::NAME.into(),
+            Shape::Group(group),
+            vec![],
+            Default::default(),
+        ))
+    }
+
+    fn background(&self) -> Option<Fill> {
+        Some(Fill::color(self.theme.color(Self::WIDGET, ThemeToken::Surface)))
+    }
+
+    fn show_incoming(&mut self, view: &mut Node<Self>) {
+        let (mut scene, transition) = match self.incoming.take() {
+            Some(incoming) => incoming,
+            None => return,
+        };
+
+        if self.width > 0.0 || self.height > 0.0 {
+            scene.send_system_msg(SystemMessage::WindowResized {
+                width: self.width as u32,
+                height: self.height as u32,
+            });
+        }
+
+        let outgoing = match view.get_prim_mut(Self::CURRENT_ID) {
+            Some(current) => {
+                current.invalidate_layout();
+                mem::replace(&mut current.children, vec![Node::Comp(scene)])
+            },
+            None => return,
+        };
+        if let Some(prim) = view.get_prim_mut(Self::OUTGOING_ID) {
+            prim.invalidate_layout();
+            // The scene still fading out from the previous change is replaced
+            let mut retired = mem::take(&mut prim.children);
+            if let SceneTransition::Cut = transition {
+                self.transition = None;
+                retired.extend(outgoing);
+            } else {
+                prim.children = outgoing;
+                self.transition = Some((transition, Duration::default()));
+            }
+            self.switcher.retire(retired);
+        }
+    }
+
+    fn apply_transition(&mut self, view: &mut Node<Self>) {
+        let progress = self.progress();
+        let transition = self.transition.map(|(transition, _)| transition);
+        let (current, outgoing) = match transition {
+            Some(SceneTransition::Fade(_)) => ((1.0 - progress, 0.0, 0.0), (progress, 0.0, 0.0)),
+            Some(SceneTransition::Slide(direction, _)) => {
+                let (x, y) = direction.unit();
+                let (x, y) = (x * self.width, y * self.height);
+                (
+                    (0.0, -x * (1.0 - progress), -y * (1.0 - progress)),
+                    (0.0, x * progress, y * progress),
+                )
+            },
+            _ => ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0)),
+        };
+
+        for &(id, (transparency, x, y)) in &[(Self::CURRENT_ID, current), (Self::OUTGOING_ID, outgoing)] {
+            if let Some(prim) = view.get_prim_mut(id) {
+                *prim.transform_mut() = Transform::new().with_translation(x, y);
+                if let Some(group) = prim.shape_mut().group_mut() {
+                    group.transparency = Some(transparency);
+                }
+            }
+        }
+
+        if progress >= 1.0 {
+            self.transition = None;
+            if let Some(prim) = view.get_prim_mut(Self::OUTGOING_ID) {
+                self.switcher.retire(mem::take(&mut prim.children));
+            }
+        }
+    }
+}
+
+impl Model for SceneStage {
+    type Message = SceneMsg;
+    type Properties = SceneSwitcher;
+
+    fn create(switcher: Self::Properties) -> Self {
+        Self::new(switcher)
+    }
+
+    fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+        match msg {
+            SystemMessage::WindowResized { width, height } => Some(SceneMsg::Resize {
+                width: width as Real,
+                height: height as Real,
+            }),
+            SystemMessage::Draw(elapsed) => Some(SceneMsg::Draw(elapsed)),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ChangeView {
+        match msg {
+            SceneMsg::Resize { width, height } => {
+                self.width = width;
+                self.height = height;
+                ChangeView::None
+            },
+            SceneMsg::Draw(elapsed) => {
+                if let Some(incoming) = self.switcher.take() {
+                    self.incoming = Some(incoming);
+                    ChangeView::Modify
+                } else if let Some((_, transition_elapsed)) = self.transition.as_mut() {
+                    *transition_elapsed += elapsed;
+                    ChangeView::Modify
+                } else {
+                    ChangeView::None
+                }
+            },
+        }
+    }
+
+    fn build_view(&self) -> Node<Self> {
+        let root = Rect {
+            id: Some(Self::STAGE_ID.to_string()),
+            width: Pct(100.0).into(),
+            height: Pct(100.0).into(),
+            fill: self.background(),
+            ..Default::default()
+        };
+        Node::Prim(Prim::new(
+            Rect::NAME.into(),
+            Shape::Rect(root),
+            vec![
+                Self::scene_group(Self::OUTGOING_ID),
+                Self::scene_group(Self::CURRENT_ID),
+            ],
+            Default::default(),
+        ))
+    }
+
+    fn modify_view(&mut self, view: &mut Node<Self>) {
+        if let Some(root) = view.get_prim_mut(Self::STAGE_ID) {
+            root.set_fill(self.background());
+        }
+        self.show_incoming(view);
+        self.apply_transition(view);
+    }
+}
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use crate::{InputEvent, MousePos, MouseScroll, Real, ScrollSource};
+
+/// The smooth scroll offset of a scroll view. The wheel notches glide to their
+/// target instead of jumping by the whole lines, the touchpad scrolls follow
+/// the fingers, and the touch flings keep moving with the decaying velocity
+/// after the finger is lifted. The offset is advanced by `animate` from
+/// `SystemMessage::Draw` and is kept between zero and the max offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollPhysics {
+    offset: (Real, Real),
+    target: (Real, Real),
+    velocity: (Real, Real),
+    max: (Real, Real),
+    line_height: Real,
+    friction: Real,
+    touch: Option<(u64, MousePos)>,
+    touch_moved: (Real, Real),
+}
+
+impl ScrollPhysics {
+    /// The time constant of the glide to the wheel target, in seconds.
+    const GLIDE_TIME: Real = 0.08;
+    /// The fling speed in pixels per second below which it stops.
+    const MIN_VELOCITY: Real = 10.0;
+    /// The distance to the target in pixels at which the glide snaps to it.
+    const SNAP_DISTANCE: Real = 0.5;
+
+    pub fn new(max: (Real, Real)) -> Self {
+        Self {
+            offset: (0.0, 0.0),
+            target: (0.0, 0.0),
+            velocity: (0.0, 0.0),
+            max,
+            line_height: MouseScroll::LINE_PIXELS,
+            friction: 4.0,
+            touch: None,
+            touch_moved: (0.0, 0.0),
+        }
+    }
+
+    /// Sets the pixels scrolled by one wheel line.
+    pub fn with_line_height(mut self, line_height: Real) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Sets the decay rate of the fling velocity per second, 4 by default.
+    pub fn with_friction(mut self, friction: Real) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn offset(&self) -> (Real, Real) {
+        self.offset
+    }
+
+    /// Scrolls to the offset at once, stopping the glide and the fling.
+    pub fn set_offset(&mut self, offset: (Real, Real)) {
+        self.offset = self.clamp(offset);
+        self.target = self.offset;
+        self.velocity = (0.0, 0.0);
+    }
+
+    pub fn max(&self) -> (Real, Real) {
+        self.max
+    }
+
+    /// Sets the max offset, e.g. when the content or the viewport is resized.
+    pub fn set_max(&mut self, max: (Real, Real)) {
+        self.max = max;
+        self.offset = self.clamp(self.offset);
+        self.target = self.clamp(self.target);
+    }
+
+    /// Checks the offset is still moving to the target or by the fling.
+    pub fn is_transient(&self) -> bool {
+        self.offset != self.target || self.velocity != (0.0, 0.0)
+    }
+
+    /// Integrates the scroll and touch events over the view. Returns `true` if
+    /// the event is used.
+    pub fn handle(&mut self, event: &InputEvent) -> bool {
+        match event {
+            InputEvent::MouseScroll(scroll) => self.scroll(scroll),
+            InputEvent::TouchStart(touch) if self.touch.is_none() => {
+                self.touch = Some((touch.id, touch.pos));
+                self.touch_moved = (0.0, 0.0);
+                self.velocity = (0.0, 0.0);
+                self.target = self.offset;
+            },
+            InputEvent::TouchMove(touch) => match self.touch {
+                Some((id, pos)) if id == touch.id => {
+                    let delta = (pos.x - touch.pos.x, pos.y - touch.pos.y);
+                    self.touch = Some((id, touch.pos));
+                    self.touch_moved.0 += delta.0;
+                    self.touch_moved.1 += delta.1;
+                    self.move_by(delta);
+                },
+                _ => return false,
+            },
+            InputEvent::TouchEnd(touch) => match self.touch {
+                Some((id, _)) if id == touch.id => self.touch = None,
+                _ => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn scroll(&mut self, scroll: &MouseScroll) {
+        if scroll.source == ScrollSource::Touchpad {
+            self.velocity = (0.0, 0.0);
+            self.move_by((-scroll.raw_delta.0, -scroll.raw_delta.1));
+        } else {
+            let target = (
+                self.target.0 - scroll.delta.0 * self.line_height,
+                self.target.1 - scroll.delta.1 * self.line_height,
+            );
+            self.velocity = (0.0, 0.0);
+            self.target = self.clamp(target);
+        }
+    }
+
+    fn move_by(&mut self, delta: (Real, Real)) {
+        self.offset = self.clamp((self.offset.0 + delta.0, self.offset.1 + delta.1));
+        self.target = self.offset;
+    }
+
+    /// Advances the glide and the fling by the frame time. Returns `true` if the offset is changed.
+    pub fn animate(&mut self, elapsed: Duration) -> bool {
+        let dt = elapsed.as_secs_f32();
+        if dt <= 0.0 {
+            return false;
+        }
+        let before = self.offset;
+
+        if self.touch.is_some() {
+            // The velocity of the finger, the fling starts with it.
+            self.velocity = (self.touch_moved.0 / dt, self.touch_moved.1 / dt);
+            self.touch_moved = (0.0, 0.0);
+        } else if self.velocity != (0.0, 0.0) {
+            let target = (
+                self.target.0 + self.velocity.0 * dt,
+                self.target.1 + self.velocity.1 * dt,
+            );
+            self.target = self.clamp(target);
+            let decay = (-self.friction * dt).exp();
+            self.velocity = (
+                stop_at_bound(self.velocity.0 * decay, target.0, self.target.0),
+                stop_at_bound(self.velocity.1 * decay, target.1, self.target.1),
+            );
+            self.offset = self.target;
+        }
+
+        let glide = 1.0 - (-dt / Self::GLIDE_TIME).exp();
+        self.offset = (
+            approach(self.offset.0, self.target.0, glide),
+            approach(self.offset.1, self.target.1, glide),
+        );
+        self.offset != before
+    }
+
+    fn clamp(&self, offset: (Real, Real)) -> (Real, Real) {
+        (
+            offset.0.max(0.0).min(self.max.0.max(0.0)),
+            offset.1.max(0.0).min(self.max.1.max(0.0)),
+        )
+    }
+}
+
+fn stop_at_bound(velocity: Real, unclamped: Real, clamped: Real) -> Real {
+    if unclamped != clamped || velocity.abs() < ScrollPhysics::MIN_VELOCITY {
+        0.0
+    } else {
+        velocity
+    }
+}
+
+fn approach(current: Real, target: Real, glide: Real) -> Real {
+    let next = current + (target - current) * glide;
+    if (target - next).abs() < ScrollPhysics::SNAP_DISTANCE {
+        target
+    } else {
+        next
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    any::Any,
+    cell::{RefCell, RefMut},
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+/// The event sent to a component from outside of the event loop, delivered to
+/// `Model::system_update` as `SystemMessage::Custom`.
+#[derive(Clone)]
+pub struct CustomEvent(Arc<dyn Any + Send + Sync>);
+
+impl CustomEvent {
+    pub fn new(event: impl Any + Send + Sync) -> Self {
+        Self(Arc::new(event))
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl fmt::Debug for CustomEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomEvent(..)")
+    }
+}
+
+impl PartialEq for CustomEvent {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The shared function waking the event loop of the controller, see `Comp::set_waker`.
+pub(crate) type WakeFn = Arc<Mutex<Option<Box<dyn Fn() + Send>>>>;
+
+/// The handle to send the values to a component from the other threads, e.g. the
+/// progress of the background jobs, the network or the file watchers events. The
+/// `CompSender<M::Message>` of `Comp::message_sender` passes the messages to the
+/// `update` of the model, the default one of `Comp::sender` passes the custom
+/// events to `Model::system_update`. Each sent value wakes the event loop of the
+/// controller, which passes it to the component.
+pub struct CompSender<T = CustomEvent> {
+    sender: Sender<Letter>,
+    waker: WakeFn,
+    _value: PhantomData<fn(T)>,
+}
+
+impl<T: Send + 'static> CompSender<T> {
+    /// Sends the value, returns `false` if the component is dropped.
+    pub fn send(&self, value: T) -> bool {
+        if self.sender.send(Box::new(value)).is_err() {
+            return false;
+        }
+        if let Some(wake) = self.waker.lock().unwrap().as_ref() {
+            wake();
+        }
+        true
+    }
+}
+
+impl<T> Clone for CompSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for CompSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CompSender")
+    }
+}
+
+type Letter = Box<dyn Any + Send>;
+
+/// The receiving side of the component senders.
+pub(crate) struct Mailbox {
+    sender: Sender<Letter>,
+    receiver: Receiver<Letter>,
+    pending: RefCell<VecDeque<Letter>>,
+    waker: WakeFn,
+}
+
+impl Mailbox {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            pending: Default::default(),
+            waker: Default::default(),
+        }
+    }
+
+    pub(crate) fn sender<T>(&self) -> CompSender<T> {
+        CompSender {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+            _value: PhantomData,
+        }
+    }
+
+    pub(crate) fn set_waker(&self, wake: Box<dyn Fn() + Send>) {
+        *self.waker.lock().unwrap() = Some(wake);
+    }
+
+    pub(crate) fn waker(&self) -> WakeFn {
+        self.waker.clone()
+    }
+
+    pub(crate) fn try_recv(&self) -> Option<Letter> {
+        let letter = self.pending.borrow_mut().pop_front();
+        letter.or_else(|| self.receiver.try_recv().ok())
+    }
+
+    /// The letters not received yet, moved to the queue to be inspected.
+    pub(crate) fn pending(&self) -> RefMut<'_, VecDeque<Letter>> {
+        let mut pending = self.pending.borrow_mut();
+        pending.extend(self.receiver.try_iter());
+        pending
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{ChangeView, Comp, Model};
+
+/// The model state persisted between the runs, e.g. the level progress of a game.
+/// Written in any serde format, e.g. by `serde_json::to_string(&comp.save_state::<M>())`.
+pub trait SaveState: Model {
+    type State: Serialize;
+
+    fn save_state(&self) -> Self::State;
+}
+
+/// The model state restored from the previous run, e.g. by
+/// `comp.load_state::<M>(serde_json::from_str(&saved)?)`.
+pub trait LoadState: Model {
+    type State: DeserializeOwned;
+
+    fn load_state(&mut self, state: Self::State) -> ChangeView;
+}
+
+impl Comp {
+    pub fn save_state<M: SaveState>(&self) -> M::State {
+        self.model::<M>().save_state()
+    }
+
+    /// Restores the model state and updates the view as `LoadState::load_state` returns.
+    pub fn load_state<M: LoadState>(&mut self, state: M::State) {
+        let change = self.model_mut::<M>().load_state(state);
+        self.inner_mut::<M>().change_view(change);
+    }
+}
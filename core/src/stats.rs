@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use crate::{Comp, CompositeShape, Real, SystemMessage};
+
+/// The performance of the frames since the previous stats, for the HUDs.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    pub fps: Real,
+    /// The average time of a frame.
+    pub frame_time: Duration,
+    /// The number of the prims in the tree.
+    pub prim_count: usize,
+    /// The number of the draw calls of the last rendered frame, see `Render::draw_calls`.
+    pub draw_calls: usize,
+}
+
+/// Gathers the frames and sends `SystemMessage::Stats` to the root component periodically.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatsCounter {
+    interval: Duration,
+    elapsed: Duration,
+    frames: u32,
+    draw_calls: usize,
+}
+
+impl StatsCounter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            elapsed: Duration::default(),
+            frames: 0,
+            draw_calls: 0,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Counts the frame with the draw calls, if it is rendered. Returns the stats
+    /// of the frames since the previous ones once the interval is passed, without
+    /// the prim count.
+    pub fn frame(&mut self, elapsed: Duration, draw_calls: Option<usize>) -> Option<FrameStats> {
+        self.elapsed += elapsed;
+        self.frames += 1;
+        if let Some(draw_calls) = draw_calls {
+            self.draw_calls = draw_calls;
+        }
+        if self.elapsed < self.interval || self.elapsed == Duration::default() {
+            return None;
+        }
+
+        let stats = FrameStats {
+            fps: self.frames as Real / self.elapsed.as_secs_f32(),
+            frame_time: self.elapsed / self.frames,
+            prim_count: 0,
+            draw_calls: self.draw_calls,
+        };
+        self.elapsed = Duration::default();
+        self.frames = 0;
+        Some(stats)
+    }
+
+    /// Counts the frame and sends the stats with the prim count of the component when they are due.
+    pub fn frame_comp(&mut self, comp: &mut Comp, elapsed: Duration, draw_calls: Option<usize>) {
+        if let Some(stats) = self.frame(elapsed, draw_calls) {
+            let prim_count = count_prims(comp);
+            comp.send_system_msg(SystemMessage::Stats(FrameStats { prim_count, ..stats }));
+        }
+    }
+}
+
+/// The number of the prims in the tree of the shape, including the nested components.
+pub fn count_prims(shape: &dyn CompositeShape) -> usize {
+    let own = shape.shape().is_some() as usize;
+    own + shape.children().into_iter().flatten().map(count_prims).sum::<usize>()
+}
@@ -0,0 +1,460 @@
+use std::fmt::Write;
+
+use crate::{
+    collect_overlays, AlignHor, AlignVer, Clip, Color, CompositeShape, Fill, Gradient, LineCap, LineJoin, Paint,
+    PathCommand, Real, Rounding, Shape, Stroke, Transform,
+};
+
+/// Converts the view to an SVG document. The view should be laid out by the
+/// renderer before, so the percent and auto values and the transforms are resolved.
+pub fn view_to_svg(view: &dyn CompositeShape) -> String {
+    let mut writer = SvgWriter::default();
+    writer.write_composite(view, Inherited::default());
+    let mut overlays = vec![];
+    collect_overlays(view, &mut overlays);
+    for overlay in overlays {
+        writer.write_composite(overlay, Inherited::default());
+    }
+
+    let mut svg = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg""#);
+    if let Some(bound) = view.layout().map(|layout| layout.parent_bound) {
+        let _ = write!(
+            svg,
+            r#" width="{w}" height="{h}" viewBox="0 0 {w} {h}""#,
+            w = bound.width(),
+            h = bound.height()
+        );
+    }
+    svg.push_str(">\n");
+    if !writer.defs.is_empty() {
+        let _ = write!(svg, "<defs>\n{}</defs>\n", writer.defs);
+    }
+    svg.push_str(&writer.body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The fill, stroke, transparency and clip set by the groups for their children.
+#[derive(Debug, Default, Clone, Copy)]
+struct Inherited {
+    transparency: Real,
+    fill: Option<Fill>,
+    stroke: Option<Stroke>,
+    clip: Clip,
+}
+
+#[derive(Default)]
+struct SvgWriter {
+    defs: String,
+    body: String,
+    next_id: usize,
+}
+
+impl SvgWriter {
+    fn write_composite(&mut self, composite: &dyn CompositeShape, mut inherited: Inherited) {
+        let mut is_group = false;
+        if let Some(shape) = composite.shape() {
+            if let Shape::Group(group) = shape {
+                if let Some(transparency) = group.transparency {
+                    inherited.transparency = transparency;
+                }
+                inherited.fill = group.fill.or(inherited.fill);
+                inherited.stroke = group.stroke.or(inherited.stroke);
+                inherited.clip = group.clip.or(inherited.clip);
+
+                is_group = true;
+                self.body.push_str("<g");
+                self.write_id(group.id());
+                self.body.push_str(">\n");
+            } else {
+                self.write_shape(shape, &inherited);
+            }
+        }
+
+        if let Some(children) = composite.children() {
+            let overflow_clip_id = composite.overflow_clip().and_then(|clip| self.clip_id(&clip));
+            if let Some(clip_id) = &overflow_clip_id {
+                let _ = writeln!(self.body, r#"<g clip-path="url(#{})">"#, clip_id);
+            }
+            for child in children.filter(|child| !child.is_overlay()) {
+                self.write_composite(child, inherited);
+            }
+            if overflow_clip_id.is_some() {
+                self.body.push_str("</g>\n");
+            }
+        }
+        if let Some(decorations) = composite.decorations() {
+            for (_, decoration) in decorations.filter(|(_, decoration)| !decoration.is_overlay()) {
+                self.write_composite(decoration, inherited);
+            }
+        }
+
+        if is_group {
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    fn write_shape(&mut self, shape: &Shape, inherited: &Inherited) {
+        let (transparency, clip, stroke, fill, transform) = match shape {
+            Shape::Rect(rect) => (rect.transparency, rect.clip, rect.stroke, rect.fill, &rect.transform),
+            Shape::Circle(circle) => (
+                circle.transparency,
+                circle.clip,
+                circle.stroke,
+                circle.fill,
+                &circle.transform,
+            ),
+            Shape::Path(path) => (path.transparency, path.clip, path.stroke, path.fill, &path.transform),
+            Shape::Text(text) => (text.transparency, text.clip, text.stroke, text.fill, &text.transform),
+            Shape::TileMap(tile_map) => (
+                tile_map.transparency,
+                tile_map.clip,
+                tile_map.stroke,
+                tile_map.fill,
+                &tile_map.transform,
+            ),
+            Shape::Group(_) => return,
+        };
+
+        let clip_id = self.clip_id(&clip.or(inherited.clip));
+        if let Some(clip_id) = &clip_id {
+            let _ = writeln!(self.body, r#"<g clip-path="url(#{})">"#, clip_id);
+        }
+
+        let transparency = if transparency != 0.0 {
+            transparency
+        } else {
+            inherited.transparency
+        };
+        let mut attrs = String::new();
+        if let Some(id) = shape.id() {
+            let _ = write!(attrs, r#" id="{}""#, escape(id));
+        }
+        attrs.push_str(&transform_attr(transform));
+        if transparency != 0.0 {
+            let _ = write!(attrs, r#" opacity="{}""#, 1.0 - transparency);
+        }
+
+        let fill = fill.or(inherited.fill);
+        let stroke = stroke.or(inherited.stroke);
+        match shape {
+            Shape::TileMap(tile_map) => {
+                let _ = writeln!(self.body, "<g{}>", attrs);
+                for layer in tile_map.layers.iter().filter(|layer| layer.visible) {
+                    for (tile_id, origins) in tile_map.batches(layer, 0..tile_map.rows, 0..tile_map.cols) {
+                        let tile = match tile_map.atlas.get(tile_id) {
+                            Some(tile) => tile,
+                            None => continue,
+                        };
+
+                        let paint = self.paint_attrs(tile.fill.or(fill), tile.stroke.or(stroke));
+                        let data = PathCommand::to_svg(&tile.cmd);
+                        for [x, y] in origins {
+                            let _ = writeln!(
+                                self.body,
+                                r#"<path transform="translate({} {})" d="{}"{}/>"#,
+                                x, y, data, paint
+                            );
+                        }
+                    }
+                }
+                self.body.push_str("</g>\n");
+            },
+            _ => {
+                attrs.push_str(&self.paint_attrs(fill, stroke));
+                self.write_element(shape, &attrs);
+            },
+        }
+
+        if clip_id.is_some() {
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    fn write_element(&mut self, shape: &Shape, attrs: &str) {
+        let _ = match shape {
+            Shape::Rect(rect) => {
+                let (x, y, width, height) = (rect.x.val(), rect.y.val(), rect.width.val(), rect.height.val());
+                match rect.rounding {
+                    Some(rounding) if is_uniform(&rounding) => writeln!(
+                        self.body,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}/>"#,
+                        x,
+                        y,
+                        width,
+                        height,
+                        rounding.top_left.val(),
+                        attrs
+                    ),
+                    Some(rounding) => writeln!(
+                        self.body,
+                        r#"<path d="{}"{}/>"#,
+                        rounded_rect_data(x, y, width, height, &rounding),
+                        attrs
+                    ),
+                    None => writeln!(
+                        self.body,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                        x, y, width, height, attrs
+                    ),
+                }
+            },
+            Shape::Circle(circle) => writeln!(
+                self.body,
+                r#"<circle cx="{}" cy="{}" r="{}"{}/>"#,
+                circle.cx.val(),
+                circle.cy.val(),
+                circle.r.val(),
+                attrs
+            ),
+            Shape::Path(path) => writeln!(self.body, r#"<path d="{}"{}/>"#, PathCommand::to_svg(&path.cmd), attrs),
+            Shape::Text(text) => {
+                let anchor = match text.align.0 {
+                    AlignHor::Left => "start",
+                    AlignHor::Center => "middle",
+                    AlignHor::Right => "end",
+                };
+                let baseline = match text.align.1 {
+                    AlignVer::Top => "hanging",
+                    AlignVer::Middle => "middle",
+                    AlignVer::Baseline => "alphabetic",
+                    AlignVer::Bottom => "text-after-edge",
+                };
+                let content = if text.wrapped_lines.is_empty() {
+                    escape(&text.content)
+                } else {
+                    let line_height = text.metrics.map_or(0.0, |metrics| metrics.line_height);
+                    text.lines()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, line)| {
+                            format!(
+                                r#"<tspan x="{}" y="{}">{}</tspan>"#,
+                                text.x.val(),
+                                text.y.val() + idx as Real * line_height,
+                                escape(line)
+                            )
+                        })
+                        .collect()
+                };
+                writeln!(
+                    self.body,
+                    r#"<text x="{}" y="{}" font-family="{}" font-size="{}" text-anchor="{}" dominant-baseline="{}"{}>{}</text>"#,
+                    text.x.val(),
+                    text.y.val(),
+                    escape(&text.font_name),
+                    text.font_size.val(),
+                    anchor,
+                    baseline,
+                    attrs,
+                    content
+                )
+            },
+            Shape::Group(_) | Shape::TileMap(_) => Ok(()),
+        };
+    }
+
+    fn write_id(&mut self, id: Option<&str>) {
+        if let Some(id) = id {
+            let _ = write!(self.body, r#" id="{}""#, escape(id));
+        }
+    }
+
+    fn new_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    fn clip_id(&mut self, clip: &Clip) -> Option<String> {
+        let scissor = clip.scissor()?;
+        let id = self.new_id("clip");
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="{}"><rect x="{}" y="{}" width="{}" height="{}"{}/></clipPath>"#,
+            id,
+            scissor.x.val(),
+            scissor.y.val(),
+            scissor.width.val(),
+            scissor.height.val(),
+            transform_attr(&scissor.transform)
+        );
+        Some(id)
+    }
+
+    /// The fill and stroke attributes, `fill="none"` without fill.
+    fn paint_attrs(&mut self, fill: Option<Fill>, stroke: Option<Stroke>) -> String {
+        let mut attrs = match fill {
+            Some(fill) => self.paint_attr("fill", fill.paint),
+            None => String::from(r#" fill="none""#),
+        };
+
+        if let Some(stroke) = stroke {
+            attrs.push_str(&self.paint_attr("stroke", stroke.paint));
+            let line_cap = match stroke.line_cap {
+                LineCap::Butt => "butt",
+                LineCap::Round => "round",
+                LineCap::Square => "square",
+            };
+            let line_join = match stroke.line_join {
+                LineJoin::Miter => "miter",
+                LineJoin::Round => "round",
+                LineJoin::Bevel => "bevel",
+            };
+            let _ = write!(
+                attrs,
+                r#" stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}""#,
+                stroke.width, line_cap, line_join, stroke.miter_limit
+            );
+        }
+        attrs
+    }
+
+    fn paint_attr(&mut self, name: &str, paint: Paint) -> String {
+        match paint {
+            Paint::Color(color) => {
+                let (rgb, alpha) = color_values(color);
+                if alpha < 1.0 {
+                    format!(r#" {0}="{1}" {0}-opacity="{2}""#, name, rgb, alpha)
+                } else {
+                    format!(r#" {}="{}""#, name, rgb)
+                }
+            },
+            Paint::Gradient(gradient) => format!(r#" {}="url(#{})""#, name, self.gradient_id(gradient)),
+        }
+    }
+
+    /// Defines the gradient. The box gradient has no SVG counterpart and is
+    /// approximated by the radial one.
+    fn gradient_id(&mut self, gradient: Gradient) -> String {
+        let id = self.new_id("gradient");
+        let (start_color, end_color) = match gradient {
+            Gradient::Linear {
+                start,
+                end,
+                start_color,
+                end_color,
+            } => {
+                let _ = write!(
+                    self.defs,
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+                    id, start.0, start.1, end.0, end.1
+                );
+                (start_color, end_color)
+            },
+            Gradient::Box {
+                position,
+                size,
+                radius,
+                feather,
+                start_color,
+                end_color,
+            } => {
+                let r = size.0.max(size.1) / 2.0 + feather.max(radius) / 2.0;
+                let _ = write!(
+                    self.defs,
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}">"#,
+                    id,
+                    position.0 + size.0 / 2.0,
+                    position.1 + size.1 / 2.0,
+                    r
+                );
+                (start_color, end_color)
+            },
+            Gradient::Radial {
+                center,
+                inner_radius,
+                outer_radius,
+                start_color,
+                end_color,
+            } => {
+                let _ = write!(
+                    self.defs,
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}" fr="{}">"#,
+                    id, center.0, center.1, outer_radius, inner_radius
+                );
+                (start_color, end_color)
+            },
+        };
+
+        for (offset, color) in [(0, start_color), (1, end_color)].iter() {
+            let (rgb, alpha) = color_values(*color);
+            let _ = write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                offset, rgb, alpha
+            );
+        }
+        self.defs.push_str(match gradient {
+            Gradient::Linear { .. } => "</linearGradient>\n",
+            _ => "</radialGradient>\n",
+        });
+        id
+    }
+}
+
+fn transform_attr(transform: &Transform) -> String {
+    if transform.is_not_exist() {
+        return String::new();
+    }
+
+    let matrix = transform.calculated_matrix().unwrap_or_else(|| transform.matrix());
+    if matrix.is_identity() {
+        String::new()
+    } else {
+        let [a, b, c, d, e, f] = matrix.matrix;
+        format!(r#" transform="matrix({} {} {} {} {} {})""#, a, b, c, d, e, f)
+    }
+}
+
+fn color_values(color: Color) -> (String, Real) {
+    let [r, g, b, a] = color.as_arr();
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b)), a)
+}
+
+fn is_uniform(rounding: &Rounding) -> bool {
+    let radius = rounding.top_left.val();
+    rounding.top_right.val() == radius && rounding.bottom_left.val() == radius && rounding.bottom_right.val() == radius
+}
+
+fn rounded_rect_data(x: Real, y: Real, width: Real, height: Real, rounding: &Rounding) -> String {
+    let (tl, tr) = (rounding.top_left.val(), rounding.top_right.val());
+    let (bl, br) = (rounding.bottom_left.val(), rounding.bottom_right.val());
+    let (right, bottom) = (x + width, y + height);
+    format!(
+        "M {} {} H {} A {tr} {tr} 0 0 1 {} {} V {} A {br} {br} 0 0 1 {} {} H {} A {bl} {bl} 0 0 1 {} {} V {} A {tl} {tl} 0 0 1 {} {} Z",
+        x + tl,
+        y,
+        right - tr,
+        right,
+        y + tr,
+        bottom - br,
+        right - br,
+        bottom,
+        x + bl,
+        x,
+        bottom - bl,
+        y + tl,
+        x + tl,
+        y,
+        tl = tl,
+        tr = tr,
+        bl = bl,
+        br = br
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use crate::{Color, Real};
+
+/// The named colors of the theme, consumed by the built-in widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeToken {
+    /// The highlighted parts: active items, knobs, the remote cursor labels.
+    Accent,
+    /// The background of the widgets.
+    Surface,
+    Border,
+    TextPrimary,
+    TextSecondary,
+    /// The text over the accent color.
+    TextOnAccent,
+    /// The text cursor.
+    Caret,
+    /// The background of the selected text and items.
+    Selection,
+    /// The outline of the focused widget.
+    FocusRing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub accent: Color,
+    pub surface: Color,
+    pub border: Color,
+    pub text_primary: Color,
+    pub text_secondary: Color,
+    pub text_on_accent: Color,
+    pub caret: Color,
+    pub selection: Color,
+    pub focus_ring: Color,
+}
+
+impl Palette {
+    pub fn light() -> Self {
+        Self {
+            accent: Color::RGB(0.2, 0.45, 0.85),
+            surface: Color::RGB(0.97, 0.97, 0.97),
+            border: Color::RGB(0.75, 0.75, 0.75),
+            text_primary: Color::RGB(0.1, 0.1, 0.1),
+            text_secondary: Color::RGB(0.4, 0.4, 0.4),
+            text_on_accent: Color::White,
+            caret: Color::RGB(0.1, 0.1, 0.1),
+            selection: Color::RGBA(0.2, 0.45, 0.85, 0.3),
+            focus_ring: Color::RGBA(0.2, 0.45, 0.85, 0.6),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            accent: Color::RGB(0.35, 0.6, 1.0),
+            surface: Color::RGB(0.15, 0.15, 0.16),
+            border: Color::RGB(0.35, 0.35, 0.37),
+            text_primary: Color::RGB(0.92, 0.92, 0.92),
+            text_secondary: Color::RGB(0.65, 0.65, 0.65),
+            text_on_accent: Color::White,
+            caret: Color::RGB(0.92, 0.92, 0.92),
+            selection: Color::RGBA(0.35, 0.6, 1.0, 0.35),
+            focus_ring: Color::RGBA(0.35, 0.6, 1.0, 0.7),
+        }
+    }
+
+    pub fn get(&self, token: ThemeToken) -> Color {
+        match token {
+            ThemeToken::Accent => self.accent,
+            ThemeToken::Surface => self.surface,
+            ThemeToken::Border => self.border,
+            ThemeToken::TextPrimary => self.text_primary,
+            ThemeToken::TextSecondary => self.text_secondary,
+            ThemeToken::TextOnAccent => self.text_on_accent,
+            ThemeToken::Caret => self.caret,
+            ThemeToken::Selection => self.selection,
+            ThemeToken::FocusRing => self.focus_ring,
+        }
+    }
+
+    pub fn set(&mut self, token: ThemeToken, color: Color) {
+        let slot = match token {
+            ThemeToken::Accent => &mut self.accent,
+            ThemeToken::Surface => &mut self.surface,
+            ThemeToken::Border => &mut self.border,
+            ThemeToken::TextPrimary => &mut self.text_primary,
+            ThemeToken::TextSecondary => &mut self.text_secondary,
+            ThemeToken::TextOnAccent => &mut self.text_on_accent,
+            ThemeToken::Caret => &mut self.caret,
+            ThemeToken::Selection => &mut self.selection,
+            ThemeToken::FocusRing => &mut self.focus_ring,
+        };
+        *slot = color;
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+/// The light or dark appearance preferred by the OS, delivered by the
+/// `SystemMessage::ThemeChanged` where the platform reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemTheme {
+    Light,
+    Dark,
+}
+
+impl SystemTheme {
+    /// The built-in palette matching the appearance.
+    pub fn palette(&self) -> Palette {
+        match self {
+            SystemTheme::Light => Palette::light(),
+            SystemTheme::Dark => Palette::dark(),
+        }
+    }
+}
+
+/// The size preset of the built-in widgets: the compact one for dense desktop
+/// UI and the touch one for fingers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+    Touch,
+}
+
+impl Density {
+    pub fn spacing_scale(&self) -> Real {
+        match self {
+            Density::Compact => 0.75,
+            Density::Comfortable => 1.0,
+            Density::Touch => 1.5,
+        }
+    }
+
+    pub fn font_scale(&self) -> Real {
+        match self {
+            Density::Compact => 0.9,
+            Density::Comfortable => 1.0,
+            Density::Touch => 1.15,
+        }
+    }
+
+    /// The min width and height of the interactive parts.
+    pub fn min_hit_target(&self) -> Real {
+        match self {
+            Density::Compact => 24.0,
+            Density::Comfortable => 32.0,
+            Density::Touch => 44.0,
+        }
+    }
+}
+
+/// The palette shared by the built-in widgets with the per-widget overrides.
+/// The widgets look up their colors by their name (e.g. `RemoteCursors::WIDGET`),
+/// so the palette change restyles all the widgets which have no override.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+    pub density: Density,
+    overrides: HashMap<String, HashMap<ThemeToken, Color>>,
+}
+
+impl Theme {
+    pub fn new(palette: Palette) -> Self {
+        Self {
+            palette,
+            density: Density::default(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn with_density(mut self, density: Density) -> Self {
+        self.density = density;
+        self
+    }
+
+    pub fn with_override(mut self, widget: impl Into<String>, token: ThemeToken, color: Color) -> Self {
+        self.set_override(widget, token, color);
+        self
+    }
+
+    pub fn set_override(&mut self, widget: impl Into<String>, token: ThemeToken, color: Color) {
+        self.overrides.entry(widget.into()).or_default().insert(token, color);
+    }
+
+    /// Removes all the overrides of the widget, so it uses the palette again.
+    pub fn clear_overrides(&mut self, widget: &str) {
+        self.overrides.remove(widget);
+    }
+
+    /// The color of the token for the widget: its override or the palette color.
+    pub fn color(&self, widget: &str, token: ThemeToken) -> Color {
+        self.overrides
+            .get(widget)
+            .and_then(|overrides| overrides.get(&token))
+            .copied()
+            .unwrap_or_else(|| self.palette.get(token))
+    }
+
+    /// Scales the padding, margin or gap of the widget by the density.
+    pub fn spacing(&self, value: Real) -> Real {
+        value * self.density.spacing_scale()
+    }
+
+    pub fn font_size(&self, size: Real) -> Real {
+        size * self.density.font_scale()
+    }
+
+    /// Grows the size of the interactive part up to the min hit target of the density.
+    pub fn hit_target(&self, size: Real) -> Real {
+        size.max(self.density.min_hit_target())
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fmt, time::Duration};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct TimerId(u64);
+
+struct Timer<Msg> {
+    id: TimerId,
+    due: Duration,
+    interval: Option<Duration>,
+    trigger: Box<dyn Fn() -> Msg>,
+}
+
+/// The timers of a model, returned by `Model::timers`. The component advances
+/// them by the frame time and sends the messages of the due ones to `update`, so
+/// the timers follow the fixed step clock of the controller when it is set.
+pub struct Timers<Msg> {
+    timers: Vec<Timer<Msg>>,
+    now: Duration,
+    next_id: u64,
+}
+
+impl<Msg> Timers<Msg> {
+    pub fn new() -> Self {
+        Self {
+            timers: Vec::new(),
+            now: Duration::default(),
+            next_id: 0,
+        }
+    }
+
+    /// Sends the message once after the delay.
+    pub fn once(&mut self, delay: Duration, trigger: impl Fn() -> Msg + 'static) -> TimerId {
+        self.add(delay, None, Box::new(trigger))
+    }
+
+    /// Sends the message every interval until the timer is cancelled, or every frame if the interval is zero.
+    pub fn every(&mut self, interval: Duration, trigger: impl Fn() -> Msg + 'static) -> TimerId {
+        self.add(interval, Some(interval), Box::new(trigger))
+    }
+
+    fn add(&mut self, delay: Duration, interval: Option<Duration>, trigger: Box<dyn Fn() -> Msg>) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id,
+            due: self.now + delay,
+            interval,
+            trigger,
+        });
+        id
+    }
+
+    /// Stops the timer, returns `false` if it is already finished or cancelled.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != len
+    }
+
+    pub fn is_active(&self, id: TimerId) -> bool {
+        self.timers.iter().any(|timer| timer.id == id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Advances the time and returns the messages of the due timers in the order
+    /// they are due. The interval timer fires for each of its intervals in the time.
+    pub fn advance(&mut self, elapsed: Duration) -> Vec<Msg> {
+        self.now += elapsed;
+        let mut fired = vec![];
+        for (idx, timer) in self.timers.iter_mut().enumerate() {
+            while timer.due <= self.now {
+                fired.push((timer.due, idx));
+                match timer.interval {
+                    Some(interval) if interval > Duration::default() => timer.due += interval,
+                    _ => break,
+                }
+            }
+        }
+        fired.sort_by_key(|(due, _)| *due);
+        let messages = fired.iter().map(|(_, idx)| (self.timers[*idx].trigger)()).collect();
+
+        let now = self.now;
+        self.timers.retain(|timer| timer.due > now || timer.interval.is_some());
+        messages
+    }
+}
+
+impl<Msg> Default for Timers<Msg> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Msg> fmt::Debug for Timers<Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timers")
+            .field("now", &self.now)
+            .field("active", &self.timers.len())
+            .finish()
+    }
+}
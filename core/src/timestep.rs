@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use crate::{Comp, FixedClock, Real, SystemMessage};
+
+/// The fixed-rate update loop of the game logic, independent of the render
+/// rate. The frame time is accumulated and spent by the whole steps, each one
+/// sent as `SystemMessage::Update`, and the rest is sent as the interpolation
+/// factor of `SystemMessage::Interpolate` to draw the state between the last
+/// two updates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    clock: FixedClock,
+}
+
+impl FixedTimestep {
+    pub const DEFAULT_MAX_STEPS: u32 = 8;
+
+    pub fn new(step: Duration) -> Self {
+        Self {
+            clock: FixedClock::new(step).with_max_steps(Self::DEFAULT_MAX_STEPS),
+        }
+    }
+
+    /// Sets the max number of the steps in one frame, 8 by default. The time
+    /// beyond them is dropped, so a long frame does not stall the next ones.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.clock = self.clock.with_max_steps(max_steps);
+        self
+    }
+
+    pub fn step(&self) -> Duration {
+        self.clock.step()
+    }
+
+    pub fn clock(&self) -> &FixedClock {
+        &self.clock
+    }
+
+    /// The fraction of the step accumulated since the last update, from 0 to 1.
+    pub fn alpha(&self) -> Real {
+        if self.step() == Duration::default() {
+            return 0.0;
+        }
+        self.clock.accumulated().as_secs_f32() / self.step().as_secs_f32()
+    }
+
+    /// Accumulates the frame time and returns the number of the steps due.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.clock.advance(elapsed)
+    }
+
+    /// Sends the updates due by the frame time and the interpolation factor to the component.
+    pub fn advance_comp(&mut self, comp: &mut Comp, elapsed: Duration) {
+        for _ in 0..self.advance(elapsed) {
+            comp.send_system_msg(SystemMessage::Update(self.step()));
+        }
+        comp.send_system_msg(SystemMessage::Interpolate(self.alpha()));
+    }
+}
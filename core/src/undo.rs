@@ -0,0 +1,164 @@
+use std::fmt;
+
+use crate::{InputEvent, Model, Modifiers, VirtualKeyCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoAction {
+    Undo,
+    Redo,
+}
+
+struct Edit<Msg> {
+    redo: Msg,
+    undo: Msg,
+}
+
+/// The undo history of a model as the pairs of the messages, which make the
+/// edit and revert it. The edits recorded between `begin` and `commit` are
+/// undone at once. The messages returned by `undo` and `redo` should be applied
+/// to the model without recording them again.
+pub struct UndoStack<M: Model> {
+    done: Vec<Vec<Edit<M::Message>>>,
+    undone: Vec<Vec<Edit<M::Message>>>,
+    transaction: Option<Vec<Edit<M::Message>>>,
+    limit: Option<usize>,
+    modifiers: Modifiers,
+}
+
+impl<M: Model> UndoStack<M> {
+    pub fn new() -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            transaction: None,
+            limit: None,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    /// Sets the max number of the transactions kept, the oldest ones are dropped.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Records the edit made by the `redo` message, which the `undo` message reverts.
+    /// Outside of a transaction the edit is a transaction of its own. Clears the redo history.
+    pub fn record(&mut self, redo: M::Message, undo: M::Message) {
+        self.undone.clear();
+        let edit = Edit { redo, undo };
+        match self.transaction.as_mut() {
+            Some(transaction) => transaction.push(edit),
+            None => self.push(vec![edit]),
+        }
+    }
+
+    /// Starts the transaction, e.g. on the start of a drag or of a typed word.
+    pub fn begin(&mut self) {
+        self.commit();
+        self.transaction = Some(Vec::new());
+    }
+
+    pub fn commit(&mut self) {
+        if let Some(transaction) = self.transaction.take() {
+            if !transaction.is_empty() {
+                self.push(transaction);
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty() || self.transaction.as_ref().is_some_and(|edits| !edits.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+        self.transaction = None;
+    }
+
+    /// The shortcut of the key event, Ctrl+Z to undo and Ctrl+Y or Ctrl+Shift+Z to redo
+    /// (Cmd on macOS). Usually called from `Model::system_update` with the input events.
+    pub fn handle(&mut self, event: &InputEvent) -> Option<UndoAction> {
+        let (key, pressed) = match event {
+            InputEvent::KeyDown(event) => (event.keycode?, true),
+            InputEvent::KeyUp(event) => (event.keycode?, false),
+            _ => return None,
+        };
+        if self.modifiers.update(key, pressed) || !pressed {
+            return None;
+        }
+
+        let command = if cfg!(target_os = "macos") {
+            self.modifiers.logo
+        } else {
+            self.modifiers.ctrl
+        };
+        match key {
+            _ if !command || self.modifiers.alt => None,
+            VirtualKeyCode::Z if self.modifiers.shift => Some(UndoAction::Redo),
+            VirtualKeyCode::Z => Some(UndoAction::Undo),
+            VirtualKeyCode::Y if !self.modifiers.shift => Some(UndoAction::Redo),
+            _ => None,
+        }
+    }
+
+    fn push(&mut self, transaction: Vec<Edit<M::Message>>) {
+        self.done.push(transaction);
+        if let Some(limit) = self.limit {
+            if self.done.len() > limit {
+                self.done.drain(..self.done.len() - limit);
+            }
+        }
+    }
+}
+
+impl<M: Model> UndoStack<M>
+where
+    M::Message: Clone,
+{
+    /// The messages reverting the last transaction, in the reverse order of the edits.
+    pub fn undo(&mut self) -> Vec<M::Message> {
+        self.commit();
+        match self.done.pop() {
+            Some(transaction) => {
+                let msgs = transaction.iter().rev().map(|edit| edit.undo.clone()).collect();
+                self.undone.push(transaction);
+                msgs
+            },
+            None => Vec::new(),
+        }
+    }
+
+    /// The messages making the last undone transaction again.
+    pub fn redo(&mut self) -> Vec<M::Message> {
+        match self.undone.pop() {
+            Some(transaction) => {
+                let msgs = transaction.iter().map(|edit| edit.redo.clone()).collect();
+                self.done.push(transaction);
+                msgs
+            },
+            None => Vec::new(),
+        }
+    }
+}
+
+impl<M: Model> Default for UndoStack<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Model> fmt::Debug for UndoStack<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UndoStack")
+            .field("done", &self.done.len())
+            .field("undone", &self.undone.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
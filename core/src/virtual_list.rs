@@ -0,0 +1,227 @@
+use std::{borrow::Cow, collections::HashMap, mem, ops::Range};
+
+use crate::{
+    Fill, Group, InputEvent, Model, MouseScroll, Node, Overflow, Pct, Prim, Real, Rect, Shape, Theme, ThemeToken,
+    Transform,
+};
+
+/// The scroll state of a list of the fixed-height rows which materializes only
+/// the rows in its viewport, so the lists with a lot of rows are built and
+/// drawn as fast as the short ones.
+///
+/// The model builds the list by `build_view` (or the `virtual_list` builder)
+/// and adds an `on_mouse_scroll` listener to it, which returns a message with
+/// the `MouseScroll`. Its `update` passes the scroll to `scroll_by` and, if the
+/// offset is changed, returns `ChangeView::Modify`, so `modify_view` updates the
+/// rows by `sync_view`. An offset from a `ScrollPhysics` animated on
+/// `SystemMessage::Draw` reaches the list the same way by `set_scroll`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualList {
+    item_count: usize,
+    item_height: Real,
+    height: Real,
+    scroll: Real,
+    overscan: usize,
+    selected: Option<usize>,
+    theme: Theme,
+}
+
+impl VirtualList {
+    pub const LIST_ID: &'static str = "virtual_list";
+    pub const WIDGET: &'static str = "virtual_list";
+
+    const CONTENT_ID: &'static str = "virtual_list_content";
+    const ITEM_PREFIX: &'static str = "virtual_item_";
+    const SELECTION_ID: &'static str = "virtual_list_selection";
+
+    pub fn new(item_count: usize, item_height: Real, height: Real) -> Self {
+        Self {
+            item_count,
+            item_height,
+            height,
+            scroll: 0.0,
+            overscan: 2,
+            selected: None,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Sets the number of the rows materialized above and below the viewport.
+    pub fn with_overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Highlights the row with the `Selection` color of the theme, applied by `sync_view`.
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        self.selected = selected.filter(|idx| *idx < self.item_count);
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    pub fn set_item_count(&mut self, item_count: usize) {
+        self.item_count = item_count;
+        self.selected = self.selected.filter(|idx| *idx < item_count);
+        self.set_scroll(self.scroll);
+    }
+
+    pub fn scroll(&self) -> Real {
+        self.scroll
+    }
+
+    pub fn set_scroll(&mut self, scroll: Real) {
+        self.scroll = scroll.max(0.0).min(self.max_scroll());
+    }
+
+    pub fn max_scroll(&self) -> Real {
+        (self.item_count as Real * self.item_height - self.height).max(0.0)
+    }
+
+    /// Scrolls the list to show the row.
+    pub fn scroll_to(&mut self, idx: usize) {
+        let top = idx as Real * self.item_height;
+        if top < self.scroll {
+            self.set_scroll(top);
+        } else if top + self.item_height > self.scroll + self.height {
+            self.set_scroll(top + self.item_height - self.height);
+        }
+    }
+
+    /// The indices of the materialized rows: the visible ones and the overscan.
+    pub fn visible_range(&self) -> Range<usize> {
+        if self.item_height <= 0.0 {
+            return 0..0;
+        }
+        let first = (self.scroll / self.item_height).floor() as usize;
+        let last = ((self.scroll + self.height) / self.item_height).ceil() as usize;
+        first.saturating_sub(self.overscan)..(last + self.overscan).min(self.item_count)
+    }
+
+    pub fn item_id(idx: usize) -> String {
+        format!("{}{}", Self::ITEM_PREFIX, idx)
+    }
+
+    fn item_index(id: &str) -> Option<usize> {
+        id.strip_prefix(Self::ITEM_PREFIX)?.parse().ok()
+    }
+
+    /// Scrolls the list by the mouse wheel, one row per line.
+    /// Returns `true` if the scroll is changed.
+    pub fn scroll_by(&mut self, scroll: &MouseScroll) -> bool {
+        let prev = self.scroll;
+        self.set_scroll(self.scroll - scroll.delta.1 * self.item_height);
+        self.scroll != prev
+    }
+
+    /// Scrolls the list by the mouse wheel over it, for the input not delivered by the listeners.
+    /// Returns `true` if the scroll is changed.
+    pub fn handle<M: Model>(&mut self, view: &Node<M>, event: InputEvent) -> bool {
+        if let InputEvent::MouseScroll(scroll) = event {
+            let over_list = view.global_bounds_of(Self::LIST_ID).is_some_and(|bound| {
+                scroll.pos.x >= bound.min_x
+                    && scroll.pos.x <= bound.max_x
+                    && scroll.pos.y >= bound.min_y
+                    && scroll.pos.y <= bound.max_y
+            });
+            return over_list && self.scroll_by(&scroll);
+        }
+        false
+    }
+
+    /// Builds the list with the rows of the visible range made by the `build_item`.
+    pub fn build_view<M: Model>(&self, build_item: impl Fn(usize) -> Node<M>) -> Node<M> {
+        let rows = self.visible_range().map(|idx| self.build_row(idx, build_item(idx)));
+        let content = Group {
+            id: Some(Self::CONTENT_ID.to_string()),
+            transform: Transform::new().with_translation(0.0, -self.scroll),
+            ..Default::default()
+        };
+        let list = Rect {
+            id: Some(Self::LIST_ID.to_string()),
+            width: Pct(100.0).into(),
+            height: self.height.into(),
+            overflow: Overflow::Hidden,
+            fill: Some(Fill::color(self.theme.color(Self::WIDGET, ThemeToken::Surface))),
+            ..Default::default()
+        };
+        prim(Shape::Rect(list), vec![prim(Shape::Group(content), rows.collect())])
+    }
+
+    /// Scrolls the rows in the view built by `build_view` and materializes the
+    /// rows of the new visible range. The rows which stay visible are kept with
+    /// their state.
+    pub fn sync_view<M: Model>(&self, view: &mut Node<M>, build_item: impl Fn(usize) -> Node<M>) {
+        if let Some(list) = view.get_prim_mut(Self::LIST_ID) {
+            list.set_fill(Some(Fill::color(self.theme.color(Self::WIDGET, ThemeToken::Surface))));
+        }
+        let content = match view.get_prim_mut(Self::CONTENT_ID) {
+            Some(content) => content,
+            None => return,
+        };
+        *content.transform_mut() = Transform::new().with_translation(0.0, -self.scroll);
+
+        let mut rows: HashMap<_, _> = mem::take(&mut content.children)
+            .into_iter()
+            .filter_map(|row| Some((Self::item_index(row.get_id()?)?, row)))
+            .collect();
+        content.children = self
+            .visible_range()
+            .map(|idx| match rows.remove(&idx) {
+                Some(mut row) => {
+                    if let Node::Prim(row) = &mut row {
+                        row.children.retain(|node| node.get_id() != Some(Self::SELECTION_ID));
+                        row.children.splice(0..0, self.highlight(idx));
+                    }
+                    row
+                },
+                None => self.build_row(idx, build_item(idx)),
+            })
+            .collect();
+        content.invalidate_layout();
+    }
+
+    fn build_row<M: Model>(&self, idx: usize, item: Node<M>) -> Node<M> {
+        let row = Group {
+            id: Some(Self::item_id(idx)),
+            transform: Transform::new().with_translation(0.0, idx as Real * self.item_height),
+            ..Default::default()
+        };
+        let mut children = self.highlight(idx);
+        children.push(item);
+        prim(Shape::Group(row), children)
+    }
+
+    /// The highlight behind the item of the row if it is selected.
+    fn highlight<M: Model>(&self, idx: usize) -> Vec<Node<M>> {
+        if self.selected != Some(idx) {
+            return vec![];
+        }
+        let highlight = Rect {
+            id: Some(Self::SELECTION_ID.to_string()),
+            width: Pct(100.0).into(),
+            height: self.item_height.into(),
+            fill: Some(Fill::color(self.theme.color(Self::WIDGET, ThemeToken::Selection))),
+            ..Default::default()
+        };
+        vec![prim(Shape::Rect(highlight), vec![])]
+    }
+}
+
+fn prim<M: Model>(shape: Shape, children: Vec<Node<M>>) -> Node<M> {
+    Node::Prim(Prim::new(Cow::Borrowed(shape.name()), shape, children, HashMap::new()))
+}
@@ -1,15 +1,17 @@
 pub use engel_builder as builder;
 pub use engel_core::{self as core, *};
+pub use engel_macros::view;
 
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
 
-    use crate::{ChangeView, Model, Node, Rect, Shaped, Text};
+    use crate::{ChangeView, Model, Node, Rect, Shaped, SystemMessage, Text};
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     struct Counter(i32);
 
+    #[derive(Clone)]
     enum Msg {
         Increment,
         Decrement,
@@ -23,6 +25,16 @@ mod tests {
             Counter(prop)
         }
 
+        fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+            match msg {
+                SystemMessage::Custom(event) if event.is::<Msg>() => match event.downcast_ref() {
+                    Some(Msg::Increment) => Some(Msg::Increment),
+                    _ => Some(Msg::Decrement),
+                },
+                _ => None,
+            }
+        }
+
         fn update(&mut self, msg: Self::Message) -> ChangeView {
             match msg {
                 Msg::Increment => self.0 += 1,
@@ -47,6 +59,10 @@ mod tests {
             view.get_prim_mut("counter")
                 .map(|prim| prim.set_text(format!("{}", self.0)));
         }
+
+        fn checksum(&self) -> Option<u64> {
+            Some(crate::checksum(&self.0))
+        }
     }
 
     #[test]
@@ -99,4 +115,3122 @@ mod tests {
         });
         assert_eq!(child.children.len(), 0);
     }
+
+    #[test]
+    fn view_macro() {
+        use crate::{builder::*, view};
+
+        let items = ["a", "b"];
+        let show_rect = false;
+        let view: Node<Counter> = view! {
+            <rect id="root">
+                <text("-") />
+                for item in items.iter() {
+                    <text(*item) />
+                }
+                if show_rect {
+                    <rect />
+                } else {
+                    { text("+") }
+                }
+            </rect>
+        };
+
+        let root = view.as_prim().unwrap();
+        assert_eq!(root.id(), Some("root"));
+        let contents: Vec<_> = root
+            .children
+            .iter()
+            .map(|child| child.as_prim().unwrap().shape.text().unwrap().content.as_str())
+            .collect();
+        assert_eq!(contents, ["-", "a", "b", "+"]);
+    }
+
+    #[test]
+    fn id_index() {
+        let mut view = Counter::create(0).build_view();
+        view.build_id_index();
+
+        let index = view.as_prim().unwrap().id_index().unwrap();
+        assert_eq!(index.path("counter"), Some(&[1][..]));
+        assert!(view.get_prim_mut("counter").unwrap().set_text("1"));
+
+        view.get_prim_mut("counter").unwrap().set_id("renamed");
+        assert!(view.get_prim("counter").is_none());
+        assert_eq!(view.get_prim("renamed").unwrap().shape.text().unwrap().content, "1");
+    }
+
+    #[test]
+    fn detach_and_attach() {
+        use crate::builder::*;
+
+        let mut view = Counter::create(0).build_view();
+        view.set_id("root");
+        view.build_id_index();
+
+        let counter = view.detach("counter").unwrap();
+        assert!(view.get("counter").is_none());
+        assert_eq!(view.as_prim().unwrap().children.len(), 2);
+
+        view.attach("root", 0, rect().id("panel").build()).ok().unwrap();
+        view.attach("panel", 0, counter).ok().unwrap();
+        assert_eq!(
+            view.as_prim().unwrap().id_index().unwrap().path("counter"),
+            Some(&[0, 0][..])
+        );
+
+        assert!(view.reparent("counter", "root", usize::MAX));
+        assert_eq!(
+            view.as_prim().unwrap().children.last().unwrap().get_id(),
+            Some("counter")
+        );
+        assert!(!view.reparent("counter", "missing", 0));
+        assert!(view.get_prim("counter").is_some());
+    }
+
+    #[test]
+    fn clone_subtree() {
+        use crate::builder::*;
+
+        let mut view: Node<Counter> = rect()
+            .child(text("label").id("label"))
+            .child(cloneable_comp(Counter(5)).id("nested"))
+            .build();
+        let mut snapshot = view.try_clone().unwrap();
+
+        view.get_comp_mut("nested").unwrap().model_mut::<Counter>().0 = 6;
+        view.get_prim_mut("label").unwrap().set_text("changed");
+
+        assert_eq!(snapshot.get_comp_mut("nested").unwrap().model::<Counter>(), &Counter(5));
+        assert_eq!(
+            snapshot.get_prim("label").unwrap().shape.text().unwrap().content,
+            "label"
+        );
+
+        let plain: Node<Counter> = rect().child(comp(Counter(0))).build();
+        assert!(plain.try_clone().is_none());
+    }
+
+    #[test]
+    fn scene_transition() {
+        use std::time::Duration;
+
+        use crate::{SceneMsg, SceneStage, SceneSwitcher, SceneTransition};
+
+        let switcher = SceneSwitcher::new();
+        let mut stage = SceneStage::new(switcher.clone());
+        let mut view = stage.build_view();
+        view.build_id_index();
+
+        switcher.switch_to(Counter(0), SceneTransition::Cut);
+        assert_eq!(stage.update(SceneMsg::Draw(Duration::default())), ChangeView::Modify);
+        stage.modify_view(&mut view);
+        assert!(!stage.is_transient());
+        assert_eq!(view.get_prim("scene_current").unwrap().children.len(), 1);
+
+        let fade = SceneTransition::Fade(Duration::from_millis(200));
+        switcher.switch_to(Counter(1), fade);
+        stage.update(SceneMsg::Draw(Duration::default()));
+        stage.modify_view(&mut view);
+        assert!(stage.is_transient());
+        assert_eq!(view.get_prim("scene_outgoing").unwrap().children.len(), 1);
+
+        stage.update(SceneMsg::Draw(Duration::from_millis(300)));
+        stage.modify_view(&mut view);
+        assert!(!stage.is_transient());
+        assert!(view.get_prim("scene_outgoing").unwrap().children.is_empty());
+        assert_eq!(view.get_prim("scene_current").unwrap().children.len(), 1);
+    }
+
+    #[test]
+    fn router() {
+        use std::time::Duration;
+
+        use crate::{Comp, Navigator, Router, SceneStage, SceneTransition, SystemMessage};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Route {
+            Menu,
+            Game,
+        }
+
+        fn game(comp: &mut Comp) -> Option<&mut Comp> {
+            let stage = comp
+                .inner_mut::<Router<Route>>()
+                .view_mut()?
+                .as_prim_mut()?
+                .node_mut(0)?
+                .as_comp_mut()?;
+            stage.inner_mut::<SceneStage>().view_mut()?.get_comp_mut("game")
+        }
+
+        let frames = |comp: &mut Comp| {
+            for _ in 0..3 {
+                comp.send_system_msg(SystemMessage::Draw(Duration::from_millis(100)));
+                comp.update_view();
+            }
+        };
+
+        let navigator = Navigator::new();
+        let mut comp = Comp::new(Router::new(Route::Menu, navigator.clone(), |route| {
+            let mut comp = Comp::new(Counter(0));
+            comp.set_id(format!("{:?}", route).to_lowercase());
+            comp
+        }));
+        frames(&mut comp);
+
+        navigator.push(Route::Game, SceneTransition::Fade(Duration::from_millis(100)));
+        frames(&mut comp);
+        assert_eq!(comp.model::<Router<Route>>().route(), &Route::Game);
+        game(&mut comp).unwrap().send::<Counter>(Msg::Increment);
+
+        navigator.push(Route::Menu, SceneTransition::Cut);
+        frames(&mut comp);
+        assert!(game(&mut comp).is_none());
+        assert_eq!(comp.model::<Router<Route>>().history().collect::<Vec<_>>(), vec![
+            &Route::Menu,
+            &Route::Game,
+            &Route::Menu
+        ]);
+
+        navigator.back(SceneTransition::Cut);
+        frames(&mut comp);
+        assert_eq!(game(&mut comp).unwrap().model::<Counter>().0, 1);
+    }
+
+    #[test]
+    fn shape_tree() {
+        use crate::ShapeTree;
+
+        let view = Counter::create(0).build_view();
+        let tree = ShapeTree::snapshot(&view).unwrap();
+        assert_eq!(tree.children.len(), 3);
+        assert_eq!(tree.children[1].shape.text().unwrap().content, "0");
+
+        let copy: Node<Counter> = tree.clone().into_node();
+        assert_eq!(copy.as_prim().unwrap().children[0].as_prim().unwrap().name, Rect::NAME);
+        assert_eq!(ShapeTree::snapshot(&copy), Some(tree));
+    }
+
+    #[test]
+    fn load_view() {
+        use crate::{Comp, CompDescription, CompRegistry, Listener, LoadViewError, Shape, ViewDescription, ViewLoader};
+
+        let button = Rect {
+            id: Some("plus".to_string()),
+            ..Default::default()
+        };
+        let mut description = ViewDescription {
+            shape: Shape::Rect(Default::default()),
+            listeners: vec![],
+            children: vec![ViewDescription {
+                shape: Shape::Rect(button),
+                listeners: vec!["increment".to_string()],
+                children: vec![],
+                comp: None,
+            }],
+            comp: None,
+        };
+
+        let loader = ViewLoader::<Counter>::new().with_listener("increment", Listener::OnClick(|_| Msg::Increment));
+        let view = loader.load(description.clone()).unwrap();
+        assert_eq!(
+            view.as_prim().unwrap().children[0].as_prim().unwrap().listeners.len(),
+            1
+        );
+
+        description.children[0].listeners.push("decrement".to_string());
+        assert_eq!(
+            loader.load(description).err(),
+            Some(LoadViewError::UnknownListener {
+                id: Some("plus".to_string()),
+                name: "decrement".to_string(),
+            })
+        );
+
+        let registry = CompRegistry::new().with("counter", |props| {
+            props.parse().ok().map(Counter::create).map(Comp::new)
+        });
+        let loader = ViewLoader::<Counter>::new().with_registry(registry);
+        let mut description = ViewDescription {
+            shape: Shape::Rect(Rect {
+                id: Some("nested".to_string()),
+                ..Default::default()
+            }),
+            listeners: vec![],
+            children: vec![],
+            comp: Some(CompDescription {
+                name: "counter".to_string(),
+                props: "7".to_string(),
+            }),
+        };
+        let view = loader.load(description.clone()).unwrap();
+        assert_eq!(view.as_comp().unwrap().model::<Counter>().0, 7);
+        assert_eq!(view.as_comp().unwrap().id(), Some("nested"));
+
+        description.comp.as_mut().unwrap().props = "seven".to_string();
+        assert_eq!(
+            loader.load(description).err(),
+            Some(LoadViewError::InvalidProps {
+                name: "counter".to_string(),
+                props: "seven".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn svg_export() {
+        let mut view = Counter::create(0).build_view();
+        view.get_prim_mut("counter").unwrap().set_text("<1>");
+
+        let svg = view.to_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg">"#));
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert!(svg.contains(r#"id="counter""#));
+        assert!(svg.contains(">&lt;1&gt;</text>"));
+    }
+
+    #[test]
+    fn hit_test() {
+        use crate::builder::*;
+
+        let view: Node<Counter> = rect()
+            .id("root")
+            .width(100.0)
+            .height(100.0)
+            .child(rect().id("inner").left_top_pos(10.0, 10.0).width(20.0).height(20.0))
+            .child(
+                rect()
+                    .id("clipped")
+                    .left_top_pos(50.0, 50.0)
+                    .width(40.0)
+                    .height(40.0)
+                    .clip(0.0, 0.0, 60.0, 60.0),
+            )
+            .build();
+
+        assert_eq!(view.hit_test(15.0, 15.0).and_then(|prim| prim.id()), Some("inner"));
+        assert_eq!(view.hit_test(55.0, 55.0).and_then(|prim| prim.id()), Some("clipped"));
+        assert_eq!(view.hit_test(80.0, 80.0).and_then(|prim| prim.id()), Some("root"));
+        assert!(view.hit_test(150.0, 50.0).is_none());
+    }
+
+    #[test]
+    fn decorations() {
+        use crate::{builder::*, Anchor, BoundingBox};
+
+        let bound = BoundingBox {
+            min_x: 10.0,
+            min_y: 20.0,
+            max_x: 110.0,
+            max_y: 60.0,
+        };
+        assert_eq!(Anchor::default().point(&bound), (110.0, 20.0));
+        assert_eq!(Anchor::Center.point(&bound), (60.0, 40.0));
+        assert_eq!(Anchor::BottomLeft.point(&bound), (10.0, 60.0));
+
+        let view: Node<Counter> = rect()
+            .id("host")
+            .width(100.0)
+            .height(100.0)
+            .child(rect().id("child").width(50.0).height(50.0))
+            .decoration(
+                Anchor::TopRight,
+                rect().id("badge").left_top_pos(40.0, 0.0).width(20.0).height(20.0),
+            )
+            .build();
+        let host = view.as_prim().unwrap();
+        assert_eq!(host.nodes().filter_map(Node::get_id).collect::<Vec<_>>(), vec![
+            "child", "badge"
+        ]);
+        assert_eq!(host.node(1).and_then(Node::get_id), Some("badge"));
+        assert!(view.get_prim("badge").is_some());
+
+        // The decorations are over the children
+        assert_eq!(view.hit_test(45.0, 10.0).and_then(|prim| prim.id()), Some("badge"));
+        assert_eq!(view.hit_test(10.0, 10.0).and_then(|prim| prim.id()), Some("child"));
+        assert_eq!(view.hit_test(80.0, 80.0).and_then(|prim| prim.id()), Some("host"));
+    }
+
+    #[test]
+    fn margin_layout() {
+        use crate::{builder::*, BoundingBox, Margin, Shape, TransformMatrix};
+
+        let mut view: Node<Counter> = rect()
+            .id("spaced")
+            .width(20.0)
+            .height(10.0)
+            .margin(2.0)
+            .margin_left_and_right(5.0)
+            .margin_bottom(4.0)
+            .build();
+        let rect = match &mut view.get_prim_mut("spaced").unwrap().shape {
+            Shape::Rect(rect) => rect,
+            _ => unreachable!(),
+        };
+        assert_eq!(rect.margin.left_and_right(), 10.0);
+        assert_eq!(rect.margin.top_and_bottom(), 6.0);
+
+        // The shape is offset by the left and top margin and its parent reserves the whole margin
+        let global = rect.recalculate_transform(TransformMatrix::identity().with_translation(100.0, 100.0));
+        assert_eq!(global.translate_xy(), (105.0, 102.0));
+        let bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 20.0,
+            max_y: 10.0,
+        };
+        assert_eq!(bound.with_margin(&rect.margin), BoundingBox {
+            max_x: 30.0,
+            max_y: 16.0,
+            ..bound
+        });
+        assert_eq!(Margin::default().offset(), TransformMatrix::identity());
+    }
+
+    #[test]
+    fn overflow_hidden() {
+        use crate::{builder::*, CompositeShape, Overflow};
+
+        let view: Node<Counter> = rect()
+            .id("panel")
+            .width(50.0)
+            .height(50.0)
+            .overflow(Overflow::Hidden)
+            .child(rect().id("content").width(100.0).height(100.0))
+            .build();
+
+        assert_eq!(view.hit_test(40.0, 40.0).and_then(|prim| prim.id()), Some("content"));
+        assert!(view.hit_test(80.0, 80.0).is_none());
+        assert!(view.overflow_clip().unwrap().contains(50.0, 50.0));
+
+        let svg = view.to_svg();
+        assert!(svg.contains(r#"<clipPath id="clip1"><rect x="0" y="0" width="50" height="50"/></clipPath>"#));
+        assert!(svg.contains(r#"<g clip-path="url(#clip1)">"#));
+    }
+
+    #[test]
+    fn overlay() {
+        use crate::{builder::*, Overflow};
+
+        let view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .child(
+                rect()
+                    .id("panel")
+                    .width(50.0)
+                    .height(50.0)
+                    .overflow(Overflow::Hidden)
+                    .child(
+                        group()
+                            .overlay()
+                            .child(rect().id("popup").left_top_pos(40.0, 40.0).width(40.0).height(40.0)),
+                    ),
+            )
+            .child(rect().id("cover").left_top_pos(30.0, 30.0).width(60.0).height(60.0))
+            .build();
+
+        assert_eq!(view.hit_test(70.0, 70.0).and_then(|prim| prim.id()), Some("popup"));
+        assert_eq!(view.hit_test(20.0, 20.0).and_then(|prim| prim.id()), Some("panel"));
+
+        let svg = view.to_svg();
+        assert!(svg.find(r#"id="popup""#).unwrap() > svg.find(r#"id="cover""#).unwrap());
+        assert!(svg.trim_end().ends_with("</g>\n</svg>"));
+    }
+
+    #[test]
+    fn global_bounds() {
+        use crate::{builder::*, BoundingBox, CompositeShape, Layout, Transform, TransformMatrix};
+
+        let mut view: Node<Counter> = rect()
+            .child(
+                rect()
+                    .id("panel")
+                    .transform(Transform::new().with_translation(10.0, 20.0)),
+            )
+            .build();
+        assert!(view.global_bounds_of("panel").is_none());
+
+        let bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 30.0,
+            max_y: 40.0,
+        };
+        let panel = view.get_prim_mut("panel").unwrap();
+        panel
+            .transform_mut()
+            .calculate_global(TransformMatrix::identity().with_scale(2.0, 2.0));
+        panel.set_layout(Layout {
+            parent_bound: bound,
+            parent_transform: TransformMatrix::identity(),
+            wrap_width: None,
+            bound,
+        });
+
+        assert_eq!(
+            view.global_transform_of("panel"),
+            Some(TransformMatrix {
+                matrix: [2.0, 0.0, 0.0, 2.0, 20.0, 40.0]
+            })
+        );
+        assert_eq!(
+            view.global_bounds_of("panel"),
+            Some(BoundingBox {
+                min_x: 20.0,
+                min_y: 40.0,
+                max_x: 80.0,
+                max_y: 120.0,
+            })
+        );
+    }
+
+    #[test]
+    fn drag_and_drop() {
+        use crate::{
+            builder::*, BoundingBox, CompositeShape, DragDrop, DragEvent, InputEvent, Layout, MouseButton, MousePos,
+            TransformMatrix,
+        };
+
+        let mut view: Node<Counter> = rect()
+            .child(rect().id("card").width(50.0).height(50.0))
+            .child(rect().id("bin").left_top_pos(100.0, 0.0).width(50.0).height(50.0))
+            .build();
+        for (id, min_x) in [("card", 0.0), ("bin", 100.0)].iter() {
+            let bound = BoundingBox {
+                min_x: *min_x,
+                min_y: 0.0,
+                max_x: min_x + 50.0,
+                max_y: 50.0,
+            };
+            view.get_prim_mut(id).unwrap().set_layout(Layout {
+                parent_bound: bound,
+                parent_transform: TransformMatrix::identity(),
+                wrap_width: None,
+                bound,
+            });
+        }
+
+        let mut drag = DragDrop::new()
+            .with_source("card", 7)
+            .with_target("bin", |payload: &i32| *payload > 0)
+            .with_target("hidden", |_| true);
+        let pos = |x, y| MousePos { x, y };
+
+        assert!(drag
+            .handle(&view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left))
+            .is_empty());
+        assert!(drag.handle(&view, InputEvent::mouse_move(pos(11.0, 10.0))).is_empty());
+        assert_eq!(drag.handle(&view, InputEvent::mouse_move(pos(120.0, 10.0))), vec![
+            DragEvent::Started {
+                source: "card".to_string()
+            },
+            DragEvent::Entered {
+                source: "card".to_string(),
+                target: "bin".to_string()
+            },
+        ]);
+        assert_eq!(drag.dragged(), Some("card"));
+        assert_eq!(drag.build_view::<Counter>().as_prim().unwrap().children.len(), 2);
+
+        assert_eq!(
+            drag.handle(&view, InputEvent::mouse_up(pos(120.0, 10.0), MouseButton::Left)),
+            vec![DragEvent::Dropped {
+                source: "card".to_string(),
+                target: "bin".to_string(),
+                payload: 7,
+            }]
+        );
+        assert!(!drag.is_dragging());
+
+        // The targets are hit tested, so the clipped out part of a target does not take the drop.
+        view.as_prim_mut().unwrap().children.push(
+            rect()
+                .id("hidden")
+                .left_top_pos(200.0, 0.0)
+                .width(50.0)
+                .height(50.0)
+                .clip(0.0, 0.0, 220.0, 60.0)
+                .build(),
+        );
+        drag.handle(&view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        drag.handle(&view, InputEvent::mouse_move(pos(210.0, 10.0)));
+        assert_eq!(drag.target(), Some("hidden"));
+        assert_eq!(
+            drag.handle(&view, InputEvent::mouse_up(pos(230.0, 10.0), MouseButton::Left)),
+            vec![DragEvent::Cancelled {
+                source: "card".to_string()
+            }]
+        );
+
+        // The events of `system_update` are kept until `modify_view` has the view.
+        assert!(!drag.queue(&InputEvent::mouse_move(pos(20.0, 10.0))));
+        assert!(drag.queue(&InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left)));
+        assert!(drag.queue(&InputEvent::mouse_move(pos(120.0, 10.0))));
+        assert!(drag.queue(&InputEvent::mouse_up(pos(120.0, 10.0), MouseButton::Left)));
+        let events = drag.handle_queued(&view);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events.last(), Some(DragEvent::Dropped { payload: 7, .. })));
+        assert!(drag.handle_queued(&view).is_empty());
+    }
+
+    #[test]
+    fn reorderable_list() {
+        use crate::{
+            builder::*, BoundingBox, CompositeShape, InputEvent, Layout, MouseButton, MousePos, ReorderEvent,
+            ReorderableList, Shape, TransformMatrix,
+        };
+
+        type List = ReorderableList<&'static str>;
+
+        let mut list = List::new(vec!["a", "b", "c", "d"], 20.0, 60.0);
+        let mut view: Node<Counter> = list.build_view(|item| text(*item).build());
+        // The hit test needs the global transforms and the sizes of the last layout pass.
+        fn calc_transforms(node: &mut Node<Counter>, parent: TransformMatrix) {
+            if let Node::Prim(prim) = node {
+                let global = prim.transform_mut().calculate_global(parent);
+                for child in &mut prim.children {
+                    calc_transforms(child, global);
+                }
+            }
+        }
+        calc_transforms(&mut view, TransformMatrix::identity());
+        if let Some(Shape::Rect(list_rect)) = view.get_prim_mut(List::LIST_ID).map(|prim| prim.shape_mut()) {
+            list_rect.width.set_val(200.0);
+        }
+        let mut set_bound = |id: &str, min_x, min_y, max_x, max_y| {
+            let bound = BoundingBox {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            };
+            view.get_prim_mut(id).unwrap().set_layout(Layout {
+                parent_bound: bound,
+                parent_transform: TransformMatrix::identity(),
+                wrap_width: None,
+                bound,
+            });
+        };
+        set_bound(List::LIST_ID, 0.0, 0.0, 200.0, 60.0);
+        for idx in 0..4 {
+            let min_y = idx as f32 * 20.0;
+            set_bound(&List::item_id(idx), 0.0, min_y, 200.0, min_y + 20.0);
+            set_bound(&List::handle_id(idx), 0.0, min_y, 32.0, min_y + 20.0);
+        }
+        let pos = |x, y| MousePos { x, y };
+
+        list.handle(&view, InputEvent::mouse_down(pos(100.0, 10.0), MouseButton::Left));
+        assert!(list.handle(&view, InputEvent::mouse_move(pos(100.0, 50.0))).is_empty());
+        assert_eq!(list.dragged(), None);
+
+        list.handle(&view, InputEvent::mouse_up(pos(100.0, 50.0), MouseButton::Left));
+        list.handle(&view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        assert_eq!(list.handle(&view, InputEvent::mouse_move(pos(10.0, 50.0))), vec![
+            ReorderEvent::Started { index: 0 }
+        ]);
+        assert_eq!(list.dragged(), Some(0));
+        assert_eq!(list.insertion(), Some(2));
+        assert!(list.is_transient());
+
+        assert_eq!(
+            list.handle(&view, InputEvent::mouse_up(pos(10.0, 50.0), MouseButton::Left)),
+            vec![ReorderEvent::Completed { from: 0, to: 2 }]
+        );
+        assert_eq!(list.items(), &["b", "c", "a", "d"]);
+        assert_eq!(list.dragged(), None);
+
+        assert!(list.queue(&InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left)));
+        assert!(list.queue(&InputEvent::mouse_move(pos(10.0, 30.0))));
+        assert!(list.queue(&InputEvent::mouse_up(pos(10.0, 30.0), MouseButton::Left)));
+        assert_eq!(list.handle_queued(&view), vec![
+            ReorderEvent::Started { index: 0 },
+            ReorderEvent::Completed { from: 0, to: 1 }
+        ]);
+        assert_eq!(list.items(), &["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn virtual_list() {
+        use crate::{builder::*, MousePos, MouseScroll, VirtualList};
+
+        let build_item = |idx: usize| text(format!("Row {}", idx)).build();
+        let mut list = VirtualList::new(10_000, 20.0, 100.0);
+        let mut view: Node<Counter> = rect().child(virtual_list(&list, build_item)).build();
+        let rows = |view: &Node<Counter>| {
+            view.get_prim("virtual_list_content")
+                .unwrap()
+                .children
+                .iter()
+                .map(|row| row.get_id().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(&view).len(), 7);
+
+        list.set_scroll(1000.0);
+        assert_eq!(list.visible_range(), 48..57);
+        list.sync_view(&mut view, build_item);
+        assert_eq!(rows(&view).first().map(String::as_str), Some("virtual_item_48"));
+        assert_eq!(rows(&view).len(), 9);
+
+        list.set_scroll(1e9);
+        assert_eq!(list.scroll(), list.max_scroll());
+        assert!(list.scroll_by(&MouseScroll::lines(MousePos { x: 0.0, y: 0.0 }, (0.0, 2.0))));
+        assert_eq!(list.scroll(), list.max_scroll() - 40.0);
+        list.set_scroll(1e9);
+        assert_eq!(list.visible_range().end, 10_000);
+    }
+
+    #[test]
+    fn theme_tokens() {
+        use crate::{
+            builder::*, BoundingBox, Color, CompositeShape, DragDrop, Fill, GlyphPos, InkStroke, InputEvent, Layout,
+            MouseButton, MousePos, Palette, Prim, SceneStage, SceneSwitcher, Shape, Text, TextMetrics, TextSelection,
+            Theme, ThemeToken, TransformMatrix, VirtualList,
+        };
+
+        let theme = Theme::new(Palette::dark()).with_override(VirtualList::WIDGET, ThemeToken::Selection, Color::Red);
+        fn fill<M: Model>(prim: Option<&Prim<M>>) -> Option<Fill> {
+            match prim.map(|prim| &prim.shape) {
+                Some(Shape::Rect(rect)) => rect.fill,
+                _ => None,
+            }
+        }
+
+        let build_item = |idx: usize| text(format!("Row {}", idx)).build();
+        let mut list = VirtualList::new(100, 20.0, 100.0).with_theme(theme.clone());
+        list.set_selected(Some(1));
+        let mut view: Node<Counter> = virtual_list(&list, build_item);
+        assert_eq!(
+            fill(view.get_prim("virtual_list")),
+            Some(Fill::color(Palette::dark().surface))
+        );
+        assert_eq!(
+            fill(view.get_prim("virtual_list_selection")),
+            Some(Fill::color(Color::Red))
+        );
+        list.set_selected(Some(3));
+        list.sync_view(&mut view, build_item);
+        let row = view.get_prim("virtual_item_3").unwrap();
+        assert_eq!(row.children[0].get_id(), Some("virtual_list_selection"));
+        assert_eq!(view.get_prim("virtual_item_1").unwrap().children.len(), 1);
+
+        let text = Text {
+            glyph_positions: (0..5)
+                .map(|idx| GlyphPos {
+                    x: idx as f32 * 10.0,
+                    y: 0.0,
+                    width: 10.0,
+                })
+                .collect(),
+            metrics: Some(TextMetrics {
+                ascender: 12.0,
+                descender: 4.0,
+                line_height: 16.0,
+            }),
+            ..Default::default()
+        };
+        let highlight = TextSelection { anchor: 3, caret: 1 }.highlight(&text, &theme);
+        let rects: Vec<_> = highlight
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Rect(rect) => Some((rect.x.val(), rect.width.val(), rect.height.val(), rect.fill)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rects, vec![
+            (10.0, 20.0, 16.0, Some(Fill::color(Palette::dark().selection))),
+            (9.25, 1.5, 16.0, Some(Fill::color(Palette::dark().caret))),
+        ]);
+
+        let mut view: Node<Counter> = rect()
+            .child(rect().id("card").width(50.0).height(50.0))
+            .child(rect().id("bin").left_top_pos(100.0, 0.0).width(50.0).height(50.0))
+            .build();
+        for (id, min_x) in [("card", 0.0), ("bin", 100.0)].iter() {
+            let bound = BoundingBox {
+                min_x: *min_x,
+                min_y: 0.0,
+                max_x: min_x + 50.0,
+                max_y: 50.0,
+            };
+            view.get_prim_mut(id).unwrap().set_layout(Layout {
+                parent_bound: bound,
+                parent_transform: TransformMatrix::identity(),
+                wrap_width: None,
+                bound,
+            });
+        }
+        let mut drag = DragDrop::new()
+            .with_source("card", ())
+            .with_target("bin", |_| true)
+            .with_theme(theme.clone());
+        let pos = |x, y| MousePos { x, y };
+        drag.handle(&view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        drag.handle(&view, InputEvent::mouse_move(pos(120.0, 10.0)));
+        let overlay = drag.build_view::<Counter>();
+        let outline = match overlay.as_prim().unwrap().children[0].as_prim().map(|prim| &prim.shape) {
+            Some(Shape::Rect(rect)) => rect.clone(),
+            _ => panic!("no target outline"),
+        };
+        assert_eq!((outline.x.val(), outline.width.val()), (100.0, 50.0));
+        assert_eq!(
+            outline.stroke.map(|stroke| stroke.paint),
+            Some(Palette::dark().focus_ring.into())
+        );
+
+        let stage = SceneStage::new(SceneSwitcher::new()).with_theme(theme.clone());
+        assert_eq!(
+            fill(stage.build_view().as_prim()),
+            Some(Fill::color(Palette::dark().surface))
+        );
+
+        assert_eq!(InkStroke::themed(2.0, &theme).color, Palette::dark().text_primary);
+    }
+
+    #[test]
+    fn density_presets() {
+        use crate::{Density, Palette, Theme};
+
+        let theme = Theme::default();
+        assert_eq!(theme.density, Density::Comfortable);
+        assert_eq!(theme.spacing(8.0), 8.0);
+
+        let compact = Theme::new(Palette::light()).with_density(Density::Compact);
+        assert_eq!(compact.spacing(8.0), 6.0);
+        assert_eq!(compact.font_size(20.0), 18.0);
+        assert_eq!(compact.hit_target(10.0), 24.0);
+        assert_eq!(compact.hit_target(30.0), 30.0);
+
+        let touch = compact.with_density(Density::Touch);
+        assert_eq!(touch.spacing(8.0), 12.0);
+        assert_eq!(touch.hit_target(30.0), 44.0);
+    }
+
+    #[test]
+    fn throttled_draw() {
+        use std::time::Duration;
+
+        use crate::{builder::*, Comp, DrawThrottles, ListenerRate};
+
+        struct Ticker(u32);
+
+        impl Model for Ticker {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Ticker(0)
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                self.0 += 1;
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().on_draw_throttled(ListenerRate::EveryNthFrame(2), |_| ()).build()
+            }
+        }
+
+        struct Screen;
+
+        impl Model for Screen {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Screen
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().child(comp(Ticker(0)).id("ticker")).build()
+            }
+        }
+
+        // The nested component is throttled as the root one
+        let mut ticker = Comp::new(Ticker(0));
+        let mut screen = Comp::new(Screen);
+        for _ in 0..5 {
+            ticker.send_system_msg(SystemMessage::Draw(Duration::from_millis(16)));
+            screen.send_system_msg(SystemMessage::Draw(Duration::from_millis(16)));
+        }
+        assert_eq!(ticker.model::<Ticker>().0, 2);
+        let nested = screen
+            .inner_mut::<Screen>()
+            .view_mut()
+            .unwrap()
+            .get_comp_mut("ticker")
+            .unwrap();
+        assert_eq!(nested.model::<Ticker>().0, 2);
+
+        // The listeners with the same function are counted apart
+        let frame = Duration::from_millis(16);
+        let rate = ListenerRate::EveryNthFrame(2);
+        let mut throttles = DrawThrottles::default();
+        throttles.tick(frame);
+        assert_eq!(throttles.poll(rate), None);
+        throttles.tick(frame);
+        assert_eq!(throttles.poll(rate), None);
+        assert_eq!(throttles.poll(rate), None);
+        throttles.tick(frame);
+        assert_eq!(throttles.poll(rate), Some(frame * 2));
+        assert_eq!(throttles.poll(rate), None);
+    }
+
+    #[test]
+    fn global_hotkeys() {
+        use crate::{GlobalHotkeys, Hotkey, Modifiers, VirtualKeyCode};
+
+        let mut hotkeys = GlobalHotkeys::new();
+        let screenshot = hotkeys.register(Hotkey::new(VirtualKeyCode::S, Modifiers::NONE.ctrl().shift()));
+        let talk = hotkeys.register(Hotkey::new(VirtualKeyCode::F8, Modifiers::NONE));
+
+        assert!(hotkeys.key_pressed(VirtualKeyCode::S).is_empty());
+        assert!(hotkeys.key_pressed(VirtualKeyCode::LControl).is_empty());
+        assert!(hotkeys.key_pressed(VirtualKeyCode::RShift).is_empty());
+        assert_eq!(hotkeys.key_pressed(VirtualKeyCode::S), vec![screenshot]);
+        assert!(hotkeys.key_pressed(VirtualKeyCode::F8).is_empty());
+
+        hotkeys.key_released(VirtualKeyCode::LControl);
+        hotkeys.key_released(VirtualKeyCode::RShift);
+        assert_eq!(hotkeys.key_pressed(VirtualKeyCode::F8), vec![talk]);
+
+        hotkeys.unregister(talk);
+        assert!(hotkeys.key_pressed(VirtualKeyCode::F8).is_empty());
+    }
+
+    #[test]
+    fn user_data() {
+        use crate::builder::*;
+
+        #[derive(Debug, PartialEq)]
+        struct Cell {
+            row: usize,
+            col: usize,
+        }
+
+        let mut view: Node<Counter> = rect()
+            .child(rect().id("cell").user_data(Cell { row: 2, col: 3 }))
+            .build();
+        let cell = view.get_prim_mut("cell").unwrap();
+        assert_eq!(cell.user_data::<Cell>(), Some(&Cell { row: 2, col: 3 }));
+        assert!(cell.user_data::<String>().is_none());
+
+        cell.user_data_mut::<Cell>().unwrap().col = 4;
+        assert!(cell.take_user_data::<String>().is_none());
+        assert_eq!(cell.take_user_data::<Cell>(), Some(Cell { row: 2, col: 4 }));
+        assert!(cell.user_data.is_none());
+    }
+
+    #[test]
+    fn dirty_tracking() {
+        use crate::{builder::*, BoundingBox, CompositeShape, Layout, TransformMatrix};
+
+        let mut view: Node<Counter> = rect().child(rect().id("card")).child(rect().id("badge")).build();
+        let bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 50.0,
+            max_y: 50.0,
+        };
+        let layout = Layout {
+            parent_bound: bound,
+            parent_transform: TransformMatrix::identity(),
+            wrap_width: None,
+            bound,
+        };
+        view.set_layout(layout);
+        for id in ["card", "badge"].iter() {
+            view.get_prim_mut(id).unwrap().set_layout(layout);
+        }
+        assert_eq!(view.need_redraw(), Some(true));
+
+        view.mark_drawn();
+        assert_eq!(view.need_redraw(), Some(false));
+        assert!(view.update_view().is_none());
+
+        view.get_prim_mut("badge").unwrap().invalidate_paint();
+        assert_eq!(view.need_redraw(), Some(true));
+        assert_eq!(view.need_recalc(), Some(false));
+        let update = view.update_view();
+        assert!(update.is_redraw() && !update.is_recalc());
+        assert_eq!(view.need_recalc(), Some(false));
+        view.mark_drawn();
+
+        view.get_prim_mut("card").unwrap().transform_mut();
+        assert_eq!(view.need_redraw(), Some(true));
+        assert!(view.get_prim("card").unwrap().need_recalc().unwrap());
+    }
+
+    #[test]
+    fn direct_shape_changes() {
+        use crate::{builder::*, BoundingBox, Color, CompositeShape, Layout, Shape, TransformMatrix};
+
+        let mut view: Node<Counter> = rect().child(rect().id("card").width(10.0)).build();
+        let bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 50.0,
+            max_y: 50.0,
+        };
+        let layout = Layout {
+            parent_bound: bound,
+            parent_transform: TransformMatrix::identity(),
+            wrap_width: None,
+            bound,
+        };
+        assert!(layout.is_valid_for(bound, TransformMatrix::identity(), None));
+        assert!(!layout.is_valid_for(bound, TransformMatrix::identity(), Some(30.0)));
+        view.set_layout(layout);
+        view.get_prim_mut("card").unwrap().set_layout(layout);
+        view.update_view();
+        view.mark_drawn();
+
+        let card = view.get_prim_mut("card").unwrap();
+        card.set_fill(Some(Color::Red.into()));
+        let update = view.update_view();
+        assert!(update.is_redraw() && !update.is_recalc());
+        assert_eq!(view.need_recalc(), Some(false));
+        view.mark_drawn();
+
+        if let Shape::Rect(rect) = view.get_prim_mut("card").unwrap().shape_mut() {
+            rect.width = 20.0.into();
+        }
+        assert!(view.update_view().is_recalc());
+        assert_eq!(view.get_prim("card").unwrap().need_recalc(), Some(true));
+    }
+
+    #[test]
+    fn modify_ids() {
+        use crate::{Comp, Shape};
+
+        struct Badges(u32);
+
+        impl Model for Badges {
+            type Message = u32;
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Badges(0)
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 = msg;
+                ChangeView::ModifyIds(vec!["unread".into()])
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect()
+                    .child(text("0").id("unread"))
+                    .child(text("0").id("total"))
+                    .build()
+            }
+
+            fn modify_node(&mut self, id: &str, node: &mut Node<Self>) {
+                assert_eq!(id, "unread");
+                node.as_prim_mut().unwrap().set_text(self.0.to_string());
+            }
+        }
+
+        let content = |comp: &Comp, id| match comp.inner::<Badges>().view()?.get_prim(id)?.shape {
+            Shape::Text(ref text) => Some(text.content.clone()),
+            _ => None,
+        };
+
+        let mut comp = Comp::new(Badges(0));
+        comp.update_view();
+        comp.send::<Badges>(3);
+        assert!(comp.update_view().is_redraw());
+        assert_eq!(content(&comp, "unread").as_deref(), Some("3"));
+        assert_eq!(content(&comp, "total").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn update_batch() {
+        use crate::Comp;
+
+        let mut counter = Counter(0);
+        let change = counter.update_batch(vec![Msg::Increment, Msg::Increment, Msg::Decrement]);
+        assert_eq!(change, ChangeView::Modify);
+        assert_eq!(counter.0, 1);
+
+        let ids = ChangeView::ModifyIds(vec!["a".into()]).merge(ChangeView::ModifyIds(vec!["b".into(), "a".into()]));
+        assert_eq!(ids, ChangeView::ModifyIds(vec!["a".into(), "b".into()]));
+        assert_eq!(ids.merge(ChangeView::RecalcOnly), ChangeView::Modify);
+        assert_eq!(ChangeView::None.merge(ChangeView::Rebuild), ChangeView::Rebuild);
+
+        let mut comp = Comp::new(Counter(0));
+        comp.update_view();
+        comp.send_batch::<Counter>(vec![Msg::Increment, Msg::Increment, Msg::Increment]);
+        assert_eq!(comp.model::<Counter>().0, 3);
+        assert!(comp.update_view().is_redraw());
+    }
+
+    #[test]
+    fn undo_stack() {
+        use crate::{InputEvent, KeyboardEvent, UndoAction, UndoStack, VirtualKeyCode};
+
+        let mut counter = Counter(0);
+        let mut undo = UndoStack::<Counter>::new();
+        assert!(!undo.can_undo());
+
+        counter.update(Msg::Increment);
+        undo.record(Msg::Increment, Msg::Decrement);
+        undo.begin();
+        for _ in 0..2 {
+            counter.update(Msg::Increment);
+            undo.record(Msg::Increment, Msg::Decrement);
+        }
+        undo.commit();
+        assert_eq!(counter.0, 3);
+
+        counter.update_batch(undo.undo());
+        assert_eq!(counter.0, 1);
+        counter.update_batch(undo.undo());
+        assert_eq!(counter.0, 0);
+        assert!(!undo.can_undo());
+        assert!(undo.undo().is_empty());
+
+        counter.update_batch(undo.redo());
+        assert_eq!(counter.0, 1);
+        undo.record(Msg::Decrement, Msg::Increment);
+        assert!(!undo.can_redo());
+
+        let key = |keycode| KeyboardEvent::new(0, Some(keycode));
+        let command = if cfg!(target_os = "macos") {
+            VirtualKeyCode::LWin
+        } else {
+            VirtualKeyCode::LControl
+        };
+        assert_eq!(undo.handle(&InputEvent::KeyDown(key(VirtualKeyCode::Z))), None);
+        assert_eq!(undo.handle(&InputEvent::KeyDown(key(command))), None);
+        assert_eq!(
+            undo.handle(&InputEvent::KeyDown(key(VirtualKeyCode::Z))),
+            Some(UndoAction::Undo)
+        );
+        assert_eq!(
+            undo.handle(&InputEvent::KeyDown(key(VirtualKeyCode::Y))),
+            Some(UndoAction::Redo)
+        );
+        undo.handle(&InputEvent::KeyUp(key(command)));
+        assert_eq!(undo.handle(&InputEvent::KeyDown(key(VirtualKeyCode::Z))), None);
+    }
+
+    #[test]
+    fn shared_context() {
+        use crate::{context, provide_context, take_context, Comp, Palette, Shape};
+
+        struct Themed;
+
+        impl Model for Themed {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Themed
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                let palette = context::<Palette>()
+                    .map(|palette| *palette)
+                    .unwrap_or_else(Palette::light);
+                rect().id("card").fill(palette.surface).build()
+            }
+        }
+
+        assert!(provide_context(Palette::dark()).is_none());
+        let comp = Comp::new(Themed);
+        let fill = match comp.inner::<Themed>().view().unwrap().get_prim("card").unwrap().shape {
+            Shape::Rect(ref rect) => rect.fill,
+            _ => None,
+        };
+        assert_eq!(fill, Some(Palette::dark().surface.into()));
+
+        assert_eq!(take_context::<Palette>().as_deref(), Some(&Palette::dark()));
+        assert!(context::<Palette>().is_none());
+    }
+
+    #[test]
+    fn lifecycle() {
+        use std::{cell::RefCell, rc::Rc};
+
+        use crate::{Comp, CompositeShape};
+
+        struct Screen(Rc<RefCell<Vec<&'static str>>>);
+
+        impl Model for Screen {
+            type Message = ();
+            type Properties = Rc<RefCell<Vec<&'static str>>>;
+
+            fn create(log: Self::Properties) -> Self {
+                Screen(log)
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect().build()
+            }
+
+            fn on_mount(&mut self) -> ChangeView {
+                self.0.borrow_mut().push("mount");
+                ChangeView::None
+            }
+
+            fn on_first_render(&mut self) -> ChangeView {
+                self.0.borrow_mut().push("first render");
+                ChangeView::None
+            }
+
+            fn on_unmount(&mut self) {
+                self.0.borrow_mut().push("unmount");
+            }
+        }
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut comp = Comp::new(Screen(log.clone()));
+        assert!(log.borrow().is_empty());
+
+        comp.update_view();
+        comp.mark_drawn();
+        comp.update_view();
+        comp.mark_drawn();
+        assert_eq!(*log.borrow(), vec!["mount", "first render"]);
+
+        comp.unmount();
+        comp.unmount();
+        comp.update_view();
+        drop(comp);
+        assert_eq!(*log.borrow(), vec![
+            "mount",
+            "first render",
+            "unmount",
+            "mount",
+            "unmount"
+        ]);
+    }
+
+    #[test]
+    fn should_render() {
+        use crate::{Comp, CompositeShape};
+
+        struct Static(u32);
+
+        impl Model for Static {
+            type Message = u32;
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Static(0)
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 = msg;
+                ChangeView::Modify
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect().child(text("0").id("value")).build()
+            }
+
+            fn modify_view(&mut self, view: &mut Node<Self>) {
+                view.get_prim_mut("value").unwrap().set_text(self.0.to_string());
+            }
+
+            fn should_render(&self) -> bool {
+                false
+            }
+        }
+
+        let mut comp = Comp::new(Static(0));
+        assert!(comp.update_view().is_redraw());
+        comp.mark_drawn();
+
+        comp.inner_mut::<Static>()
+            .view_mut()
+            .unwrap()
+            .get_prim_mut("value")
+            .unwrap()
+            .invalidate_paint();
+        assert!(comp.update_view().is_none());
+
+        comp.send::<Static>(1);
+        assert!(comp.update_view().is_redraw());
+    }
+
+    #[test]
+    fn middleware() {
+        use std::{cell::RefCell, rc::Rc};
+
+        use crate::Comp;
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut comp = Comp::new(Counter(0));
+        comp.add_middleware({
+            let log = log.clone();
+            move |counter: &Counter, msg| {
+                log.borrow_mut().push(counter.0);
+                Some(msg)
+            }
+        });
+        comp.add_middleware(|_: &Counter, msg| match msg {
+            Msg::Decrement => None,
+            msg => Some(msg),
+        });
+
+        comp.send::<Counter>(Msg::Increment);
+        comp.send::<Counter>(Msg::Decrement);
+        comp.send_batch::<Counter>(vec![Msg::Increment, Msg::Decrement]);
+        assert_eq!(comp.model::<Counter>().0, 2);
+        assert_eq!(*log.borrow(), vec![0, 1, 1, 1]);
+    }
+
+    #[cfg(feature = "hot-reload")]
+    #[test]
+    fn hot_reload_errors() {
+        use crate::{HotReloadError, HotView};
+        use std::{env, fs};
+
+        struct View;
+
+        impl Model for View {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                View
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect().build()
+            }
+        }
+
+        let dir = env::temp_dir().join(format!("engel-hot-reload-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("view.so");
+
+        let mut hot = unsafe { HotView::<View>::new(&path) };
+        assert!(matches!(hot.reload(), Err(HotReloadError::Io(_))));
+
+        fs::write(&path, b"not a library").unwrap();
+        assert!(matches!(hot.reload(), Err(HotReloadError::Library(_))));
+        assert!(!hot.is_loaded());
+        assert!(hot.build_view(&View).is_none());
+        assert!(!hot.modify_view(&mut View, &mut View.build_view()));
+
+        let copies = fs::read_dir(env::temp_dir())
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(&format!("view-hot-{}-", std::process::id()))
+            })
+            .count();
+        assert_eq!(copies, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn record_and_replay() {
+        use crate::{Comp, LoadState, Recorder, Replayer, SaveState};
+
+        struct Total(i32);
+
+        impl Model for Total {
+            type Message = i32;
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Total(0)
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 += msg;
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect().build()
+            }
+        }
+
+        impl SaveState for Total {
+            type State = i32;
+
+            fn save_state(&self) -> Self::State {
+                self.0
+            }
+        }
+
+        impl LoadState for Total {
+            type State = i32;
+
+            fn load_state(&mut self, state: Self::State) -> ChangeView {
+                self.0 = state;
+                ChangeView::None
+            }
+        }
+
+        let mut comp = Comp::new(Total(10));
+        let double = comp.add_middleware(|_: &Total, msg: i32| Some(msg * 2));
+        let recorder = Recorder::<Total>::start(&mut comp);
+        for msg in &[1, 2, 3] {
+            comp.send::<Total>(*msg);
+        }
+        let recording = recorder.stop(&mut comp);
+        comp.send::<Total>(100);
+        assert_eq!(comp.model::<Total>().0, 222);
+        assert_eq!(recording.state, 10);
+        assert_eq!(
+            recording.msgs.iter().map(|recorded| recorded.msg).collect::<Vec<_>>(),
+            vec![2, 4, 6]
+        );
+
+        // The recorded messages are not doubled again by the middleware
+        let mut replayer = Replayer::<Total>::new(recording);
+        replayer.replay(&mut comp);
+        assert_eq!(comp.model::<Total>().0, 22);
+        replayer.seek(&mut comp, 1);
+        assert_eq!(comp.model::<Total>().0, 12);
+        assert!(replayer.step(&mut comp).is_some());
+        assert_eq!(comp.model::<Total>().0, 16);
+
+        assert!(comp.remove_middleware::<Total>(double));
+        assert!(!comp.remove_middleware::<Total>(double));
+        comp.send::<Total>(1);
+        assert_eq!(comp.model::<Total>().0, 17);
+    }
+
+    #[test]
+    fn dyn_model() {
+        use crate::{Comp, DynModel, DynMsg};
+
+        struct Title(String);
+
+        impl Model for Title {
+            type Message = String;
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Title(String::new())
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 = msg;
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                text(self.0.clone()).build()
+            }
+        }
+
+        let mut tabs = vec![Comp::new(Counter(0)), Comp::new(Title(String::new()))];
+        for tab in &mut tabs {
+            let _ = tab.try_send(DynMsg::new(Msg::Increment));
+        }
+        let msg = tabs[0].try_send(DynMsg::new("Untitled".to_string())).unwrap_err();
+        tabs[1].try_send(msg).unwrap();
+        assert!(tabs[0].is::<Counter>() && !tabs[0].is::<Title>());
+        assert_eq!(tabs[0].model::<Counter>().0, 1);
+        assert_eq!(tabs[1].model::<Title>().0, "Untitled");
+
+        let mut models: Vec<Box<dyn DynModel>> = vec![Box::new(Counter(0)), Box::new(Title(String::new()))];
+        assert!(models[1].update_dyn(DynMsg::new(Msg::Decrement)).is_err());
+        assert_eq!(
+            models[0].update_dyn(DynMsg::new(Msg::Decrement)).unwrap(),
+            ChangeView::Modify
+        );
+        assert_eq!(models[0].downcast_ref::<Counter>(), Some(&Counter(-1)));
+    }
+
+    #[test]
+    fn nested_comps() {
+        use crate::{builder::*, Comp};
+
+        struct Parent(i32);
+
+        impl Model for Parent {
+            type Message = i32;
+            type Properties = i32;
+
+            fn create(props: Self::Properties) -> Self {
+                Parent(props)
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 = msg;
+                ChangeView::Rebuild
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().child(comp_with_props::<Counter>(self.0).id("child")).build()
+            }
+        }
+
+        let child = |parent: &mut Comp| -> i32 {
+            let view = parent.inner_mut::<Parent>().view_mut().unwrap();
+            view.get_comp_mut("child").unwrap().model::<Counter>().0
+        };
+        let mut parent = Comp::new(Parent(5));
+        parent.update_view();
+        assert_eq!(child(&mut parent), 5);
+
+        let view = parent.inner_mut::<Parent>().view_mut().unwrap();
+        view.get_comp_mut("child").unwrap().send::<Counter>(Msg::Increment);
+        parent.send::<Parent>(10);
+        parent.update_view();
+        assert_eq!(child(&mut parent), 6);
+    }
+
+    #[test]
+    fn change_props() {
+        use crate::{builder::*, Comp};
+
+        struct Label {
+            text: String,
+            clicks: u32,
+        }
+
+        impl Model for Label {
+            type Message = ();
+            type Properties = String;
+
+            fn create(text: Self::Properties) -> Self {
+                Label { text, clicks: 0 }
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                self.clicks += 1;
+                ChangeView::None
+            }
+
+            fn change(&mut self, text: Self::Properties) -> ChangeView {
+                self.text = text;
+                ChangeView::Rebuild
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                text(self.text.as_str()).build()
+            }
+        }
+
+        let mut comp = Comp::new(Label::create("old".into()));
+        assert!(comp.update_view().is_redraw());
+        comp.send::<Label>(());
+        comp.change_props::<Label>("new".into());
+        assert!(comp.update_view().is_redraw());
+
+        let label = comp.model::<Label>();
+        assert_eq!(label.text, "new");
+        assert_eq!(label.clicks, 1);
+    }
+
+    #[test]
+    fn error_boundary() {
+        use crate::{builder::*, Comp, SystemMessage};
+
+        struct Fragile;
+
+        impl Model for Fragile {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Fragile
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                panic!("broken")
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().build()
+            }
+        }
+
+        struct App(Vec<String>);
+
+        impl Model for App {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                App(vec![])
+            }
+
+            fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+                if let SystemMessage::CompFailed(error) = msg {
+                    self.0.push(error.message);
+                }
+                None
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect()
+                    .child(
+                        comp(Fragile)
+                            .fallback::<Fragile>(|_| text("failed").build())
+                            .id("fragile"),
+                    )
+                    .build()
+            }
+        }
+
+        fn fragile(app: &mut Comp) -> &mut Comp {
+            let view = app.inner_mut::<App>().view_mut().unwrap();
+            view.get_comp_mut("fragile").unwrap()
+        }
+        let mut app = Comp::new(App::create(()));
+        app.update_view();
+        fragile(&mut app).send::<Fragile>(());
+        assert_eq!(fragile(&mut app).error().unwrap().message, "broken");
+        assert!(app.update_view().is_redraw());
+
+        assert!(app.send_failures());
+        assert_eq!(app.model::<App>().0, vec!["broken".to_string()]);
+        assert!(!app.send_failures());
+
+        fragile(&mut app).recover();
+        assert!(fragile(&mut app).error().is_none());
+    }
+
+    #[test]
+    fn exit_request() {
+        use crate::{builder::*, request_exit, take_exit_request, Comp};
+
+        struct Quit;
+
+        impl Model for Quit {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Quit
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                request_exit();
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().build()
+            }
+        }
+
+        let mut comp = Comp::new(Quit);
+        assert!(!take_exit_request());
+        comp.send::<Quit>(());
+        assert!(take_exit_request());
+        assert!(!take_exit_request());
+    }
+
+    #[test]
+    fn resources() {
+        use crate::{builder::*, resource, with_resource, Comp};
+
+        struct Config {
+            step: i32,
+        }
+
+        struct Stepper(i32);
+
+        impl Model for Stepper {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Stepper(0)
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                self.0 += with_resource(|config: &mut Config| config.step).unwrap_or(1);
+                with_resource(|sent: &mut u32| *sent += 1);
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().build()
+            }
+        }
+
+        let mut comp = Comp::new(Stepper(0));
+        comp.send::<Stepper>(());
+        assert_eq!(comp.model::<Stepper>().0, 1);
+
+        comp.insert_resource(Config { step: 10 });
+        comp.insert_resource(0_u32);
+        comp.send::<Stepper>(());
+        comp.send::<Stepper>(());
+        assert_eq!(comp.model::<Stepper>().0, 21);
+        assert_eq!(comp.resource::<u32>(), Some(&2));
+        assert_eq!(resource::<u32>(), None);
+    }
+
+    #[test]
+    fn fixed_timestep() {
+        use crate::{builder::*, Comp, FixedTimestep, SystemMessage};
+        use std::time::Duration;
+
+        struct Game {
+            updates: u32,
+            alpha: f32,
+        }
+
+        impl Model for Game {
+            type Message = ();
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Game { updates: 0, alpha: 0.0 }
+            }
+
+            fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+                match msg {
+                    SystemMessage::Update(_) => self.updates += 1,
+                    SystemMessage::Interpolate(alpha) => self.alpha = alpha,
+                    _ => (),
+                }
+                None
+            }
+
+            fn update(&mut self, _msg: Self::Message) -> ChangeView {
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().build()
+            }
+        }
+
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10));
+        let mut comp = Comp::new(Game::create(()));
+        timestep.advance_comp(&mut comp, Duration::from_millis(25));
+        assert_eq!(comp.model::<Game>().updates, 2);
+        assert!((comp.model::<Game>().alpha - 0.5).abs() < 1e-3);
+
+        timestep.advance_comp(&mut comp, Duration::from_millis(1000));
+        assert_eq!(comp.model::<Game>().updates, 10);
+        assert!(timestep.alpha() < 1.0);
+    }
+
+    #[test]
+    fn frame_stats() {
+        use crate::{count_prims, Comp, StatsCounter};
+        use std::time::Duration;
+
+        let mut comp = Comp::new(Counter::create(0));
+        comp.update_view();
+        let prims = count_prims(&comp);
+        assert!(prims > 1);
+
+        let mut stats = StatsCounter::new(Duration::from_secs(1));
+        for _ in 0..9 {
+            assert_eq!(stats.frame(Duration::from_millis(100), Some(3)), None);
+        }
+        let frame = stats.frame(Duration::from_millis(100), None).unwrap();
+        assert!((frame.fps - 10.0).abs() < 1e-3);
+        assert_eq!(frame.frame_time, Duration::from_millis(100));
+        assert_eq!(frame.draw_calls, 3);
+    }
+
+    #[test]
+    fn message_bus() {
+        use crate::{builder::*, Comp, MessageBus, SystemMessage};
+
+        struct Selected(u32);
+
+        struct Window(Option<u32>);
+
+        impl Model for Window {
+            type Message = u32;
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Window(None)
+            }
+
+            fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+                match msg {
+                    SystemMessage::Custom(event) => event.downcast_ref::<Selected>().map(|selected| selected.0),
+                    _ => None,
+                }
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                self.0 = Some(msg);
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().build()
+            }
+        }
+
+        let bus = MessageBus::new();
+        let mut windows = vec![Comp::new(Window(None)), Comp::new(Window(None))];
+        for window in &mut windows {
+            bus.subscribe(window);
+            window.insert_resource(bus.clone());
+        }
+        windows[0].update_view();
+        bus.publish(Selected(7));
+
+        for window in &mut windows {
+            assert!(window.receive_events());
+            assert_eq!(window.model::<Window>().0, Some(7));
+        }
+
+        windows.pop();
+        bus.publish(Selected(8));
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn inspection() {
+        use crate::{ChangeViewState, Comp};
+
+        let mut comp = Comp::new(Counter(0));
+        comp.update_view();
+        assert!(comp.view::<Counter>().is_some());
+        assert_eq!(comp.last_change(), &ChangeView::None);
+
+        comp.send::<Counter>(Msg::Increment);
+        assert_eq!(comp.last_change(), &ChangeView::Modify);
+        assert!(comp.view_state().need_modify);
+        comp.update_view();
+        assert_eq!(comp.view_state(), &ChangeViewState::default());
+
+        let sender = comp.message_sender::<Counter>();
+        sender.send(Msg::Decrement);
+        sender.send(Msg::Decrement);
+        assert_eq!(comp.pending_count(), 2);
+        assert!(matches!(comp.pending_messages::<Counter>()[..], [
+            Msg::Decrement,
+            Msg::Decrement
+        ]));
+
+        comp.receive_events();
+        assert_eq!(comp.pending_count(), 0);
+        assert_eq!(comp.model::<Counter>().0, -1);
+    }
+
+    #[test]
+    fn sub_models() {
+        use crate::{builder::*, sub_models, Comp, Dispatch, SubModel};
+
+        struct Score(u32);
+
+        enum ScoreMsg {
+            Add(u32),
+        }
+
+        impl SubModel for Score {
+            type Message = ScoreMsg;
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                match msg {
+                    ScoreMsg::Add(points) => self.0 += points,
+                }
+                ChangeView::Modify
+            }
+        }
+
+        struct Game {
+            score: Score,
+            restarts: u32,
+        }
+
+        enum GameMsg {
+            Score(ScoreMsg),
+            Restart,
+        }
+
+        sub_models!(Game, GameMsg {
+            Score(ScoreMsg) => score,
+        });
+
+        impl Model for Game {
+            type Message = GameMsg;
+            type Properties = ();
+
+            fn create(_props: Self::Properties) -> Self {
+                Game {
+                    score: Score(0),
+                    restarts: 0,
+                }
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                match msg.dispatch(self) {
+                    Ok(change) => change,
+                    Err(_) => {
+                        self.restarts += 1;
+                        ChangeView::Rebuild
+                    },
+                }
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().on_click(|_| ScoreMsg::Add(10).into()).build()
+            }
+        }
+
+        let mut comp = Comp::new(Game::create(()));
+        comp.send::<Game>(ScoreMsg::Add(5).into());
+        comp.send::<Game>(ScoreMsg::Add(2).into());
+        comp.send::<Game>(GameMsg::Restart);
+
+        let game = comp.model::<Game>();
+        assert_eq!(game.score.0, 7);
+        assert_eq!(game.restarts, 1);
+    }
+
+    #[test]
+    fn keyframes() {
+        use crate::{Easing, Keyframes};
+        use std::time::Duration;
+
+        let ms = Duration::from_millis;
+        let mut slide = Keyframes::new(-100.0)
+            .key(ms(200), 10.0, Easing::EaseOut)
+            .key(ms(300), 0.0, Easing::EaseInOut);
+        assert_eq!(slide.duration(), ms(300));
+        assert_eq!(*slide, -100.0);
+
+        slide.animate(ms(100));
+        assert!(*slide > -45.0 && *slide < 10.0);
+        slide.animate(ms(100));
+        assert_eq!(*slide, 10.0);
+        slide.animate(ms(50));
+        assert_eq!(*slide, 5.0);
+        slide.animate(ms(100));
+        assert_eq!(*slide, 0.0);
+        assert!(!slide.is_transient());
+
+        let mut blink = Keyframes::new(0.0).key(ms(100), 1.0, Easing::Step).looping(true);
+        blink.animate(ms(150));
+        assert_eq!(*blink, 0.0);
+        assert_eq!(blink.elapsed(), ms(50));
+    }
+
+    #[test]
+    fn spring() {
+        use crate::Spring;
+        use std::time::Duration;
+
+        let mut spring = Spring::new(0.0, 100.0);
+        spring.animate(Duration::from_millis(100));
+        let (position, velocity) = (*spring, spring.velocity());
+        assert!(position > 0.0 && position < 100.0);
+        assert!(velocity > 0.0);
+
+        spring.to(0.0);
+        spring.animate(Duration::from_millis(1));
+        assert!(*spring > position);
+        assert!(spring.velocity() < velocity);
+
+        spring.animate(Duration::from_secs(5));
+        assert_eq!(*spring, 0.0);
+        assert!(!spring.is_transient());
+    }
+
+    #[test]
+    fn color_animate() {
+        use crate::{Color, ColorAnimate, ColorSpace};
+        use std::time::Duration;
+
+        let mut fade =
+            ColorAnimate::new(Color::Red, Color::Blue, Duration::from_millis(100)).with_space(ColorSpace::Hsl);
+        fade.animate(Duration::from_millis(50));
+        let [r, g, b, a] = fade.as_arr();
+        assert!((r - 1.0).abs() < 1e-5 && g.abs() < 1e-5 && (b - 1.0).abs() < 1e-5);
+        assert_eq!(a, 1.0);
+
+        fade.to(Color::White);
+        assert_eq!(fade.val().as_arr(), [r, g, b, a]);
+        fade.animate(Duration::from_millis(100));
+        assert_eq!(*fade, Color::White);
+        assert!(!fade.is_transient());
+
+        let mid = Color::Black.mix(Color::White, 0.5, ColorSpace::LinearRgb).as_arr();
+        assert!(mid[0] > 0.7 && mid[0] < 0.75);
+    }
+
+    #[test]
+    fn child_callback() {
+        use crate::{builder::*, Callback, Comp, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        struct Child(Callback<i32>);
+
+        impl Model for Child {
+            type Message = ();
+            type Properties = Callback<i32>;
+
+            fn create(props: Self::Properties) -> Self {
+                Child(props)
+            }
+
+            fn update(&mut self, _: Self::Message) -> ChangeView {
+                self.0.emit(1);
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                rect().width(50.0).height(50.0).on_click(|_| ()).build()
+            }
+        }
+
+        let mut parent = Comp::new(Counter(0));
+        let on_click = Callback::new(|step: i32| if step > 0 { Msg::Increment } else { Msg::Decrement });
+        parent
+            .inner_mut::<Counter>()
+            .view_mut()
+            .unwrap()
+            .as_prim_mut()
+            .unwrap()
+            .children = vec![comp_with_props::<Child>(on_click).build()];
+
+        let pos = MousePos { x: 10.0, y: 10.0 };
+        parent.send_system_msg(SystemMessage::Input(InputEvent::mouse_down(pos, MouseButton::Left)));
+        parent.send_system_msg(SystemMessage::Input(InputEvent::mouse_up(pos, MouseButton::Left)));
+        assert_eq!(parent.model::<Counter>().0, 1);
+    }
+
+    #[test]
+    fn timers() {
+        use std::time::Duration;
+
+        use crate::Timers;
+
+        let ms = Duration::from_millis;
+        let mut timers = Timers::new();
+        let tick = timers.every(ms(100), || Msg::Increment);
+        let once = timers.once(ms(150), || Msg::Decrement);
+        let names = |msgs: Vec<Msg>| msgs.iter().map(|msg| matches!(msg, Msg::Increment)).collect::<Vec<_>>();
+
+        assert_eq!(names(timers.advance(ms(50))), vec![]);
+        assert_eq!(names(timers.advance(ms(60))), vec![true]);
+        assert_eq!(names(timers.advance(ms(200))), vec![false, true, true]);
+        assert!(!timers.is_active(once));
+        assert!(timers.cancel(tick));
+        assert!(timers.is_empty());
+        assert_eq!(names(timers.advance(ms(1000))), vec![]);
+    }
+
+    #[test]
+    fn commands() {
+        use std::{
+            cell::{Cell, RefCell},
+            mem,
+            rc::Rc,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            task::{Context, Wake, Waker},
+            time::Duration,
+        };
+
+        use crate::{Command, Comp, SystemMessage};
+
+        #[derive(Default)]
+        struct Loader {
+            value: Option<u32>,
+            commands: Vec<Command<Option<u32>>>,
+        }
+
+        impl Model for Loader {
+            type Message = Option<u32>;
+            type Properties = ();
+
+            fn create(_: Self::Properties) -> Self {
+                Default::default()
+            }
+
+            fn update(&mut self, msg: Self::Message) -> ChangeView {
+                match msg {
+                    None => self.commands.push(Command::new(|| Some((1..=10).sum()))),
+                    Some(0) => self.commands.push(Command::future(async { Some(7) })),
+                    Some(1) => self.commands.push(Command::future(std::future::pending())),
+                    value => self.value = value,
+                }
+                ChangeView::None
+            }
+
+            fn build_view(&self) -> Node<Self> {
+                use crate::builder::*;
+
+                rect().build()
+            }
+
+            fn commands(&mut self) -> Vec<Command<Self::Message>> {
+                mem::take(&mut self.commands)
+            }
+        }
+
+        let mut comp = Comp::new(Loader::default());
+        comp.send::<Loader>(None);
+        for _ in 0..100 {
+            comp.send_system_msg(SystemMessage::Draw(Duration::from_millis(10)));
+            if comp.model::<Loader>().value.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(comp.model::<Loader>().value, Some(55));
+
+        comp.send::<Loader>(Some(0));
+        for _ in 0..100 {
+            comp.send_system_msg(SystemMessage::Draw(Duration::from_millis(10)));
+            if comp.model::<Loader>().value == Some(7) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(comp.model::<Loader>().value, Some(7));
+
+        let spawned = Rc::new(Cell::new(0));
+        comp.set_spawner({
+            let spawned = spawned.clone();
+            move |_| spawned.set(spawned.get() + 1)
+        });
+        comp.send::<Loader>(Some(0));
+        assert_eq!(spawned.get(), 1);
+
+        let mut comp = Comp::new(Loader::default());
+        comp.shutdown();
+        comp.send::<Loader>(Some(0));
+        std::thread::sleep(Duration::from_millis(50));
+        comp.send_system_msg(SystemMessage::Draw(Duration::from_millis(10)));
+        assert_eq!(comp.model::<Loader>().value, None);
+
+        // The pending future is woken by the shutdown and finishes
+        struct CountWakes(AtomicUsize);
+
+        impl Wake for CountWakes {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let tasks = Rc::new(RefCell::new(Vec::new()));
+        let mut comp = Comp::new(Loader::default());
+        comp.set_spawner({
+            let tasks = tasks.clone();
+            move |task| tasks.borrow_mut().push(task)
+        });
+        comp.send::<Loader>(Some(1));
+        let mut task = tasks.borrow_mut().pop().unwrap();
+        let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+        assert!(task.as_mut().poll(&mut cx).is_pending());
+        comp.shutdown();
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+        assert!(task.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn custom_events() {
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            thread,
+        };
+
+        use crate::{Comp, CustomEvent};
+
+        let mut comp = Comp::new(Counter(0));
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let counter = wakes.clone();
+        comp.set_waker(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(!comp.receive_events());
+
+        let sender = comp.sender();
+        thread::spawn(move || {
+            assert!(sender.send(CustomEvent::new(Msg::Increment)));
+            assert!(sender.send(CustomEvent::new(Msg::Increment)));
+            assert!(sender.send(CustomEvent::new("ignored")));
+            assert!(sender.send(CustomEvent::new(Msg::Decrement)));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(wakes.load(Ordering::SeqCst), 4);
+
+        assert!(comp.receive_events());
+        assert_eq!(comp.model::<Counter>().0, 1);
+
+        let sender = comp.message_sender::<Counter>();
+        let progress = sender.clone();
+        thread::spawn(move || {
+            assert!(sender.send(Msg::Increment));
+            assert!(progress.send(Msg::Increment));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(wakes.load(Ordering::SeqCst), 6);
+        assert!(comp.receive_events());
+        assert_eq!(comp.model::<Counter>().0, 3);
+
+        let sender = comp.message_sender::<Counter>();
+        drop(comp);
+        assert!(!sender.send(Msg::Increment));
+    }
+
+    #[test]
+    fn determinism() {
+        use std::time::Duration;
+
+        use crate::{Comp, FixedClock};
+
+        let mut clock = FixedClock::new(Duration::from_millis(10));
+        assert_eq!(clock.advance(Duration::from_millis(25)), 2);
+        assert_eq!(clock.advance(Duration::from_millis(5)), 1);
+        assert_eq!(clock.time(), Duration::from_millis(30));
+
+        assert_eq!(crate::checksum(&1_i32), crate::checksum(&1_i32));
+        assert_ne!(crate::checksum(&1_i32), crate::checksum(&2_i32));
+
+        let mut first = Comp::new(Counter(0));
+        let mut second = Comp::new(Counter(0));
+        first.send::<Counter>(Msg::Increment);
+        assert_ne!(first.checksum(), second.checksum());
+        second.send::<Counter>(Msg::Increment);
+        assert_eq!(first.checksum(), second.checksum());
+        assert!(first.checksum().is_some());
+    }
+
+    #[test]
+    fn mouse_move() {
+        use crate::{builder::*, InputEvent, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .child(rect().width(50.0).height(50.0).on_mouse_move(|_| Msg::Increment))
+            .build();
+        let mut outputs = vec![];
+        let mut move_to = |view: &mut Node<Counter>, x, y| {
+            outputs.clear();
+            let event = InputEvent::mouse_move(MousePos { x, y });
+            view.send_system_msg(SystemMessage::Input(event), &mut outputs);
+            outputs.len()
+        };
+        assert_eq!(move_to(&mut view, 20.0, 20.0), 1);
+        assert_eq!(move_to(&mut view, 80.0, 80.0), 0);
+    }
+
+    #[test]
+    fn mouse_enter_leave() {
+        use crate::{builder::*, InputEvent, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .child(
+                rect()
+                    .id("button")
+                    .width(50.0)
+                    .height(50.0)
+                    .on_mouse_enter(|_| Msg::Increment)
+                    .on_mouse_leave(|_| Msg::Decrement),
+            )
+            .build();
+        let move_to = |view: &mut Node<Counter>, x, y| {
+            let mut outputs = vec![];
+            let event = InputEvent::mouse_move(MousePos { x, y });
+            view.send_system_msg(SystemMessage::Input(event), &mut outputs);
+            outputs
+                .into_iter()
+                .map(|msg| matches!(msg, Msg::Increment))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(move_to(&mut view, 10.0, 10.0), vec![true]);
+        assert!(move_to(&mut view, 20.0, 20.0).is_empty());
+        assert!(view.get_prim("button").unwrap().is_hovered());
+        assert_eq!(move_to(&mut view, 80.0, 80.0), vec![false]);
+        assert!(move_to(&mut view, 90.0, 90.0).is_empty());
+    }
+
+    #[test]
+    fn transformed_and_clipped_pointer() {
+        use crate::{builder::*, InputEvent, MouseButton, MousePos, Overflow, SystemMessage, TransformMatrix};
+
+        let mut view: Node<Counter> = rect()
+            .child(
+                rect().width(50.0).height(50.0).overflow(Overflow::Hidden).child(
+                    rect()
+                        .id("content")
+                        .width(100.0)
+                        .height(100.0)
+                        .on_mouse_enter(|_| Msg::Increment),
+                ),
+            )
+            .child(
+                rect()
+                    .id("field")
+                    .width(20.0)
+                    .height(20.0)
+                    .on_mouse_down(|_| Msg::Decrement),
+            )
+            .build();
+        view.get_prim_mut("field")
+            .unwrap()
+            .transform_mut()
+            .calculate_global(TransformMatrix::identity().with_translation(200.0, 0.0));
+        let pos = |x, y| MousePos { x, y };
+        let mut outputs = vec![];
+
+        view.send_system_msg(
+            SystemMessage::Input(InputEvent::mouse_move(pos(80.0, 80.0))),
+            &mut outputs,
+        );
+        assert!(outputs.is_empty());
+        assert!(!view.get_prim("content").unwrap().is_hovered());
+        view.send_system_msg(
+            SystemMessage::Input(InputEvent::mouse_move(pos(40.0, 40.0))),
+            &mut outputs,
+        );
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+
+        outputs.clear();
+        let press = |x, y| SystemMessage::Input(InputEvent::mouse_down(pos(x, y), MouseButton::Left));
+        view.send_system_msg(press(10.0, 10.0), &mut outputs);
+        assert!(outputs.is_empty());
+        view.send_system_msg(press(210.0, 10.0), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Decrement]));
+    }
+
+    #[test]
+    fn double_click() {
+        use std::time::{Duration, Instant};
+
+        use crate::{builder::*, InputEvent, MouseButton, MouseController, SystemMessage};
+
+        let mut mouse = MouseController::new().with_double_click_interval(Duration::from_millis(300));
+        let start = Instant::now();
+        mouse.update_pos(10.0, 10.0);
+        assert_eq!(mouse.press(MouseButton::Left, start).clicks, 1);
+        let second = mouse.press(MouseButton::Left, start + Duration::from_millis(200));
+        assert_eq!(second.clicks, 2);
+        assert_eq!(
+            mouse
+                .press(MouseButton::Right, start + Duration::from_millis(250))
+                .clicks,
+            1
+        );
+        assert_eq!(
+            mouse
+                .press(MouseButton::Right, start + Duration::from_millis(600))
+                .clicks,
+            1
+        );
+
+        let mut view: Node<Counter> = rect()
+            .width(50.0)
+            .height(50.0)
+            .on_double_click(|_| Msg::Increment)
+            .build();
+        let mut outputs = vec![];
+        view.send_system_msg(SystemMessage::Input(InputEvent::MouseDown(second)), &mut outputs);
+        assert_eq!(outputs.len(), 1);
+        let first = InputEvent::mouse_down(mouse.last_pos(), MouseButton::Left);
+        view.send_system_msg(SystemMessage::Input(first), &mut outputs);
+        assert_eq!(outputs.len(), 1);
+    }
+
+    #[test]
+    fn drag_listeners() {
+        use crate::{builder::*, DragTracker, InputEvent, MouseButton, MousePos};
+
+        let view: Node<Counter> = rect()
+            .child(
+                rect()
+                    .id("card")
+                    .width(50.0)
+                    .height(50.0)
+                    .on_drag_start(|_| Msg::Increment),
+            )
+            .child(
+                rect()
+                    .id("bin")
+                    .left_top_pos(100.0, 0.0)
+                    .width(50.0)
+                    .height(50.0)
+                    .on_drop(|on| {
+                        assert_eq!(on.event.source, "card");
+                        Msg::Decrement
+                    }),
+            )
+            .build();
+        let pos = |x, y| MousePos { x, y };
+        let mut tracker = DragTracker::default();
+        let mut outputs = vec![];
+
+        tracker.handle(
+            &view,
+            InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left),
+            &mut outputs,
+        );
+        tracker.handle(&view, InputEvent::mouse_move(pos(12.0, 10.0)), &mut outputs);
+        assert!(outputs.is_empty() && tracker.drag().is_none());
+
+        tracker.handle(&view, InputEvent::mouse_move(pos(120.0, 10.0)), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+        assert_eq!(tracker.drag().unwrap().target.as_deref(), Some("bin"));
+
+        tracker.handle(
+            &view,
+            InputEvent::mouse_up(pos(120.0, 10.0), MouseButton::Left),
+            &mut outputs,
+        );
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+        assert!(tracker.drag().is_none());
+    }
+
+    #[test]
+    fn mouse_capture() {
+        use crate::{builder::*, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .child(
+                rect()
+                    .id("thumb")
+                    .width(20.0)
+                    .height(20.0)
+                    .capture_mouse()
+                    .on_mouse_move(|_| Msg::Increment)
+                    .on_mouse_up(|_| Msg::Decrement),
+            )
+            .build();
+        let pos = |x, y| MousePos { x, y };
+        let mut outputs = vec![];
+        let mut send = |view: &mut Node<Counter>, event| {
+            view.send_system_msg(SystemMessage::Input(event), &mut outputs);
+            outputs.len()
+        };
+
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(100.0, 10.0))), 0);
+        send(&mut view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        assert!(view.get_prim("thumb").unwrap().has_mouse_capture());
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(100.0, 10.0))), 1);
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_up(pos(100.0, 10.0), MouseButton::Left)),
+            2
+        );
+        assert!(!view.get_prim("thumb").unwrap().has_mouse_capture());
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(120.0, 10.0))), 2);
+    }
+
+    #[test]
+    fn event_phases() {
+        use crate::{builder::*, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .on_mouse_down_capture(|_| Msg::Increment)
+            .on_mouse_down(|_| Msg::Decrement)
+            .child(
+                rect()
+                    .left_top_pos(0.0, 0.0)
+                    .width(50.0)
+                    .height(50.0)
+                    .on_mouse_down(|_| Msg::Decrement),
+            )
+            .child(
+                rect()
+                    .left_top_pos(0.0, 0.0)
+                    .width(50.0)
+                    .height(50.0)
+                    .on_mouse_down(|_| Msg::Increment),
+            )
+            .build();
+        let press = |x, y| SystemMessage::Input(InputEvent::mouse_down(MousePos { x, y }, MouseButton::Left));
+
+        let mut outputs = vec![];
+        view.send_system_msg(press(10.0, 10.0), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [
+            Msg::Increment,
+            Msg::Increment,
+            Msg::Decrement
+        ]));
+
+        outputs.clear();
+        view.send_system_msg(press(70.0, 70.0), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+    }
+
+    #[test]
+    fn stop_propagation() {
+        use crate::{builder::*, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .on_mouse_down(|_| Msg::Decrement)
+            .child(rect().width(50.0).height(50.0).on_mouse_down(|case| {
+                case.stop_propagation();
+                Msg::Increment
+            }))
+            .build();
+        let press = |x, y| SystemMessage::Input(InputEvent::mouse_down(MousePos { x, y }, MouseButton::Left));
+
+        let mut outputs = vec![];
+        view.send_system_msg(press(10.0, 10.0), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+
+        outputs.clear();
+        view.send_system_msg(press(70.0, 70.0), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Decrement]));
+    }
+
+    #[test]
+    fn cursor() {
+        use crate::{builder::*, CursorIcon, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .child(
+                rect()
+                    .width(50.0)
+                    .height(50.0)
+                    .cursor(CursorIcon::Pointer)
+                    .child(rect().width(10.0).height(10.0)),
+            )
+            .child(
+                rect()
+                    .left_top_pos(80.0, 0.0)
+                    .width(10.0)
+                    .height(100.0)
+                    .cursor(CursorIcon::EwResize)
+                    .capture_mouse(),
+            )
+            .build();
+        let prim = view.as_prim().unwrap();
+        assert_eq!(prim.cursor_at(5.0, 5.0), Some(CursorIcon::Pointer));
+        assert_eq!(prim.cursor_at(30.0, 30.0), Some(CursorIcon::Pointer));
+        assert_eq!(prim.cursor_at(60.0, 60.0), None);
+
+        let press = InputEvent::mouse_down(MousePos { x: 85.0, y: 10.0 }, MouseButton::Left);
+        view.send_system_msg(SystemMessage::Input(press), &mut vec![]);
+        assert_eq!(
+            view.as_prim().unwrap().cursor_at(20.0, 20.0),
+            Some(CursorIcon::EwResize)
+        );
+    }
+
+    #[test]
+    fn file_drop() {
+        use crate::{builder::*, InputEvent, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .child(rect().on_file_hover(|_| Msg::Increment))
+            .child(rect().on_file_drop(|case| {
+                if case.event.extension().is_some_and(|ext| ext == "png") {
+                    Msg::Increment
+                } else {
+                    Msg::Decrement
+                }
+            }))
+            .build();
+        let mut outputs = vec![];
+        let send = |view: &mut Node<Counter>, event, outputs: &mut Vec<Msg>| {
+            view.send_system_msg(SystemMessage::Input(event), outputs)
+        };
+
+        send(&mut view, InputEvent::FileHovered("image.png".into()), &mut outputs);
+        send(&mut view, InputEvent::FileHoverCancelled, &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+        send(&mut view, InputEvent::FileDropped("notes.txt".into()), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+    }
+
+    #[test]
+    fn click() {
+        use crate::{builder::*, InputEvent, MouseButton, MousePos, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .child(
+                rect()
+                    .id("button")
+                    .width(50.0)
+                    .height(50.0)
+                    .on_click(|_| Msg::Increment)
+                    .on_mouse_up(|_| Msg::Decrement),
+            )
+            .build();
+        let pos = |x, y| MousePos { x, y };
+        let mut outputs = vec![];
+        let mut send = |view: &mut Node<Counter>, event| {
+            view.send_system_msg(SystemMessage::Input(event), &mut outputs);
+            outputs
+                .iter()
+                .map(|msg| matches!(msg, Msg::Increment))
+                .collect::<Vec<_>>()
+        };
+
+        send(&mut view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        assert!(view.get_prim("button").unwrap().is_pressed());
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_up(pos(20.0, 20.0), MouseButton::Left)),
+            vec![false, true]
+        );
+        assert!(!view.get_prim("button").unwrap().is_pressed());
+
+        send(&mut view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left));
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_up(pos(80.0, 80.0), MouseButton::Left)),
+            vec![false, true, false]
+        );
+    }
+
+    #[test]
+    fn text_selection() {
+        use crate::{builder::*, GlyphPos, InputEvent, MouseButton, MousePos, Shape, SystemMessage, TextMetrics};
+
+        let mut view: Node<Counter> = rect()
+            .width(200.0)
+            .height(100.0)
+            .child(text("hello").id("text").on_select(|on| {
+                if on.event.is_empty() {
+                    Msg::Decrement
+                } else {
+                    Msg::Increment
+                }
+            }))
+            .build();
+        if let Some(Shape::Text(text)) = view.get_prim_mut("text").map(|prim| &mut prim.shape) {
+            text.glyph_positions = (0..5)
+                .map(|idx| GlyphPos {
+                    x: idx as f32 * 10.0,
+                    y: 0.0,
+                    width: 10.0,
+                })
+                .collect();
+            text.metrics = Some(TextMetrics {
+                ascender: 12.0,
+                descender: 4.0,
+                line_height: 16.0,
+            });
+        }
+        let pos = |x, y| MousePos { x, y };
+        let send = |view: &mut Node<Counter>, event| {
+            let mut outputs = vec![];
+            view.send_system_msg(SystemMessage::Input(event), &mut outputs);
+            outputs
+                .iter()
+                .map(|msg| matches!(msg, Msg::Increment))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_down(pos(12.0, 8.0), MouseButton::Left)),
+            vec![false]
+        );
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(13.0, 8.0))), vec![]);
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(150.0, 50.0))), vec![true]);
+        let selection = view.get_prim("text").unwrap().selection().unwrap();
+        assert_eq!(selection.range(), 1..5);
+
+        send(&mut view, InputEvent::mouse_up(pos(150.0, 50.0), MouseButton::Left));
+        assert_eq!(view.get_prim("text").unwrap().selection(), None);
+        assert_eq!(send(&mut view, InputEvent::mouse_move(pos(0.0, 8.0))), vec![]);
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_down(pos(150.0, 8.0), MouseButton::Left)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn state_style() {
+        use crate::{builder::*, Color, Fill, InputEvent, MouseButton, MousePos, Shape, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .child(
+                rect()
+                    .id("button")
+                    .width(50.0)
+                    .height(50.0)
+                    .fill(Color::White)
+                    .fill_hover(Color::Yellow)
+                    .fill_pressed(Color::Blue),
+            )
+            .build();
+        let pos = |x, y| MousePos { x, y };
+        let send = |view: &mut Node<Counter>, event| {
+            view.send_system_msg(SystemMessage::Input(event), &mut vec![]);
+            match &view.get_prim("button").unwrap().shape {
+                Shape::Rect(rect) => rect.fill,
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_move(pos(10.0, 10.0))),
+            Some(Fill::from(Color::Yellow))
+        );
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_down(pos(10.0, 10.0), MouseButton::Left)),
+            Some(Fill::from(Color::Blue))
+        );
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_up(pos(10.0, 10.0), MouseButton::Left)),
+            Some(Fill::from(Color::Yellow))
+        );
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_move(pos(80.0, 80.0))),
+            Some(Fill::from(Color::White))
+        );
+
+        // The fill changed by the model while hovered is restored instead of the previous one
+        send(&mut view, InputEvent::mouse_move(pos(10.0, 10.0)));
+        if let Some(button) = view.get_prim_mut("button") {
+            button.set_fill(Some(Color::Green.into()));
+        }
+        assert_eq!(
+            send(&mut view, InputEvent::mouse_move(pos(80.0, 80.0))),
+            Some(Fill::from(Color::Green))
+        );
+    }
+
+    #[test]
+    fn long_press() {
+        use crate::{builder::*, InputEvent, LongPressRecognizer, MouseButton, MousePos, SystemMessage, Touch};
+        use std::time::Duration;
+
+        let view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .on_long_press(|_| Msg::Increment)
+            .on_long_press_after(Duration::from_secs(1), |_| Msg::Decrement)
+            .build();
+        let pos = |x, y| MousePos { x, y };
+        let frame = SystemMessage::Draw(Duration::from_millis(300));
+        let mut recognizer = LongPressRecognizer::default();
+        let mut outputs = vec![];
+        let mut send = |msg| recognizer.handle(&view, &msg, &mut outputs);
+
+        send(SystemMessage::Input(InputEvent::mouse_down(
+            pos(10.0, 10.0),
+            MouseButton::Left,
+        )));
+        send(frame.clone());
+        send(SystemMessage::Input(InputEvent::mouse_move(pos(14.0, 14.0))));
+        send(frame.clone());
+        send(frame.clone());
+        send(frame.clone());
+        send(frame.clone());
+        send(SystemMessage::Input(InputEvent::mouse_up(
+            pos(14.0, 14.0),
+            MouseButton::Left,
+        )));
+
+        send(SystemMessage::Input(InputEvent::TouchStart(Touch::new(
+            1,
+            pos(50.0, 50.0),
+        ))));
+        send(SystemMessage::Input(InputEvent::TouchMove(Touch::new(
+            1,
+            pos(70.0, 50.0),
+        ))));
+        send(frame.clone());
+        send(frame);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+    }
+
+    #[test]
+    fn scroll_physics() {
+        use crate::{InputEvent, MousePos, MouseScroll, ScrollPhysics, Touch};
+        use std::time::Duration;
+
+        let pos = |x, y| MousePos { x, y };
+        let frame = Duration::from_millis(16);
+        let mut scroll = ScrollPhysics::new((0.0, 500.0));
+
+        assert!(scroll.handle(&InputEvent::mouse_scroll(MouseScroll::lines(
+            pos(0.0, 0.0),
+            (0.0, -2.0)
+        ))));
+        assert!(scroll.animate(frame));
+        assert!(scroll.offset().1 > 0.0 && scroll.offset().1 < 40.0);
+        while scroll.animate(frame) {}
+        assert_eq!(scroll.offset(), (0.0, 40.0));
+
+        scroll.handle(&InputEvent::TouchStart(Touch::new(1, pos(10.0, 100.0))));
+        scroll.handle(&InputEvent::TouchMove(Touch::new(1, pos(10.0, 80.0))));
+        assert_eq!(scroll.offset(), (0.0, 60.0));
+        scroll.animate(frame);
+        scroll.handle(&InputEvent::TouchEnd(Touch::new(1, pos(10.0, 80.0))));
+        assert!(scroll.animate(frame));
+        assert!(scroll.offset().1 > 60.0);
+        while scroll.animate(frame) {}
+        assert!(!scroll.is_transient());
+        assert!(scroll.offset().1 > 100.0 && scroll.offset().1 <= 500.0);
+
+        scroll.set_offset((0.0, 1000.0));
+        assert_eq!(scroll.offset(), (0.0, 500.0));
+    }
+
+    #[test]
+    fn access_action() {
+        use crate::{builder::*, AccessAction, AccessRequest, SystemMessage};
+
+        let mut view: Node<Counter> = rect()
+            .child(rect().id("plus").on_access_action(|on| match on.event {
+                AccessAction::Activate => Msg::Increment,
+                _ => Msg::Decrement,
+            }))
+            .build();
+        let mut send = |target, action| {
+            let mut outputs = vec![];
+            let request = AccessRequest::new(target, action);
+            view.send_system_msg(SystemMessage::Access(request), &mut outputs);
+            outputs
+                .iter()
+                .map(|msg| matches!(msg, Msg::Increment))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(send("plus", AccessAction::Activate), vec![true]);
+        assert_eq!(send("plus", AccessAction::Focus), vec![false]);
+        assert_eq!(send("minus", AccessAction::Activate), vec![]);
+    }
+
+    #[test]
+    fn context_menu() {
+        use crate::{builder::*, send_context_menu, MouseButton, MouseDown, MousePos};
+
+        let view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .on_context_menu(|_| Msg::Decrement)
+            .child(rect().width(50.0).height(50.0).on_context_menu(|_| Msg::Increment))
+            .child(
+                group()
+                    .overlay()
+                    .child(rect().left_top_pos(60.0, 60.0).width(30.0).height(30.0)),
+            )
+            .build();
+        let press = |x, y, button| MouseDown {
+            pos: MousePos { x, y },
+            button,
+            clicks: 1,
+        };
+        let mut outputs = vec![];
+
+        send_context_menu(&view, press(10.0, 10.0, MouseButton::Left), &mut outputs);
+        assert!(outputs.is_empty());
+        send_context_menu(&view, press(10.0, 10.0, MouseButton::Right), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+        send_context_menu(&view, press(55.0, 55.0, MouseButton::Right), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+        send_context_menu(&view, press(70.0, 70.0, MouseButton::Right), &mut outputs);
+        assert_eq!(outputs.len(), 2);
+    }
+
+    #[test]
+    fn key_repeat() {
+        use crate::{KeyboardController, KeyboardEvent, VirtualKeyCode};
+
+        let mut keyboard = KeyboardController::new();
+        let key = KeyboardEvent::new(30, Some(VirtualKeyCode::A));
+        assert!(!keyboard.press(key).is_repeat());
+        assert_eq!(keyboard.press(key).repeat, 1);
+        assert_eq!(keyboard.press(key).repeat, 2);
+        assert!(!keyboard.release(key).is_repeat());
+        assert!(!keyboard.press(key).is_repeat());
+    }
+
+    #[test]
+    fn keymap() {
+        use crate::{KeyboardController, KeyboardEvent, VirtualKeyCode};
+
+        let mut keyboard = KeyboardController::new();
+        let q = KeyboardEvent::new(16, Some(VirtualKeyCode::Q));
+        let shift = KeyboardEvent::new(42, Some(VirtualKeyCode::LShift));
+        let ctrl = KeyboardEvent::new(29, Some(VirtualKeyCode::LControl));
+        assert_eq!(keyboard.char_for(&q), Some('q'));
+
+        // The AZERTY layout types 'a' by the key of 'q' on the US one.
+        keyboard.press(q);
+        keyboard.typed('a');
+        keyboard.release(q);
+        assert_eq!(keyboard.char_for(&q), Some('a'));
+
+        keyboard.press(shift);
+        assert_eq!(keyboard.char_for(&q), Some('Q'));
+        keyboard.press(q);
+        keyboard.typed('A');
+        keyboard.release(q);
+        keyboard.release(shift);
+        assert_eq!(keyboard.char_for(&q), Some('a'));
+
+        keyboard.press(ctrl);
+        keyboard.press(q);
+        keyboard.typed('\u{11}');
+        keyboard.release(q);
+        keyboard.release(ctrl);
+        keyboard.press(shift);
+        assert_eq!(keyboard.char_for(&q), Some('A'));
+    }
+
+    #[test]
+    fn touch() {
+        use crate::{builder::*, InputEvent, MousePos, SystemMessage, Touch};
+
+        let mut view: Node<Counter> = rect()
+            .width(50.0)
+            .height(50.0)
+            .on_touch_start(|on| {
+                assert_eq!(on.event.id, 3);
+                Msg::Increment
+            })
+            .on_touch_end(|_| Msg::Decrement)
+            .build();
+        let touch = |x, y| Touch::new(3, MousePos { x, y });
+        let mut outputs = vec![];
+        view.send_system_msg(
+            SystemMessage::Input(InputEvent::TouchStart(touch(10.0, 10.0))),
+            &mut outputs,
+        );
+        view.send_system_msg(
+            SystemMessage::Input(InputEvent::TouchMove(touch(20.0, 10.0))),
+            &mut outputs,
+        );
+        view.send_system_msg(
+            SystemMessage::Input(InputEvent::TouchEnd(touch(80.0, 10.0))),
+            &mut outputs,
+        );
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+    }
+
+    #[test]
+    fn gestures() {
+        use crate::{builder::*, GestureRecognizer, InputEvent, MousePos, Touch};
+
+        let view: Node<Counter> = rect()
+            .width(100.0)
+            .height(100.0)
+            .on_pinch(|on| {
+                assert!((on.event.scale - 2.0).abs() < 1e-5);
+                Msg::Increment
+            })
+            .on_rotate(|_| Msg::Decrement)
+            .build();
+        let touch = |id, x, y| Touch::new(id, MousePos { x, y });
+        let mut gestures = GestureRecognizer::default();
+        let mut outputs = vec![];
+
+        gestures.handle(&view, InputEvent::TouchStart(touch(1, 40.0, 50.0)), &mut outputs);
+        gestures.handle(&view, InputEvent::TouchStart(touch(2, 60.0, 50.0)), &mut outputs);
+        gestures.handle(&view, InputEvent::TouchMove(touch(2, 80.0, 50.0)), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+
+        gestures.handle(&view, InputEvent::TouchMove(touch(2, 40.0, 90.0)), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment, Msg::Decrement]));
+    }
+
+    #[test]
+    fn pixel_scroll() {
+        use crate::{MousePos, MouseScroll, ScrollSource};
+
+        let scroll = MouseScroll::pixels(MousePos::default(), (0.0, 30.0));
+        assert_eq!(scroll.source, ScrollSource::Touchpad);
+        assert_eq!(scroll.delta, (0.0, 30.0 / MouseScroll::LINE_PIXELS));
+        assert_eq!(scroll.raw_delta, (0.0, 30.0));
+        assert!(!scroll.source.is_discrete());
+    }
+
+    #[test]
+    fn gamepad() {
+        use crate::{builder::*, GamepadAxis, GamepadButton, GamepadController, GamepadId, InputEvent, SystemMessage};
+
+        let pad = GamepadId(0);
+        let mut gamepads = GamepadController::new();
+        let press = gamepads.button(pad, GamepadButton::South, true).unwrap();
+        assert!(gamepads.button(pad, GamepadButton::South, true).is_none());
+        assert!(gamepads.is_pressed(pad, GamepadButton::South));
+        assert!(gamepads.axis_moved(pad, GamepadAxis::LeftStickX, 0.05).is_none());
+        assert_eq!(
+            gamepads.axis_moved(pad, GamepadAxis::LeftStickX, 0.5).unwrap().value,
+            0.5
+        );
+        gamepads.disconnected(pad);
+        assert_eq!(gamepads.axis(pad, GamepadAxis::LeftStickX), 0.0);
+
+        let mut view: Node<Counter> = rect()
+            .on_gamepad_button(|on| {
+                if on.event.pressed {
+                    Msg::Increment
+                } else {
+                    Msg::Decrement
+                }
+            })
+            .build();
+        let mut outputs = vec![];
+        view.send_system_msg(SystemMessage::Input(InputEvent::GamepadButton(press)), &mut outputs);
+        assert!(matches!(outputs.as_slice(), [Msg::Increment]));
+    }
+
+    #[test]
+    fn physics_bridge() {
+        use crate::{builder::*, BodyPose, CollisionEvent, Contact, PhysicsBridge, PhysicsWorld, Real, Transform};
+        use std::time::Duration;
+
+        // The ball falls with 10 units per step and hits the floor at 25.
+        #[derive(Default)]
+        struct Falling {
+            ys: [Real; 2],
+            contacts: Vec<Contact<usize>>,
+        }
+
+        impl PhysicsWorld for Falling {
+            type Body = usize;
+
+            fn step(&mut self, _dt: Duration) {
+                self.ys[0] += 10.0;
+                if self.ys[0] > 25.0 && self.ys[0] <= 35.0 {
+                    self.contacts.push(Contact::Started(0, 1));
+                }
+            }
+
+            fn pose(&self, body: usize) -> Option<BodyPose> {
+                self.ys.get(body).map(|&y| BodyPose {
+                    x: 0.0,
+                    y,
+                    rotation: 0.0,
+                })
+            }
+
+            fn drain_contacts(&mut self) -> Vec<Contact<usize>> {
+                std::mem::take(&mut self.contacts)
+            }
+        }
+
+        let mut view: Node<Counter> = rect().child(rect().id("ball")).child(rect().id("floor")).build();
+        let mut bridge = PhysicsBridge::new(Falling::default(), Duration::from_millis(10));
+        bridge.bind("ball", 0);
+        bridge.bind("floor", 1);
+        assert_eq!(bridge.node_id(0), Some("ball"));
+
+        assert_eq!(bridge.advance(Duration::from_millis(25)), 2);
+        bridge.sync(&mut view);
+        assert_eq!(
+            view.get_mut("ball").map(|node| *node.transform_mut()),
+            Some(Transform::new().with_translation(0.0, 20.0))
+        );
+        assert_eq!(
+            view.get_mut("floor").map(|node| *node.transform_mut()),
+            Some(Transform::new())
+        );
+        assert!(bridge.drain_collisions().is_empty());
+
+        assert_eq!(bridge.advance(Duration::from_millis(5)), 1);
+        assert_eq!(bridge.drain_collisions(), vec![CollisionEvent::Started {
+            first: "ball".to_string(),
+            second: "floor".to_string(),
+        }]);
+
+        // The unbound bodies are not mirrored.
+        assert_eq!(bridge.unbind("ball"), Some(0));
+        bridge.advance(Duration::from_millis(10));
+        bridge.sync(&mut view);
+        assert_eq!(
+            view.get_mut("ball").map(|node| *node.transform_mut()),
+            Some(Transform::new().with_translation(0.0, 20.0))
+        );
+    }
+
+    #[cfg(feature = "rapier2d")]
+    #[test]
+    fn rapier_world() {
+        use crate::{builder::*, rapier2d::prelude::*, CollisionEvent, PhysicsBridge, RapierWorld, Transform};
+        use std::time::Duration;
+
+        let mut world = RapierWorld::new(0.0, 100.0);
+        let ball = world.insert(
+            RigidBodyBuilder::dynamic().translation(vector![50.0, 0.0]),
+            ColliderBuilder::ball(5.0),
+        );
+        let ground = world.insert(
+            RigidBodyBuilder::fixed().translation(vector![50.0, 100.0]),
+            ColliderBuilder::cuboid(100.0, 5.0),
+        );
+
+        let mut view: Node<Counter> = rect().child(rect().id("ball")).child(rect().id("ground")).build();
+        let mut bridge = PhysicsBridge::new(world, Duration::from_millis(10));
+        bridge.bind("ball", ball);
+        bridge.bind("ground", ground);
+
+        let mut collisions = vec![];
+        for _ in 0..100 {
+            bridge.advance(Duration::from_millis(40));
+            collisions.extend(bridge.drain_collisions());
+        }
+        assert_eq!(collisions.len(), 1);
+        assert!(matches!(
+            &collisions[0],
+            CollisionEvent::Started { first, second }
+                if (first.as_str(), second.as_str()) == ("ball", "ground")
+                    || (first.as_str(), second.as_str()) == ("ground", "ball")
+        ));
+
+        // The ball rests on the ground.
+        bridge.sync(&mut view);
+        let y = match view.get_mut("ball").map(|node| *node.transform_mut()) {
+            Some(Transform::Local(matrix)) => matrix.matrix[5],
+            transform => panic!("Unexpected transform: {:?}", transform),
+        };
+        assert!((y - 90.0).abs() < 1.0, "{}", y);
+        assert_eq!(
+            view.get_mut("ground").map(|node| *node.transform_mut()),
+            Some(Transform::new().with_translation(50.0, 100.0))
+        );
+    }
 }
@@ -1,8 +1,8 @@
-use std::{env, time::Duration};
+use std::{env, rc::Rc, time::Duration};
 
 use engel::{
-    builder::*, Animate, ChangeView, Color, LineCap, LineJoin, Model, Node, PathCommand::*, Pct, Real, Shaped, Stroke,
-    SystemMessage, Transform, VirtualKeyCode,
+    builder::*, Animate, ChangeView, Color, Fragment, LineCap, LineJoin, Model, Node, PathCommand, PathCommand::*, Pct,
+    Real, Shaped, Stroke, SystemMessage, Tile, TileAtlas, TileId, Transform, VirtualKeyCode,
 };
 use engel_controller_glutin::App;
 use engel_render_pathfinder::PathfinderRender as Render;
@@ -116,6 +116,7 @@ struct Game {
     level: Level,
     state: GameState,
     docker: Docker,
+    cell_box: Fragment<Game, Real>,
 }
 
 impl Game {
@@ -229,6 +230,7 @@ impl Model for Game {
             level: Level::new(),
             state: GameState::Run,
             docker: Default::default(),
+            cell_box: Fragment::new(Self::build_box),
         };
         game.reset_docker();
         game
@@ -295,7 +297,7 @@ impl Model for Game {
     }
 
     fn build_view(&self) -> Node<Self> {
-        let mut cells = vec![];
+        let mut board = vec![None; self.level.rows() * self.level.cols()];
         let mut docker = None;
         let mut boxes = vec![];
         let (field_x, field_y) = self.field_pos();
@@ -304,29 +306,34 @@ impl Model for Game {
             for col in 0..self.level.cols() {
                 let x = field_x + col as Real * self.canvas.cell_size;
                 let y = field_y + row as Real * self.canvas.cell_size;
+                let tile = &mut board[row * self.level.cols() + col];
                 match self.level.cell(row, col).expect("Cell expected") {
-                    Cell::Wall => cells.push(self.build_wall(x, y)),
-                    Cell::Box => boxes.push(self.build_box(row, col, x, y)),
+                    Cell::Wall => *tile = Some(Self::WALL_TILE),
+                    Cell::Box => boxes.push(self.build_box_instance(row, col, x, y)),
                     Cell::BoxOnPlace => {
-                        cells.push(self.build_place(x, y));
-                        boxes.push(self.build_box(row, col, x, y));
+                        *tile = Some(Self::PLACE_TILE);
+                        boxes.push(self.build_box_instance(row, col, x, y));
                     },
                     Cell::Docker => docker = Some(self.build_docker(x, y)),
                     Cell::DockerOnPlace => {
-                        cells.push(self.build_place(x, y));
+                        *tile = Some(Self::PLACE_TILE);
                         docker = Some(self.build_docker(x, y));
                     },
-                    Cell::Place => cells.push(self.build_place(x, y)),
+                    Cell::Place => *tile = Some(Self::PLACE_TILE),
                     _ => (),
                 }
             }
         }
-        for cell_box in boxes {
-            cells.push(cell_box);
-        }
-        if let Some(docker) = docker {
-            cells.push(docker);
-        }
+        let cell_size = self.canvas.cell_size;
+        let board = tile_map(
+            self.level.cols(),
+            self.level.rows(),
+            cell_size,
+            cell_size,
+            Rc::new(Self::build_atlas(cell_size)),
+        )
+        .layer(board)
+        .transform(translate(field_x, field_y));
 
         rect()
             .width(Pct(100))
@@ -337,7 +344,9 @@ impl Model for Game {
                 group()
                     .id("field")
                     .transform(self.field_transform())
-                    .children(cells)
+                    .child(board)
+                    .children(boxes)
+                    .maybe_child(docker)
                     .child(
                         group().id("info").transparency(1.0).child(
                             rect()
@@ -384,7 +393,7 @@ impl Model for Game {
             }
         }
         if let GameState::LevelComplete = self.state {
-            if let Some(info) = view.get_prim_mut("info").and_then(|info| info.shape.group_mut()) {
+            if let Some(info) = view.get_prim_mut("info").and_then(|info| info.shape_mut().group_mut()) {
                 info.transparency = None;
             }
         }
@@ -392,78 +401,79 @@ impl Model for Game {
 }
 
 impl Game {
-    fn build_wall(&self, x: Real, y: Real) -> Node<Self> {
-        let brick_color = Color::RGB(1.0, 0.4, 0.2);
-        let brick_space = self.canvas.cell_size / 15.0;
-        let brick_height = self.canvas.cell_size / 2.0 - brick_space;
-        let brick_chunk_size = (self.canvas.cell_size - brick_space) / 3.0;
-        let round_radius = brick_space / 1.5;
-        let epsilon = self.canvas.cell_size / 100.0;
+    const PLACE_TILE: TileId = 1;
+    const WALL_TILE: TileId = 0;
+
+    fn build_atlas(cell_size: Real) -> TileAtlas {
+        let brick_space = cell_size / 15.0;
+        let brick_height = cell_size / 2.0 - brick_space;
+        let brick_chunk_size = (cell_size - brick_space) / 3.0;
+        let epsilon = cell_size / 100.0;
+        let second_row_y = brick_height + brick_space * 1.5;
+
+        let wall = [
+            (-epsilon, brick_space / 2.0, brick_chunk_size + epsilon),
+            (
+                brick_chunk_size + epsilon + brick_space,
+                brick_space / 2.0,
+                brick_chunk_size * 2.0 + epsilon,
+            ),
+            (-epsilon, second_row_y, brick_chunk_size * 2.0 + epsilon),
+            (
+                brick_chunk_size * 2.0 + epsilon + brick_space,
+                second_row_y,
+                brick_chunk_size + epsilon,
+            ),
+        ]
+        .iter()
+        .flat_map(|&(x, y, width)| rect_cmd(x, y, width, brick_height))
+        .collect();
+
+        let center = cell_size / 2.0;
+        let place_radius = cell_size * 0.5 / 2.0_f32.sqrt();
+        let place = vec![
+            Move([center, center - place_radius]),
+            Line([center + place_radius, center]),
+            Line([center, center + place_radius]),
+            Line([center - place_radius, center]),
+            Close,
+        ];
+
+        TileAtlas::new()
+            .with(Tile {
+                cmd: wall,
+                fill: Some(Color::RGB(1.0, 0.4, 0.2).into()),
+                ..Default::default()
+            })
+            .with(Tile {
+                cmd: place,
+                fill: Some(Color::RGB(0.2, 0.6, 1.0).into()),
+                ..Default::default()
+            })
+    }
 
-        rect()
-            .id("wall")
-            .width(self.canvas.cell_size)
-            .height(self.canvas.cell_size)
-            .transparency(1.0)
+    fn build_box_instance(&self, row: usize, col: usize, x: Real, y: Real) -> Node<Self> {
+        self.cell_box
+            .instance(&self.canvas.cell_size)
+            .id(format!("box_{}_{}", row, col))
             .transform(translate(x, y))
-            .child(
-                rect()
-                    .width(brick_chunk_size + epsilon)
-                    .height(brick_height)
-                    .fill(brick_color)
-                    .rounding_top_right(round_radius)
-                    .rounding_bottom_right(round_radius)
-                    .transform(translate(-epsilon, brick_space / 2.0)),
-            )
-            .child(
-                rect()
-                    .width(brick_chunk_size * 2.0 + epsilon)
-                    .height(brick_height)
-                    .fill(brick_color)
-                    .rounding_top_left(round_radius)
-                    .rounding_bottom_left(round_radius)
-                    .transform(translate(brick_chunk_size + epsilon + brick_space, brick_space / 2.0)),
-            )
-            .child(
-                rect()
-                    .width(brick_chunk_size * 2.0 + epsilon)
-                    .height(brick_height)
-                    .fill(brick_color)
-                    .rounding_top_right(round_radius)
-                    .rounding_bottom_right(round_radius)
-                    .transform(translate(-epsilon, brick_height + brick_space * 1.5)),
-            )
-            .child(
-                rect()
-                    .width(brick_chunk_size + epsilon)
-                    .height(brick_height)
-                    .fill(brick_color)
-                    .rounding_top_left(round_radius)
-                    .rounding_bottom_left(round_radius)
-                    .transform(translate(
-                        brick_chunk_size * 2.0 + epsilon + brick_space,
-                        brick_height + brick_space * 1.5,
-                    )),
-            )
             .build()
     }
 
-    fn build_box(&self, row: usize, col: usize, x: Real, y: Real) -> Node<Self> {
+    fn build_box(&cell_size: &Real) -> Node<Self> {
         let board_color = Color::RGB(1.0, 0.7, 0.1);
-        let board_space = self.canvas.cell_size / 15.0;
+        let board_space = cell_size / 15.0;
         let board_space_half = board_space / 2.0;
-        let board_chunk_size = (self.canvas.cell_size - board_space * 3.0) / 3.0;
+        let board_chunk_size = (cell_size - board_space * 3.0) / 3.0;
         let round_radius = 1.0;
 
         rect()
-            .id(format!("box_{}_{}", row, col))
-            .width(self.canvas.cell_size)
-            .height(self.canvas.cell_size)
+            .width(cell_size)
+            .height(cell_size)
             .transparency(1.0)
-            .transform(translate(x, y))
             .child(
                 rect()
-                    .width(self.canvas.cell_size - board_space)
+                    .width(cell_size - board_space)
                     .height(board_chunk_size)
                     .fill(board_color)
                     .rounding(round_radius)
@@ -504,7 +514,7 @@ impl Game {
             )
             .child(
                 rect()
-                    .width(self.canvas.cell_size - board_space)
+                    .width(cell_size - board_space)
                     .height(board_chunk_size)
                     .fill(board_color)
                     .rounding(round_radius)
@@ -554,36 +564,16 @@ impl Game {
             )
             .build()
     }
+}
 
-    fn build_place(&self, x: Real, y: Real) -> Node<Self> {
-        let place_color = Color::RGB(0.2, 0.6, 1.0);
-        let place_size = self.canvas.cell_size * 0.5;
-        let place_diagonal = (2.0 * place_size.powi(2)).sqrt();
-        let round_radius = 1.0;
-
-        rect()
-            .id("place")
-            .width(self.canvas.cell_size)
-            .height(self.canvas.cell_size)
-            .transparency(1.0)
-            .transform(translate(x, y))
-            .child(
-                rect()
-                    .width(place_size)
-                    .height(place_size)
-                    .fill(place_color)
-                    .rounding(round_radius)
-                    .transform(
-                        Transform::new()
-                            .with_rotation(std::f32::consts::PI / 4.0)
-                            .with_translation(
-                                self.canvas.cell_size / 2.0,
-                                (self.canvas.cell_size - place_diagonal) / 2.0,
-                            ),
-                    ),
-            )
-            .build()
-    }
+fn rect_cmd(x: Real, y: Real, width: Real, height: Real) -> Vec<PathCommand> {
+    vec![
+        Move([x, y]),
+        LineAlonX(x + width),
+        LineAlonY(y + height),
+        LineAlonX(x),
+        Close,
+    ]
 }
 
 fn main() -> anyhow::Result<()> {
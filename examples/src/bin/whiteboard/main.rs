@@ -0,0 +1,252 @@
+use std::{env, fs, time::Instant};
+
+use engel::{
+    builder::*, pressure_by_speed, ChangeView, Color, InkPoint, InkStroke, InputEvent, Model, MouseButton, Node,
+    PathCommand, Real, Shaped, SystemMessage, VirtualKeyCode,
+};
+use engel_controller_glutin::App;
+use engel_render_pathfinder::PathfinderRender as Render;
+
+enum Msg {
+    Resize { width: Real, height: Real },
+    PenDown(Real, Real, Option<Real>),
+    PenMove(Real, Real, Option<Real>),
+    PenUp,
+    KeyDown(VirtualKeyCode),
+}
+
+struct Whiteboard {
+    width: Real,
+    height: Real,
+    strokes: Vec<InkStroke>,
+    undone: Vec<InkStroke>,
+    current: Option<InkStroke>,
+    last_move: Instant,
+    color: Color,
+    pen_width: Real,
+}
+
+impl Whiteboard {
+    const CURRENT_STROKE_ID: &'static str = "current_stroke";
+    const HEIGHT: Real = 600.0;
+    const SIMPLIFY_TOLERANCE: Real = 0.75;
+    const SVG_PATH: &'static str = "whiteboard.svg";
+    const WIDTH: Real = 800.0;
+
+    fn pen_down(&mut self, x: Real, y: Real, force: Option<Real>) {
+        let mut stroke = InkStroke::new(self.pen_width, self.color);
+        stroke.push(InkPoint::new(x, y, force.unwrap_or(1.0)));
+        self.current = Some(stroke);
+        self.last_move = Instant::now();
+    }
+
+    /// Uses the pressure of the pen if it is reported, otherwise simulates it by the speed.
+    fn pen_move(&mut self, x: Real, y: Real, force: Option<Real>) -> bool {
+        let stroke = match self.current.as_mut() {
+            Some(stroke) => stroke,
+            None => return false,
+        };
+
+        let elapsed = self.last_move.elapsed().as_secs_f32().max(0.001);
+        self.last_move = Instant::now();
+        let pressure = match (force, stroke.points.last()) {
+            (Some(force), _) => force,
+            (None, Some(last)) => pressure_by_speed((x - last.x).hypot(y - last.y) / elapsed),
+            (None, None) => 1.0,
+        };
+        stroke.push(InkPoint::new(x, y, pressure));
+        true
+    }
+
+    fn pen_up(&mut self) -> bool {
+        match self.current.take() {
+            Some(mut stroke) => {
+                stroke.simplify(Self::SIMPLIFY_TOLERANCE);
+                self.strokes.push(stroke);
+                self.undone.clear();
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn undo(&mut self) -> bool {
+        match self.strokes.pop() {
+            Some(stroke) => {
+                self.undone.push(stroke);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(stroke) => {
+                self.strokes.push(stroke);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn export_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+            self.width, self.height
+        );
+        for stroke in &self.strokes {
+            let [r, g, b, a] = stroke.color.as_arr();
+            svg.push_str(&format!(
+                "  <path d=\"{}\" fill=\"rgb({}, {}, {})\" fill-opacity=\"{}\"/>\n",
+                PathCommand::to_svg(&stroke.outline()),
+                (r * 255.0).round(),
+                (g * 255.0).round(),
+                (b * 255.0).round(),
+                a
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl Model for Whiteboard {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties) -> Self {
+        Self {
+            width: Self::WIDTH,
+            height: Self::HEIGHT,
+            strokes: vec![],
+            undone: vec![],
+            current: None,
+            last_move: Instant::now(),
+            color: Color::Black,
+            pen_width: 6.0,
+        }
+    }
+
+    fn system_update(&mut self, msg: SystemMessage) -> Option<Self::Message> {
+        match msg {
+            SystemMessage::WindowResized { width, height } => Some(Msg::Resize {
+                width: width as Real,
+                height: height as Real,
+            }),
+            SystemMessage::Input(InputEvent::MouseDown(event)) if event.button == MouseButton::Left => {
+                Some(Msg::PenDown(event.pos.x, event.pos.y, None))
+            },
+            SystemMessage::Input(InputEvent::MouseMove(event)) => Some(Msg::PenMove(event.pos.x, event.pos.y, None)),
+            SystemMessage::Input(InputEvent::MouseUp(event)) if event.button == MouseButton::Left => Some(Msg::PenUp),
+            SystemMessage::Input(InputEvent::TouchStart(touch)) => {
+                Some(Msg::PenDown(touch.pos.x, touch.pos.y, touch.force))
+            },
+            SystemMessage::Input(InputEvent::TouchMove(touch)) => {
+                Some(Msg::PenMove(touch.pos.x, touch.pos.y, touch.force))
+            },
+            SystemMessage::Input(InputEvent::TouchEnd(_)) => Some(Msg::PenUp),
+            SystemMessage::Input(InputEvent::KeyDown(event)) => event.keycode.map(Msg::KeyDown),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ChangeView {
+        match msg {
+            Msg::Resize { width, height } => {
+                self.width = width;
+                self.height = height;
+                ChangeView::Rebuild
+            },
+            Msg::PenDown(x, y, force) => {
+                self.pen_down(x, y, force);
+                ChangeView::Modify
+            },
+            Msg::PenMove(x, y, force) if self.pen_move(x, y, force) => ChangeView::Modify,
+            Msg::PenUp if self.pen_up() => ChangeView::Rebuild,
+            Msg::KeyDown(code) => match code {
+                VirtualKeyCode::Z if self.undo() => ChangeView::Rebuild,
+                VirtualKeyCode::Y if self.redo() => ChangeView::Rebuild,
+                VirtualKeyCode::C => {
+                    self.undone.extend(self.strokes.drain(..).rev());
+                    ChangeView::Rebuild
+                },
+                VirtualKeyCode::S => {
+                    if let Err(err) = fs::write(Self::SVG_PATH, self.export_svg()) {
+                        eprintln!("Can't export the whiteboard: {}", err);
+                    }
+                    ChangeView::None
+                },
+                VirtualKeyCode::Key1 => {
+                    self.color = Color::Black;
+                    ChangeView::None
+                },
+                VirtualKeyCode::Key2 => {
+                    self.color = Color::RGB(0.85, 0.2, 0.2);
+                    ChangeView::None
+                },
+                VirtualKeyCode::Key3 => {
+                    self.color = Color::RGB(0.2, 0.45, 0.85);
+                    ChangeView::None
+                },
+                _ => ChangeView::None,
+            },
+            _ => ChangeView::None,
+        }
+    }
+
+    fn build_view(&self) -> Node<Self> {
+        let strokes = self
+            .strokes
+            .iter()
+            .map(|stroke| path(stroke.outline()).fill(stroke.color).build());
+
+        rect()
+            .width(self.width)
+            .height(self.height)
+            .fill(Color::White)
+            .children_iter(strokes)
+            .child(path(vec![]).id(Self::CURRENT_STROKE_ID).fill(self.color))
+            .child(
+                text("Z: undo, Y: redo, C: clear, S: export to SVG, 1-3: color")
+                    .font_name("Roboto-Regular")
+                    .font_size(14)
+                    .fill(Color::RGB(0.5, 0.5, 0.5))
+                    .transform(translate(10.0, 10.0)),
+            )
+            .build()
+    }
+
+    fn modify_view(&mut self, view: &mut Node<Self>) {
+        let (cmd, color) = match self.current.as_ref() {
+            Some(stroke) => (stroke.outline(), stroke.color),
+            None => (vec![], self.color),
+        };
+
+        if let Some(prim) = view.get_prim_mut(Self::CURRENT_STROKE_ID) {
+            if let Some(path) = prim.shape.path_mut() {
+                path.cmd = cmd;
+                path.fill = Some(color.into());
+            }
+            prim.invalidate_layout();
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let font_path = env::current_dir()?
+        .join("examples")
+        .join("resources")
+        .join("Roboto-Regular.ttf");
+
+    App::new(Render::default())
+        .with_title("Whiteboard example")
+        .with_inner_size(Whiteboard::WIDTH, Whiteboard::HEIGHT)
+        .with_vsync(true)
+        .with_double_buffer(true)
+        .with_multisampling(8)
+        .with_srgb(true)
+        .with_font("Roboto-Regular", font_path)
+        .run(Whiteboard::create(()))
+        .map_err(Into::into)
+}
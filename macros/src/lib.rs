@@ -0,0 +1,36 @@
+use proc_macro::TokenStream;
+use syn::parse_macro_input;
+
+use self::view::Element;
+
+mod view;
+
+/// Declares a view with a markup syntax and expands it into the builder calls.
+///
+/// ```ignore
+/// view! {
+///     <rect fill={Color::WHITE} padding=10>
+///         <text("Counter") font_size=20 />
+///         { counter_text }
+///         if self.0 > 0 {
+///             <rect on_mouse_down={|_| Msg::Decrement}>
+///                 <text("-") />
+///             </rect>
+///         }
+///         for item in &self.items {
+///             <text(item.name.clone()) />
+///         }
+///     </rect>
+/// }
+/// ```
+///
+/// Every element `<name(args) attr=value ... />` becomes `name(args).attr(value)...`,
+/// so any builder function and method in scope can be used. Attribute values
+/// are literals, `{ expressions }` or `(argument, lists)` for methods with several
+/// arguments, and an attribute without a value calls a method without arguments.
+/// The whole view evaluates to a built `Node`.
+#[proc_macro]
+pub fn view(input: TokenStream) -> TokenStream {
+    let root = parse_macro_input!(input as Element);
+    root.node_tokens().into()
+}
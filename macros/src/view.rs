@@ -0,0 +1,288 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    braced,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token, Block, Error, Expr, Ident, Lit, Pat, Path, Result, Token,
+};
+
+pub struct Element {
+    name: Path,
+    args: Punctuated<Expr, Token![,]>,
+    attrs: Vec<Attribute>,
+    children: Vec<Child>,
+}
+
+impl Element {
+    /// Tokens of the built `Node` expression.
+    pub fn node_tokens(&self) -> TokenStream {
+        quote! { (#self).build() }
+    }
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![<]>()?;
+        let name = Path::parse_mod_style(input)?;
+
+        let args = if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            content.parse_terminated(Expr::parse)?
+        } else {
+            Punctuated::new()
+        };
+
+        let mut attrs = Vec::new();
+        while !input.peek(Token![/]) && !input.peek(Token![>]) {
+            attrs.push(input.parse()?);
+        }
+
+        let mut children = Vec::new();
+        if input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            input.parse::<Token![>]>()?;
+        } else {
+            input.parse::<Token![>]>()?;
+            while !(input.peek(Token![<]) && input.peek2(Token![/])) {
+                if input.is_empty() {
+                    return Err(Error::new_spanned(
+                        &name,
+                        format!("Unclosed element <{}>", name.to_token_stream()),
+                    ));
+                }
+                children.push(input.parse()?);
+            }
+
+            input.parse::<Token![<]>()?;
+            input.parse::<Token![/]>()?;
+            let close_name = Path::parse_mod_style(input)?;
+            if close_name.to_token_stream().to_string() != name.to_token_stream().to_string() {
+                return Err(Error::new_spanned(
+                    &close_name,
+                    format!("Expected closing tag </{}>", name.to_token_stream()),
+                ));
+            }
+            input.parse::<Token![>]>()?;
+        }
+
+        Ok(Self {
+            name,
+            args,
+            attrs,
+            children,
+        })
+    }
+}
+
+impl ToTokens for Element {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            name,
+            args,
+            attrs,
+            children,
+        } = self;
+
+        tokens.extend(quote! { #name(#args) #(#attrs)* });
+        for child in children {
+            tokens.extend(match child {
+                Child::Element(element) => quote! { .child(#element) },
+                Child::Block(block) => quote! { .child(#block) },
+                Child::If(_) | Child::For(_) => {
+                    let nodes = child.nodes_tokens();
+                    quote! { .children(#nodes) }
+                },
+            });
+        }
+    }
+}
+
+struct Attribute {
+    name: Ident,
+    value: AttributeValue,
+}
+
+enum AttributeValue {
+    None,
+    Lit(Lit),
+    Block(Block),
+    Args(Punctuated<Expr, Token![,]>),
+}
+
+impl Parse for Attribute {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            if input.peek(token::Brace) {
+                AttributeValue::Block(input.parse()?)
+            } else if input.peek(token::Paren) {
+                let content;
+                parenthesized!(content in input);
+                AttributeValue::Args(content.parse_terminated(Expr::parse)?)
+            } else {
+                AttributeValue::Lit(input.parse()?)
+            }
+        } else {
+            AttributeValue::None
+        };
+
+        Ok(Self { name, value })
+    }
+}
+
+impl ToTokens for Attribute {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        tokens.extend(match &self.value {
+            AttributeValue::None => quote! { .#name() },
+            AttributeValue::Lit(lit) => quote! { .#name(#lit) },
+            AttributeValue::Block(block) => quote! { .#name(#block) },
+            AttributeValue::Args(args) => quote! { .#name(#args) },
+        });
+    }
+}
+
+enum Child {
+    Element(Element),
+    Block(Block),
+    If(IfChild),
+    For(ForChild),
+}
+
+impl Child {
+    /// Tokens of an expression that evaluates to `Vec<Node<_>>`.
+    fn nodes_tokens(&self) -> TokenStream {
+        match self {
+            Child::Element(element) => {
+                let node = element.node_tokens();
+                quote! { ::std::vec![#node] }
+            },
+            Child::Block(block) => quote! { ::std::vec![(#block).build()] },
+            Child::If(if_child) => if_child.nodes_tokens(),
+            Child::For(ForChild { pat, expr, body }) => {
+                let nodes = nodes_tokens(body);
+                quote! {
+                    ::std::iter::IntoIterator::into_iter(#expr)
+                        .flat_map(|#pat| #nodes)
+                        .collect::<::std::vec::Vec<_>>()
+                }
+            },
+        }
+    }
+}
+
+fn nodes_tokens(children: &[Child]) -> TokenStream {
+    let nodes = children.iter().map(Child::nodes_tokens);
+    quote! {
+        {
+            let mut nodes = ::std::vec::Vec::new();
+            #(nodes.extend(#nodes);)*
+            nodes
+        }
+    }
+}
+
+impl Parse for Child {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![<]) {
+            input.parse().map(Child::Element)
+        } else if input.peek(token::Brace) {
+            input.parse().map(Child::Block)
+        } else if input.peek(Token![if]) {
+            input.parse().map(Child::If)
+        } else if input.peek(Token![for]) {
+            input.parse().map(Child::For)
+        } else {
+            Err(input.error("Expected an element, a block, `if` or `for`"))
+        }
+    }
+}
+
+struct IfChild {
+    cond: Expr,
+    then_branch: Vec<Child>,
+    else_branch: Option<ElseBranch>,
+}
+
+impl IfChild {
+    fn nodes_tokens(&self) -> TokenStream {
+        let cond = &self.cond;
+        let then_nodes = nodes_tokens(&self.then_branch);
+        let else_nodes = match &self.else_branch {
+            Some(ElseBranch::If(if_child)) => if_child.nodes_tokens(),
+            Some(ElseBranch::Children(children)) => nodes_tokens(children),
+            None => quote! { ::std::vec::Vec::new() },
+        };
+        quote! {
+            if #cond {
+                #then_nodes
+            } else {
+                #else_nodes
+            }
+        }
+    }
+}
+
+enum ElseBranch {
+    If(Box<IfChild>),
+    Children(Vec<Child>),
+}
+
+impl Parse for IfChild {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![if]>()?;
+        let cond = input.call(Expr::parse_without_eager_brace)?;
+        let then_branch = parse_children_block(input)?;
+
+        let else_branch = if input.peek(Token![else]) {
+            input.parse::<Token![else]>()?;
+            if input.peek(Token![if]) {
+                Some(ElseBranch::If(Box::new(input.parse()?)))
+            } else {
+                Some(ElseBranch::Children(parse_children_block(input)?))
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
+            cond,
+            then_branch,
+            else_branch,
+        })
+    }
+}
+
+struct ForChild {
+    pat: Pat,
+    expr: Expr,
+    body: Vec<Child>,
+}
+
+impl Parse for ForChild {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![for]>()?;
+        let pat = input.parse()?;
+        input.parse::<Token![in]>()?;
+        let expr = input.call(Expr::parse_without_eager_brace)?;
+        let body = parse_children_block(input)?;
+        Ok(Self { pat, expr, body })
+    }
+}
+
+fn parse_children_block(input: ParseStream) -> Result<Vec<Child>> {
+    let content;
+    braced!(content in input);
+
+    let mut children = Vec::new();
+    while !content.is_empty() {
+        children.push(content.parse()?);
+    }
+    Ok(children)
+}
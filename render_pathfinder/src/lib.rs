@@ -2,31 +2,32 @@ use std::{
     fmt,
     fs::File,
     io::{self, Read},
-    iter,
-    ops::Mul,
+    iter, mem,
     path::Path,
     sync::Arc,
 };
 
 use engel_core::{
-    AlignHor, AlignVer, Clip, Color, CompositeShape, Fill, GlyphPos, Gradient, LineCap, LineJoin, Padding, Paint, Real,
-    Render, Rounding, Shape, Stroke, Text, TextMetrics, Transform, TransformMatrix,
+    collect_overlays, AlignHor, AlignVer, BoundingBox, Clip, Color, CompositeShape, Fill, GlyphPos, Gradient, Layout,
+    LineCap, LineJoin, Margin, Padding, Paint, PathCommand, Real, Render, Rounding, Shape, Stroke, Text, TextMetrics,
+    Transform, TransformMatrix,
 };
 use font_kit::handle::Handle;
 use pathfinder_canvas::{
     vec2f, vec2i, Canvas, CanvasFontContext, CanvasRenderingContext2D, ColorF, FillRule, FillStyle,
-    LineCap as PathfinderLineCap, LineJoin as PathfinderLineJoin, Path2D, RectF, TextAlign, TextBaseline, Transform2F,
-    Vector2F, Vector2I,
+    LineCap as PathfinderLineCap, LineJoin as PathfinderLineJoin, Path2D, RectF, RectI, TextAlign, TextBaseline,
+    Transform2F, Vector2F, Vector2I,
 };
 use pathfinder_content::gradient::Gradient as PathfinderGradient;
 use pathfinder_gl::{GLDevice, GLVersion};
+use pathfinder_gpu::{Device, RenderTarget, TextureData, TextureFormat};
 use pathfinder_renderer::{
     concurrent::{rayon::RayonExecutor, scene_proxy::SceneProxy},
     gpu::{
         options::{DestFramebuffer, RendererOptions},
         renderer::Renderer,
     },
-    options::BuildOptions,
+    options::{BuildOptions, RenderTransform},
 };
 use pathfinder_resources::embedded::EmbeddedResourceLoader;
 use pathfinder_simd::default::F32x2;
@@ -35,37 +36,6 @@ use thiserror::Error;
 
 const PI_2: f32 = std::f32::consts::PI * 2.0;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq)]
-pub struct BoundingBox {
-    pub min_x: Real,
-    pub min_y: Real,
-    pub max_x: Real,
-    pub max_y: Real,
-}
-
-impl BoundingBox {
-    pub fn width(&self) -> Real {
-        self.max_x - self.min_x
-    }
-
-    pub fn height(&self) -> Real {
-        self.max_y - self.min_y
-    }
-}
-
-impl Mul<BoundingBox> for TransformMatrix {
-    type Output = [(Real, Real); 4];
-
-    fn mul(self, rhs: BoundingBox) -> Self::Output {
-        [
-            self * (rhs.min_x, rhs.min_y),
-            self * (rhs.min_x, rhs.max_y),
-            self * (rhs.max_x, rhs.min_y),
-            self * (rhs.max_x, rhs.max_y),
-        ]
-    }
-}
-
 #[derive(Debug, Error)]
 pub enum PathfinderRenderError {
     #[error("Context is not initialized")]
@@ -91,12 +61,29 @@ impl fmt::Debug for RendererContext {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PathfinderRender {
     pub context: Option<RendererContext>,
     pub width: u32,
     pub height: u32,
     pub framebuffer_size: Vector2I,
+    /// The internal resolution relative to the window framebuffer.
+    render_scale: Real,
+    /// The frame must be drawn again after the render scale changed.
+    scale_changed: bool,
+}
+
+impl Default for PathfinderRender {
+    fn default() -> Self {
+        Self {
+            context: None,
+            width: 0,
+            height: 0,
+            framebuffer_size: Vector2I::zero(),
+            render_scale: 1.0,
+            scale_changed: false,
+        }
+    }
 }
 
 impl Render for PathfinderRender {
@@ -106,10 +93,12 @@ impl Render for PathfinderRender {
         if self.context.is_none() {
             let colors = background_color.as_arr();
             assert_ne!(self.framebuffer_size, Vector2I::zero());
+            let device = GLDevice::new(GLVersion::GL3, 0);
+            let dest_framebuffer = self.dest_framebuffer(&device);
             let renderer = Renderer::new(
-                GLDevice::new(GLVersion::GL3, 0),
+                device,
                 &EmbeddedResourceLoader::new(),
-                DestFramebuffer::full_window(self.framebuffer_size),
+                dest_framebuffer,
                 RendererOptions {
                     background_color: Some(ColorF::new(colors[0], colors[1], colors[2], colors[3])),
                 },
@@ -146,11 +135,7 @@ impl Render for PathfinderRender {
             self.width = physical_width;
             self.height = physical_height;
             self.framebuffer_size = framebuffer_size;
-            if let Some(context) = &mut self.context {
-                context
-                    .renderer
-                    .replace_dest_framebuffer(DestFramebuffer::full_window(framebuffer_size));
-            }
+            self.replace_dest_framebuffer();
         }
     }
 
@@ -168,7 +153,10 @@ impl Render for PathfinderRender {
 
         // Recalculate tree data and fill canvas
         if node.need_recalc().unwrap_or(true) {
-            let mut defaults = ShapeDefaults::default();
+            let mut defaults = ShapeDefaults {
+                wrap_width: Some(bound.width()),
+                ..Default::default()
+            };
             Self::recalc_composite(
                 &mut canvas_context,
                 node,
@@ -178,13 +166,31 @@ impl Render for PathfinderRender {
             );
         }
 
-        if node.need_redraw().unwrap_or(true) {
+        let scale_changed = mem::take(&mut self.scale_changed);
+        if scale_changed || node.need_redraw().unwrap_or(true) {
             let mut defaults = ShapeDefaults::default();
             Self::render_composite(&mut canvas_context, node, None, &mut defaults);
 
-            // Render the canvas to screen.
-            let scene = SceneProxy::from_scene(canvas_context.into_canvas().into_scene(), RayonExecutor);
-            scene.build_and_render(&mut renderer_context.renderer, BuildOptions::default());
+            // Render the overlays above the view without the defaults of their ancestors.
+            let mut overlays = vec![];
+            collect_overlays(&*node, &mut overlays);
+            for overlay in overlays {
+                Self::render_composite(&mut canvas_context, overlay, None, &mut ShapeDefaults::default());
+            }
+
+            node.mark_drawn();
+
+            // Render the canvas to screen, or to the offscreen framebuffer of the render scale.
+            let mut scene = canvas_context.into_canvas().into_scene();
+            let mut options = BuildOptions::default();
+            if let DestFramebuffer::Other(framebuffer) = renderer_context.renderer.dest_framebuffer() {
+                let render_size = renderer_context.renderer.device.framebuffer_texture(framebuffer).size;
+                scene.set_view_box(RectF::new(Vector2F::zero(), render_size.to_f32()));
+                options.transform = RenderTransform::Transform2D(Transform2F::from_scale(self.render_scale));
+            }
+            let scene = SceneProxy::from_scene(scene, RayonExecutor);
+            scene.build_and_render(&mut renderer_context.renderer, options);
+            Self::present(renderer_context, self.framebuffer_size);
             Ok(true)
         } else {
             Ok(false)
@@ -198,6 +204,8 @@ pub struct ShapeDefaults {
     pub fill: Option<Fill>,
     pub stroke: Option<Stroke>,
     pub clip: Clip,
+    /// The content width of the nearest parent with the width, for the text wrapping.
+    pub wrap_width: Option<Real>,
 }
 
 impl PathfinderRender {
@@ -207,6 +215,98 @@ impl PathfinderRender {
         render
     }
 
+    pub fn render_scale(&self) -> Real {
+        self.render_scale
+    }
+
+    /// Sets the internal resolution relative to the window framebuffer: below 1.0 the frame is drawn at a lower
+    /// resolution and upscaled, above 1.0 it is supersampled. The layout and hit testing stay in the window
+    /// coordinates. Takes effect with the next drawn frame, so it can be changed at runtime, e.g. from the redraw
+    /// hook of the app.
+    pub fn set_render_scale(&mut self, scale: Real) {
+        let scale = scale.max(Real::EPSILON);
+        if self.render_scale != scale {
+            self.render_scale = scale;
+            self.scale_changed = true;
+            self.replace_dest_framebuffer();
+        }
+    }
+
+    /// The size of the frame drawn with the render scale.
+    pub fn render_size(&self) -> Vector2I {
+        (self.framebuffer_size.to_f32() * self.render_scale)
+            .round()
+            .to_i32()
+            .max(vec2i(1, 1))
+    }
+
+    /// Reads the RGBA pixels of the last frame drawn at the render scale, top row first, e.g. to capture a
+    /// screenshot at a higher than the window resolution. Returns `None` without the offscreen framebuffer, when
+    /// the render scale is 1.0.
+    pub fn read_render_pixels(&self) -> Option<(Vector2I, Vec<u8>)> {
+        let renderer = &self.context.as_ref()?.renderer;
+        match renderer.dest_framebuffer() {
+            DestFramebuffer::Other(framebuffer) => {
+                let size = renderer.device.framebuffer_texture(framebuffer).size;
+                let receiver = renderer.device.read_pixels(
+                    &RenderTarget::Framebuffer(framebuffer),
+                    RectI::new(Vector2I::zero(), size),
+                );
+                match renderer.device.recv_texture_data(&receiver) {
+                    TextureData::U8(pixels) => {
+                        // GL rows go bottom up.
+                        let row = size.x() as usize * 4;
+                        let pixels = pixels.chunks(row).rev().flatten().copied().collect();
+                        Some((size, pixels))
+                    },
+                    _ => None,
+                }
+            },
+            DestFramebuffer::Default { .. } => None,
+        }
+    }
+
+    fn dest_framebuffer(&self, device: &GLDevice) -> DestFramebuffer<GLDevice> {
+        if self.render_scale == 1.0 {
+            DestFramebuffer::full_window(self.framebuffer_size)
+        } else {
+            let texture = device.create_texture(TextureFormat::RGBA8, self.render_size());
+            DestFramebuffer::Other(device.create_framebuffer(texture))
+        }
+    }
+
+    fn replace_dest_framebuffer(&mut self) {
+        if let Some(mut context) = self.context.take() {
+            let dest_framebuffer = self.dest_framebuffer(&context.renderer.device);
+            context.renderer.replace_dest_framebuffer(dest_framebuffer);
+            self.context = Some(context);
+        }
+    }
+
+    /// Blits the offscreen framebuffer of the render scale to the window, scaling it to the window size.
+    fn present(context: &RendererContext, window_size: Vector2I) {
+        if let DestFramebuffer::Other(framebuffer) = context.renderer.dest_framebuffer() {
+            let size = framebuffer.texture.size;
+            unsafe {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer.gl_framebuffer);
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+                gl::BlitFramebuffer(
+                    0,
+                    0,
+                    size.x(),
+                    size.y(),
+                    0,
+                    0,
+                    window_size.x(),
+                    window_size.y(),
+                    gl::COLOR_BUFFER_BIT,
+                    gl::LINEAR,
+                );
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            }
+        }
+    }
+
     fn recalc_composite(
         canvas: &mut CanvasRenderingContext2D,
         composite: &mut dyn CompositeShape,
@@ -214,7 +314,18 @@ impl PathfinderRender {
         mut parent_global_transform: TransformMatrix,
         defaults: &mut ShapeDefaults,
     ) -> BoundingBox {
+        let parent_transform = parent_global_transform;
+        let wrap_width = defaults.wrap_width;
+        if !composite.need_recalc().unwrap_or(true) {
+            if let Some(layout) = composite.layout() {
+                if layout.is_valid_for(parent_bound, parent_transform, wrap_width) {
+                    return layout.bound;
+                }
+            }
+        }
+
         let mut bound = parent_bound;
+        let mut shape_transform = parent_global_transform;
 
         if let Some(shape) = composite.shape_mut() {
             match shape {
@@ -235,13 +346,22 @@ impl PathfinderRender {
                         rounding.bottom_right.set_by_pct(radius);
                     }
                     Self::set_by_pct_padding(&mut rect.padding, &parent_bound);
+                    Self::set_by_pct_margin(&mut rect.margin, &parent_bound);
                     Self::set_by_pct_clip(&mut rect.clip, &parent_bound);
 
                     parent_global_transform = rect.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
                     let (scale_x, scale_y) = parent_global_transform.scale_xy();
                     parent_global_transform
                         .translate_add(rect.padding.left.val() * scale_x, rect.padding.top.val() * scale_y);
 
+                    let width = if rect.width.is_auto() {
+                        wrap_width
+                    } else {
+                        Some(rect.width.val())
+                    };
+                    defaults.wrap_width = width.map(|width| width - rect.padding.left_and_right().val());
+
                     bound = BoundingBox {
                         min_x: rect.x.val(),
                         min_y: rect.y.val(),
@@ -258,9 +378,11 @@ impl PathfinderRender {
                     }
                     circle.r.set_by_pct(parent_bound.width().min(parent_bound.height()));
                     Self::set_by_pct_padding(&mut circle.padding, &parent_bound);
+                    Self::set_by_pct_margin(&mut circle.margin, &parent_bound);
                     Self::set_by_pct_clip(&mut circle.clip, &parent_bound);
 
                     parent_global_transform = circle.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
                     let (scale_x, scale_y) = parent_global_transform.scale_xy();
                     parent_global_transform
                         .translate_add(circle.padding.left.val() * scale_x, circle.padding.top.val() * scale_y);
@@ -280,9 +402,11 @@ impl PathfinderRender {
                     if text.y.set_by_pct(parent_bound.height()) {
                         text.y.0 += parent_bound.min_y;
                     }
+                    Self::set_by_pct_margin(&mut text.margin, &parent_bound);
                     Self::set_by_pct_clip(&mut text.clip, &parent_bound);
 
                     parent_global_transform = text.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
 
                     canvas.save();
                     Self::set_text_options(canvas, text, defaults);
@@ -324,23 +448,35 @@ impl PathfinderRender {
                         }
                         prev_pos = Some(pos);
                     }
+                    if let Some(width) = wrap_width {
+                        text.wrap_lines(width - (text.x.val() - parent_bound.min_x).max(0.0));
+                    }
 
                     canvas.restore();
 
+                    let width = text.glyph_positions.iter().map(GlyphPos::max_x).fold(0.0, Real::max);
                     bound = BoundingBox {
                         min_x: text.x.val(),
                         min_y: text.y.val(),
-                        max_x: text.x.val() + text.glyph_positions.last().map(|pos| pos.max_x()).unwrap_or(0.0),
-                        max_y: text.y.val() + line_height,
+                        max_x: text.x.val() + width,
+                        max_y: text.y.val() + line_height * text.line_count() as Real,
                     };
                 },
                 Shape::Path(path) => {
                     Self::set_by_pct_clip(&mut path.clip, &parent_bound);
                     parent_global_transform = path.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
+                },
+                Shape::TileMap(tile_map) => {
+                    Self::set_by_pct_clip(&mut tile_map.clip, &parent_bound);
+                    parent_global_transform = tile_map.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
+                    bound = tile_map.bound();
                 },
                 Shape::Group(group) => {
                     Self::set_by_pct_clip(&mut group.clip, &parent_bound);
                     parent_global_transform = group.recalculate_transform(parent_global_transform);
+                    shape_transform = parent_global_transform;
 
                     if let Some(transparency) = group.transparency {
                         defaults.transparency = transparency;
@@ -359,6 +495,8 @@ impl PathfinderRender {
         }
 
         let inner_bound = Self::calc_inner_bound(canvas, composite, bound, parent_global_transform, defaults);
+        defaults.wrap_width = wrap_width;
+        let mut shape_bound = inner_bound;
 
         if let Some(shape) = composite.shape_mut() {
             match shape {
@@ -376,6 +514,8 @@ impl PathfinderRender {
                         max_x: rect.x.val() + rect.width.val(),
                         max_y: rect.y.val() + rect.height.val(),
                     };
+                    shape_bound = bound;
+                    bound = bound.with_margin(&rect.margin);
                 },
                 Shape::Circle(circle) => {
                     circle.cx.set_by_auto(inner_bound.min_x + inner_bound.width() / 2.0);
@@ -393,8 +533,11 @@ impl PathfinderRender {
                         max_x: cx + r,
                         max_y: cy + r,
                     };
+                    shape_bound = bound;
+                    bound = bound.with_margin(&circle.margin);
                 },
                 Shape::Text(text) => {
+                    shape_bound = bound;
                     let transform = text.transform.matrix();
                     let inner_bound_points = transform * inner_bound;
                     let bound_points = transform * bound;
@@ -409,13 +552,52 @@ impl PathfinderRender {
                         bound.min_y = bound.min_y.min(bound_points[idx].1).min(inner_bound_points[idx].1);
                         bound.max_y = bound.max_y.max(bound_points[idx].1).max(inner_bound_points[idx].1);
                     }
+                    bound = bound.with_margin(&text.margin);
+                },
+                Shape::TileMap(tile_map) => {
+                    shape_bound = tile_map.bound();
                 },
                 _ => (),
             }
         }
+
+        Self::recalc_decorations(canvas, composite, shape_bound, shape_transform, defaults);
+
+        composite.set_layout(Layout {
+            parent_bound,
+            parent_transform,
+            wrap_width,
+            bound,
+        });
         bound
     }
 
+    fn recalc_decorations(
+        canvas: &mut CanvasRenderingContext2D,
+        composite: &mut dyn CompositeShape,
+        host_bound: BoundingBox,
+        host_transform: TransformMatrix,
+        defaults: &mut ShapeDefaults,
+    ) {
+        let decoration_bound = BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: host_bound.width(),
+            max_y: host_bound.height(),
+        };
+
+        if let Some(decorations) = composite.decorations_mut() {
+            for (anchor, decoration) in decorations {
+                let (x, y) = anchor.point(&host_bound);
+                let mut transform = host_transform;
+                let (scale_x, scale_y) = transform.scale_xy();
+                transform.translate_add(x * scale_x, y * scale_y);
+
+                Self::recalc_composite(canvas, decoration, decoration_bound, transform, defaults);
+            }
+        }
+    }
+
     fn calc_inner_bound(
         canvas: &mut CanvasRenderingContext2D,
         composite: &mut dyn CompositeShape,
@@ -508,72 +690,8 @@ impl PathfinderRender {
                     }
                 },
                 Shape::Path(path) => {
-                    use engel_core::PathCommand::*;
-
-                    let mut last_xy = Vector2F::new(0.0, 0.0);
-                    let mut bez_ctrls = [Vector2F::new(0.0, 0.0), Vector2F::new(0.0, 0.0)];
                     let mut draw_path = Path2D::new();
-
-                    for cmd in path.cmd.iter() {
-                        match cmd {
-                            Move(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.move_to(last_xy);
-                            },
-                            MoveRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.move_to(last_xy);
-                            },
-                            Line(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.line_to(last_xy);
-                            },
-                            LineRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.line_to(last_xy);
-                            },
-                            LineAlonX(ref x) => {
-                                last_xy.set_x(*x);
-                                draw_path.line_to(last_xy);
-                            },
-                            LineAlonXRel(ref x) => {
-                                last_xy.set_x(last_xy.x() + *x);
-                                draw_path.line_to(last_xy);
-                            },
-                            LineAlonY(ref y) => {
-                                last_xy.set_y(*y);
-                                draw_path.line_to(last_xy);
-                            },
-                            LineAlonYRel(ref y) => {
-                                last_xy.set_y(last_xy.y() + *y);
-                                draw_path.line_to(last_xy);
-                            },
-                            Close => draw_path.close_path(),
-                            BezCtrl(ref xy) => {
-                                bez_ctrls = [bez_ctrls[1], Vector2F::new(xy[0], xy[1])];
-                            },
-                            BezCtrlRel(ref xy) => {
-                                bez_ctrls = [bez_ctrls[1], Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1])];
-                            },
-                            QuadBezTo(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
-                            },
-                            QuadBezToRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.quadratic_curve_to(bez_ctrls[1], last_xy);
-                            },
-                            CubBezTo(ref xy) => {
-                                last_xy = Vector2F::new(xy[0], xy[1]);
-                                draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
-                            },
-                            CubBezToRel(ref xy) => {
-                                last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
-                                draw_path.bezier_curve_to(bez_ctrls[0], bez_ctrls[1], last_xy);
-                            },
-                            _ => panic!("Not impl rendering cmd {:?}", cmd), // TODO: need refl impl
-                        }
-                    }
+                    Self::append_path(&mut draw_path, &path.cmd, Vector2F::zero());
 
                     Self::set_path_options(canvas, path.transparency, path.clip, &path.transform, defaults);
                     if let Some(fill) = path.fill.as_ref().or_else(|| defaults.fill.as_ref()) {
@@ -585,19 +703,76 @@ impl PathfinderRender {
                         canvas.stroke_path(draw_path);
                     }
                 },
+                Shape::TileMap(tile_map) => {
+                    Self::set_path_options(
+                        canvas,
+                        tile_map.transparency,
+                        tile_map.clip,
+                        &tile_map.transform,
+                        defaults,
+                    );
+
+                    // Cull the cells out of the canvas
+                    let canvas_rect = RectF::new(Vector2F::zero(), canvas.canvas().size().to_f32());
+                    let view = canvas.transform().inverse() * canvas_rect;
+                    let (rows, cols) = tile_map.visible_cells(&BoundingBox {
+                        min_x: view.min_x(),
+                        min_y: view.min_y(),
+                        max_x: view.max_x(),
+                        max_y: view.max_y(),
+                    });
+
+                    for layer in tile_map.layers.iter().filter(|layer| layer.visible) {
+                        for (tile_id, origins) in tile_map.batches(layer, rows.clone(), cols.clone()) {
+                            let tile = match tile_map.atlas.get(tile_id) {
+                                Some(tile) => tile,
+                                None => continue,
+                            };
+
+                            let mut draw_path = Path2D::new();
+                            for &[x, y] in &origins {
+                                Self::append_path(&mut draw_path, &tile.cmd, Vector2F::new(x, y));
+                            }
+
+                            let fill = tile.fill.as_ref().or(tile_map.fill.as_ref());
+                            if let Some(fill) = fill.or(defaults.fill.as_ref()) {
+                                Self::set_fill_option(canvas, fill);
+                                canvas.fill_path(draw_path.clone(), FillRule::Winding);
+                            };
+                            let stroke = tile.stroke.as_ref().or(tile_map.stroke.as_ref());
+                            if let Some(stroke) = stroke.or(defaults.stroke.as_ref()) {
+                                Self::set_stroke_option(canvas, stroke);
+                                canvas.stroke_path(draw_path);
+                            }
+                        }
+                    }
+                },
                 Shape::Text(this_text) => {
                     text = Some(this_text);
 
-                    let pos = Vector2F::new(this_text.x.val(), this_text.y.val());
+                    let line_height = this_text.metrics.map_or(0.0, |metrics| metrics.line_height);
+                    let lines: Vec<_> = this_text
+                        .lines()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, line)| {
+                            let pos = Vector2F::new(this_text.x.val(), this_text.y.val() + idx as Real * line_height);
+                            (line, pos)
+                        })
+                        .collect();
 
                     Self::set_text_options(canvas, this_text, defaults);
                     if let Some(fill) = this_text.fill.as_ref().or_else(|| defaults.fill.as_ref()) {
                         Self::set_fill_option(canvas, fill);
-                        canvas.fill_text(&this_text.content, pos);
+                        for (line, pos) in &lines {
+                            canvas.fill_text(line, *pos);
+                        }
                     };
                     if let Some(stroke) = this_text.stroke.as_ref().or_else(|| defaults.stroke.as_ref()) {
                         Self::set_stroke_option(canvas, stroke);
-                        canvas.stroke_text(&this_text.content, pos);
+                        for (line, pos) in &lines {
+                            canvas.stroke_text(line, *pos);
+                        }
                     }
                 },
                 Shape::Group(group) => {
@@ -619,8 +794,93 @@ impl PathfinderRender {
         canvas.restore();
 
         if let Some(children) = composite.children() {
-            for child in children {
-                Self::render_composite(canvas, child, text, defaults);
+            if let Some(clip) = composite.overflow_clip() {
+                let mut defaults = ShapeDefaults {
+                    clip,
+                    ..defaults.clone()
+                };
+                for child in children.filter(|child| !child.is_overlay()) {
+                    Self::render_composite(canvas, child, text, &mut defaults);
+                }
+            } else {
+                for child in children.filter(|child| !child.is_overlay()) {
+                    Self::render_composite(canvas, child, text, defaults);
+                }
+            }
+        }
+
+        if let Some(decorations) = composite.decorations() {
+            for (_, decoration) in decorations.filter(|(_, decoration)| !decoration.is_overlay()) {
+                Self::render_composite(canvas, decoration, text, defaults);
+            }
+        }
+    }
+
+    /// Appends the path commands to the path, offset by the origin.
+    fn append_path(draw_path: &mut Path2D, cmd: &[PathCommand], origin: Vector2F) {
+        use engel_core::PathCommand::*;
+
+        let mut last_xy = Vector2F::new(0.0, 0.0);
+        let mut bez_ctrls = [Vector2F::new(0.0, 0.0), Vector2F::new(0.0, 0.0)];
+
+        for cmd in cmd.iter() {
+            match cmd {
+                Move(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.move_to(origin + last_xy);
+                },
+                MoveRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.move_to(origin + last_xy);
+                },
+                Line(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.line_to(origin + last_xy);
+                },
+                LineRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.line_to(origin + last_xy);
+                },
+                LineAlonX(ref x) => {
+                    last_xy.set_x(*x);
+                    draw_path.line_to(origin + last_xy);
+                },
+                LineAlonXRel(ref x) => {
+                    last_xy.set_x(last_xy.x() + *x);
+                    draw_path.line_to(origin + last_xy);
+                },
+                LineAlonY(ref y) => {
+                    last_xy.set_y(*y);
+                    draw_path.line_to(origin + last_xy);
+                },
+                LineAlonYRel(ref y) => {
+                    last_xy.set_y(last_xy.y() + *y);
+                    draw_path.line_to(origin + last_xy);
+                },
+                Close => draw_path.close_path(),
+                BezCtrl(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], Vector2F::new(xy[0], xy[1])];
+                },
+                BezCtrlRel(ref xy) => {
+                    bez_ctrls = [bez_ctrls[1], Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1])];
+                },
+                QuadBezTo(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.quadratic_curve_to(origin + bez_ctrls[1], origin + last_xy);
+                },
+                QuadBezToRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.quadratic_curve_to(origin + bez_ctrls[1], origin + last_xy);
+                },
+                CubBezTo(ref xy) => {
+                    last_xy = Vector2F::new(xy[0], xy[1]);
+                    draw_path.bezier_curve_to(origin + bez_ctrls[0], origin + bez_ctrls[1], origin + last_xy);
+                },
+                CubBezToRel(ref xy) => {
+                    last_xy = Vector2F::new(last_xy.x() + xy[0], last_xy.y() + xy[1]);
+                    draw_path.bezier_curve_to(origin + bez_ctrls[0], origin + bez_ctrls[1], origin + last_xy);
+                },
+                _ => panic!("Not impl rendering cmd {:?}", cmd), // TODO: need refl impl
             }
         }
     }
@@ -632,6 +892,13 @@ impl PathfinderRender {
         padding.bottom.set_by_pct(parent_bound.height());
     }
 
+    fn set_by_pct_margin(margin: &mut Margin, parent_bound: &BoundingBox) {
+        margin.left.set_by_pct(parent_bound.width());
+        margin.right.set_by_pct(parent_bound.width());
+        margin.top.set_by_pct(parent_bound.height());
+        margin.bottom.set_by_pct(parent_bound.height());
+    }
+
     fn set_by_pct_clip(clip: &mut Clip, parent_bound: &BoundingBox) {
         if let Clip::Scissor(scissor) = clip {
             scissor.x.set_by_pct(parent_bound.width());